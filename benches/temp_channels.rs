@@ -0,0 +1,91 @@
+//! Compares the old `RwLock<HashMap>` against `DashMap` for `temp_channels` with
+//! 100 concurrent voice events against a guild with 250 tracked channels.
+//!
+//! Most events are a join or leave that touches one channel's entry, every tenth is a
+//! creator channel join that scans for the owner's existing channel. Each side does
+//! what its handler did: a leave awaits the member list and the deletion scheduling,
+//! the old handler held the write lock over those awaits, the new one holds no entry
+//! while it waits. The `yield_now` stands in for those awaits.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use dashmap::DashMap;
+use std::{collections::HashMap, sync::Arc};
+use tokio::{runtime::Runtime, sync::RwLock};
+
+const CHANNELS: u64 = 250;
+const EVENTS: u64 = 100;
+
+const CREATOR_JOIN_EVERY: u64 = 10;
+
+/// Stand-in for `ChannelInfo`.
+struct Info {
+    owner_id: u64,
+    pending_delete: bool,
+}
+
+fn info(owner_id: u64) -> Info {
+    Info {
+        owner_id,
+        pending_delete: false,
+    }
+}
+
+fn bench_temp_channels(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let mut group = c.benchmark_group("100 concurrent voice events");
+
+    let locked: Arc<RwLock<HashMap<u64, Info>>> = Arc::new(RwLock::new(
+        (0..CHANNELS).map(|id| (id, info(id))).collect(),
+    ));
+    group.bench_function("RwLock<HashMap>", |b| {
+        b.to_async(&runtime).iter(|| {
+            let map = Arc::clone(&locked);
+            async move {
+                let events = (0..EVENTS).map(|event| {
+                    let map = Arc::clone(&map);
+                    tokio::spawn(async move {
+                        if event % CREATOR_JOIN_EVERY == 0 {
+                            return map.read().await.values().any(|i| i.owner_id == event);
+                        }
+                        let mut map = map.write().await;
+                        if let Some(info) = map.get_mut(&(event % CHANNELS)) {
+                            tokio::task::yield_now().await;
+                            info.pending_delete = !info.pending_delete;
+                        }
+                        false
+                    })
+                });
+                futures::future::join_all(events).await
+            }
+        })
+    });
+
+    let sharded: Arc<DashMap<u64, Info>> =
+        Arc::new((0..CHANNELS).map(|id| (id, info(id))).collect());
+    group.bench_function("DashMap", |b| {
+        b.to_async(&runtime).iter(|| {
+            let map = Arc::clone(&sharded);
+            async move {
+                let events = (0..EVENTS).map(|event| {
+                    let map = Arc::clone(&map);
+                    tokio::spawn(async move {
+                        if event % CREATOR_JOIN_EVERY == 0 {
+                            return map.iter().any(|i| i.owner_id == event);
+                        }
+                        tokio::task::yield_now().await;
+                        if let Some(mut info) = map.get_mut(&(event % CHANNELS)) {
+                            info.pending_delete = !info.pending_delete;
+                        }
+                        false
+                    })
+                });
+                futures::future::join_all(events).await
+            }
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_temp_channels);
+criterion_main!(benches);
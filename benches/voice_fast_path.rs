@@ -0,0 +1,142 @@
+//! What the early return in `handle_voice_state_update` saves on voice events in
+//! channels the bot doesn't manage: 100 events in a guild with 250 tracked channels,
+//! none of them touching a tracked channel or the creator channel.
+//!
+//! The fast path is the handler's own check from `src/handler/voice_filter.rs`. The
+//! full path does the lookups the handler did for every event before: session and
+//! visit bookkeeping for both channels, the creator channel from the guild config, the
+//! empty-channel check, the deletion cancel and the join sound check.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use dashmap::DashMap;
+use serenity::model::id::{ChannelId, GuildId};
+use std::{collections::HashMap, sync::Arc};
+use tokio::{runtime::Runtime, sync::RwLock};
+
+#[path = "../src/handler/voice_filter.rs"]
+mod voice_filter;
+
+const CHANNELS: u64 = 250;
+const EVENTS: u64 = 100;
+const CREATOR_CHANNEL: u64 = 1_000_000;
+const GUILD: GuildId = GuildId(1);
+
+/// Stand-in for `ChannelInfo` in the full path.
+#[derive(Default)]
+struct Info {
+    members: Vec<u64>,
+    visits: HashMap<u64, u64>,
+}
+
+/// Stand-in for `GuildConfig`, cloned on every read like the real one.
+#[derive(Clone, Default)]
+struct Config {
+    creator_channel_id: Option<u64>,
+    /// Only there to make the clone cost what a real one does.
+    #[allow(dead_code)]
+    name_filter: Vec<String>,
+    join_sound_enabled: bool,
+}
+
+struct State {
+    channels: DashMap<u64, Info>,
+    config: RwLock<Config>,
+    /// What the handler keeps for the fast path.
+    temp_channels: DashMap<ChannelId, Info>,
+    creator_channels: DashMap<GuildId, ChannelId>,
+}
+
+impl State {
+    async fn config(&self) -> Config {
+        self.config.read().await.clone()
+    }
+
+    fn concerns_us(&self, left: u64, joined: u64) -> bool {
+        voice_filter::concerns_us(
+            &self.temp_channels,
+            &self.creator_channels,
+            None,
+            Some(GUILD),
+            Some(ChannelId(left)),
+            Some(ChannelId(joined)),
+        )
+    }
+
+    async fn full_path(&self, user: u64, left: u64, joined: u64) -> bool {
+        if let Some(mut info) = self.channels.get_mut(&left) {
+            info.members.retain(|m| *m != user);
+        }
+        if let Some(mut info) = self.channels.get_mut(&joined) {
+            info.members.push(user);
+        }
+        let is_creator = self.config().await.creator_channel_id == Some(joined);
+        let visited = self.channels.get_mut(&left).and_then(|mut i| i.visits.remove(&user)).is_some();
+        let emptied = self.channels.get(&left).is_some_and(|i| i.members.is_empty());
+        if let Some(mut info) = self.channels.get_mut(&joined) {
+            info.visits.remove(&user);
+        }
+        let sound = self.config().await.join_sound_enabled && self.channels.contains_key(&joined);
+        is_creator || visited || emptied || sound
+    }
+}
+
+fn state() -> Arc<State> {
+    Arc::new(State {
+        channels: (0..CHANNELS).map(|id| (id, Info::default())).collect(),
+        config: RwLock::new(Config {
+            creator_channel_id: Some(CREATOR_CHANNEL),
+            name_filter: (0..20).map(|i| format!("word{}", i)).collect(),
+            join_sound_enabled: true,
+        }),
+        temp_channels: (0..CHANNELS).map(|id| (ChannelId(id), Info::default())).collect(),
+        creator_channels: [(GUILD, ChannelId(CREATOR_CHANNEL))].into_iter().collect(),
+    })
+}
+
+/// Channel IDs past the tracked ones, so no event concerns the bot.
+fn untracked(event: u64) -> (u64, u64) {
+    (CHANNELS + event, CHANNELS + event + 1)
+}
+
+fn bench_fast_path(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let mut group = c.benchmark_group("100 unrelated voice events");
+    let state = state();
+
+    group.bench_function("full path", |b| {
+        b.to_async(&runtime).iter(|| {
+            let state = Arc::clone(&state);
+            async move {
+                let events = (0..EVENTS).map(|event| {
+                    let state = Arc::clone(&state);
+                    tokio::spawn(async move {
+                        let (left, joined) = untracked(event);
+                        state.full_path(event, left, joined).await
+                    })
+                });
+                futures::future::join_all(events).await
+            }
+        })
+    });
+
+    group.bench_function("fast path", |b| {
+        b.to_async(&runtime).iter(|| {
+            let state = Arc::clone(&state);
+            async move {
+                let events = (0..EVENTS).map(|event| {
+                    let state = Arc::clone(&state);
+                    tokio::spawn(async move {
+                        let (left, joined) = untracked(event);
+                        state.concerns_us(left, joined) && state.full_path(event, left, joined).await
+                    })
+                });
+                futures::future::join_all(events).await
+            }
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_fast_path);
+criterion_main!(benches);
@@ -0,0 +1,5 @@
+-- Per-guild settings from /config and /setup, stored as JSON
+CREATE TABLE IF NOT EXISTS guild_configs (
+    guild_id INTEGER PRIMARY KEY NOT NULL,
+    config TEXT NOT NULL
+);
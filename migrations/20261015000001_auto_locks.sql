@@ -0,0 +1,5 @@
+-- Member counts at which /auto_lock locks a temp channel
+CREATE TABLE IF NOT EXISTS auto_locks (
+    channel_id INTEGER PRIMARY KEY NOT NULL,
+    threshold INTEGER NOT NULL
+);
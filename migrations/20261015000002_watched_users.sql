@@ -0,0 +1,6 @@
+-- Users whose voice activity /watch reports to the log channel
+CREATE TABLE IF NOT EXISTS watched_users (
+    guild_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    PRIMARY KEY (guild_id, user_id)
+);
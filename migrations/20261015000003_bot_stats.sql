@@ -0,0 +1,11 @@
+-- All-time counters for /stats, a single row that only ever gets incremented
+CREATE TABLE IF NOT EXISTS bot_stats (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    channels_created_total INTEGER NOT NULL DEFAULT 0,
+    channels_deleted_total INTEGER NOT NULL DEFAULT 0,
+    commands_executed_total INTEGER NOT NULL DEFAULT 0,
+    voice_events_processed_total INTEGER NOT NULL DEFAULT 0,
+    errors_total INTEGER NOT NULL DEFAULT 0
+);
+
+INSERT OR IGNORE INTO bot_stats (id) VALUES (1);
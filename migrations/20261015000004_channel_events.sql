@@ -0,0 +1,9 @@
+-- When temp channels were created, for /peak_hours. `created_at` is unix seconds
+CREATE TABLE IF NOT EXISTS channel_events (
+    guild_id INTEGER NOT NULL,
+    channel_id INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    created_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS channel_events_guild_time ON channel_events (guild_id, created_at);
@@ -0,0 +1,5 @@
+-- Preferences that follow a user across every server the bot is in
+CREATE TABLE IF NOT EXISTS user_preferences (
+    user_id INTEGER PRIMARY KEY,
+    announce_opt_out INTEGER NOT NULL DEFAULT 0
+);
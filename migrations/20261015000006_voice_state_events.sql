@@ -0,0 +1,3 @@
+-- Voice events stored for /event_replay when ALLOW_EVENT_REPLAY is on, as
+-- `{"old": VoiceState | null, "new": VoiceState}` JSON in rows of kind 'voice_state'
+ALTER TABLE channel_events ADD COLUMN payload TEXT;
@@ -0,0 +1,6 @@
+-- Temp channels and their owners, picked up again after a restart
+CREATE TABLE IF NOT EXISTS temp_channels (
+    channel_id INTEGER PRIMARY KEY NOT NULL,
+    guild_id INTEGER NOT NULL,
+    owner_id INTEGER NOT NULL
+);
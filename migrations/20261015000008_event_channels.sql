@@ -0,0 +1,7 @@
+-- Temp channels of running scheduled events, so they still end after a restart
+CREATE TABLE IF NOT EXISTS event_channels (
+    event_id INTEGER PRIMARY KEY NOT NULL,
+    guild_id INTEGER NOT NULL,
+    channel_id INTEGER NOT NULL,
+    venue_id INTEGER
+);
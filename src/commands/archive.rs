@@ -0,0 +1,102 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::{
+        application::{
+            command::CommandOptionType,
+            interaction::application_command::ApplicationCommandInteraction,
+        },
+        channel::ChannelType,
+    },
+    prelude::*,
+};
+
+use super::{option_channel, respond, SlashCommand};
+use crate::{error::BotError, handler::{ChangeAction, Handler}};
+
+pub struct ArchiveCommand;
+
+#[async_trait]
+impl SlashCommand for ArchiveCommand {
+    fn name(&self) -> &'static str {
+        "archive"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Keep a channel's text chat in the archive when the session ends")
+            .dm_permission(false)
+            .create_option(|o| {
+                o.name("channel")
+                    .description("The temporary voice channel")
+                    .kind(CommandOptionType::Channel)
+                    .channel_types(&[ChannelType::Voice])
+                    .required(true)
+            });
+        command
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let guild_id = match command.guild_id {
+        Some(id) => id,
+        None => return respond(ctx, command, "This command only works in a server.", true).await,
+    };
+
+    let channel_id = match option_channel(&command.data.options, "channel") {
+        Some(id) => id,
+        None => return respond(ctx, command, "Please pick a channel.", true).await,
+    };
+
+    let owner = match handler.channel_owner(channel_id) {
+        Some(owner) => owner,
+        None => return respond(ctx, command, "That is not a temporary channel.", true).await,
+    };
+
+    let is_admin = command
+        .member
+        .as_ref()
+        .and_then(|m| m.permissions)
+        .is_some_and(|p| p.manage_channels());
+    if owner != command.user.id && !is_admin {
+        return respond(ctx, command, "Only the channel owner can archive this channel.", true).await;
+    }
+
+    if handler.text_channel(channel_id).is_none() {
+        return respond(ctx, command, "That channel has no text channel to archive.", true).await;
+    }
+
+    if handler.guild_config(guild_id).await.archive_category_id.is_none() {
+        return respond(
+            ctx,
+            command,
+            "No archive category is set, ask an admin to run `/config archive`.",
+            true,
+        )
+        .await;
+    }
+
+    handler.mark_for_archive(channel_id);
+    handler.record_change(channel_id, command.user.id, ChangeAction::Archive);
+    respond(
+        ctx,
+        command,
+        format!("The chat of <#{}> will be archived when the session ends.", channel_id),
+        true,
+    )
+    .await
+}
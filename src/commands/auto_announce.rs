@@ -0,0 +1,72 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::application::{
+        command::CommandOptionType,
+        interaction::application_command::ApplicationCommandInteraction,
+    },
+    prelude::*,
+};
+
+use super::{respond, SlashCommand};
+use crate::{error::BotError, handler::Handler};
+
+pub struct AutoAnnounceCommand;
+
+#[async_trait]
+impl SlashCommand for AutoAnnounceCommand {
+    fn name(&self) -> &'static str {
+        "auto_announce"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Choose whether your channels show up in announce channels, in every server")
+            .dm_permission(true)
+            .create_option(|o| {
+                o.name("on")
+                    .description("List your channels in announce channels again")
+                    .kind(CommandOptionType::SubCommand)
+            })
+            .create_option(|o| {
+                o.name("off")
+                    .description("Keep your channels out of announce channels")
+                    .kind(CommandOptionType::SubCommand)
+            });
+        command
+    }
+
+    fn dm_allowed(&self) -> bool {
+        true
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        run(handler, ctx, command).await
+    }
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), BotError> {
+    let opt_out = match command.data.options.first().map(|s| s.name.as_str()) {
+        Some("on") => false,
+        Some("off") => true,
+        _ => return Ok(()),
+    };
+
+    handler.set_announce_opt_out(command.user.id, opt_out).await?;
+    let reply = if opt_out {
+        "Your channels are no longer listed in announce channels, in any server."
+    } else {
+        "Your channels are listed in announce channels again."
+    };
+    Ok(respond(ctx, command, reply, true).await?)
+}
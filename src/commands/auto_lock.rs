@@ -0,0 +1,108 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::application::{
+        command::CommandOptionType,
+        interaction::application_command::ApplicationCommandInteraction,
+    },
+    prelude::*,
+};
+
+use super::{option_str, respond, SlashCommand};
+use crate::{error::BotError, handler::Handler};
+
+/// Thresholds `/auto_lock` accepts, Discord voice channels hold at most 99 members.
+const MIN_THRESHOLD: u32 = 2;
+const MAX_THRESHOLD: u32 = 99;
+
+pub struct AutoLockCommand;
+
+#[async_trait]
+impl SlashCommand for AutoLockCommand {
+    fn name(&self) -> &'static str {
+        "auto_lock"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Lock your channel automatically once enough members are in it")
+            .dm_permission(false)
+            .create_option(|o| {
+                o.name("threshold")
+                    .description("Member count that locks the channel, or `off`")
+                    .kind(CommandOptionType::String)
+                    .required(true)
+            });
+        command
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+/// `Some(None)` for `off`, `None` if `input` is neither `off` nor a valid threshold.
+pub fn parse_threshold(input: &str) -> Option<Option<u32>> {
+    let input = input.trim();
+    if input.eq_ignore_ascii_case("off") {
+        return Some(None);
+    }
+    input
+        .parse()
+        .ok()
+        .filter(|n| (MIN_THRESHOLD..=MAX_THRESHOLD).contains(n))
+        .map(Some)
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let channel_id = match handler.get_user_channel(command.user.id).await {
+        Some(id) => id,
+        None => return respond(ctx, command, "You don't own a temporary channel.", true).await,
+    };
+
+    let input = option_str(&command.data.options, "threshold").unwrap_or_default();
+    let threshold = match parse_threshold(input) {
+        Some(threshold) => threshold,
+        None => {
+            let reply = format!("Pick a member count from {} to {}, or `off`.", MIN_THRESHOLD, MAX_THRESHOLD);
+            return respond(ctx, command, reply, true).await;
+        }
+    };
+
+    handler.set_auto_lock(channel_id, threshold).await;
+    // The channel may be full enough already, then it locks right away
+    let locked = match command.guild_id {
+        Some(guild_id) if threshold.is_some() => handler.check_auto_lock(ctx, guild_id, channel_id).await,
+        _ => false,
+    };
+    let reply = match threshold {
+        Some(n) if locked => format!("Your channel already has {} or more members and is now locked.", n),
+        Some(n) => format!("Your channel locks automatically once {} members are in it.", n),
+        None => "Auto lock is off.".to_string(),
+    };
+    respond(ctx, command, reply, true).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_thresholds() {
+        assert_eq!(parse_threshold(" 5 "), Some(Some(5)));
+        assert_eq!(parse_threshold("OFF"), Some(None));
+        assert_eq!(parse_threshold("1"), None);
+        assert_eq!(parse_threshold("100"), None);
+        assert_eq!(parse_threshold("five"), None);
+    }
+}
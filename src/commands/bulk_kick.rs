@@ -0,0 +1,158 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::{
+        application::interaction::application_command::ApplicationCommandInteraction,
+        id::{ChannelId, GuildId, UserId},
+    },
+    prelude::*,
+};
+use tracing::warn;
+
+use super::{confirm, finish, respond, Prompt, SlashCommand};
+use crate::{
+    error::BotError,
+    handler::{ChangeAction, Handler},
+};
+
+pub struct BulkKickCommand;
+
+#[async_trait]
+impl SlashCommand for BulkKickCommand {
+    fn name(&self) -> &'static str {
+        "bulk_kick"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Move everyone but you out of your channel, to the waiting room if there is one")
+            .dm_permission(false);
+        command
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+/// The members of the channel that have to go, everyone in `keep` stays.
+pub fn kick_targets(members: &[UserId], keep: &[UserId]) -> Vec<UserId> {
+    members.iter().filter(|id| !keep.contains(id)).copied().collect()
+}
+
+pub fn confirm_text(count: usize, waiting_room: Option<ChannelId>) -> String {
+    match waiting_room {
+        Some(id) => format!("This will move {} people to <#{}>. Confirm?", count, id),
+        None => format!("There's no waiting room, this will disconnect {} people. Confirm?", count),
+    }
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let guild = match command.guild_id.and_then(|id| id.to_guild_cached(&ctx.cache)) {
+        Some(guild) => guild,
+        None => return respond(ctx, command, "This command only works in a server.", true).await,
+    };
+    let channel_id = match handler.get_user_channel(command.user.id).await {
+        Some(id) => id,
+        None => return respond(ctx, command, "You don't own a temporary channel.", true).await,
+    };
+
+    let bot_id = ctx.cache.current_user_id();
+    let mut keep = vec![command.user.id, bot_id];
+    keep.extend(handler.channel_owner(channel_id));
+    let targets = kick_targets(&members_in(ctx, guild.id, channel_id), &keep);
+    if targets.is_empty() {
+        return respond(ctx, command, "There's nobody to kick from your channel.", true).await;
+    }
+
+    // Moving and disconnecting both need the permission server-wide
+    let bot_can_move = guild
+        .members
+        .get(&bot_id)
+        .is_some_and(|bot| bot.permissions(&ctx.cache).is_ok_and(|p| p.move_members()));
+    if !bot_can_move {
+        return respond(ctx, command, "The bot needs Move Members in this server to do that.", true).await;
+    }
+
+    let waiting_room = handler.waiting_room(guild.id).await;
+    let prompt = Prompt::text(confirm_text(targets.len(), waiting_room));
+    let Some(interaction) = confirm(ctx, command, "Bulk kick", prompt).await? else { return Ok(()) };
+
+    // People may have come or gone while the buttons were up, only those still inside go
+    let targets = kick_targets(&members_in(ctx, guild.id, channel_id), &keep);
+    if targets.is_empty() {
+        return finish(ctx, &interaction, "Everyone left already, nobody was kicked.").await;
+    }
+    let mut failed = 0;
+    for user_id in &targets {
+        let result = match waiting_room {
+            Some(waiting_room_id) => guild.id.move_member(&ctx.http, *user_id, waiting_room_id).await.map(|_| ()),
+            None => guild.id.disconnect_member(&ctx.http, *user_id).await.map(|_| ()),
+        };
+        match result {
+            Ok(()) => handler.record_change(channel_id, command.user.id, ChangeAction::Kick(*user_id)),
+            Err(e) => {
+                warn!("Kon {} niet uit {} halen: {:?}", user_id, channel_id, e);
+                failed += 1;
+            }
+        }
+    }
+    handler
+        .audit_log(
+            ctx,
+            guild.id,
+            &format!("<@{}> kicked {} people from <#{}>", command.user.id, targets.len() - failed, channel_id),
+        )
+        .await;
+    let content = match failed {
+        0 => format!("Kicked {} people from your channel.", targets.len()),
+        n => format!("Kicked {} people, {} could not be moved.", targets.len() - n, n),
+    };
+    finish(ctx, &interaction, content).await
+}
+
+/// Who is in `channel_id` according to the cache right now.
+fn members_in(ctx: &Context, guild_id: GuildId, channel_id: ChannelId) -> Vec<UserId> {
+    guild_id
+        .to_guild_cached(&ctx.cache)
+        .map(|guild| {
+            guild
+                .voice_states
+                .values()
+                .filter(|v| v.channel_id == Some(channel_id))
+                .map(|v| v.user_id)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn everyone_but_the_kept_members_goes() {
+        let members = [UserId(1), UserId(2), UserId(3), UserId(9)];
+        assert_eq!(kick_targets(&members, &[UserId(1), UserId(9)]), [UserId(2), UserId(3)]);
+        assert!(kick_targets(&[UserId(1)], &[UserId(1)]).is_empty());
+    }
+
+    #[test]
+    fn confirmation_says_where_they_go() {
+        assert_eq!(
+            confirm_text(5, Some(ChannelId(7))),
+            "This will move 5 people to <#7>. Confirm?"
+        );
+        assert!(confirm_text(2, None).contains("disconnect 2 people"));
+    }
+}
@@ -0,0 +1,181 @@
+use serenity::{
+    async_trait,
+    builder::{CreateApplicationCommand, CreateEmbed},
+    model::{
+        application::{
+            command::CommandOptionType,
+            interaction::application_command::ApplicationCommandInteraction,
+        },
+        channel::{Channel, ChannelType},
+        id::{ChannelId, UserId},
+        permissions::Permissions,
+    },
+    prelude::*,
+};
+use std::time::Duration;
+use tokio::time::sleep;
+use tracing::warn;
+
+use super::{option_channel, respond, SlashCommand};
+use crate::{error::BotError, handler::Handler, utils::{create_embed, is_member_gone}};
+
+/// Members moved at once, with `BATCH_DELAY` in between to stay clear of rate limits.
+const BATCH_SIZE: usize = 10;
+const BATCH_DELAY: Duration = Duration::from_secs(1);
+
+pub struct BulkMoveCommand;
+
+#[async_trait]
+impl SlashCommand for BulkMoveCommand {
+    fn name(&self) -> &'static str {
+        "bulk_move"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Move everyone in one voice channel to another")
+            .dm_permission(false)
+            .create_option(|o| {
+                o.name("from_channel")
+                    .description("Channel to empty")
+                    .kind(CommandOptionType::Channel)
+                    .channel_types(&[ChannelType::Voice, ChannelType::Stage])
+                    .required(true)
+            })
+            .create_option(|o| {
+                o.name("to_channel")
+                    .description("Channel to move everyone to")
+                    .kind(CommandOptionType::Channel)
+                    .channel_types(&[ChannelType::Voice, ChannelType::Stage])
+                    .required(true)
+            });
+        command
+    }
+
+    fn required_permissions(&self) -> Permissions {
+        Permissions::MOVE_MEMBERS
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+/// How many members still fit in a channel with `user_limit` (0 is unlimited) that has
+/// `present` members, `None` if there's no limit.
+pub fn free_slots(user_limit: u64, present: usize) -> Option<usize> {
+    (user_limit > 0).then(|| (user_limit as usize).saturating_sub(present))
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let guild = match command.guild_id.and_then(|id| id.to_guild_cached(&ctx.cache)) {
+        Some(guild) => guild,
+        None => return respond(ctx, command, "This command only works in a server.", true).await,
+    };
+
+    let options = &command.data.options;
+    let (from, to) = match (option_channel(options, "from_channel"), option_channel(options, "to_channel")) {
+        (Some(from), Some(to)) if from != to => (from, to),
+        _ => return respond(ctx, command, "Pick two different channels.", true).await,
+    };
+
+    let members_of = |channel_id: ChannelId| -> Vec<UserId> {
+        guild
+            .voice_states
+            .values()
+            .filter(|v| v.channel_id == Some(channel_id))
+            .map(|v| v.user_id)
+            .collect()
+    };
+    let mut members = members_of(from);
+    if members.is_empty() {
+        return respond(ctx, command, format!("Nobody is in <#{}>.", from), true).await;
+    }
+
+    let user_limit = match guild.channels.get(&to) {
+        Some(Channel::Guild(gc)) => gc.user_limit.unwrap_or(0),
+        _ => 0,
+    };
+    let left_behind = match free_slots(user_limit, members_of(to).len()) {
+        Some(free) if free < members.len() => members.split_off(free).len(),
+        _ => 0,
+    };
+    if members.is_empty() {
+        return respond(ctx, command, format!("<#{}> is full.", to), true).await;
+    }
+
+    // Moving takes a while, Discord wants an answer within 3 seconds
+    command.defer_ephemeral(&ctx.http).await?;
+    let config = handler.guild_config(guild.id).await;
+    let total = members.len();
+    let mut moved = 0;
+    let batches = members.chunks(BATCH_SIZE).count();
+    for (i, batch) in members.chunks(BATCH_SIZE).enumerate() {
+        if i > 0 {
+            sleep(BATCH_DELAY).await;
+        }
+        for user_id in batch {
+            match guild.id.move_member(&ctx.http, *user_id, to).await {
+                Ok(_) => moved += 1,
+                Err(e) if is_member_gone(&e) => {}
+                Err(e) => warn!("Kon {} niet verplaatsen naar {}: {:?}", user_id, to, e),
+            }
+        }
+
+        let done = i + 1 == batches;
+        let mut embed = create_embed(&config);
+        progress(&mut embed, from, to, moved, total, done);
+        if done && left_behind > 0 {
+            embed.footer(|f| f.text(format!("{} member(s) stayed, the channel is full.", left_behind)));
+        }
+        command
+            .edit_original_interaction_response(&ctx.http, |r| r.set_embed(embed))
+            .await?;
+    }
+
+    handler
+        .audit_log(
+            ctx,
+            guild.id,
+            &format!("<@{}> moved {} member(s) from <#{}> to <#{}>", command.user.id, moved, from, to),
+        )
+        .await;
+    Ok(())
+}
+
+fn progress(
+    embed: &mut CreateEmbed,
+    from: ChannelId,
+    to: ChannelId,
+    moved: usize,
+    total: usize,
+    done: bool,
+) -> &mut CreateEmbed {
+    let title = if done { "Move done" } else { "Moving members" };
+    embed
+        .title(title)
+        .description(format!("<#{}> → <#{}>", from, to))
+        .field("Moved", format!("{}/{}", moved, total), true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn free_slots_respects_limit() {
+        assert_eq!(free_slots(0, 40), None);
+        assert_eq!(free_slots(10, 4), Some(6));
+        assert_eq!(free_slots(5, 7), Some(0));
+    }
+}
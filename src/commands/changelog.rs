@@ -0,0 +1,78 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::application::interaction::{
+        application_command::ApplicationCommandInteraction, InteractionResponseType,
+    },
+    prelude::*,
+};
+use std::time::Instant;
+
+use super::{respond, SlashCommand};
+use crate::{error::BotError, handler::Handler, utils::create_embed};
+
+/// Changes shown by `/changelog`, newest first.
+const SHOWN_CHANGES: usize = 5;
+
+pub struct ChangelogCommand;
+
+#[async_trait]
+impl SlashCommand for ChangelogCommand {
+    fn name(&self) -> &'static str {
+        "changelog"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Show the last changes made to your channel")
+            .dm_permission(false);
+        command
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let guild_id = match command.guild_id {
+        Some(id) => id,
+        None => return respond(ctx, command, "This command only works in a server.", true).await,
+    };
+    let channel_id = match handler.get_user_channel(command.user.id).await {
+        Some(id) => id,
+        None => return respond(ctx, command, "You don't own or co-own a temporary channel.", true).await,
+    };
+
+    let changes = handler.recent_changes(channel_id, SHOWN_CHANGES);
+    if changes.is_empty() {
+        return respond(ctx, command, "Nothing was changed in your channel yet.", true).await;
+    }
+
+    let now = Instant::now();
+    let lines: Vec<String> = changes.iter().map(|change| change.line(now)).collect();
+    let mut embed = create_embed(&handler.guild_config(guild_id).await);
+    embed
+        .title("Recent changes")
+        .description(format!("<#{}>\n\n{}", channel_id, lines.join("\n")));
+    command
+        .create_interaction_response(&ctx.http, |r| {
+            r.kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|d| {
+                    d.set_embed(embed)
+                        .ephemeral(true)
+                        .allowed_mentions(|a| a.empty_parse())
+                })
+        })
+        .await
+}
@@ -0,0 +1,92 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::application::{
+        command::CommandOptionType,
+        interaction::application_command::ApplicationCommandInteraction,
+    },
+    prelude::*,
+};
+use std::time::Duration;
+
+use super::{music::max_bitrate, option_int, respond, SlashCommand};
+use crate::{
+    error::BotError,
+    handler::{ChangeAction, Handler, MAX_BOOST},
+};
+
+pub struct ChannelBoostCommand;
+
+#[async_trait]
+impl SlashCommand for ChannelBoostCommand {
+    fn name(&self) -> &'static str {
+        "channel_boost"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Raise the bitrate of your channel to the server maximum for a while")
+            .dm_permission(false)
+            .create_option(|o| {
+                o.name("seconds")
+                    .description("How long to boost, added to a boost that is already running")
+                    .kind(CommandOptionType::Integer)
+                    .min_int_value(1)
+                    .max_int_value(MAX_BOOST.as_secs())
+                    .required(true)
+            });
+        command
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let guild = match command.guild_id.and_then(|id| id.to_guild_cached(&ctx.cache)) {
+        Some(guild) => guild,
+        None => return respond(ctx, command, "This command only works in a server.", true).await,
+    };
+
+    let channel_id = match handler.get_user_channel(command.user.id).await {
+        Some(id) => id,
+        None => return respond(ctx, command, "You don't own a temporary channel.", true).await,
+    };
+    if handler.is_stage_channel(channel_id) {
+        return respond(ctx, command, "Stage channels can't be boosted.", true).await;
+    }
+
+    let seconds = match option_int(&command.data.options, "seconds") {
+        Some(secs) if (1..=MAX_BOOST.as_secs() as i64).contains(&secs) => secs as u64,
+        _ => return respond(ctx, command, "Boost for 1 to 600 seconds.", true).await,
+    };
+
+    let extending = handler.boost_remaining(channel_id).is_some();
+    let bitrate = max_bitrate(guild.premium_tier) as u32;
+    let remaining = handler
+        .start_boost(ctx, channel_id, bitrate, Duration::from_secs(seconds))
+        .await?;
+    handler.record_change(channel_id, command.user.id, ChangeAction::Boost(Duration::from_secs(seconds)));
+
+    let reply = if extending {
+        format!("Boost extended, {}s remaining.", remaining.as_secs())
+    } else {
+        format!(
+            "⚡ Bitrate boosted to {} kbps for {}s.",
+            bitrate / 1000,
+            remaining.as_secs()
+        )
+    };
+    respond(ctx, command, reply, false).await
+}
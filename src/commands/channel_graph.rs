@@ -0,0 +1,127 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::{
+        application::interaction::{
+            application_command::ApplicationCommandInteraction, InteractionResponseType,
+        },
+        prelude::*,
+    },
+    prelude::*,
+};
+use std::borrow::Cow;
+
+use super::{respond, SlashCommand};
+use crate::{
+    error::BotError,
+    handler::Handler,
+    utils::{
+        create_embed,
+        graph::{mermaid, GraphChannel},
+    },
+};
+
+/// Longest diagram put in the embed, Discord allows 4096 characters in a description.
+const MAX_EMBED_GRAPH_LEN: usize = 4000;
+
+pub struct ChannelGraphCommand;
+
+#[async_trait]
+impl SlashCommand for ChannelGraphCommand {
+    fn name(&self) -> &'static str {
+        "channel_graph"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Draw the temporary channels, their owners and links as a Mermaid diagram")
+            .dm_permission(false);
+        command
+    }
+
+    fn required_permissions(&self) -> Permissions {
+        Permissions::MANAGE_GUILD
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let guild = match command.guild_id.and_then(|id| id.to_guild_cached(&ctx.cache)) {
+        Some(guild) => guild,
+        None => return respond(ctx, command, "This command only works in a server.", true).await,
+    };
+    let config = handler.guild_config(guild.id).await;
+
+    let channel_name = |id: ChannelId| match guild.channels.get(&id) {
+        Some(Channel::Guild(gc)) => gc.name.clone(),
+        _ => id.to_string(),
+    };
+    let mut channels = Vec::new();
+    for channel in guild.channels.values() {
+        let Channel::Guild(gc) = channel else { continue };
+        let Some(owner_id) = handler.channel_owner(gc.id) else { continue };
+        let owner_name = guild
+            .members
+            .get(&owner_id)
+            .map_or_else(|| owner_id.to_string(), |m| m.display_name().into_owned());
+        let mirrored = config.mirror_guild_id.is_some()
+            && !handler.in_ghost_period(gc.id)
+            && !handler.announce_opted_out(owner_id).await;
+        channels.push(GraphChannel {
+            id: gc.id,
+            name: gc.name.clone(),
+            category: gc.parent_id.map(|id| (id, channel_name(id))),
+            owner: (owner_id, owner_name),
+            text_channel: handler.text_channel(gc.id).map(|id| (id, channel_name(id))),
+            merged: handler.merged_channels(gc.id),
+            mirrored,
+            pending_deletion: handler.deletion_pending(gc.id),
+        });
+    }
+    if channels.is_empty() {
+        return respond(ctx, command, "There are no temporary channels right now.", true).await;
+    }
+    channels.sort_by_key(|c| c.id);
+
+    let mirror_name = config.mirror_guild_id.map(|id| {
+        let name = ctx.cache.guild_field(id, |g| g.name.clone()).unwrap_or_else(|| id.to_string());
+        (id, name)
+    });
+    let graph = mermaid(&channels, mirror_name.as_ref().map(|(id, name)| (*id, name.as_str())));
+
+    let mut embed = create_embed(&config);
+    embed.title("Channel graph");
+    if graph.len() <= MAX_EMBED_GRAPH_LEN {
+        embed.description(format!("```mermaid\n{}```", graph));
+    } else {
+        embed.description("The diagram is too big for an embed, render the attached file instead.");
+    }
+    embed.footer(|f| f.text(format!("{} temporary channel(s)", channels.len())));
+
+    command
+        .create_interaction_response(&ctx.http, |r| {
+            r.kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|d| {
+                    d.set_embed(embed)
+                        .add_file(AttachmentType::Bytes {
+                            data: Cow::Owned(graph.into_bytes()),
+                            filename: "channel_graph.mmd".to_string(),
+                        })
+                        .ephemeral(true)
+                })
+        })
+        .await
+}
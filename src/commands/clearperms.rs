@@ -0,0 +1,84 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::{
+        application::interaction::application_command::ApplicationCommandInteraction,
+        channel::{Channel, PermissionOverwriteType},
+    },
+    prelude::*,
+};
+use tracing::debug;
+
+use super::{respond, SlashCommand};
+use crate::{
+    error::BotError,
+    handler::{ChangeAction, Handler},
+    utils::{create_permission, delete_permission, AuditReason},
+};
+
+const REASON: AuditReason = AuditReason::Command("/clearperms");
+
+pub struct ClearPermsCommand;
+
+#[async_trait]
+impl SlashCommand for ClearPermsCommand {
+    fn name(&self) -> &'static str {
+        "clearperms"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Reset your channel's permissions to the defaults")
+            .dm_permission(false);
+        command
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let guild_id = match command.guild_id {
+        Some(id) => id,
+        None => return respond(ctx, command, "This command only works in a server.", true).await,
+    };
+
+    let channel_id = match handler.get_user_channel(command.user.id).await {
+        Some(id) => id,
+        None => return respond(ctx, command, "You don't own a temporary channel.", true).await,
+    };
+
+    let overwrites = match channel_id.to_channel(&ctx).await? {
+        Channel::Guild(gc) => gc.permission_overwrites,
+        _ => return Ok(()),
+    };
+
+    // Only member overwrites go, role overwrites are the server's and the bot keeps its own
+    let bot_id = ctx.cache.current_user_id();
+    for overwrite in overwrites {
+        match overwrite.kind {
+            PermissionOverwriteType::Member(user_id) if user_id != bot_id => {}
+            _ => continue,
+        }
+        debug!("Overwrite {:?} verwijderd van kanaal {}", overwrite.kind, channel_id);
+        delete_permission(&ctx.http, channel_id, overwrite.kind, &REASON).await?;
+    }
+
+    for overwrite in Handler::default_overwrites(guild_id, command.user.id, bot_id) {
+        create_permission(&ctx.http, channel_id, &overwrite, &REASON).await?;
+    }
+
+    handler.record_change(channel_id, command.user.id, ChangeAction::ResetPermissions);
+    respond(ctx, command, "Channel permissions have been reset to the defaults.", false).await
+}
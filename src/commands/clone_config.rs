@@ -0,0 +1,163 @@
+use serenity::{
+    async_trait,
+    builder::{CreateApplicationCommand, CreateEmbed},
+    model::{
+        application::{command::CommandOptionType, interaction::application_command::ApplicationCommandInteraction},
+        id::{GuildId, UserId},
+        permissions::Permissions,
+    },
+    prelude::*,
+};
+use super::{confirm, finish, option_str, respond, Prompt, SlashCommand};
+use crate::{
+    config::GuildConfig,
+    error::BotError,
+    handler::{Handler, DEFAULT_OWNERSHIP_EXPIRY_SECS},
+    utils::{create_embed, late_night_label},
+};
+
+pub struct CloneConfigCommand;
+
+#[async_trait]
+impl SlashCommand for CloneConfigCommand {
+    fn name(&self) -> &'static str {
+        "clone_config"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Copy the settings of another server you manage to this one")
+            .dm_permission(false)
+            .create_option(|o| {
+                o.name("source_guild")
+                    .description("ID of the server to copy from")
+                    .kind(CommandOptionType::String)
+                    .required(true)
+            });
+        command
+    }
+
+    fn required_permissions(&self) -> Permissions {
+        Permissions::MANAGE_GUILD
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let guild_id = match command.guild_id {
+        Some(id) => id,
+        None => return respond(ctx, command, "This command only works in a server.", true).await,
+    };
+
+    let source_id = match option_str(&command.data.options, "source_guild")
+        .and_then(|id| id.trim().parse::<u64>().ok())
+    {
+        Some(id) => GuildId(id),
+        None => return respond(ctx, command, "That is not a valid server ID.", true).await,
+    };
+    if source_id == guild_id {
+        return respond(ctx, command, "Pick a different server to copy from.", true).await;
+    }
+
+    // Discord only checks MANAGE_GUILD in this guild, the source is up to us
+    if !manages_guild(ctx, source_id, command.user.id).await {
+        return respond(
+            ctx,
+            command,
+            "You need Manage Server in the source server, and the bot has to be in it.",
+            true,
+        )
+        .await;
+    }
+
+    let cloned = handler.guild_config(source_id).await.portable();
+    let source_name = source_id.name(&ctx.cache).unwrap_or_else(|| source_id.to_string());
+    let mut embed = create_embed(&handler.guild_config(guild_id).await);
+    preview(&mut embed, &source_name, &cloned);
+
+    let Some(interaction) = confirm(ctx, command, "Clone", Prompt::embed(embed)).await? else { return Ok(()) };
+    handler
+        .update_guild_config(guild_id, |c| *c = cloned)
+        .await;
+    handler
+        .audit_log(
+            ctx,
+            guild_id,
+            &format!("<@{}> copied the config of **{}**", command.user.id, source_name),
+        )
+        .await;
+    let content = format!(
+        "Settings copied from **{}**. Run `/setup` to pick the channels for this server.",
+        source_name
+    );
+    finish(ctx, &interaction, content).await
+}
+
+pub(crate) async fn manages_guild(
+    ctx: &Context,
+    guild_id: GuildId,
+    user_id: UserId,
+) -> bool {
+    if guild_id.to_guild_cached(&ctx.cache).is_none() {
+        return false;
+    }
+    match guild_id.member(ctx, user_id).await {
+        Ok(member) => member
+            .permissions(&ctx.cache)
+            .is_ok_and(|p| p.manage_guild() || p.administrator()),
+        Err(_) => false,
+    }
+}
+
+fn preview<'a>(embed: &'a mut CreateEmbed, source_name: &str, config: &GuildConfig) -> &'a mut CreateEmbed {
+    let on_off = |enabled: bool| if enabled { "on" } else { "off" };
+    let filter = if config.name_filter.is_empty() {
+        "empty".to_string()
+    } else {
+        format!("{} word(s)", config.name_filter.len())
+    };
+
+    embed
+        .title(format!("Copy settings from {}", source_name))
+        .field("Activity names", on_off(config.use_activity_name), true)
+        .field("Popular game names", on_off(config.use_popular_game_name), true)
+        .field("Name filter", filter, true)
+        .field("Paired text channels", on_off(config.paired_text_channel), true)
+        .field("Archive all text channels", on_off(config.archive_text_channel), true)
+        .field("Join sounds", on_off(config.join_sound_enabled), true)
+        .field("Session summaries", on_off(config.session_summary), true)
+        .field("Access log", on_off(config.access_log), true)
+        .field("Reorder on delete", on_off(config.reorder_on_delete), true)
+        .field(
+            "Ownership expiry",
+            format!("{}s", config.ownership_expiry_secs.unwrap_or(DEFAULT_OWNERSHIP_EXPIRY_SECS)),
+            true,
+        )
+        .field("Ghost period", format!("{}s", config.ghost_period_secs), true)
+        .field(
+            "AFK kick",
+            config.afk_kick_threshold_secs.map_or_else(|| "off".to_string(), |secs| format!("{}s", secs)),
+            true,
+        )
+        .field("Channel tags", format!("{} tag(s)", config.allowed_tags.len()), true)
+        .field("Late-night mode", late_night_label(config), true)
+        .footer(|f| {
+            f.text(
+                "Channels, the log webhook and the music bot are cleared, \
+                 run /setup and /config again afterwards. This replaces the current settings.",
+            )
+        })
+}
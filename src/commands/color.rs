@@ -0,0 +1,114 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::{
+        application::{
+            command::CommandOptionType,
+            interaction::{
+                application_command::ApplicationCommandInteraction, InteractionResponseType,
+            },
+        },
+        permissions::Permissions,
+    },
+    prelude::*,
+};
+
+use super::{option_str, respond, SlashCommand};
+use crate::{error::BotError, handler::Handler, utils::create_embed};
+
+pub struct ColorCommand;
+
+#[async_trait]
+impl SlashCommand for ColorCommand {
+    fn name(&self) -> &'static str {
+        "color"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Set the accent color of the bot's embeds in this server")
+            .dm_permission(false)
+            .create_option(|o| {
+                o.name("hex")
+                    .description("Color as #RRGGBB, or `default` for Discord's color")
+                    .kind(CommandOptionType::String)
+                    .required(true)
+            });
+        command
+    }
+
+    fn required_permissions(&self) -> Permissions {
+        Permissions::MANAGE_GUILD
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+/// Parses `#RRGGBB` or `RRGGBB` into `0xRRGGBB`.
+pub fn parse_hex_color(input: &str) -> Option<u32> {
+    let hex = input.trim().trim_start_matches('#');
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    u32::from_str_radix(hex, 16).ok()
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let guild_id = match command.guild_id {
+        Some(id) => id,
+        None => return respond(ctx, command, "This command only works in a server.", true).await,
+    };
+
+    let input = option_str(&command.data.options, "hex").unwrap_or_default();
+    let color = if input.trim().eq_ignore_ascii_case("default") {
+        0
+    } else {
+        match parse_hex_color(input) {
+            Some(color) => color,
+            None => return respond(ctx, command, "Use a hex color like `#5865F2`.", true).await,
+        }
+    };
+
+    handler
+        .update_guild_config(guild_id, |c| c.embed_color = color)
+        .await;
+
+    // Reply with an embed so the new color is visible right away
+    let mut embed = create_embed(&handler.guild_config(guild_id).await);
+    embed.description(match color {
+        0 => "Embeds will use Discord's default color.".to_string(),
+        color => format!("Embeds will use `#{:06X}`.", color),
+    });
+    command
+        .create_interaction_response(&ctx.http, |r| {
+            r.kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|d| d.set_embed(embed).ephemeral(true))
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_six_digit_hex() {
+        assert_eq!(parse_hex_color("#5865F2"), Some(0x5865F2));
+        assert_eq!(parse_hex_color("ff0000"), Some(0xFF0000));
+        assert_eq!(parse_hex_color("#fff"), None);
+        assert_eq!(parse_hex_color("+12345"), None);
+        assert_eq!(parse_hex_color("#12345G"), None);
+    }
+}
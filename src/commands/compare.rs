@@ -0,0 +1,187 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::{
+        application::{
+            command::CommandOptionType,
+            interaction::{
+                application_command::ApplicationCommandInteraction, InteractionResponseType,
+            },
+        },
+        channel::{Channel, ChannelType, GuildChannel, PermissionOverwriteType},
+        id::UserId,
+        permissions::Permissions,
+    },
+    prelude::*,
+};
+
+use super::{option_channel, respond, SlashCommand};
+use crate::{error::BotError, handler::Handler, utils::create_embed};
+
+/// Shown for the bot's own properties of channels it doesn't manage.
+const UNMANAGED: &str = "unmanaged";
+
+/// Embed color when the channels differ, Discord's red.
+const DIFFERENT_COLOR: u32 = 0xED4245;
+
+pub struct CompareCommand;
+
+#[async_trait]
+impl SlashCommand for CompareCommand {
+    fn name(&self) -> &'static str {
+        "compare"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Show the settings of two voice channels side by side")
+            .dm_permission(false);
+        for name in ["channel1", "channel2"] {
+            command.create_option(|o| {
+                o.name(name)
+                    .description("Voice channel to compare")
+                    .kind(CommandOptionType::Channel)
+                    .channel_types(&[ChannelType::Voice, ChannelType::Stage])
+                    .required(true)
+            });
+        }
+        command
+    }
+
+    fn required_permissions(&self) -> Permissions {
+        Permissions::MANAGE_CHANNELS
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+/// The two columns as ```diff blocks, rows that differ start with `-` so Discord shows
+/// them in red. Both sides have the same properties in the same order.
+pub fn diff_columns(left: &[(&str, String)], right: &[(&str, String)]) -> (String, String, bool) {
+    let mut columns = (String::from("```diff\n"), String::from("```diff\n"));
+    let mut differs = false;
+    for ((name, a), (_, b)) in left.iter().zip(right) {
+        let marker = if a == b { ' ' } else { '-' };
+        differs |= a != b;
+        columns.0.push_str(&format!("{} {}: {}\n", marker, name, a));
+        columns.1.push_str(&format!("{} {}: {}\n", marker, name, b));
+    }
+    columns.0.push_str("```");
+    columns.1.push_str("```");
+    (columns.0, columns.1, differs)
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let options = &command.data.options;
+    let (first, second) = match (option_channel(options, "channel1"), option_channel(options, "channel2")) {
+        (Some(first), Some(second)) => (first, second),
+        _ => return respond(ctx, command, "Pick two voice channels.", true).await,
+    };
+
+    // Uses the cache when it can, channels it doesn't have come from the API
+    let (first_channel, second_channel) = match (first.to_channel(ctx).await?, second.to_channel(ctx).await?) {
+        (Channel::Guild(a), Channel::Guild(b)) => (a, b),
+        _ => return respond(ctx, command, "Both have to be server channels.", true).await,
+    };
+    let left = properties(handler, &first_channel);
+    let right = properties(handler, &second_channel);
+    let (left_column, right_column, differs) = diff_columns(&left, &right);
+
+    let guild_config = handler.guild_config(first_channel.guild_id).await;
+    let mut embed = create_embed(&guild_config);
+    embed
+        .title("Channel comparison")
+        .field(&first_channel.name, left_column, true)
+        .field(&second_channel.name, right_column, true);
+    if differs {
+        embed.colour(DIFFERENT_COLOR).footer(|f| f.text("Rows with a - differ."));
+    } else {
+        embed.footer(|f| f.text("The channels are set up the same."));
+    }
+
+    command
+        .create_interaction_response(&ctx.http, |r| {
+            r.kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|d| d.set_embed(embed).ephemeral(true))
+        })
+        .await
+}
+
+fn properties(handler: &Handler, channel: &GuildChannel) -> Vec<(&'static str, String)> {
+    let everyone = PermissionOverwriteType::Role(channel.guild_id.0.into());
+    let locked = channel
+        .permission_overwrites
+        .iter()
+        .any(|o| o.kind == everyone && o.deny.contains(Permissions::CONNECT));
+    let yes_no = |value: bool| if value { "yes" } else { "no" }.to_string();
+
+    let mut properties = vec![
+        ("Name", channel.name.clone()),
+        ("User limit", channel.user_limit.filter(|l| *l > 0).map_or("none".to_string(), |l| l.to_string())),
+        ("Bitrate", channel.bitrate.map_or("-".to_string(), |b| format!("{} kbps", b / 1000))),
+        ("Locked", yes_no(locked)),
+        ("Allowed members", allowed_members(channel, handler.channel_owner(channel.id)).to_string()),
+    ];
+
+    let owner = match handler.channel_owner(channel.id) {
+        Some(owner) => owner,
+        None => {
+            for name in ["Owner", "Co-owners", "Ghosted", "Tags", "Mode"] {
+                properties.push((name, UNMANAGED.to_string()));
+            }
+            return properties;
+        }
+    };
+    let co_owners = handler.co_owners(channel.id);
+    let tags = handler.channel_tags(channel.id);
+    properties.extend([
+        // Code blocks don't render mentions
+        ("Owner", owner.to_string()),
+        ("Co-owners", if co_owners.is_empty() { "none".to_string() } else { co_owners.len().to_string() }),
+        ("Ghosted", yes_no(handler.in_ghost_period(channel.id))),
+        ("Tags", if tags.is_empty() { "none".to_string() } else { tags.join(", ") }),
+        ("Mode", handler.channel_mode(channel.id).map_or(UNMANAGED.to_string(), |m| format!("{:?}", m))),
+    ]);
+    properties
+}
+
+/// Members let in by a member overwrite, like `/visit` grants, the owner left out.
+fn allowed_members(channel: &GuildChannel, owner: Option<UserId>) -> usize {
+    channel
+        .permission_overwrites
+        .iter()
+        .filter(|o| match o.kind {
+            PermissionOverwriteType::Member(id) => Some(id) != owner && o.allow.contains(Permissions::CONNECT),
+            _ => false,
+        })
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn marks_rows_that_differ() {
+        let left = [("Name", "Games".to_string()), ("Locked", "no".to_string())];
+        let right = [("Name", "Study".to_string()), ("Locked", "no".to_string())];
+        let (a, b, differs) = diff_columns(&left, &right);
+        assert!(differs);
+        assert_eq!(a, "```diff\n- Name: Games\n  Locked: no\n```");
+        assert_eq!(b, "```diff\n- Name: Study\n  Locked: no\n```");
+
+        assert!(!diff_columns(&left, &left).2);
+    }
+}
@@ -0,0 +1,856 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::{
+        application::{
+            command::CommandOptionType,
+            interaction::application_command::ApplicationCommandInteraction,
+        },
+        channel::ChannelType,
+        permissions::Permissions,
+    },
+    prelude::*,
+};
+
+use super::{
+    option_bool, option_channel, option_int, option_role, option_str, option_user, respond, tag::parse_allowed_tags,
+    SlashCommand,
+};
+use crate::{
+    config::{GuildConfig, LateNightConfig},
+    error::BotError,
+    handler::{Handler, DEFAULT_OWNERSHIP_EXPIRY_SECS},
+    utils::{format_utc_offset, parse_utc_offset, UTC_OFFSET_RANGE},
+};
+
+/// Bounds for `/config ownership_expiry`, in seconds.
+const MIN_OWNERSHIP_EXPIRY_SECS: u64 = 60;
+const MAX_OWNERSHIP_EXPIRY_SECS: u64 = 24 * 60 * 60;
+
+/// Bounds for `/config afk_kick`, in seconds. Members are checked every 5 minutes, so
+/// anything shorter wouldn't be kept to.
+const MIN_AFK_KICK_SECS: u64 = 5 * 60;
+const MAX_AFK_KICK_SECS: u64 = 24 * 60 * 60;
+
+/// Longest `/config ghost_period`, in seconds.
+const MAX_GHOST_PERIOD_SECS: u32 = 10 * 60;
+
+/// Bounds for `/config late_night`.
+const MAX_LATE_NIGHT_CHANNELS: u32 = 500;
+const MAX_LATE_NIGHT_DELETION_DELAY_SECS: u64 = 60 * 60;
+
+/// Most channels `/config set_role_quota` lets a role own at once.
+const MAX_ROLE_QUOTA: u32 = 10;
+
+const WEBHOOK_PREFIXES: [&str; 2] = [
+    "https://discord.com/api/webhooks/",
+    "https://discordapp.com/api/webhooks/",
+];
+
+pub struct ConfigCommand;
+
+#[async_trait]
+impl SlashCommand for ConfigCommand {
+    fn name(&self) -> &'static str {
+        "config"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Configure the bot for this server")
+            .dm_permission(false)
+            .create_option(|o| {
+                o.name("activity_name")
+                    .description("Name new channels after the owner's current game")
+                    .kind(CommandOptionType::SubCommand)
+                    .create_sub_option(|s| {
+                        s.name("enabled")
+                            .description("Use activity-based names")
+                            .kind(CommandOptionType::Boolean)
+                            .required(true)
+                    })
+            })
+            .create_option(|o| {
+                o.name("popular_game_name")
+                    .description("Name new channels after the game most members are playing")
+                    .kind(CommandOptionType::SubCommand)
+                    .create_sub_option(|s| {
+                        s.name("enabled")
+                            .description("Use the most played game")
+                            .kind(CommandOptionType::Boolean)
+                            .required(true)
+                    })
+            })
+            .create_option(|o| {
+                o.name("add_filter")
+                    .description("Ban a word from activity-based channel names")
+                    .kind(CommandOptionType::SubCommand)
+                    .create_sub_option(|s| {
+                        s.name("word")
+                            .description("Word or phrase to ban")
+                            .kind(CommandOptionType::String)
+                            .required(true)
+                    })
+            })
+            .create_option(|o| {
+                o.name("remove_filter")
+                    .description("Remove a word from the name filter")
+                    .kind(CommandOptionType::SubCommand)
+                    .create_sub_option(|s| {
+                        s.name("word")
+                            .description("Word or phrase to remove")
+                            .kind(CommandOptionType::String)
+                            .required(true)
+                    })
+            })
+            .create_option(|o| {
+                o.name("list_filters")
+                    .description("Show the banned words")
+                    .kind(CommandOptionType::SubCommand)
+            })
+            .create_option(|o| {
+                o.name("text_channel")
+                    .description("Create a text channel next to every temporary channel")
+                    .kind(CommandOptionType::SubCommand)
+                    .create_sub_option(|s| {
+                        s.name("enabled")
+                            .description("Create paired text channels")
+                            .kind(CommandOptionType::Boolean)
+                            .required(true)
+                    })
+            })
+            .create_option(|o| {
+                o.name("archive")
+                    .description("Where ended sessions' text channels are archived")
+                    .kind(CommandOptionType::SubCommand)
+                    .create_sub_option(|s| {
+                        s.name("category")
+                            .description("Archive category, leave empty to turn archiving off")
+                            .kind(CommandOptionType::Channel)
+                            .channel_types(&[ChannelType::Category])
+                    })
+                    .create_sub_option(|s| {
+                        s.name("auto")
+                            .description("Archive every text channel, not just ones marked with /archive")
+                            .kind(CommandOptionType::Boolean)
+                    })
+            })
+            .create_option(|o| {
+                o.name("join_sound")
+                    .description("Play a sound when someone joins or leaves a temporary channel")
+                    .kind(CommandOptionType::SubCommand)
+                    .create_sub_option(|s| {
+                        s.name("enabled")
+                            .description("Play join/leave sounds")
+                            .kind(CommandOptionType::Boolean)
+                            .required(true)
+                    })
+            })
+            .create_option(|o| {
+                o.name("session_summary")
+                    .description("Post a summary when a session ends")
+                    .kind(CommandOptionType::SubCommand)
+                    .create_sub_option(|s| {
+                        s.name("enabled")
+                            .description("Post session summaries")
+                            .kind(CommandOptionType::Boolean)
+                            .required(true)
+                    })
+            })
+            .create_option(|o| {
+                o.name("access_log")
+                    .description("Post every join and leave in the channel's text channel")
+                    .kind(CommandOptionType::SubCommand)
+                    .create_sub_option(|s| {
+                        s.name("enabled")
+                            .description("Log joins and leaves")
+                            .kind(CommandOptionType::Boolean)
+                            .required(true)
+                    })
+            })
+            .create_option(|o| {
+                o.name("reorder_on_delete")
+                    .description("Close the gaps deleted channels leave in their category")
+                    .kind(CommandOptionType::SubCommand)
+                    .create_sub_option(|s| {
+                        s.name("enabled")
+                            .description("Move the remaining channels up")
+                            .kind(CommandOptionType::Boolean)
+                            .required(true)
+                    })
+            })
+            .create_option(|o| {
+                o.name("log_channel")
+                    .description("Send audit logs to a channel, leave empty to turn off")
+                    .kind(CommandOptionType::SubCommand)
+                    .create_sub_option(|s| {
+                        s.name("channel")
+                            .description("Text channel for audit logs")
+                            .kind(CommandOptionType::Channel)
+                            .channel_types(&[ChannelType::Text])
+                    })
+            })
+            .create_option(|o| {
+                o.name("announce_channel")
+                    .description("Channel for other servers' /mirror lists, leave empty to turn off")
+                    .kind(CommandOptionType::SubCommand)
+                    .create_sub_option(|s| {
+                        s.name("channel")
+                            .description("Text channel for the lists")
+                            .kind(CommandOptionType::Channel)
+                            .channel_types(&[ChannelType::Text])
+                    })
+            })
+            .create_option(|o| {
+                o.name("diagnostics_category")
+                    .description("Category /diagnostics tests in, leave empty for the creator channel's")
+                    .kind(CommandOptionType::SubCommand)
+                    .create_sub_option(|s| {
+                        s.name("category")
+                            .description("Category for the test channel")
+                            .kind(CommandOptionType::Channel)
+                            .channel_types(&[ChannelType::Category])
+                    })
+            })
+            .create_option(|o| {
+                o.name("log_webhook")
+                    .description("Send audit logs through a webhook instead, leave empty to turn off")
+                    .kind(CommandOptionType::SubCommand)
+                    .create_sub_option(|s| {
+                        s.name("url")
+                            .description("Discord webhook URL")
+                            .kind(CommandOptionType::String)
+                    })
+            })
+            .create_option(|o| {
+                o.name("ownership_expiry")
+                    .description("Seconds before a channel nobody else joined is deleted")
+                    .kind(CommandOptionType::SubCommand)
+                    .create_sub_option(|s| {
+                        s.name("seconds")
+                            .description("Idle time in seconds, leave empty for the default (300)")
+                            .kind(CommandOptionType::Integer)
+                            .min_int_value(MIN_OWNERSHIP_EXPIRY_SECS)
+                            .max_int_value(MAX_OWNERSHIP_EXPIRY_SECS)
+                    })
+            })
+            .create_option(|o| {
+                o.name("afk_kick")
+                    .description("Move members who stay muted and deafened to the waiting room")
+                    .kind(CommandOptionType::SubCommand)
+                    .create_sub_option(|s| {
+                        s.name("seconds")
+                            .description("How long they can stay muted and deafened, leave empty to turn it off")
+                            .kind(CommandOptionType::Integer)
+                            .min_int_value(MIN_AFK_KICK_SECS)
+                            .max_int_value(MAX_AFK_KICK_SECS)
+                    })
+            })
+            .create_option(|o| {
+                o.name("ghost_period")
+                    .description("Keep new channels hidden for a while so owners can set them up")
+                    .kind(CommandOptionType::SubCommand)
+                    .create_sub_option(|s| {
+                        s.name("seconds")
+                            .description("Seconds a new channel stays hidden, 0 turns it off")
+                            .kind(CommandOptionType::Integer)
+                            .min_int_value(0)
+                            .max_int_value(MAX_GHOST_PERIOD_SECS)
+                            .required(true)
+                    })
+            })
+            .create_option(|o| {
+                o.name("allowed_tags")
+                    .description("Tags owners can give their channel with /tag, leave empty to remove them all")
+                    .kind(CommandOptionType::SubCommand)
+                    .create_sub_option(|s| {
+                        s.name("tags")
+                            .description("Comma separated, e.g. Gaming, Study, Chill")
+                            .kind(CommandOptionType::String)
+                    })
+            })
+            .create_option(|o| {
+                o.name("forum")
+                    .description("Forum where every new post is a temporary session, leave empty to turn off")
+                    .kind(CommandOptionType::SubCommand)
+                    .create_sub_option(|s| {
+                        s.name("channel")
+                            .description("The forum channel")
+                            .kind(CommandOptionType::Channel)
+                            .channel_types(&[ChannelType::Forum])
+                    })
+                    .create_sub_option(|s| {
+                        s.name("tags")
+                            .description("Forum tags new posts get, comma separated")
+                            .kind(CommandOptionType::String)
+                    })
+            })
+            .create_option(|o| {
+                o.name("late_night")
+                    .description("Fewer channels and slower cleanup at night, leave empty to turn off")
+                    .kind(CommandOptionType::SubCommand)
+                    .create_sub_option(|s| {
+                        s.name("start_hour")
+                            .description("Hour it starts, in the server's timezone (/config utc_offset)")
+                            .kind(CommandOptionType::Integer)
+                            .min_int_value(0)
+                            .max_int_value(23)
+                    })
+                    .create_sub_option(|s| {
+                        s.name("end_hour")
+                            .description("Hour it ends")
+                            .kind(CommandOptionType::Integer)
+                            .min_int_value(0)
+                            .max_int_value(23)
+                    })
+                    .create_sub_option(|s| {
+                        s.name("max_channels")
+                            .description("Temporary channels the server can have at once")
+                            .kind(CommandOptionType::Integer)
+                            .min_int_value(1)
+                            .max_int_value(MAX_LATE_NIGHT_CHANNELS)
+                    })
+                    .create_sub_option(|s| {
+                        s.name("deletion_delay")
+                            .description("Seconds an empty channel is kept")
+                            .kind(CommandOptionType::Integer)
+                            .min_int_value(0)
+                            .max_int_value(MAX_LATE_NIGHT_DELETION_DELAY_SECS)
+                    })
+            })
+            .create_option(|o| {
+                o.name("utc_offset")
+                    .description("The server's timezone, for late-night mode")
+                    .kind(CommandOptionType::SubCommand)
+                    .create_sub_option(|s| {
+                        s.name("offset")
+                            .description("Offset from UTC, e.g. +02:00 or -5")
+                            .kind(CommandOptionType::String)
+                            .required(true)
+                    })
+            })
+            .create_option(|o| {
+                o.name("music_bot")
+                    .description("Music bot that /music brings along, leave empty to turn off")
+                    .kind(CommandOptionType::SubCommand)
+                    .create_sub_option(|s| {
+                        s.name("bot")
+                            .description("The music bot")
+                            .kind(CommandOptionType::User)
+                    })
+            })
+            .create_option(|o| {
+                o.name("set_role_quota")
+                    .description("Let members with a role own more channels at once")
+                    .kind(CommandOptionType::SubCommand)
+                    .create_sub_option(|s| {
+                        s.name("role")
+                            .description("The role")
+                            .kind(CommandOptionType::Role)
+                            .required(true)
+                    })
+                    .create_sub_option(|s| {
+                        s.name("limit")
+                            .description("Channels they can own, 0 removes the role's quota")
+                            .kind(CommandOptionType::Integer)
+                            .min_int_value(0)
+                            .max_int_value(MAX_ROLE_QUOTA)
+                            .required(true)
+                    })
+            })
+            .create_option(|o| {
+                o.name("admin_user")
+                    .description("Who can manage the bot from DMs with /config_summary, leave empty to turn off")
+                    .kind(CommandOptionType::SubCommand)
+                    .create_sub_option(|s| {
+                        s.name("user")
+                            .description("The admin")
+                            .kind(CommandOptionType::User)
+                    })
+            })
+            .create_option(|o| {
+                o.name("debug_role")
+                    .description("Who can inspect the bot's voice state cache with /debug_voice_states")
+                    .kind(CommandOptionType::SubCommand)
+                    .create_sub_option(|s| {
+                        s.name("role")
+                            .description("The role, leave empty to leave it to the bot owner")
+                            .kind(CommandOptionType::Role)
+                    })
+            })
+            .create_option(|o| {
+                o.name("test_mode")
+                    .description("Only handle voice events of one user while testing, leave empty to turn off")
+                    .kind(CommandOptionType::SubCommand)
+                    .create_sub_option(|s| {
+                        s.name("user")
+                            .description("The test user")
+                            .kind(CommandOptionType::User)
+                    })
+            });
+        command
+    }
+
+    fn required_permissions(&self) -> Permissions {
+        Permissions::MANAGE_GUILD
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let guild_id = match command.guild_id {
+        Some(id) => id,
+        None => return respond(ctx, command, "This command only works in a server.", true).await,
+    };
+
+    let subcommand = match command.data.options.first() {
+        Some(s) => s,
+        None => return Ok(()),
+    };
+    let options = &subcommand.options;
+
+    let reply = match subcommand.name.as_str() {
+        "activity_name" => {
+            let enabled = option_bool(options, "enabled").unwrap_or(false);
+            handler
+                .update_guild_config(guild_id, |c| c.use_activity_name = enabled)
+                .await;
+            if enabled {
+                "Channels will now be named after the owner's current game.".to_string()
+            } else {
+                "Channels will now be named after the owner's display name.".to_string()
+            }
+        }
+        "popular_game_name" => {
+            let enabled = option_bool(options, "enabled").unwrap_or(false);
+            handler
+                .update_guild_config(guild_id, |c| c.use_popular_game_name = enabled)
+                .await;
+            if enabled {
+                "Channels will now be named after the game most members are playing.".to_string()
+            } else {
+                "Channels will no longer be named after the most played game.".to_string()
+            }
+        }
+        "add_filter" => {
+            let word = option_str(options, "word").unwrap_or_default().trim().to_lowercase();
+            if word.is_empty() {
+                return respond(ctx, command, "Please provide a word to ban.", true).await;
+            }
+            let added = handler
+                .update_guild_config(guild_id, |c| {
+                    if c.name_filter.contains(&word) {
+                        false
+                    } else {
+                        c.name_filter.push(word.clone());
+                        true
+                    }
+                })
+                .await;
+            if added {
+                format!("`{}` added to the name filter.", word)
+            } else {
+                format!("`{}` is already in the name filter.", word)
+            }
+        }
+        "remove_filter" => {
+            let word = option_str(options, "word").unwrap_or_default().trim().to_lowercase();
+            let removed = handler
+                .update_guild_config(guild_id, |c| {
+                    let before = c.name_filter.len();
+                    c.name_filter.retain(|w| *w != word);
+                    c.name_filter.len() != before
+                })
+                .await;
+            if removed {
+                format!("`{}` removed from the name filter.", word)
+            } else {
+                format!("`{}` is not in the name filter.", word)
+            }
+        }
+        "list_filters" => {
+            let config = handler.guild_config(guild_id).await;
+            if config.name_filter.is_empty() {
+                "The name filter is empty.".to_string()
+            } else {
+                let words: Vec<String> = config
+                    .name_filter
+                    .iter()
+                    .map(|w| format!("`{}`", w))
+                    .collect();
+                format!("Banned words: {}", words.join(", "))
+            }
+        }
+        "text_channel" => {
+            let enabled = option_bool(options, "enabled").unwrap_or(false);
+            handler
+                .update_guild_config(guild_id, |c| c.paired_text_channel = enabled)
+                .await;
+            if enabled {
+                "New channels will get a paired text channel.".to_string()
+            } else {
+                "New channels will no longer get a paired text channel.".to_string()
+            }
+        }
+        "archive" => {
+            let category = option_channel(options, "category");
+            let auto = option_bool(options, "auto").unwrap_or(false);
+            handler
+                .update_guild_config(guild_id, |c| {
+                    c.archive_category_id = category;
+                    c.archive_text_channel = auto;
+                })
+                .await;
+            match (category, auto) {
+                (Some(id), true) => format!("All text channels will be archived to <#{}>.", id),
+                (Some(id), false) => {
+                    format!("Text channels marked with `/archive` will be archived to <#{}>.", id)
+                }
+                (None, _) => "Archiving is turned off.".to_string(),
+            }
+        }
+        "join_sound" => {
+            let enabled = option_bool(options, "enabled").unwrap_or(false);
+            handler
+                .update_guild_config(guild_id, |c| c.join_sound_enabled = enabled)
+                .await;
+            if enabled {
+                "A sound will play when someone joins or leaves a channel.".to_string()
+            } else {
+                "Join and leave sounds are turned off.".to_string()
+            }
+        }
+        "session_summary" => {
+            let enabled = option_bool(options, "enabled").unwrap_or(false);
+            handler
+                .update_guild_config(guild_id, |c| c.session_summary = enabled)
+                .await;
+            if enabled {
+                "A summary will be posted when a session ends.".to_string()
+            } else {
+                "Session summaries are turned off.".to_string()
+            }
+        }
+        "access_log" => {
+            let enabled = option_bool(options, "enabled").unwrap_or(false);
+            handler
+                .update_guild_config(guild_id, |c| c.access_log = enabled)
+                .await;
+            if enabled {
+                "Joins and leaves will be posted in the channels' text channels.".to_string()
+            } else {
+                "The access log is turned off.".to_string()
+            }
+        }
+        "reorder_on_delete" => {
+            let enabled = option_bool(options, "enabled").unwrap_or(false);
+            handler
+                .update_guild_config(guild_id, |c| c.reorder_on_delete = enabled)
+                .await;
+            if enabled {
+                "Channels will move up to fill the gap when a temporary channel is deleted.".to_string()
+            } else {
+                "Channels will keep their positions when a temporary channel is deleted.".to_string()
+            }
+        }
+        "log_channel" => {
+            let channel = option_channel(options, "channel");
+            handler
+                .update_guild_config(guild_id, |c| c.log_channel_id = channel)
+                .await;
+            match channel {
+                Some(id) => format!("Audit logs will be sent to <#{}>.", id),
+                None => "Audit logs to a channel are turned off.".to_string(),
+            }
+        }
+        "announce_channel" => {
+            let channel = option_channel(options, "channel");
+            handler
+                .update_guild_config(guild_id, |c| c.announce_channel_id = channel)
+                .await;
+            match channel {
+                Some(id) => format!("Servers that /mirror to this one are listed in <#{}>.", id),
+                None => "Other servers' channel lists won't be posted here anymore.".to_string(),
+            }
+        }
+        "diagnostics_category" => {
+            let category = option_channel(options, "category");
+            handler
+                .update_guild_config(guild_id, |c| c.diagnostics_category_id = category)
+                .await;
+            match category {
+                Some(id) => format!("/diagnostics will test in <#{}>.", id),
+                None => "/diagnostics will test in the creator channel's category.".to_string(),
+            }
+        }
+        "log_webhook" => {
+            let url = option_str(options, "url").map(|u| u.trim().to_string());
+            if let Some(url) = &url {
+                if !WEBHOOK_PREFIXES.iter().any(|p| url.starts_with(p)) {
+                    return respond(ctx, command, "That is not a Discord webhook URL.", true).await;
+                }
+            }
+            let enabled = url.is_some();
+            handler
+                .update_guild_config(guild_id, |c| c.log_webhook_url = url)
+                .await;
+            if enabled {
+                "Audit logs will be sent through the webhook.".to_string()
+            } else {
+                "Webhook logging is turned off.".to_string()
+            }
+        }
+        "ownership_expiry" => {
+            let seconds = option_int(options, "seconds").map(|s| {
+                s.clamp(MIN_OWNERSHIP_EXPIRY_SECS as i64, MAX_OWNERSHIP_EXPIRY_SECS as i64) as u64
+            });
+            handler
+                .update_guild_config(guild_id, |c| c.ownership_expiry_secs = seconds)
+                .await;
+            format!(
+                "Channels nobody else joins are deleted after {} seconds once the owner is gone.",
+                seconds.unwrap_or(DEFAULT_OWNERSHIP_EXPIRY_SECS)
+            )
+        }
+        "afk_kick" => {
+            let seconds = option_int(options, "seconds")
+                .map(|s| s.clamp(MIN_AFK_KICK_SECS as i64, MAX_AFK_KICK_SECS as i64) as u64);
+            handler
+                .update_guild_config(guild_id, |c| c.afk_kick_threshold_secs = seconds)
+                .await;
+            match seconds {
+                Some(seconds) => format!(
+                    "Members muted and deafened for over {} seconds are moved to the waiting room.",
+                    seconds
+                ),
+                None => "Muted and deafened members are no longer moved.".to_string(),
+            }
+        }
+        "ghost_period" => {
+            let seconds = option_int(options, "seconds")
+                .unwrap_or(0)
+                .clamp(0, MAX_GHOST_PERIOD_SECS as i64) as u32;
+            handler
+                .update_guild_config(guild_id, |c| c.ghost_period_secs = seconds)
+                .await;
+            if seconds == 0 {
+                "New channels are visible right away.".to_string()
+            } else {
+                format!("New channels stay hidden for {} seconds.", seconds)
+            }
+        }
+        "allowed_tags" => {
+            let tags = match parse_allowed_tags(option_str(options, "tags").unwrap_or_default()) {
+                Ok(tags) => tags,
+                Err(problem) => return respond(ctx, command, problem, true).await,
+            };
+            let reply = if tags.is_empty() {
+                "Channels can no longer be tagged.".to_string()
+            } else {
+                format!("Owners can now tag their channel with: {}", tags.join(", "))
+            };
+            handler
+                .update_guild_config(guild_id, |c| c.allowed_tags = tags)
+                .await;
+            reply
+        }
+        "forum" => {
+            let forum = option_channel(options, "channel");
+            let tags = match parse_allowed_tags(option_str(options, "tags").unwrap_or_default()) {
+                Ok(tags) => tags,
+                Err(problem) => return respond(ctx, command, problem, true).await,
+            };
+            let reply = match (forum, tags.is_empty()) {
+                (Some(id), true) => format!("Every new post in <#{}> is now a temporary session.", id),
+                (Some(id), false) => format!(
+                    "Every new post in <#{}> is now a temporary session and gets the tags: {}",
+                    id,
+                    tags.join(", ")
+                ),
+                (None, _) => "Forum sessions are turned off.".to_string(),
+            };
+            handler
+                .update_guild_config(guild_id, |c| {
+                    c.forum_creator_id = forum;
+                    c.forum_tags = if forum.is_some() { tags } else { Vec::new() };
+                })
+                .await;
+            reply
+        }
+        "late_night" => {
+            let values = (
+                option_int(options, "start_hour"),
+                option_int(options, "end_hour"),
+                option_int(options, "max_channels"),
+                option_int(options, "deletion_delay"),
+            );
+            let late_night = match values {
+                (None, None, None, None) => None,
+                (Some(start), Some(end), Some(max), Some(delay)) => Some(LateNightConfig {
+                    start_hour: start.clamp(0, 23) as u8,
+                    end_hour: end.clamp(0, 23) as u8,
+                    max_channels: max.clamp(1, MAX_LATE_NIGHT_CHANNELS as i64) as u32,
+                    deletion_delay_secs: delay.clamp(0, MAX_LATE_NIGHT_DELETION_DELAY_SECS as i64) as u64,
+                }),
+                _ => return respond(ctx, command, "Fill in all four options, or none to turn it off.", true).await,
+            };
+            handler
+                .update_guild_config(guild_id, |c| c.late_night_config = late_night)
+                .await;
+            match late_night {
+                Some(l) => format!(
+                    "From {:02}:00 to {:02}:00 the server allows {} channel(s), empty ones are kept for {} seconds.",
+                    l.start_hour, l.end_hour, l.max_channels, l.deletion_delay_secs
+                ),
+                None => "Late-night mode is turned off.".to_string(),
+            }
+        }
+        "utc_offset" => {
+            let offset = match parse_utc_offset(option_str(options, "offset").unwrap_or_default()) {
+                Some(offset) => offset,
+                None => return respond(ctx, command, "Use an offset between -12:00 and +14:00, like +02:00.", true).await,
+            };
+            handler
+                .update_guild_config(guild_id, |c| c.utc_offset_minutes = offset)
+                .await;
+            format!("The server's timezone is now {}.", format_utc_offset(offset))
+        }
+        "music_bot" => {
+            let bot = option_user(options, "bot");
+            handler
+                .update_guild_config(guild_id, |c| c.music_bot_id = bot)
+                .await;
+            match bot {
+                Some(id) => format!("/music will bring <@{}> along from the waiting room.", id),
+                None => "/music will no longer bring a music bot along.".to_string(),
+            }
+        }
+        "set_role_quota" => {
+            let role_id = match option_role(options, "role") {
+                Some(id) => id,
+                None => return respond(ctx, command, "Please pick a role.", true).await,
+            };
+            let limit = option_int(options, "limit").unwrap_or(0).clamp(0, MAX_ROLE_QUOTA as i64) as u32;
+            let position = |role| {
+                guild_id
+                    .to_guild_cached(&ctx.cache)
+                    .and_then(|g| g.roles.get(&role).map(|r| r.position))
+                    .unwrap_or(0)
+            };
+            handler
+                .update_guild_config(guild_id, |c| {
+                    c.role_quotas.retain(|(role, _)| *role != role_id);
+                    if limit > 0 {
+                        c.role_quotas.push((role_id, limit));
+                    }
+                    c.role_quotas.sort_by_key(|(role, _)| std::cmp::Reverse(position(*role)));
+                })
+                .await;
+            if limit == 0 {
+                format!("<@&{}> no longer has its own channel quota.", role_id)
+            } else {
+                format!("Members with <@&{}> can now own {} channel(s) at once.", role_id, limit)
+            }
+        }
+        "admin_user" => {
+            let user = option_user(options, "user");
+            handler
+                .update_guild_config(guild_id, |c| c.admin_user_id = user)
+                .await;
+            match user {
+                Some(id) => format!("<@{}> can now use /config_summary from DMs.", id),
+                None => "Nobody can use /config_summary for this server anymore.".to_string(),
+            }
+        }
+        "debug_role" => {
+            let role = option_role(options, "role");
+            handler
+                .update_guild_config(guild_id, |c| c.debug_role_id = role)
+                .await;
+            match role {
+                Some(id) => format!("<@&{}> can now use /debug_voice_states.", id),
+                None => "Only the bot owner can use /debug_voice_states now.".to_string(),
+            }
+        }
+        "test_mode" => {
+            let user = option_user(options, "user");
+            handler
+                .update_guild_config(guild_id, |c| c.test_mode_user_id = user)
+                .await;
+            match user {
+                Some(id) => format!(
+                    "Test mode is on, only voice events of <@{}> are handled. Use `/config test_mode` without a user to turn it off.",
+                    id
+                ),
+                None => "Test mode is off, everyone's voice events are handled again.".to_string(),
+            }
+        }
+        _ => return Ok(()),
+    };
+
+    respond(ctx, command, reply, true).await
+}
+
+/// What's wrong with a config that didn't come through `/config`, like an
+/// `/import_config` file, by the same limits the subcommands use.
+pub fn config_problem(config: &GuildConfig) -> Option<String> {
+    if let Some(secs) = config.ownership_expiry_secs {
+        if !(MIN_OWNERSHIP_EXPIRY_SECS..=MAX_OWNERSHIP_EXPIRY_SECS).contains(&secs) {
+            return Some(format!(
+                "`ownership_expiry_secs` has to be between {} and {}.",
+                MIN_OWNERSHIP_EXPIRY_SECS, MAX_OWNERSHIP_EXPIRY_SECS
+            ));
+        }
+    }
+    if let Some(secs) = config.afk_kick_threshold_secs {
+        if !(MIN_AFK_KICK_SECS..=MAX_AFK_KICK_SECS).contains(&secs) {
+            return Some(format!(
+                "`afk_kick_threshold_secs` has to be between {} and {}.",
+                MIN_AFK_KICK_SECS, MAX_AFK_KICK_SECS
+            ));
+        }
+    }
+    if config.ghost_period_secs > MAX_GHOST_PERIOD_SECS {
+        return Some(format!("`ghost_period_secs` can be at most {}.", MAX_GHOST_PERIOD_SECS));
+    }
+    if let Some(late_night) = config.late_night_config {
+        if late_night.start_hour > 23 || late_night.end_hour > 23 {
+            return Some("Late-night hours have to be between 0 and 23.".to_string());
+        }
+        if !(1..=MAX_LATE_NIGHT_CHANNELS).contains(&late_night.max_channels)
+            || late_night.deletion_delay_secs > MAX_LATE_NIGHT_DELETION_DELAY_SECS
+        {
+            return Some("The late-night limits are out of range.".to_string());
+        }
+    }
+    if !UTC_OFFSET_RANGE.contains(&config.utc_offset_minutes) {
+        return Some("`utc_offset_minutes` is not a timezone.".to_string());
+    }
+    if config.role_quotas.iter().any(|(_, quota)| !(1..=MAX_ROLE_QUOTA).contains(quota)) {
+        return Some(format!("Role quotas have to be between 1 and {}.", MAX_ROLE_QUOTA));
+    }
+    if config.embed_color > 0xFFFFFF {
+        return Some("`embed_color` is not a color.".to_string());
+    }
+    if let Some(url) = &config.log_webhook_url {
+        if !WEBHOOK_PREFIXES.iter().any(|p| url.starts_with(p)) {
+            return Some("`log_webhook_url` is not a Discord webhook URL.".to_string());
+        }
+    }
+    parse_allowed_tags(&config.allowed_tags.join(","))
+        .and_then(|_| parse_allowed_tags(&config.forum_tags.join(",")))
+        .err()
+}
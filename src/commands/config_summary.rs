@@ -0,0 +1,279 @@
+use serenity::{
+    async_trait,
+    builder::{CreateApplicationCommand, CreateComponents, CreateEmbed},
+    model::{
+        application::{
+            component::ButtonStyle,
+            interaction::{
+                application_command::ApplicationCommandInteraction,
+                message_component::MessageComponentInteraction, InteractionResponseType,
+            },
+        },
+        id::{GuildId, UserId},
+    },
+    prelude::*,
+};
+
+use super::{server_info::feature_flags, SlashCommand};
+use crate::{config::GuildConfig, error::BotError, handler::Handler, utils::create_embed};
+
+pub const TOGGLE_PREFIX: &str = "config_summary_toggle";
+
+/// A message holds 5 rows of buttons, one per server.
+const MAX_GUILDS: usize = 5;
+
+pub struct ConfigSummaryCommand;
+
+#[async_trait]
+impl SlashCommand for ConfigSummaryCommand {
+    fn name(&self) -> &'static str {
+        "config_summary"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("See and change the settings of the servers you manage, also from DMs")
+            .dm_permission(true);
+        command
+    }
+
+    fn dm_allowed(&self) -> bool {
+        true
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+/// The settings the buttons can turn on and off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Toggle {
+    ActivityName,
+    PairedText,
+    SessionSummary,
+    AccessLog,
+}
+
+impl Toggle {
+    const ALL: [Toggle; 4] = [
+        Toggle::ActivityName,
+        Toggle::PairedText,
+        Toggle::SessionSummary,
+        Toggle::AccessLog,
+    ];
+
+    fn key(self) -> &'static str {
+        match self {
+            Toggle::ActivityName => "activity_name",
+            Toggle::PairedText => "paired_text",
+            Toggle::SessionSummary => "session_summary",
+            Toggle::AccessLog => "access_log",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Toggle::ActivityName => "Activity names",
+            Toggle::PairedText => "Text channels",
+            Toggle::SessionSummary => "Summaries",
+            Toggle::AccessLog => "Access log",
+        }
+    }
+
+    fn flag(self, config: &mut GuildConfig) -> &mut bool {
+        match self {
+            Toggle::ActivityName => &mut config.use_activity_name,
+            Toggle::PairedText => &mut config.paired_text_channel,
+            Toggle::SessionSummary => &mut config.session_summary,
+            Toggle::AccessLog => &mut config.access_log,
+        }
+    }
+}
+
+pub fn toggle_id(guild_id: GuildId, toggle: Toggle) -> String {
+    format!("{}:{}:{}", TOGGLE_PREFIX, guild_id, toggle.key())
+}
+
+pub fn parse_toggle_id(id: &str) -> Option<(GuildId, Toggle)> {
+    let mut parts = id.strip_prefix(TOGGLE_PREFIX)?.strip_prefix(':')?.split(':');
+    let guild_id = GuildId(parts.next()?.parse().ok()?);
+    let key = parts.next()?;
+    let toggle = Toggle::ALL.into_iter().find(|t| t.key() == key)?;
+    Some((guild_id, toggle))
+}
+
+/// Servers `user_id` can manage from here, or `None` when they aren't the
+/// `admin_user_id` of any server the bot is in. Only looks at cached members, so it
+/// doesn't make a request per server.
+async fn managed_guilds(
+    handler: &Handler,
+    ctx: &Context,
+    user_id: UserId,
+) -> Option<Vec<(GuildId, String, GuildConfig)>> {
+    let mut configs = Vec::new();
+    for guild_id in ctx.cache.guilds() {
+        configs.push((guild_id, handler.guild_config(guild_id).await));
+    }
+    if !configs.iter().any(|(_, config)| config.admin_user_id == Some(user_id)) {
+        return None;
+    }
+
+    let mut guilds: Vec<_> = configs
+        .into_iter()
+        .filter(|(guild_id, _)| manages_guild_cached(ctx, *guild_id, user_id))
+        .map(|(guild_id, config)| {
+            let name = guild_id.name(&ctx.cache).unwrap_or_else(|| guild_id.to_string());
+            (guild_id, name, config)
+        })
+        .collect();
+    guilds.sort_by(|a, b| a.1.cmp(&b.1));
+    Some(guilds)
+}
+
+/// Whether `user_id` has Manage Server in `guild_id`, `false` when they aren't cached.
+fn manages_guild_cached(ctx: &Context, guild_id: GuildId, user_id: UserId) -> bool {
+    ctx.cache
+        .member(guild_id, user_id)
+        .is_some_and(|member| member.permissions(&ctx.cache).is_ok_and(|p| p.manage_guild() || p.administrator()))
+}
+
+fn summary_embeds(guilds: &[(GuildId, String, GuildConfig)]) -> Vec<CreateEmbed> {
+    guilds
+        .iter()
+        .take(MAX_GUILDS)
+        .enumerate()
+        .map(|(i, (_, name, config))| {
+            let mut embed = create_embed(config);
+            embed
+                .title(format!("{}. {}", i + 1, name))
+                .field("Features", feature_flags(config), true)
+                .field("Name filter", format!("{} word(s)", config.name_filter.len()), true)
+                .field("Ghost period", format!("{}s", config.ghost_period_secs), true);
+            embed
+        })
+        .collect()
+}
+
+fn buttons<'a>(
+    components: &'a mut CreateComponents,
+    guilds: &[(GuildId, String, GuildConfig)],
+) -> &'a mut CreateComponents {
+    for (i, (guild_id, _, config)) in guilds.iter().take(MAX_GUILDS).enumerate() {
+        let mut config = config.clone();
+        components.create_action_row(|row| {
+            for toggle in Toggle::ALL {
+                let on = *toggle.flag(&mut config);
+                row.create_button(|b| {
+                    b.custom_id(toggle_id(*guild_id, toggle))
+                        .label(format!("{}. {}", i + 1, toggle.label()))
+                        .style(if on { ButtonStyle::Success } else { ButtonStyle::Secondary })
+                });
+            }
+            row
+        });
+    }
+    components
+}
+
+fn summary_content(guilds: &[(GuildId, String, GuildConfig)]) -> String {
+    let shown = guilds.len().min(MAX_GUILDS);
+    let mut content = "Green buttons are on, click one to switch it.".to_string();
+    if guilds.len() > shown {
+        content.push_str(&format!(" Showing {} of {} servers.", shown, guilds.len()));
+    }
+    content
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    // Going through every server can take a while with many of them
+    command.defer_ephemeral(&ctx.http).await?;
+
+    let guilds = managed_guilds(handler, ctx, command.user.id).await;
+    let problem = match &guilds {
+        None => Some("You need to be set as admin with `/config admin_user` in a server first."),
+        Some(guilds) if guilds.is_empty() => Some("You don't have Manage Server in any server the bot is in."),
+        Some(_) => None,
+    };
+    if let Some(problem) = problem {
+        command.edit_original_interaction_response(&ctx.http, |r| r.content(problem)).await?;
+        return Ok(());
+    }
+    let guilds = guilds.unwrap_or_default();
+
+    command
+        .edit_original_interaction_response(&ctx.http, |r| {
+            r.content(summary_content(&guilds))
+                .set_embeds(summary_embeds(&guilds))
+                .components(|c| buttons(c, &guilds))
+        })
+        .await?;
+    Ok(())
+}
+
+/// Handles the buttons of `/config_summary`, which can be clicked long after the
+/// command, so the invoker's access is checked again.
+pub async fn handle_toggle(
+    handler: &Handler,
+    ctx: &Context,
+    component: &MessageComponentInteraction,
+) -> Result<(), SerenityError> {
+    let (guild_id, toggle) = match parse_toggle_id(&component.data.custom_id) {
+        Some(parsed) => parsed,
+        None => return Ok(()),
+    };
+    let allowed = managed_guilds(handler, ctx, component.user.id)
+        .await
+        .is_some_and(|guilds| guilds.iter().any(|(id, _, _)| *id == guild_id));
+    if !allowed {
+        return component
+            .create_interaction_response(&ctx.http, |r| {
+                r.kind(InteractionResponseType::ChannelMessageWithSource)
+                    .interaction_response_data(|d| d.content("You can no longer manage that server.").ephemeral(true))
+            })
+            .await;
+    }
+
+    handler
+        .update_guild_config(guild_id, |c| {
+            let flag = toggle.flag(c);
+            *flag = !*flag;
+        })
+        .await;
+
+    let guilds = managed_guilds(handler, ctx, component.user.id).await.unwrap_or_default();
+    component
+        .create_interaction_response(&ctx.http, |r| {
+            r.kind(InteractionResponseType::UpdateMessage).interaction_response_data(|d| {
+                d.content(summary_content(&guilds))
+                    .set_embeds(summary_embeds(&guilds))
+                    .components(|c| buttons(c, &guilds))
+            })
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toggle_ids_round_trip() {
+        for toggle in Toggle::ALL {
+            assert_eq!(parse_toggle_id(&toggle_id(GuildId(42), toggle)), Some((GuildId(42), toggle)));
+        }
+        assert_eq!(parse_toggle_id("config_summary_toggle:42:volume"), None);
+        assert_eq!(parse_toggle_id("config_summary_toggle:abc:access_log"), None);
+        assert_eq!(parse_toggle_id("speaker_approve:1:2"), None);
+    }
+}
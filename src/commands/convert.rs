@@ -0,0 +1,167 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::{
+        application::{
+            command::CommandOptionType,
+            interaction::application_command::ApplicationCommandInteraction,
+        },
+        channel::{Channel, ChannelType},
+        permissions::Permissions,
+    },
+    prelude::*,
+};
+
+use super::{option_channel, option_user, respond, SlashCommand};
+use crate::{error::BotError, handler::Handler};
+
+pub struct ConvertCommand;
+
+#[async_trait]
+impl SlashCommand for ConvertCommand {
+    fn name(&self) -> &'static str {
+        "convert"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Let the bot manage an existing voice channel like a temporary one")
+            .dm_permission(false)
+            .create_option(|o| {
+                o.name("channel")
+                    .description("The voice channel")
+                    .kind(CommandOptionType::Channel)
+                    .channel_types(&[ChannelType::Voice, ChannelType::Stage])
+                    .required(true)
+            })
+            .create_option(|o| {
+                o.name("owner")
+                    .description("Who owns it from now on")
+                    .kind(CommandOptionType::User)
+                    .required(true)
+            });
+        command
+    }
+
+    fn required_permissions(&self) -> Permissions {
+        Permissions::MANAGE_CHANNELS
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        run_convert(handler, ctx, command).await
+    }
+}
+
+pub struct DeconvertCommand;
+
+#[async_trait]
+impl SlashCommand for DeconvertCommand {
+    fn name(&self) -> &'static str {
+        "deconvert"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Stop managing a temporary channel but keep it, with the server's default permissions")
+            .dm_permission(false)
+            .create_option(|o| {
+                o.name("channel")
+                    .description("The temporary channel")
+                    .kind(CommandOptionType::Channel)
+                    .channel_types(&[ChannelType::Voice, ChannelType::Stage])
+                    .required(true)
+            });
+        command
+    }
+
+    fn required_permissions(&self) -> Permissions {
+        Permissions::MANAGE_CHANNELS
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        run_deconvert(handler, ctx, command).await
+    }
+}
+
+async fn run_convert(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), BotError> {
+    let guild = match command.guild_id.and_then(|id| id.to_guild_cached(&ctx.cache)) {
+        Some(guild) => guild,
+        None => return Ok(respond(ctx, command, "This command only works in a server.", true).await?),
+    };
+    let options = &command.data.options;
+    let (channel_id, owner_id) = match (option_channel(options, "channel"), option_user(options, "owner")) {
+        (Some(channel_id), Some(owner_id)) => (channel_id, owner_id),
+        _ => return Ok(respond(ctx, command, "Please pick a channel and an owner.", true).await?),
+    };
+
+    let channel = match guild.channels.get(&channel_id) {
+        Some(Channel::Guild(gc)) if matches!(gc.kind, ChannelType::Voice | ChannelType::Stage) => gc.clone(),
+        _ => return Ok(respond(ctx, command, "Pick a voice channel in this server.", true).await?),
+    };
+    let special = [handler.creator_channel(guild.id), handler.waiting_room(guild.id).await];
+    if special.contains(&Some(channel_id)) {
+        return Ok(respond(ctx, command, "The creator channel and waiting room can't be converted.", true).await?);
+    }
+    let owner = match guild.member(ctx, owner_id).await {
+        Ok(member) if !member.user.bot => member,
+        _ => return Ok(respond(ctx, command, "The owner has to be a member of this server, not a bot.", true).await?),
+    };
+
+    if !handler.convert_channel(ctx, guild.id, &channel, &owner).await? {
+        return Ok(respond(ctx, command, format!("<#{}> is already a temporary channel.", channel_id), true).await?);
+    }
+    handler
+        .audit_log(
+            ctx,
+            guild.id,
+            &format!("<@{}> converted **{}** into a temporary channel for <@{}>", command.user.id, channel.name, owner_id),
+        )
+        .await;
+
+    let reply = format!(
+        "<#{}> is now a temporary channel owned by <@{}>. Like any other, it's deleted once it's empty.",
+        channel_id, owner_id
+    );
+    Ok(respond(ctx, command, reply, true).await?)
+}
+
+async fn run_deconvert(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), BotError> {
+    let guild_id = match command.guild_id {
+        Some(id) => id,
+        None => return Ok(respond(ctx, command, "This command only works in a server.", true).await?),
+    };
+    let channel_id = match option_channel(&command.data.options, "channel") {
+        Some(id) => id,
+        None => return Ok(respond(ctx, command, "Please pick a channel.", true).await?),
+    };
+
+    if !handler.deconvert_channel(ctx, guild_id, channel_id).await? {
+        return Ok(respond(ctx, command, format!("<#{}> is not a temporary channel.", channel_id), true).await?);
+    }
+    handler
+        .audit_log(ctx, guild_id, &format!("<@{}> stopped managing <#{}>", command.user.id, channel_id))
+        .await;
+
+    let reply = format!("<#{}> is a regular voice channel again, with the server's default permissions.", channel_id);
+    Ok(respond(ctx, command, reply, true).await?)
+}
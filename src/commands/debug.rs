@@ -0,0 +1,247 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::{
+        application::interaction::{
+            application_command::ApplicationCommandInteraction, InteractionResponseType,
+        },
+        channel::AttachmentType,
+        id::{ChannelId, UserId},
+    },
+    prelude::*,
+};
+use std::{borrow::Cow, collections::HashSet};
+
+use super::{respond, SlashCommand};
+use crate::{error::BotError, handler::Handler, utils::create_embed};
+
+/// An embed holds 25 fields, longer lists are sent as a file.
+const MAX_EMBED_ENTRIES: usize = 25;
+
+/// Shows the cached voice states of the temp channels next to what the bot tracks, for
+/// when join or leave events seem to get lost. For the bot owner and the
+/// `GuildConfig::debug_role_id`.
+pub struct DebugVoiceStatesCommand;
+
+#[async_trait]
+impl SlashCommand for DebugVoiceStatesCommand {
+    fn name(&self) -> &'static str {
+        "debug_voice_states"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Compare the cached voice states of temporary channels with what the bot tracks")
+            .dm_permission(false);
+        command
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct VoiceFlags {
+    pub deaf: bool,
+    pub mute: bool,
+    pub self_deaf: bool,
+    pub self_mute: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mismatch {
+    /// In the cache, but the bot doesn't count them as a member of the channel.
+    NotTracked,
+    /// Tracked as a member, but the cache has no voice state for them in the channel.
+    NotInCache,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VoiceEntry {
+    pub user_id: UserId,
+    pub channel_id: ChannelId,
+    /// `None` when the member is only tracked.
+    pub flags: Option<VoiceFlags>,
+    pub mismatch: Option<Mismatch>,
+}
+
+/// Lines up the cached voice states with the tracked members of each channel, ordered
+/// by channel and then user.
+pub fn compare_voice_states(
+    cached: &[(UserId, ChannelId, VoiceFlags)],
+    tracked: &[(ChannelId, HashSet<UserId>)],
+) -> Vec<VoiceEntry> {
+    let is_tracked = |user_id: UserId, channel_id: ChannelId| {
+        tracked.iter().any(|(id, members)| *id == channel_id && members.contains(&user_id))
+    };
+    let mut entries: Vec<VoiceEntry> = cached
+        .iter()
+        .map(|&(user_id, channel_id, flags)| VoiceEntry {
+            user_id,
+            channel_id,
+            flags: Some(flags),
+            mismatch: (!is_tracked(user_id, channel_id)).then_some(Mismatch::NotTracked),
+        })
+        .collect();
+    for (channel_id, members) in tracked {
+        for &user_id in members {
+            if !cached.iter().any(|(u, c, _)| *u == user_id && c == channel_id) {
+                entries.push(VoiceEntry {
+                    user_id,
+                    channel_id: *channel_id,
+                    flags: None,
+                    mismatch: Some(Mismatch::NotInCache),
+                });
+            }
+        }
+    }
+    entries.sort_by_key(|entry| (entry.channel_id, entry.user_id));
+    entries
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value {
+        "yes"
+    } else {
+        "no"
+    }
+}
+
+/// The voice flags and any mismatch of `entry`, without the IDs.
+pub fn describe(entry: &VoiceEntry) -> String {
+    let mut text = match entry.flags {
+        Some(f) => format!(
+            "deaf {} (self {}), mute {} (self {})",
+            yes_no(f.deaf),
+            yes_no(f.self_deaf),
+            yes_no(f.mute),
+            yes_no(f.self_mute)
+        ),
+        None => "no voice state".to_string(),
+    };
+    match entry.mismatch {
+        Some(Mismatch::NotTracked) => text.push_str(", ⚠ not tracked"),
+        Some(Mismatch::NotInCache) => text.push_str(", ⚠ not in the cache"),
+        None => {}
+    }
+    text
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let guild = match command.guild_id.and_then(|id| id.to_guild_cached(&ctx.cache)) {
+        Some(guild) => guild,
+        None => return respond(ctx, command, "This command only works in a server.", true).await,
+    };
+    let config = handler.guild_config(guild.id).await;
+    let has_debug_role = match (config.debug_role_id, &command.member) {
+        (Some(role_id), Some(member)) => member.roles.contains(&role_id),
+        _ => false,
+    };
+    if !handler.is_bot_owner(command.user.id) && !has_debug_role {
+        return respond(ctx, command, "This command is only for the bot owner and the debug role.", true).await;
+    }
+
+    let tracked: Vec<(ChannelId, HashSet<UserId>)> = handler
+        .tracked_members()
+        .into_iter()
+        .filter(|(channel_id, _)| guild.channels.contains_key(channel_id))
+        .collect();
+    // Bots aren't tracked as members, leaving them out keeps the list to real mismatches
+    let cached: Vec<(UserId, ChannelId, VoiceFlags)> = guild
+        .voice_states
+        .values()
+        .filter(|v| !v.member.as_ref().is_some_and(|m| m.user.bot))
+        .filter_map(|v| {
+            let channel_id = v.channel_id.filter(|id| tracked.iter().any(|(t, _)| t == id))?;
+            let flags = VoiceFlags {
+                deaf: v.deaf,
+                mute: v.mute,
+                self_deaf: v.self_deaf,
+                self_mute: v.self_mute,
+            };
+            Some((v.user_id, channel_id, flags))
+        })
+        .collect();
+    let entries = compare_voice_states(&cached, &tracked);
+    let mismatches = entries.iter().filter(|e| e.mismatch.is_some()).count();
+    let summary = format!(
+        "{} voice state(s) in {} temporary channel(s), {} mismatch(es).",
+        entries.len(),
+        tracked.len(),
+        mismatches
+    );
+
+    if entries.len() > MAX_EMBED_ENTRIES {
+        let text: String = entries
+            .iter()
+            .map(|e| format!("user {} in channel {}: {}\n", e.user_id, e.channel_id, describe(e)))
+            .collect();
+        return command
+            .create_interaction_response(&ctx.http, |r| {
+                r.kind(InteractionResponseType::ChannelMessageWithSource)
+                    .interaction_response_data(|d| {
+                        d.content(summary)
+                            .add_file(AttachmentType::Bytes {
+                                data: Cow::Owned(text.into_bytes()),
+                                filename: "voice_states.txt".to_string(),
+                            })
+                            .ephemeral(true)
+                    })
+            })
+            .await;
+    }
+
+    let mut embed = create_embed(&config);
+    embed.title("Voice states of temporary channels").description(&summary);
+    for entry in &entries {
+        embed.field(
+            format!("User {}", entry.user_id),
+            format!("<@{}> in <#{}>\n{}", entry.user_id, entry.channel_id, describe(entry)),
+            false,
+        );
+    }
+    command
+        .create_interaction_response(&ctx.http, |r| {
+            r.kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|d| d.set_embed(embed).ephemeral(true))
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_members_only_one_side_knows() {
+        let muted = VoiceFlags {
+            self_mute: true,
+            ..VoiceFlags::default()
+        };
+        let cached = [
+            (UserId(1), ChannelId(10), muted),
+            (UserId(2), ChannelId(10), VoiceFlags::default()),
+        ];
+        let tracked = [(ChannelId(10), HashSet::from([UserId(1), UserId(3)]))];
+
+        let entries = compare_voice_states(&cached, &tracked);
+        let summary: Vec<(u64, Option<Mismatch>)> = entries.iter().map(|e| (e.user_id.0, e.mismatch)).collect();
+        assert_eq!(
+            summary,
+            [(1, None), (2, Some(Mismatch::NotTracked)), (3, Some(Mismatch::NotInCache))]
+        );
+        assert_eq!(describe(&entries[0]), "deaf no (self no), mute no (self yes)");
+        assert_eq!(describe(&entries[2]), "no voice state, ⚠ not in the cache");
+    }
+}
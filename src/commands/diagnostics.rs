@@ -0,0 +1,185 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::{
+        application::interaction::application_command::ApplicationCommandInteraction,
+        channel::{ChannelType, PermissionOverwrite, PermissionOverwriteType},
+        id::{ChannelId, GuildId},
+        permissions::Permissions,
+    },
+    prelude::*,
+};
+use std::{sync::Arc, time::Duration};
+use tokio::time::{sleep, timeout};
+use tracing::warn;
+
+use super::{respond, SlashCommand};
+use crate::{
+    error::BotError,
+    handler::Handler,
+    utils::{create_channel, create_embed, create_permission, delete_channel, AuditReason},
+};
+
+/// The whole self-test has to finish within this.
+const DIAGNOSTICS_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How often the cache is checked for the test channel.
+const CACHE_POLL: Duration = Duration::from_millis(250);
+
+const TEST_CHANNEL_NAME: &str = "bot-diagnostics";
+
+pub struct DiagnosticsCommand;
+
+#[async_trait]
+impl SlashCommand for DiagnosticsCommand {
+    fn name(&self) -> &'static str {
+        "diagnostics"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Check that the bot can create, change and delete channels here")
+            .dm_permission(false);
+        command
+    }
+
+    fn required_permissions(&self) -> Permissions {
+        Permissions::MANAGE_GUILD
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+/// A self-test step and why it failed, if it did.
+type Step = (&'static str, Result<(), String>);
+
+/// "✅ Create channel" or "❌ Set permissions: Missing Permissions"
+pub fn step_line((name, result): &Step) -> String {
+    match result {
+        Ok(()) => format!("✅ {}", name),
+        Err(e) => format!("❌ {}: {}", name, e),
+    }
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let guild_id = match command.guild_id {
+        Some(id) => id,
+        None => return respond(ctx, command, "This command only works in a server.", true).await,
+    };
+    command.defer_ephemeral(&ctx.http).await?;
+
+    let config = handler.guild_config(guild_id).await;
+    let category = match config.diagnostics_category_id {
+        Some(id) => Some(id),
+        None => handler
+            .creator_channel(guild_id)
+            .and_then(|id| id.to_channel_cached(&ctx.cache))
+            .and_then(|c| c.guild())
+            .and_then(|gc| gc.parent_id),
+    };
+
+    let mut steps = Vec::new();
+    if timeout(DIAGNOSTICS_TIMEOUT, self_test(ctx, guild_id, category, &mut steps)).await.is_err() {
+        steps.push(("Finish in time", Err(format!("gave up after {}s", DIAGNOSTICS_TIMEOUT.as_secs()))));
+    }
+
+    let passed = steps.iter().all(|(_, result)| result.is_ok());
+    let report = steps.iter().map(step_line).collect::<Vec<_>>().join("\n");
+    let mut embed = create_embed(&config);
+    embed
+        .title(if passed { "Diagnostics passed" } else { "Diagnostics failed" })
+        .description(report)
+        .footer(|f| {
+            f.text(match category {
+                Some(id) => format!("Tested in category {}", id),
+                None => "Tested outside a category".to_string(),
+            })
+        });
+    command
+        .edit_original_interaction_response(&ctx.http, |r| r.set_embed(embed))
+        .await?;
+    Ok(())
+}
+
+/// Runs the steps in order, stopping at the first failure. The test channel is deleted
+/// by a guard if the test doesn't get to delete it itself, also when it times out.
+async fn self_test(ctx: &Context, guild_id: GuildId, category: Option<ChannelId>, steps: &mut Vec<Step>) {
+    let reason = AuditReason::Diagnostics;
+    let created = create_channel(&ctx.http, guild_id, &reason, |c| {
+        c.name(TEST_CHANNEL_NAME).kind(ChannelType::Voice);
+        if let Some(category) = category {
+            c.category(category);
+        }
+        c
+    })
+    .await;
+    let channel_id = match created {
+        Ok(channel) => channel.id,
+        Err(e) => return steps.push(("Create a voice channel", Err(e.to_string()))),
+    };
+    steps.push(("Create a voice channel", Ok(())));
+
+    let http = Arc::clone(&ctx.http);
+    let cleanup = scopeguard::guard(channel_id, move |channel_id| {
+        tokio::spawn(async move {
+            if let Err(e) = delete_channel(&http, channel_id, &AuditReason::Diagnostics).await {
+                warn!("Kon diagnostics kanaal {} niet opruimen: {:?}", channel_id, e);
+            }
+        });
+    });
+
+    // The channel create event can arrive a bit after the API call returned
+    let in_cache = || {
+        guild_id
+            .to_guild_cached(&ctx.cache)
+            .is_some_and(|g| g.channels.contains_key(&channel_id))
+    };
+    while !in_cache() {
+        sleep(CACHE_POLL).await;
+    }
+    steps.push(("Channel shows up in the server", Ok(())));
+
+    let overwrite = PermissionOverwrite {
+        kind: PermissionOverwriteType::Role(guild_id.0.into()),
+        allow: Permissions::empty(),
+        deny: Permissions::CONNECT,
+    };
+    if let Err(e) = create_permission(&ctx.http, channel_id, &overwrite, &reason).await {
+        return steps.push(("Set permissions", Err(e.to_string())));
+    }
+    steps.push(("Set permissions", Ok(())));
+
+    match delete_channel(&ctx.http, channel_id, &reason).await {
+        Ok(()) => {
+            scopeguard::ScopeGuard::into_inner(cleanup);
+            steps.push(("Delete the channel", Ok(())));
+        }
+        Err(e) => steps.push(("Delete the channel", Err(e.to_string()))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn step_lines_show_the_error() {
+        assert_eq!(step_line(&("Set permissions", Ok(()))), "✅ Set permissions");
+        assert_eq!(
+            step_line(&("Set permissions", Err("Missing Permissions".to_string()))),
+            "❌ Set permissions: Missing Permissions"
+        );
+    }
+}
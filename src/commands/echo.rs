@@ -0,0 +1,68 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::{
+        application::interaction::{
+            application_command::ApplicationCommandInteraction, InteractionResponseType,
+        },
+        channel::AttachmentType,
+    },
+    prelude::*,
+};
+use std::borrow::Cow;
+
+use super::{respond, SlashCommand};
+use crate::{error::BotError, handler::Handler};
+
+/// Dumps the tracked temp channels as JSON. A developer command, only registered in
+/// `DevConfig::guild_id` and only answered for `DevConfig::bot_owner_id`.
+pub struct EchoCommand;
+
+#[async_trait]
+impl SlashCommand for EchoCommand {
+    fn name(&self) -> &'static str {
+        "echo"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command.description("Dump the bot's temp channel state as JSON (bot owner only)");
+        command
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    if !handler.is_bot_owner(command.user.id) {
+        return respond(ctx, command, "This command is only for the bot owner.", true).await;
+    }
+
+    let dump = handler.state_dump(ctx);
+    let json = serde_json::to_string_pretty(&dump)?;
+    let channels = dump.as_array().map_or(0, Vec::len);
+    command
+        .create_interaction_response(&ctx.http, |r| {
+            r.kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|d| {
+                    d.content(format!("{} temp channel(s) tracked.", channels))
+                        .add_file(AttachmentType::Bytes {
+                            data: Cow::Owned(json.into_bytes()),
+                            filename: "state_dump.json".to_string(),
+                        })
+                        .ephemeral(true)
+                })
+        })
+        .await
+}
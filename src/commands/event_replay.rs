@@ -0,0 +1,67 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::application::{
+        command::CommandOptionType,
+        interaction::application_command::ApplicationCommandInteraction,
+    },
+    prelude::*,
+};
+
+use super::{option_int, respond, SlashCommand};
+use crate::{error::BotError, handler::Handler};
+
+/// Feeds a stored voice event through the handler again to reproduce a bug. A developer
+/// command like `/echo`, and only does anything with `DevConfig::allow_event_replay`.
+pub struct EventReplayCommand;
+
+#[async_trait]
+impl SlashCommand for EventReplayCommand {
+    fn name(&self) -> &'static str {
+        "event_replay"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Process a stored voice event again (bot owner only)")
+            .create_option(|o| {
+                o.name("id")
+                    .description("ID of the stored event, logged when it came in")
+                    .kind(CommandOptionType::Integer)
+                    .min_int_value(1)
+                    .required(true)
+            });
+        command
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        if !handler.is_bot_owner(command.user.id) {
+            return Ok(respond(ctx, command, "This command is only for the bot owner.", true).await?);
+        }
+        if !handler.event_replay_allowed() {
+            return Ok(respond(
+                ctx,
+                command,
+                "Event replay is off, start the bot with `ALLOW_EVENT_REPLAY=true` to store and replay events.",
+                true,
+            )
+            .await?);
+        }
+        let Some(id) = option_int(&command.data.options, "id") else {
+            return Ok(respond(ctx, command, "Please give an event ID.", true).await?);
+        };
+
+        let reply = if handler.replay_voice_event(ctx, id).await? {
+            format!("Replayed event {}, look for `[REPLAY]` in the logs.", id)
+        } else {
+            format!("There's no stored voice event {}.", id)
+        };
+        Ok(respond(ctx, command, reply, true).await?)
+    }
+}
@@ -0,0 +1,246 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::{
+        application::{
+            command::CommandOptionType,
+            interaction::{
+                application_command::ApplicationCommandInteraction, InteractionResponseType,
+            },
+        },
+        channel::AttachmentType,
+        id::GuildId,
+        permissions::Permissions,
+        Timestamp,
+    },
+    prelude::*,
+};
+use serde_json::Value;
+use std::borrow::Cow;
+
+use super::{config::config_problem, confirm, finish, option_attachment, respond, Prompt, SlashCommand};
+use crate::{
+    config::GuildConfig,
+    db::migrations::{migrate_config, stored_version, CURRENT_VERSION},
+    error::BotError,
+    handler::Handler,
+};
+
+/// Exported configs are a few KB, anything much bigger isn't one.
+const MAX_IMPORT_BYTES: u64 = 64 * 1024;
+
+pub struct ExportConfigCommand;
+
+#[async_trait]
+impl SlashCommand for ExportConfigCommand {
+    fn name(&self) -> &'static str {
+        "export_config"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Download this server's settings as a JSON file")
+            .dm_permission(false);
+        command
+    }
+
+    fn required_permissions(&self) -> Permissions {
+        Permissions::MANAGE_GUILD
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(export(handler, ctx, command).await?)
+    }
+}
+
+pub struct ImportConfigCommand;
+
+#[async_trait]
+impl SlashCommand for ImportConfigCommand {
+    fn name(&self) -> &'static str {
+        "import_config"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Replace this server's settings with a file from /export_config")
+            .dm_permission(false)
+            .create_option(|o| {
+                o.name("file")
+                    .description("The exported JSON file")
+                    .kind(CommandOptionType::Attachment)
+                    .required(true)
+            });
+        command
+    }
+
+    fn required_permissions(&self) -> Permissions {
+        Permissions::MANAGE_GUILD
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(import(handler, ctx, command).await?)
+    }
+}
+
+/// `guild_config_<guild_id>_<YYYY-MM-DD>.json`
+pub fn export_filename(guild_id: GuildId, now: Timestamp) -> String {
+    let date = now.to_string();
+    format!("guild_config_{}_{}.json", guild_id, date.get(..10).unwrap_or_default())
+}
+
+/// Reads an exported config, upgrading it like stored configs are and checking it
+/// against the limits of `/config`.
+pub fn parse_import(data: &[u8]) -> Result<GuildConfig, String> {
+    let value: Value = serde_json::from_slice(data).map_err(|e| format!("That is not valid JSON: {}", e))?;
+    if !value.is_object() {
+        return Err("That file is not an exported config.".to_string());
+    }
+    let version = stored_version(&value);
+    if version > CURRENT_VERSION {
+        return Err("That file was exported by a newer version of the bot.".to_string());
+    }
+    let config = migrate_config(value, version).map_err(|e| format!("That file is not a valid config: {}", e))?;
+    match config_problem(&config) {
+        Some(problem) => Err(problem),
+        None => Ok(config),
+    }
+}
+
+/// The config an import leaves behind. The mirror target is only set through `/mirror`,
+/// which checks the admin manages that guild, so a file can't point it anywhere else.
+pub fn apply_import(current: &GuildConfig, imported: GuildConfig) -> GuildConfig {
+    GuildConfig {
+        mirror_guild_id: current.mirror_guild_id,
+        mirror_message_id: current.mirror_message_id,
+        ..imported
+    }
+}
+
+async fn export(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let guild_id = match command.guild_id {
+        Some(id) => id,
+        None => return respond(ctx, command, "This command only works in a server.", true).await,
+    };
+
+    let config = GuildConfig {
+        version: CURRENT_VERSION,
+        ..handler.guild_config(guild_id).await
+    };
+    let json = serde_json::to_string_pretty(&config)?;
+    command
+        .create_interaction_response(&ctx.http, |r| {
+            r.kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|d| {
+                    d.content("The settings of this server, restore them with /import_config.")
+                        .add_file(AttachmentType::Bytes {
+                            data: Cow::Owned(json.into_bytes()),
+                            filename: export_filename(guild_id, Timestamp::now()),
+                        })
+                        .ephemeral(true)
+                })
+        })
+        .await
+}
+
+async fn import(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let guild_id = match command.guild_id {
+        Some(id) => id,
+        None => return respond(ctx, command, "This command only works in a server.", true).await,
+    };
+    let attachment = match option_attachment(&command.data.options, "file") {
+        Some(attachment) => attachment,
+        None => return respond(ctx, command, "Please attach an exported config.", true).await,
+    };
+    if attachment.size > MAX_IMPORT_BYTES {
+        return respond(ctx, command, "That file is too big to be an exported config.", true).await;
+    }
+
+    let imported = match parse_import(&attachment.download().await?) {
+        Ok(config) => config,
+        Err(problem) => return respond(ctx, command, problem, true).await,
+    };
+
+    let prompt = Prompt::text(format!(
+        "Import the settings from **{}**? Every current setting of this server is replaced.",
+        attachment.filename
+    ))
+    .warning("I understand this will overwrite current config");
+    let Some(interaction) = confirm(ctx, command, "Import", prompt).await? else { return Ok(()) };
+
+    handler.update_guild_config(guild_id, |c| *c = apply_import(c, imported)).await;
+    handler
+        .audit_log(
+            ctx,
+            guild_id,
+            &format!("<@{}> imported the config from **{}**", command.user.id, attachment.filename),
+        )
+        .await;
+    finish(ctx, &interaction, "Settings imported.").await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serenity::model::id::MessageId;
+
+    #[test]
+    fn exported_config_imports_again() {
+        let config = GuildConfig {
+            ghost_period_secs: 30,
+            allowed_tags: vec!["Ranked".to_string()],
+            version: CURRENT_VERSION,
+            ..GuildConfig::default()
+        };
+        let json = serde_json::to_string_pretty(&config).unwrap();
+        let imported = parse_import(json.as_bytes()).unwrap();
+        assert_eq!(imported.ghost_period_secs, 30);
+        assert_eq!(imported.allowed_tags, ["Ranked"]);
+
+        assert!(parse_import(b"[1, 2]").is_err());
+        assert!(parse_import(br#"{ "ghost_period_secs": 100000 }"#).unwrap_err().contains("ghost_period"));
+        let newer = format!(r#"{{ "version": {} }}"#, CURRENT_VERSION + 1);
+        assert!(parse_import(newer.as_bytes()).unwrap_err().contains("newer"));
+    }
+
+    #[test]
+    fn import_keeps_the_mirror_target() {
+        let current = GuildConfig { mirror_guild_id: Some(GuildId(1)), ..GuildConfig::default() };
+        let imported = GuildConfig {
+            mirror_guild_id: Some(GuildId(2)),
+            mirror_message_id: Some(MessageId(3)),
+            ghost_period_secs: 30,
+            ..GuildConfig::default()
+        };
+        let config = apply_import(&current, imported);
+        assert_eq!(config.mirror_guild_id, Some(GuildId(1)));
+        assert_eq!(config.mirror_message_id, None);
+        assert_eq!(config.ghost_period_secs, 30);
+    }
+
+    #[test]
+    fn filename_has_guild_and_date() {
+        let now = Timestamp::parse("2026-10-15T12:00:00Z").unwrap();
+        assert_eq!(export_filename(GuildId(42), now), "guild_config_42_2026-10-15.json");
+    }
+}
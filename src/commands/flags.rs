@@ -0,0 +1,94 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::application::{
+        command::CommandOptionType,
+        interaction::application_command::ApplicationCommandInteraction,
+    },
+    prelude::*,
+};
+
+use super::{option_str, respond, SlashCommand};
+use crate::{
+    error::BotError,
+    handler::{ChangeAction, Handler},
+    utils::{channel_flags, flag_names, set_channel_flags, voice_channel_flag, AuditReason, VOICE_CHANNEL_FLAGS},
+};
+
+pub struct FlagsCommand;
+
+#[async_trait]
+impl SlashCommand for FlagsCommand {
+    fn name(&self) -> &'static str {
+        "flags"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("See or change the Discord flags of your channel")
+            .dm_permission(false)
+            .create_option(|o| {
+                o.name("show")
+                    .description("Show the flags set on your channel")
+                    .kind(CommandOptionType::SubCommand)
+            })
+            .create_option(|o| {
+                o.name("toggle")
+                    .description("Turn a flag on or off")
+                    .kind(CommandOptionType::SubCommand)
+                    .create_sub_option(|s| {
+                        s.name("flag")
+                            .description(format!("Flag to toggle: {}", VOICE_CHANNEL_FLAGS.join(", ")))
+                            .kind(CommandOptionType::String)
+                            .required(true)
+                    })
+            });
+        command
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let channel_id = match handler.get_user_channel(command.user.id).await {
+        Some(id) => id,
+        None => return respond(ctx, command, "You don't own a temporary channel.", true).await,
+    };
+    let subcommand = match command.data.options.first() {
+        Some(s) => s,
+        None => return Ok(()),
+    };
+
+    let flags = channel_flags(&ctx.http, channel_id).await?;
+    let reply = match subcommand.name.as_str() {
+        "show" => match flag_names(flags)[..] {
+            [] => "Your channel has no flags set.".to_string(),
+            ref names => format!("Flags on your channel: {}", names.join(", ")),
+        },
+        "toggle" => {
+            let (flag, bit) = match voice_channel_flag(option_str(&subcommand.options, "flag").unwrap_or_default()) {
+                Ok(flag) => flag,
+                Err(problem) => return respond(ctx, command, problem, true).await,
+            };
+            let enabled = flags & bit == 0;
+            set_channel_flags(&ctx.http, channel_id, flags ^ bit, &AuditReason::Command("/flags")).await?;
+            handler.record_change(channel_id, command.user.id, ChangeAction::Flag(flag, enabled));
+            format!("**{}** is now {}.", flag, if enabled { "on" } else { "off" })
+        }
+        _ => return Ok(()),
+    };
+
+    respond(ctx, command, reply, true).await
+}
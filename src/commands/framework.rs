@@ -0,0 +1,118 @@
+//! Prefix commands through serenity's `StandardFramework`, built with the `framework`
+//! feature. It takes over from `prefix::handle_message` and adds `!help` plus the
+//! owner-only `!debug_state`, which has no slash command on purpose.
+
+use serenity::{
+    framework::standard::{
+        help_commands,
+        macros::{command, group, help, hook},
+        Args, CommandGroup, CommandResult, HelpOptions, StandardFramework,
+    },
+    model::{channel::Message, id::UserId},
+    prelude::*,
+};
+use std::collections::HashSet;
+use tracing::error;
+
+use super::prefix;
+use crate::handler::Handler;
+
+/// Gives framework commands the `Handler`, inserted into the client data in `main`.
+pub struct HandlerKey;
+
+impl TypeMapKey for HandlerKey {
+    type Value = Handler;
+}
+
+/// The same commands `prefix::parse` knows, so existing `!` users see no difference.
+#[group]
+#[only_in(guilds)]
+#[commands(rename, lock, kick, info)]
+struct Channel;
+
+/// Prefix-only, never registered as slash commands.
+#[group]
+#[owners_only]
+#[commands(debug_state)]
+struct Debug;
+
+pub fn standard_framework(prefix: &str, owners: HashSet<UserId>) -> StandardFramework {
+    StandardFramework::new()
+        .configure(|c| c.prefix(prefix).owners(owners))
+        .after(after)
+        .help(&HELP)
+        .group(&CHANNEL_GROUP)
+        .group(&DEBUG_GROUP)
+}
+
+async fn handler(ctx: &Context) -> Handler {
+    ctx.data
+        .read()
+        .await
+        .get::<HandlerKey>()
+        .cloned()
+        .expect("Handler is inserted into the client data in main")
+}
+
+/// Runs a channel command through `prefix`, which parses and answers it like before.
+async fn channel_command(ctx: &Context, msg: &Message) -> CommandResult {
+    let handler = handler(ctx).await;
+    if let Some(prefix) = handler.command_prefix() {
+        prefix::handle_message(&handler, ctx, msg, prefix).await;
+    }
+    Ok(())
+}
+
+#[command]
+#[description = "Rename your temporary channel"]
+#[usage = "<name>"]
+async fn rename(ctx: &Context, msg: &Message) -> CommandResult {
+    channel_command(ctx, msg).await
+}
+
+#[command]
+#[description = "Lock your temporary channel"]
+async fn lock(ctx: &Context, msg: &Message) -> CommandResult {
+    channel_command(ctx, msg).await
+}
+
+#[command]
+#[description = "Kick someone from your temporary channel"]
+#[usage = "<@user>"]
+async fn kick(ctx: &Context, msg: &Message) -> CommandResult {
+    channel_command(ctx, msg).await
+}
+
+#[command]
+#[description = "Show details about your temporary channel"]
+async fn info(ctx: &Context, msg: &Message) -> CommandResult {
+    channel_command(ctx, msg).await
+}
+
+#[command]
+#[description = "Show what the bot is tracking right now"]
+async fn debug_state(ctx: &Context, msg: &Message) -> CommandResult {
+    let state = handler(ctx).await.debug_state().await;
+    msg.reply(&ctx.http, state).await?;
+    Ok(())
+}
+
+#[help]
+async fn help(
+    ctx: &Context,
+    msg: &Message,
+    args: Args,
+    help_options: &'static HelpOptions,
+    groups: &[&'static CommandGroup],
+    owners: HashSet<UserId>,
+) -> CommandResult {
+    help_commands::with_embeds(ctx, msg, args, help_options, groups, owners).await?;
+    Ok(())
+}
+
+#[hook]
+async fn after(_ctx: &Context, msg: &Message, command_name: &str, result: CommandResult) {
+    if let Err(e) = result {
+        error!("Fout bij prefix command '{}' ({}): {:?}", command_name, msg.content, e);
+    }
+}
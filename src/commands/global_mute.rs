@@ -0,0 +1,130 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::{
+        application::interaction::application_command::ApplicationCommandInteraction,
+        id::UserId,
+    },
+    prelude::*,
+};
+
+use super::{respond, SlashCommand};
+use crate::{error::BotError, handler::{ChangeAction, Handler}};
+
+pub struct GlobalMuteCommand;
+
+#[async_trait]
+impl SlashCommand for GlobalMuteCommand {
+    fn name(&self) -> &'static str {
+        "global_mute"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Server-mute everyone in your channel except you")
+            .dm_permission(false);
+        command
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+pub struct GlobalUnmuteCommand;
+
+#[async_trait]
+impl SlashCommand for GlobalUnmuteCommand {
+    fn name(&self) -> &'static str {
+        "global_unmute"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Unmute everyone muted with /global_mute")
+            .dm_permission(false);
+        command
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let guild = match command.guild_id.and_then(|id| id.to_guild_cached(&ctx.cache)) {
+        Some(guild) => guild,
+        None => return respond(ctx, command, "This command only works in a server.", true).await,
+    };
+
+    let channel_id = match handler.get_user_channel(command.user.id).await {
+        Some(id) => id,
+        None => return respond(ctx, command, "You don't own a temporary channel.", true).await,
+    };
+
+    if command.data.name == "global_unmute" {
+        let muted = handler.global_muted(channel_id);
+        if muted.is_empty() {
+            return respond(ctx, command, "Nobody was muted with `/global_mute`.", true).await;
+        }
+        // Can take a while with the batching, Discord wants an answer within 3 seconds
+        command.defer(&ctx.http).await?;
+        let unmuted = handler.set_server_mute(ctx, guild.id, &muted, false).await;
+        // The rest stay noted, they're unmuted once they're back in voice
+        handler.remove_global_muted(guild.id, channel_id, &unmuted);
+        handler.record_change(channel_id, command.user.id, ChangeAction::GlobalUnmute(unmuted.len()));
+
+        let failed = muted.len() - unmuted.len();
+        let reply = match failed {
+            0 => format!("Unmuted {} member(s).", unmuted.len()),
+            n => format!(
+                "Unmuted {} member(s), {} couldn't be unmuted right now and will be when they're back in voice.",
+                unmuted.len(),
+                n
+            ),
+        };
+        command
+            .edit_original_interaction_response(&ctx.http, |r| r.content(reply))
+            .await?;
+        return Ok(());
+    }
+
+    // Members a moderator already muted are left alone, so unmuting won't undo that
+    let targets: Vec<UserId> = guild
+        .voice_states
+        .values()
+        .filter(|v| v.channel_id == Some(channel_id) && v.user_id != command.user.id && !v.mute)
+        .map(|v| v.user_id)
+        .collect();
+    if targets.is_empty() {
+        return respond(ctx, command, "There's nobody to mute.", true).await;
+    }
+
+    command.defer(&ctx.http).await?;
+    let muted = handler.set_server_mute(ctx, guild.id, &targets, true).await;
+    handler.add_global_muted(guild.id, channel_id, &muted);
+    handler.record_change(channel_id, command.user.id, ChangeAction::GlobalMute(muted.len()));
+
+    command
+        .edit_original_interaction_response(&ctx.http, |r| {
+            r.content(format!("Muted {} member(s), `/global_unmute` lifts it.", muted.len()))
+        })
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,222 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::{
+        application::{
+            command::CommandOptionType,
+            component::ButtonStyle,
+            interaction::application_command::ApplicationCommandInteraction,
+        },
+        channel::Channel,
+        id::{ChannelId, UserId},
+        permissions::Permissions,
+        user::OnlineStatus,
+    },
+    prelude::*,
+};
+use std::collections::{HashMap, HashSet};
+use tracing::warn;
+
+use super::{option_role, respond, SlashCommand};
+use crate::{
+    error::BotError,
+    handler::Handler,
+    utils::{create_embed, is_member_gone},
+};
+
+/// Bigger roles should be pinged instead, this many DMs already takes a while.
+const MAX_ROLE_MEMBERS: usize = 50;
+
+pub struct GroupInviteCommand;
+
+#[async_trait]
+impl SlashCommand for GroupInviteCommand {
+    fn name(&self) -> &'static str {
+        "group_invite"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Invite the online members of a role to your channel")
+            .dm_permission(false)
+            .create_option(|o| {
+                o.name("role")
+                    .description("Role to invite")
+                    .kind(CommandOptionType::Role)
+                    .required(true)
+            });
+        command
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+/// Who of a role gets what: a DM, a move, or nothing because they're in voice already.
+#[derive(Debug, Default, PartialEq)]
+pub struct Invitees {
+    pub dm: Vec<UserId>,
+    pub moves: Vec<UserId>,
+    pub in_voice: usize,
+}
+
+/// Sorts the online `members` by where they are. Members in `channel_id` are skipped,
+/// members in another voice channel are moved if `can_move`.
+pub fn sort_invitees(
+    members: &[UserId],
+    online: &HashSet<UserId>,
+    voice: &HashMap<UserId, ChannelId>,
+    channel_id: ChannelId,
+    can_move: bool,
+) -> Invitees {
+    let mut invitees = Invitees::default();
+    for user_id in members.iter().filter(|m| online.contains(m)) {
+        match voice.get(user_id) {
+            None => invitees.dm.push(*user_id),
+            Some(current) if *current == channel_id => {}
+            Some(_) if can_move => invitees.moves.push(*user_id),
+            Some(_) => invitees.in_voice += 1,
+        }
+    }
+    invitees
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let guild = match command.guild_id.and_then(|id| id.to_guild_cached(&ctx.cache)) {
+        Some(guild) => guild,
+        None => return respond(ctx, command, "This command only works in a server.", true).await,
+    };
+    let channel_id = match handler.get_user_channel(command.user.id).await {
+        Some(id) => id,
+        None => return respond(ctx, command, "You don't own a temporary channel.", true).await,
+    };
+    let role_id = match option_role(&command.data.options, "role") {
+        Some(id) => id,
+        None => return respond(ctx, command, "Please pick a role.", true).await,
+    };
+
+    let members: Vec<UserId> = guild
+        .members
+        .values()
+        .filter(|m| m.roles.contains(&role_id) && !m.user.bot && m.user.id != command.user.id)
+        .map(|m| m.user.id)
+        .collect();
+    if members.len() > MAX_ROLE_MEMBERS {
+        return respond(
+            ctx,
+            command,
+            format!(
+                "<@&{}> has {} members, group invites work for up to {}.",
+                role_id,
+                members.len(),
+                MAX_ROLE_MEMBERS
+            ),
+            true,
+        )
+        .await;
+    }
+
+    let online: HashSet<UserId> = guild
+        .presences
+        .values()
+        .filter(|p| p.status != OnlineStatus::Offline && p.status != OnlineStatus::Invisible)
+        .map(|p| p.user.id)
+        .collect();
+    let voice: HashMap<UserId, ChannelId> = guild
+        .voice_states
+        .values()
+        .filter_map(|v| v.channel_id.map(|c| (v.user_id, c)))
+        .collect();
+    let can_move = command
+        .member
+        .as_ref()
+        .and_then(|m| m.permissions)
+        .is_some_and(|p| p.contains(Permissions::MOVE_MEMBERS));
+    let invitees = sort_invitees(&members, &online, &voice, channel_id, can_move);
+
+    // One DM per member takes a while, Discord wants an answer within 3 seconds
+    command.defer_ephemeral(&ctx.http).await?;
+    let config = handler.guild_config(guild.id).await;
+    let channel_name = match guild.channels.get(&channel_id) {
+        Some(Channel::Guild(gc)) => gc.name.clone(),
+        _ => channel_id.to_string(),
+    };
+    let role_name = guild.roles.get(&role_id).map(|r| r.name.clone()).unwrap_or_else(|| role_id.to_string());
+    let join_url = format!("https://discord.com/channels/{}/{}", guild.id, channel_id);
+
+    let mut moved = 0;
+    for user_id in &invitees.moves {
+        match guild.id.move_member(&ctx.http, *user_id, channel_id).await {
+            Ok(_) => moved += 1,
+            Err(e) if is_member_gone(&e) => {}
+            Err(e) => warn!("Kon {} niet verplaatsen naar {}: {:?}", user_id, channel_id, e),
+        }
+    }
+
+    let mut dmed = 0;
+    for user_id in &invitees.dm {
+        let mut embed = create_embed(&config);
+        embed.title("Group invite").description(format!(
+            "<@{}> invites **{}** to join **{}** in **{}**.",
+            command.user.id, role_name, channel_name, guild.name
+        ));
+        let sent = handler
+            .send_dm_message(ctx, *user_id, |m| {
+                m.set_embed(embed).components(|c| {
+                    c.create_action_row(|row| {
+                        row.create_button(|b| b.label("Join").style(ButtonStyle::Link).url(&join_url))
+                    })
+                })
+            })
+            .await;
+        if sent {
+            dmed += 1;
+        }
+    }
+
+    let mut embed = create_embed(&config);
+    embed.title("Group invite").description(format!(
+        "Invited {} users, DMed {} users, {} users already in a voice channel.",
+        moved + dmed,
+        dmed,
+        invitees.in_voice
+    ));
+    if moved > 0 {
+        embed.field("Moved", moved, true);
+    }
+    command
+        .edit_original_interaction_response(&ctx.http, |r| r.set_embed(embed))
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sorts_by_voice_state() {
+        let channel = ChannelId(100);
+        let members: Vec<UserId> = (1..=5).map(UserId).collect();
+        let online = HashSet::from([UserId(1), UserId(2), UserId(3), UserId(4)]);
+        let voice = HashMap::from([(UserId(2), channel), (UserId(3), ChannelId(200))]);
+
+        let invitees = sort_invitees(&members, &online, &voice, channel, false);
+        assert_eq!(invitees, Invitees { dm: vec![UserId(1), UserId(4)], moves: vec![], in_voice: 1 });
+
+        let invitees = sort_invitees(&members, &online, &voice, channel, true);
+        assert_eq!(invitees.moves, [UserId(3)]);
+        assert_eq!(invitees.in_voice, 0);
+    }
+}
@@ -0,0 +1,151 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::{
+        application::{
+            command::CommandOptionType,
+            interaction::application_command::ApplicationCommandInteraction,
+        },
+        channel::ChannelType,
+        prelude::*,
+    },
+    prelude::*,
+};
+
+use super::{option_channel, respond, SlashCommand};
+use crate::{
+    error::BotError,
+    handler::{voice_server_host, Handler},
+    utils::{create_embed, probe_voice_connection, VoiceProbeError, VOICE_PROBE_TIMEOUT},
+};
+
+pub struct HealthVoiceCommand;
+
+#[async_trait]
+impl SlashCommand for HealthVoiceCommand {
+    fn name(&self) -> &'static str {
+        "health_voice"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Check that the bot can connect to a voice channel")
+            .dm_permission(false)
+            .create_option(|o| {
+                o.name("channel")
+                    .description("Voice channel to connect to")
+                    .kind(CommandOptionType::Channel)
+                    .channel_types(&[ChannelType::Voice, ChannelType::Stage])
+                    .required(true)
+            });
+        command
+    }
+
+    fn required_permissions(&self) -> Permissions {
+        Permissions::MANAGE_GUILD
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+/// What went wrong and what to do about it.
+fn failure_message(error: &VoiceProbeError) -> String {
+    match error {
+        VoiceProbeError::NoVoiceSupport => {
+            "This build of the bot has no voice support, rebuild it with the `sound` feature.".to_string()
+        }
+        VoiceProbeError::NoSongbird => {
+            "Songbird isn't registered on the client, check the bot's startup code.".to_string()
+        }
+        VoiceProbeError::Busy => {
+            "The bot is already connected to a voice channel in this server, try again when it has left.".to_string()
+        }
+        VoiceProbeError::TimedOut => format!(
+            "No connection within {}s. Discord's voice servers may be having trouble, try another region with `/region`.",
+            VOICE_PROBE_TIMEOUT.as_secs()
+        ),
+        VoiceProbeError::Failed(e) => format!(
+            "Connecting failed: {}. Check that the bot has the Connect permission in the channel.",
+            e
+        ),
+    }
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let guild = match command.guild_id.and_then(|id| id.to_guild_cached(&ctx.cache)) {
+        Some(guild) => guild,
+        None => return respond(ctx, command, "This command only works in a server.", true).await,
+    };
+
+    let channel = match option_channel(&command.data.options, "channel").and_then(|id| guild.channels.get(&id)) {
+        Some(Channel::Guild(channel)) => channel.clone(),
+        _ => return respond(ctx, command, "That channel doesn't exist anymore, pick another one.", true).await,
+    };
+
+    let bot_can_connect = guild
+        .members
+        .get(&ctx.cache.current_user_id())
+        .map(|bot| guild.user_permissions_in(&channel, bot).is_ok_and(|p| p.connect()));
+    if bot_can_connect == Some(false) {
+        return respond(
+            ctx,
+            command,
+            format!("The bot doesn't have the Connect permission in <#{}>, grant it and try again.", channel.id),
+            true,
+        )
+        .await;
+    }
+
+    command.defer_ephemeral(&ctx.http).await?;
+    let result = probe_voice_connection(ctx, guild.id, channel.id).await;
+
+    let config = handler.guild_config(guild.id).await;
+    let mut embed = create_embed(&config);
+    embed.title(format!("Voice check: {}", channel.name));
+    match result {
+        Ok(probe) => {
+            let server = probe
+                .endpoint
+                .as_deref()
+                .map_or_else(|| "Unknown".to_string(), |e| voice_server_host(e).to_string());
+            embed
+                .field("Status", "✅ Connected", true)
+                .field("Join latency", format!("{} ms", probe.latency.as_millis()), true)
+                .field("Voice server", server, true);
+        }
+        Err(e) => {
+            embed
+                .field("Status", "❌ Failed", true)
+                .description(failure_message(&e));
+        }
+    }
+
+    command
+        .edit_original_interaction_response(&ctx.http, |r| r.set_embed(embed))
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failures_say_what_to_do() {
+        assert!(failure_message(&VoiceProbeError::NoVoiceSupport).contains("`sound` feature"));
+        assert!(failure_message(&VoiceProbeError::TimedOut).contains("5s"));
+        assert!(failure_message(&VoiceProbeError::Failed("Dropped".to_string())).contains("Connect permission"));
+    }
+}
@@ -0,0 +1,146 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::{
+        application::{
+            command::CommandOptionType,
+            interaction::application_command::ApplicationCommandInteraction,
+        },
+        channel::Channel,
+        guild::Guild,
+        id::{ChannelId, GuildId, UserId},
+    },
+    prelude::*,
+};
+
+use super::{option_user, respond, SlashCommand};
+use crate::{
+    error::BotError,
+    handler::{ChangeAction, Handler},
+    utils::is_member_gone,
+};
+
+pub struct JoinMeCommand;
+
+#[async_trait]
+impl SlashCommand for JoinMeCommand {
+    fn name(&self) -> &'static str {
+        "join_me"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Pull someone from another voice channel into yours")
+            .dm_permission(false)
+            .create_option(|o| {
+                o.name("user")
+                    .description("Who to bring in")
+                    .kind(CommandOptionType::User)
+                    .required(true)
+            });
+        command
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let guild = match command.guild_id.and_then(|id| id.to_guild_cached(&ctx.cache)) {
+        Some(guild) => guild,
+        None => return respond(ctx, command, "This command only works in a server.", true).await,
+    };
+    let channel_id = match handler.get_user_channel(command.user.id).await {
+        Some(id) => id,
+        None => return respond(ctx, command, "You don't own a temporary channel.", true).await,
+    };
+    let target = match option_user(&command.data.options, "user") {
+        Some(id) => id,
+        None => return respond(ctx, command, "Please pick a user.", true).await,
+    };
+
+    // Moving someone in needs the permission server-wide, not only in the temp channel
+    let bot_can_move = guild
+        .members
+        .get(&ctx.cache.current_user_id())
+        .is_some_and(|bot| bot.permissions(&ctx.cache).is_ok_and(|p| p.move_members()));
+    if !bot_can_move {
+        return respond(ctx, command, "The bot needs Move Members in this server to do that.", true).await;
+    }
+
+    match guild.voice_states.get(&target).and_then(|v| v.channel_id) {
+        Some(current) if current == channel_id => {
+            return respond(ctx, command, format!("<@{}> is already in your channel.", target), true).await;
+        }
+        // The bot can move anyone, owners only get to pull people from channels they could join
+        Some(current) if !can_join(&guild, current, command.user.id) => {
+            return respond(
+                ctx,
+                command,
+                format!("<@{}> is in a channel you can't join, they'll have to come over themselves.", target),
+                true,
+            )
+            .await;
+        }
+        Some(_) => {}
+        None if in_voice_elsewhere(ctx, guild.id, target) => {
+            return respond(
+                ctx,
+                command,
+                format!("<@{}> is in a voice channel in another server, Discord can't move people between servers.", target),
+                true,
+            )
+            .await;
+        }
+        None => return respond(ctx, command, format!("<@{}> is not in a voice channel.", target), true).await,
+    }
+
+    // Locked channels get the same one-time grant as /visit, revoked when they leave
+    let can_connect = match (guild.channels.get(&channel_id), guild.members.get(&target)) {
+        (Some(Channel::Guild(channel)), Some(member)) => {
+            guild.user_permissions_in(channel, member).is_ok_and(|p| p.connect())
+        }
+        _ => false,
+    };
+    if !can_connect {
+        handler.start_visit(ctx, channel_id, target, "/join_me").await?;
+    }
+
+    match guild.id.move_member(&ctx.http, target, channel_id).await {
+        Ok(_) => {}
+        Err(e) if is_member_gone(&e) => {
+            return respond(ctx, command, format!("<@{}> left voice before they could be moved.", target), true).await;
+        }
+        Err(e) => return Err(e),
+    }
+    handler.record_change(channel_id, command.user.id, ChangeAction::JoinMe(target));
+
+    respond(ctx, command, format!("Moved <@{}> into <#{}>.", target, channel_id), true).await
+}
+
+fn can_join(guild: &Guild, channel_id: ChannelId, user_id: UserId) -> bool {
+    match (guild.channels.get(&channel_id), guild.members.get(&user_id)) {
+        (Some(Channel::Guild(channel)), Some(member)) => guild
+            .user_permissions_in(channel, member)
+            .is_ok_and(|p| p.view_channel() && p.connect()),
+        _ => false,
+    }
+}
+
+fn in_voice_elsewhere(ctx: &Context, guild_id: GuildId, user_id: UserId) -> bool {
+    ctx.cache.guilds().into_iter().filter(|id| *id != guild_id).any(|id| {
+        id.to_guild_cached(&ctx.cache)
+            .is_some_and(|g| g.voice_states.get(&user_id).is_some_and(|v| v.channel_id.is_some()))
+    })
+}
@@ -0,0 +1,174 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::{
+        application::{command::CommandOptionType, interaction::application_command::ApplicationCommandInteraction},
+        channel::{Channel, ChannelType},
+        guild::Guild,
+        id::{ChannelId, UserId},
+        permissions::Permissions,
+    },
+    prelude::*,
+};
+use super::{confirm, finish, option_channel, respond, Prompt, SlashCommand};
+use crate::{error::BotError, handler::{ChangeAction, Handler}, utils::create_embed};
+
+pub struct LinkChannelsCommand;
+
+#[async_trait]
+impl SlashCommand for LinkChannelsCommand {
+    fn name(&self) -> &'static str {
+        "link_channels"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Merge two temporary channels, everyone moves to the busier one")
+            .dm_permission(false)
+            .create_option(|o| {
+                o.name("first")
+                    .description("First temporary channel")
+                    .kind(CommandOptionType::Channel)
+                    .channel_types(&[ChannelType::Voice, ChannelType::Stage])
+                    .required(true)
+            })
+            .create_option(|o| {
+                o.name("second")
+                    .description("Second temporary channel")
+                    .kind(CommandOptionType::Channel)
+                    .channel_types(&[ChannelType::Voice, ChannelType::Stage])
+                    .required(true)
+            });
+        command
+    }
+
+    fn required_permissions(&self) -> Permissions {
+        Permissions::MANAGE_GUILD
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+/// Which channel stays: the one with more members, the first one on a tie.
+pub fn merge_order(first: (ChannelId, usize), second: (ChannelId, usize)) -> (ChannelId, ChannelId) {
+    if second.1 > first.1 {
+        (second.0, first.0)
+    } else {
+        (first.0, second.0)
+    }
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let guild = match command.guild_id.and_then(|id| id.to_guild_cached(&ctx.cache)) {
+        Some(guild) => guild,
+        None => return respond(ctx, command, "This command only works in a server.", true).await,
+    };
+
+    let options = &command.data.options;
+    let (first, second) = match (option_channel(options, "first"), option_channel(options, "second")) {
+        (Some(first), Some(second)) if first != second => (first, second),
+        _ => return respond(ctx, command, "Pick two different channels.", true).await,
+    };
+    if !handler.is_tracked(first) || !handler.is_tracked(second) {
+        return respond(ctx, command, "Both channels have to be temporary channels.", true).await;
+    }
+
+    let members_of = |channel_id| members(&guild, channel_id);
+    let (kept, merged) = merge_order(
+        (first, members_of(first).len()),
+        (second, members_of(second).len()),
+    );
+    if let Some(problem) = limit_problem(&guild, kept, merged) {
+        return respond(ctx, command, problem, true).await;
+    }
+
+    let mentions = |channel_id| {
+        let members = members_of(channel_id);
+        if members.is_empty() {
+            "Nobody".to_string()
+        } else {
+            members.iter().map(|id| format!("<@{}>", id)).collect::<Vec<_>>().join("\n")
+        }
+    };
+    let mut embed = create_embed(&handler.guild_config(guild.id).await);
+    embed
+        .title("Merge channels")
+        .description(format!("Everyone in <#{}> moves to <#{}>, which stays.", merged, kept))
+        .field("Stays", format!("<#{}>\n{}", kept, mentions(kept)), true)
+        .field("Gets deleted", format!("<#{}>\n{}", merged, mentions(merged)), true)
+        .footer(|f| f.text("The owner of the deleted channel becomes a co-owner."));
+
+    let Some(interaction) = confirm(ctx, command, "Merge", Prompt::embed(embed)).await? else { return Ok(()) };
+    let content = if !handler.is_tracked(kept) || !handler.is_tracked(merged) {
+        "One of the channels is gone, nothing was merged.".to_string()
+    } else if let Some(problem) = guild
+        .id
+        .to_guild_cached(&ctx.cache)
+        .and_then(|guild| limit_problem(&guild, kept, merged))
+    {
+        // People joined while the buttons were up
+        format!("{} Nothing was merged.", problem)
+    } else {
+        handler.merge_channels(ctx, guild.id, kept, merged).await?;
+        handler.record_change(kept, command.user.id, ChangeAction::Merged(merged));
+        handler
+            .audit_log(
+                ctx,
+                guild.id,
+                &format!("<@{}> merged <#{}> into <#{}>", command.user.id, merged, kept),
+            )
+            .await;
+        format!("Merged into <#{}>.", kept)
+    };
+    finish(ctx, &interaction, content).await
+}
+
+fn members(guild: &Guild, channel_id: ChannelId) -> Vec<UserId> {
+    guild
+        .voice_states
+        .values()
+        .filter(|v| v.channel_id == Some(channel_id))
+        .map(|v| v.user_id)
+        .collect()
+}
+
+/// Why `merged` doesn't fit into `kept`, `None` if it does.
+fn limit_problem(guild: &Guild, kept: ChannelId, merged: ChannelId) -> Option<String> {
+    let total = members(guild, kept).len() + members(guild, merged).len();
+    user_limit(guild, kept)
+        .filter(|limit| total > *limit)
+        .map(|limit| format!("<#{}> allows {} members, merging would put {} in it.", kept, limit, total))
+}
+
+/// The channel's member limit, `None` when it has none.
+fn user_limit(guild: &Guild, channel_id: ChannelId) -> Option<usize> {
+    match guild.channels.get(&channel_id) {
+        Some(Channel::Guild(gc)) => gc.user_limit.filter(|limit| *limit > 0).map(|l| l as usize),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn busier_channel_stays() {
+        let (a, b) = (ChannelId(1), ChannelId(2));
+        assert_eq!(merge_order((a, 2), (b, 5)), (b, a));
+        assert_eq!(merge_order((a, 5), (b, 2)), (a, b));
+        assert_eq!(merge_order((a, 3), (b, 3)), (a, b));
+    }
+}
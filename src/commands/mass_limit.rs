@@ -0,0 +1,188 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::{
+        application::{
+            command::CommandOptionType,
+            interaction::application_command::ApplicationCommandInteraction,
+        },
+        id::ChannelId,
+        permissions::Permissions,
+    },
+    prelude::*,
+};
+use std::time::Duration;
+use tokio::time::sleep;
+use tracing::{info, warn};
+
+use super::{option_str, respond, SlashCommand};
+use crate::{
+    error::BotError,
+    handler::Handler,
+    utils::{create_embed, is_not_found},
+};
+
+/// Channels edited at once, with `BATCH_DELAY` in between to stay clear of rate limits.
+const BATCH_SIZE: usize = 5;
+const BATCH_DELAY: Duration = Duration::from_secs(1);
+
+/// Highest user limit Discord allows on a voice channel.
+const MAX_USER_LIMIT: u64 = 99;
+
+const PROGRESS_WIDTH: usize = 10;
+
+pub struct MassLimitCommand;
+
+#[async_trait]
+impl SlashCommand for MassLimitCommand {
+    fn name(&self) -> &'static str {
+        "mass_limit"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Set the user limit of every temporary channel at once, or `clear` to remove it")
+            .dm_permission(false)
+            .create_option(|o| {
+                o.name("limit")
+                    .description("Members per channel (1-99), or `clear`")
+                    .kind(CommandOptionType::String)
+                    .required(true)
+            });
+        command
+    }
+
+    fn required_permissions(&self) -> Permissions {
+        Permissions::MANAGE_GUILD
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+/// The user limit asked for, 0 for `clear`.
+pub fn parse_limit(input: &str) -> Result<u64, &'static str> {
+    let input = input.trim();
+    if input.eq_ignore_ascii_case("clear") {
+        return Ok(0);
+    }
+    match input.parse::<u64>() {
+        Ok(limit) if (1..=MAX_USER_LIMIT).contains(&limit) => Ok(limit),
+        _ => Err("The limit has to be a number from 1 to 99, or `clear`."),
+    }
+}
+
+/// `▰▰▰▱▱▱▱▱▱▱` filled up to the share of `done` in `total`.
+pub fn progress_bar(done: usize, total: usize) -> String {
+    let filled = (done * PROGRESS_WIDTH).checked_div(total).unwrap_or(PROGRESS_WIDTH).min(PROGRESS_WIDTH);
+    format!("{}{}", "▰".repeat(filled), "▱".repeat(PROGRESS_WIDTH - filled))
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let guild = match command.guild_id.and_then(|id| id.to_guild_cached(&ctx.cache)) {
+        Some(guild) => guild,
+        None => return respond(ctx, command, "This command only works in a server.", true).await,
+    };
+    let limit = match parse_limit(option_str(&command.data.options, "limit").unwrap_or_default()) {
+        Ok(limit) => limit,
+        Err(problem) => return respond(ctx, command, problem, true).await,
+    };
+
+    let mut channels: Vec<ChannelId> = guild.channels.keys().copied().filter(|id| handler.is_tracked(*id)).collect();
+    if channels.is_empty() {
+        return respond(ctx, command, "There are no temporary channels right now.", true).await;
+    }
+    channels.sort();
+
+    // Editing takes a while, Discord wants an answer within 3 seconds
+    command.defer_ephemeral(&ctx.http).await?;
+    let config = handler.guild_config(guild.id).await;
+    let label = match limit {
+        0 => "no limit".to_string(),
+        limit => format!("a limit of {}", limit),
+    };
+    let total = channels.len();
+    let (mut updated, mut gone, mut failed) = (0, 0, 0);
+    for (i, batch) in channels.chunks(BATCH_SIZE).enumerate() {
+        if i > 0 {
+            sleep(BATCH_DELAY).await;
+        }
+        for channel_id in batch {
+            match channel_id.edit(&ctx.http, |c| c.user_limit(limit)).await {
+                Ok(_) => updated += 1,
+                // Deleted since the list was made
+                Err(e) if is_not_found(&e) => gone += 1,
+                Err(e) => {
+                    warn!("Kon limiet van {} niet aanpassen: {:?}", channel_id, e);
+                    failed += 1;
+                }
+            }
+        }
+
+        let processed = updated + gone + failed;
+        let done = processed == total;
+        let mut embed = create_embed(&config);
+        embed
+            .title(if done { "User limits set" } else { "Setting user limits" })
+            .description(format!(
+                "Giving every temporary channel {}.\n{} {}/{}",
+                label,
+                progress_bar(processed, total),
+                processed,
+                total
+            ))
+            .field("Updated", updated.to_string(), true)
+            .field("Failed", failed.to_string(), true);
+        if gone > 0 {
+            embed.field("Deleted meanwhile", gone.to_string(), true);
+        }
+        command
+            .edit_original_interaction_response(&ctx.http, |r| r.set_embed(embed))
+            .await?;
+    }
+
+    info!("Mass limit in guild {}: {} bijgewerkt, {} weg, {} mislukt", guild.id, updated, gone, failed);
+    handler
+        .audit_log(
+            ctx,
+            guild.id,
+            &format!(
+                "<@{}> gave {} temporary channel(s) {} ({} failed, {} deleted meanwhile)",
+                command.user.id, updated, label, failed, gone
+            ),
+        )
+        .await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_are_numbers_or_clear() {
+        assert_eq!(parse_limit(" 5 "), Ok(5));
+        assert_eq!(parse_limit("CLEAR"), Ok(0));
+        assert!(parse_limit("0").is_err());
+        assert!(parse_limit("100").is_err());
+        assert!(parse_limit("five").is_err());
+    }
+
+    #[test]
+    fn progress_bar_fills_up() {
+        assert_eq!(progress_bar(0, 4), "▱▱▱▱▱▱▱▱▱▱");
+        assert_eq!(progress_bar(2, 4), "▰▰▰▰▰▱▱▱▱▱");
+        assert_eq!(progress_bar(4, 4), "▰▰▰▰▰▰▰▰▰▰");
+    }
+}
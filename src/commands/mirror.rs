@@ -0,0 +1,117 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::{
+        application::{
+            command::CommandOptionType,
+            interaction::application_command::ApplicationCommandInteraction,
+        },
+        id::GuildId,
+        permissions::Permissions,
+    },
+    prelude::*,
+};
+
+use super::{clone_config::manages_guild, option_str, respond, SlashCommand};
+use crate::{error::BotError, handler::Handler};
+
+pub struct MirrorCommand;
+
+#[async_trait]
+impl SlashCommand for MirrorCommand {
+    fn name(&self) -> &'static str {
+        "mirror"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("List this server's active channels in another server, read-only")
+            .dm_permission(false)
+            .create_option(|o| {
+                o.name("target_guild")
+                    .description("ID of the server to show them in, leave empty to stop")
+                    .kind(CommandOptionType::String)
+            });
+        command
+    }
+
+    fn required_permissions(&self) -> Permissions {
+        Permissions::MANAGE_GUILD
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let guild_id = match command.guild_id {
+        Some(id) => id,
+        None => return respond(ctx, command, "This command only works in a server.", true).await,
+    };
+
+    let target = match option_str(&command.data.options, "target_guild") {
+        None => {
+            handler.stop_mirror(ctx, guild_id).await;
+            return respond(ctx, command, "This server's channels are no longer mirrored.", true).await;
+        }
+        Some(id) => match id.trim().parse::<u64>() {
+            Ok(id) => GuildId(id),
+            Err(_) => return respond(ctx, command, "That is not a valid server ID.", true).await,
+        },
+    };
+    if target == guild_id {
+        return respond(ctx, command, "Pick a different server to mirror to.", true).await;
+    }
+    // Discord only checks MANAGE_GUILD in this guild, the target is up to us
+    if !manages_guild(ctx, target, command.user.id).await {
+        return respond(
+            ctx,
+            command,
+            "You need Manage Server in the target server, and the bot has to be in it.",
+            true,
+        )
+        .await;
+    }
+    if handler.guild_config(target).await.announce_channel_id.is_none() {
+        return respond(
+            ctx,
+            command,
+            "The target server has no announce channel yet, set one there with /config announce_channel.",
+            true,
+        )
+        .await;
+    }
+
+    // A mirror to another guild is replaced, its list removed there
+    handler.stop_mirror(ctx, guild_id).await;
+    handler
+        .update_guild_config(guild_id, |c| c.mirror_guild_id = Some(target))
+        .await;
+    handler.update_mirror(ctx, guild_id).await?;
+    let target_name = target.name(&ctx.cache).unwrap_or_else(|| target.to_string());
+    handler
+        .audit_log(
+            ctx,
+            guild_id,
+            &format!("<@{}> mirrored the active channels to **{}**", command.user.id, target_name),
+        )
+        .await;
+    respond(
+        ctx,
+        command,
+        format!("The active channels are now listed in **{}**, with names and member counts only.", target_name),
+        true,
+    )
+    .await
+}
@@ -1,4 +1,583 @@
-// TODO: maybe add commands for example 
-// !unlock makes it public 
+// TODO: maybe add commands for example
+// !unlock makes it public
 // !lock makes it private again
 // !help for showing commands
+
+pub mod archive;
+pub mod auto_announce;
+pub mod auto_lock;
+pub mod bulk_kick;
+pub mod bulk_move;
+pub mod changelog;
+pub mod channel_boost;
+pub mod channel_graph;
+pub mod clearperms;
+pub mod clone_config;
+pub mod color;
+pub mod compare;
+pub mod config;
+pub mod config_summary;
+pub mod convert;
+pub mod debug;
+pub mod diagnostics;
+pub mod echo;
+pub mod event_replay;
+pub mod export_config;
+pub mod flags;
+#[cfg(feature = "framework")]
+pub mod framework;
+pub mod global_mute;
+pub mod group_invite;
+pub mod health_voice;
+pub mod join_me;
+pub mod link_channels;
+pub mod mass_limit;
+pub mod mirror;
+pub mod music;
+pub mod name_history;
+pub mod peak_hours;
+pub mod permissions_audit;
+pub mod pin;
+pub mod prefix;
+pub mod purge_orphans;
+pub mod quality;
+pub mod region;
+pub mod reset_cooldowns;
+pub mod roster;
+pub mod server_info;
+pub mod set_joinable_when_full;
+pub mod set_owner_emoji;
+pub mod setup;
+pub mod shuffle;
+pub mod stage;
+pub mod stats;
+pub mod status;
+pub mod summary;
+pub mod tag;
+pub mod timeout;
+pub mod tournament;
+pub mod visit;
+pub mod watch;
+
+use serenity::{
+    async_trait,
+    builder::{CreateApplicationCommand, CreateComponents, CreateEmbed},
+    model::application::{
+        command::Command,
+        component::ButtonStyle,
+        interaction::{
+            application_command::{
+                ApplicationCommandInteraction, CommandDataOption, CommandDataOptionValue,
+            },
+            autocomplete::AutocompleteInteraction,
+            message_component::MessageComponentInteraction,
+            InteractionResponseType,
+        },
+    },
+    model::{
+        channel::Attachment,
+        id::{ChannelId, GuildId, RoleId, UserId},
+        permissions::Permissions,
+    },
+    prelude::*,
+};
+use std::{sync::Arc, time::Duration};
+use tracing::{error, warn};
+
+use crate::{config::DEFAULT_COMMAND_PREFIX, db::stats::Stat, error::BotError, handler::Handler};
+
+/// Voice regions for `/region`, as (slug, display name).
+pub const KNOWN_REGIONS: &[(&str, &str)] = &[
+    ("automatic", "Automatic"),
+    ("brazil", "Brazil"),
+    ("hongkong", "Hong Kong"),
+    ("india", "India"),
+    ("japan", "Japan"),
+    ("rotterdam", "Rotterdam"),
+    ("russia", "Russia"),
+    ("singapore", "Singapore"),
+    ("south-korea", "South Korea"),
+    ("southafrica", "South Africa"),
+    ("sydney", "Sydney"),
+    ("us-central", "US Central"),
+    ("us-east", "US East"),
+    ("us-south", "US South"),
+    ("us-west", "US West"),
+];
+
+/// Per-user cooldowns of the commands that edit a channel. Discord limits how often a
+/// channel can be edited, these keep users from spending that on a few quick retries.
+pub const CHANNEL_BOOST_COOLDOWN: Duration = Duration::from_secs(30);
+pub const MUSIC_COOLDOWN: Duration = Duration::from_secs(30);
+pub const QUALITY_COOLDOWN: Duration = Duration::from_secs(30);
+pub const REGION_COOLDOWN: Duration = Duration::from_secs(30);
+pub const RENAME_COOLDOWN: Duration = Duration::from_secs(30);
+pub const STATUS_COOLDOWN: Duration = Duration::from_secs(15);
+
+/// Buttons of the dialog from `confirm`, answered by its collector.
+pub const CONFIRM_ID: &str = "confirm";
+pub const CANCEL_ID: &str = "cancel";
+
+/// How long the buttons of `confirm` stay usable.
+pub const CONFIRM_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Command names and their cooldowns, for `Cooldown::new`. Slash and prefix commands
+/// share them, names in `prefix::NAMES` are prefix commands.
+pub const COOLDOWNS: &[(&str, Duration)] = &[
+    ("channel_boost", CHANNEL_BOOST_COOLDOWN),
+    ("music", MUSIC_COOLDOWN),
+    ("quality", QUALITY_COOLDOWN),
+    ("region", REGION_COOLDOWN),
+    ("rename", RENAME_COOLDOWN),
+    ("status", STATUS_COOLDOWN),
+];
+
+/// How a command from `COOLDOWNS` is typed, prefix commands with the configured
+/// `prefix` (the default one while they're turned off).
+pub fn cooldown_label(name: &str, prefix: Option<&str>) -> String {
+    if prefix::NAMES.contains(&name) {
+        format!("`{}{}`", prefix.unwrap_or(DEFAULT_COMMAND_PREFIX), name)
+    } else {
+        format!("`/{}`", name)
+    }
+}
+
+/// A slash command: its definition, who may use it and what it does. Adding a command
+/// only takes implementing this and listing it in `CommandRegistry::new`.
+#[async_trait]
+pub trait SlashCommand: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Everything about the command except its name, which comes from `name`.
+    fn definition(&self) -> CreateApplicationCommand;
+
+    /// Permissions a member needs to see and use the command, empty for everyone.
+    fn required_permissions(&self) -> Permissions {
+        Permissions::empty()
+    }
+
+    /// Whether the command also works in DMs, where `command.guild_id` is `None`.
+    fn dm_allowed(&self) -> bool {
+        false
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError>;
+}
+
+pub struct CommandRegistry {
+    commands: Vec<Box<dyn SlashCommand>>,
+    /// Only registered in `DevConfig::guild_id`, never globally.
+    dev_commands: Vec<Box<dyn SlashCommand>>,
+}
+
+impl CommandRegistry {
+    pub fn new() -> Self {
+        Self {
+            commands: vec![
+                Box::new(archive::ArchiveCommand),
+                Box::new(auto_announce::AutoAnnounceCommand),
+                Box::new(auto_lock::AutoLockCommand),
+                Box::new(bulk_kick::BulkKickCommand),
+                Box::new(bulk_move::BulkMoveCommand),
+                Box::new(changelog::ChangelogCommand),
+                Box::new(channel_boost::ChannelBoostCommand),
+                Box::new(channel_graph::ChannelGraphCommand),
+                Box::new(clearperms::ClearPermsCommand),
+                Box::new(clone_config::CloneConfigCommand),
+                Box::new(color::ColorCommand),
+                Box::new(compare::CompareCommand),
+                Box::new(config::ConfigCommand),
+                Box::new(config_summary::ConfigSummaryCommand),
+                Box::new(convert::ConvertCommand),
+                Box::new(convert::DeconvertCommand),
+                Box::new(debug::DebugVoiceStatesCommand),
+                Box::new(diagnostics::DiagnosticsCommand),
+                Box::new(export_config::ExportConfigCommand),
+                Box::new(flags::FlagsCommand),
+                Box::new(global_mute::GlobalMuteCommand),
+                Box::new(global_mute::GlobalUnmuteCommand),
+                Box::new(group_invite::GroupInviteCommand),
+                Box::new(health_voice::HealthVoiceCommand),
+                Box::new(export_config::ImportConfigCommand),
+                Box::new(join_me::JoinMeCommand),
+                Box::new(link_channels::LinkChannelsCommand),
+                Box::new(mass_limit::MassLimitCommand),
+                Box::new(mirror::MirrorCommand),
+                Box::new(music::MusicCommand),
+                Box::new(name_history::NameHistoryCommand),
+                Box::new(peak_hours::PeakHoursCommand),
+                Box::new(permissions_audit::PermissionsAuditCommand),
+                Box::new(pin::PinCommand),
+                Box::new(pin::UnpinCommand),
+                Box::new(quality::QualityCommand),
+                Box::new(region::RegionCommand),
+                Box::new(reset_cooldowns::ResetCooldownsCommand),
+                Box::new(roster::RosterCommand),
+                Box::new(server_info::ServerInfoCommand),
+                Box::new(set_joinable_when_full::SetJoinableWhenFullCommand),
+                Box::new(set_owner_emoji::SetOwnerEmojiCommand),
+                Box::new(setup::SetupCommand),
+                Box::new(shuffle::ShuffleCommand),
+                Box::new(stage::SpeakerCommand),
+                Box::new(stage::AudienceCommand),
+                Box::new(stage::SpeakerRequestCommand),
+                Box::new(stats::StatsCommand),
+                Box::new(status::StatusCommand),
+                Box::new(summary::SummaryCommand),
+                Box::new(tag::TagCommand),
+                Box::new(timeout::TimeoutCommand),
+                Box::new(tournament::TournamentCommand),
+                Box::new(visit::VisitCommand),
+                Box::new(watch::WatchCommand),
+                Box::new(watch::UnwatchCommand),
+            ],
+            dev_commands: vec![
+                Box::new(echo::EchoCommand),
+                Box::new(event_replay::EventReplayCommand),
+                Box::new(purge_orphans::PurgeOrphansCommand),
+            ],
+        }
+    }
+
+    pub async fn register(&self, ctx: &Context) -> Result<Vec<Command>, SerenityError> {
+        Command::set_global_application_commands(&ctx.http, |commands| {
+            for command in &self.commands {
+                commands.add_application_command(full_definition(command.as_ref()));
+            }
+            commands
+        })
+        .await
+    }
+
+    /// Registers the developer commands as guild commands of `guild_id`.
+    pub async fn register_dev(&self, ctx: &Context, guild_id: GuildId) -> Result<Vec<Command>, SerenityError> {
+        guild_id
+            .set_application_commands(&ctx.http, |commands| {
+                for command in &self.dev_commands {
+                    commands.add_application_command(full_definition(command.as_ref()));
+                }
+                commands
+            })
+            .await
+    }
+
+    pub async fn dispatch(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) {
+        let result = match self.commands.iter().chain(&self.dev_commands).find(|c| c.name() == command.data.name) {
+            Some(slash_command) if command.guild_id.is_none() && !slash_command.dm_allowed() => {
+                respond(ctx, command, "This command only works in a server.", true).await.map_err(BotError::from)
+            }
+            Some(slash_command) => match handler.cooldowns().check_and_set(command.user.id, slash_command.name()) {
+                Some(remaining) => {
+                    let message = format!(
+                        "Slow down, you can use `/{}` again in {}s.",
+                        slash_command.name(),
+                        remaining.as_secs().max(1)
+                    );
+                    respond(ctx, command, message, true).await.map_err(BotError::from)
+                }
+                None => {
+                    handler.count(Stat::CommandsExecuted);
+                    slash_command.handle(handler, ctx, command).await
+                }
+            },
+            None => {
+                warn!("Unknown command: {}", command.data.name);
+                return;
+            }
+        };
+
+        if let Err(e) = result {
+            error!("Error handling /{}: {:?}", command.data.name, e);
+            handler.count(Stat::Errors);
+        }
+    }
+}
+
+fn full_definition(command: &dyn SlashCommand) -> CreateApplicationCommand {
+    let mut definition = command.definition();
+    definition.name(command.name());
+    let permissions = command.required_permissions();
+    if !permissions.is_empty() {
+        definition.default_member_permissions(permissions);
+    }
+    definition
+}
+
+impl Default for CommandRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Answers autocomplete requests while the user is typing an option.
+pub async fn handle_autocomplete(ctx: &Context, interaction: &AutocompleteInteraction) {
+    let result = match interaction.data.name.as_str() {
+        "region" => region::autocomplete(ctx, interaction).await,
+        other => {
+            warn!("No autocomplete for command: {}", other);
+            return;
+        }
+    };
+
+    if let Err(e) = result {
+        error!("Error handling autocomplete for /{}: {:?}", interaction.data.name, e);
+    }
+}
+
+pub async fn handle_component(
+    handler: &Handler,
+    ctx: &Context,
+    component: &MessageComponentInteraction,
+) {
+    let result = match component.data.custom_id.as_str() {
+        setup::CREATOR_MENU_ID | setup::WAITING_ROOM_MENU_ID => {
+            setup::handle_select(handler, ctx, component).await
+        }
+        id if id.starts_with(setup::PAGE_PREFIX) => setup::handle_page(handler, ctx, component).await,
+        id if id.starts_with(config_summary::TOGGLE_PREFIX) => {
+            config_summary::handle_toggle(handler, ctx, component).await
+        }
+        id if id.starts_with(stage::APPROVE_PREFIX) || id.starts_with(stage::DENY_PREFIX) => {
+            stage::handle_request_button(handler, ctx, component).await
+        }
+        // Answered by the collectors in `confirm` and the commands' `run`
+        CONFIRM_ID | CANCEL_ID => return,
+        permissions_audit::PREVIOUS_ID | permissions_audit::NEXT_ID => return,
+        other => {
+            warn!("Unknown component: {}", other);
+            return;
+        }
+    };
+
+    if let Err(e) = result {
+        error!("Error handling component {}: {:?}", component.data.custom_id, e);
+    }
+}
+
+pub async fn respond(
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+    content: impl ToString,
+    ephemeral: bool,
+) -> Result<(), SerenityError> {
+    command
+        .create_interaction_response(&ctx.http, |r| {
+            r.kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|d| d.content(content).ephemeral(ephemeral))
+        })
+        .await
+}
+
+/// The question `confirm` asks.
+pub struct Prompt {
+    content: String,
+    embed: Option<CreateEmbed>,
+    confirm_label: &'static str,
+    danger: bool,
+}
+
+impl Prompt {
+    pub fn text(content: impl ToString) -> Self {
+        Self {
+            content: content.to_string(),
+            embed: None,
+            confirm_label: "Confirm",
+            danger: false,
+        }
+    }
+
+    pub fn embed(embed: CreateEmbed) -> Self {
+        Self {
+            embed: Some(embed),
+            ..Self::text("")
+        }
+    }
+
+    /// Makes the Confirm button red with `label`, for changes that are hard to undo.
+    pub fn warning(mut self, label: &'static str) -> Self {
+        self.confirm_label = label;
+        self.danger = true;
+        self
+    }
+}
+
+/// Asks the invoker to Confirm or Cancel with an ephemeral dialog. Returns the click
+/// on Confirm, which still has to be answered, see `finish`. A cancel or timeout is
+/// answered here with "`action` cancelled." or "`action` expired.".
+pub async fn confirm(
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+    action: &str,
+    prompt: Prompt,
+) -> Result<Option<Arc<MessageComponentInteraction>>, SerenityError> {
+    command
+        .create_interaction_response(&ctx.http, |r| {
+            r.kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|d| {
+                    if let Some(embed) = prompt.embed {
+                        d.set_embed(embed);
+                    }
+                    d.content(&prompt.content)
+                        .ephemeral(true)
+                        .components(|c| confirm_buttons(c, prompt.confirm_label, prompt.danger))
+                })
+        })
+        .await?;
+
+    let message = command.get_interaction_response(&ctx.http).await?;
+    let choice = message
+        .await_component_interaction(ctx)
+        .author_id(command.user.id)
+        .timeout(CONFIRM_TIMEOUT)
+        .await;
+    match choice {
+        Some(interaction) if interaction.data.custom_id == CONFIRM_ID => Ok(Some(interaction)),
+        Some(interaction) => {
+            finish(ctx, &interaction, format!("{} cancelled.", action)).await?;
+            Ok(None)
+        }
+        None => {
+            command
+                .edit_original_interaction_response(&ctx.http, |r| {
+                    r.content(format!("{} expired.", action)).set_embeds(Vec::new()).components(|c| c)
+                })
+                .await?;
+            Ok(None)
+        }
+    }
+}
+
+/// Replaces the dialog of `confirm` with `content` once the command is done.
+pub async fn finish(
+    ctx: &Context,
+    interaction: &MessageComponentInteraction,
+    content: impl ToString,
+) -> Result<(), SerenityError> {
+    interaction
+        .create_interaction_response(&ctx.http, |r| {
+            r.kind(InteractionResponseType::UpdateMessage)
+                .interaction_response_data(|d| d.content(content).set_embeds(Vec::new()).components(|c| c))
+        })
+        .await
+}
+
+fn confirm_buttons<'a>(components: &'a mut CreateComponents, label: &str, danger: bool) -> &'a mut CreateComponents {
+    let (confirm_style, cancel_style) = match danger {
+        true => (ButtonStyle::Danger, ButtonStyle::Secondary),
+        false => (ButtonStyle::Success, ButtonStyle::Danger),
+    };
+    components.create_action_row(|row| {
+        row.create_button(|b| b.custom_id(CONFIRM_ID).label(label).style(confirm_style))
+            .create_button(|b| b.custom_id(CANCEL_ID).label("Cancel").style(cancel_style))
+    })
+}
+
+pub fn option_str<'a>(options: &'a [CommandDataOption], name: &str) -> Option<&'a str> {
+    options
+        .iter()
+        .find(|o| o.name == name)
+        .and_then(|o| o.resolved.as_ref())
+        .and_then(|v| match v {
+            CommandDataOptionValue::String(s) => Some(s.as_str()),
+            _ => None,
+        })
+}
+
+pub fn option_bool(options: &[CommandDataOption], name: &str) -> Option<bool> {
+    options
+        .iter()
+        .find(|o| o.name == name)
+        .and_then(|o| o.resolved.as_ref())
+        .and_then(|v| match v {
+            CommandDataOptionValue::Boolean(b) => Some(*b),
+            _ => None,
+        })
+}
+
+pub fn option_int(options: &[CommandDataOption], name: &str) -> Option<i64> {
+    options
+        .iter()
+        .find(|o| o.name == name)
+        .and_then(|o| o.resolved.as_ref())
+        .and_then(|v| match v {
+            CommandDataOptionValue::Integer(i) => Some(*i),
+            _ => None,
+        })
+}
+
+pub fn option_user(options: &[CommandDataOption], name: &str) -> Option<UserId> {
+    options
+        .iter()
+        .find(|o| o.name == name)
+        .and_then(|o| o.resolved.as_ref())
+        .and_then(|v| match v {
+            CommandDataOptionValue::User(user, _) => Some(user.id),
+            _ => None,
+        })
+}
+
+pub fn option_channel(options: &[CommandDataOption], name: &str) -> Option<ChannelId> {
+    options
+        .iter()
+        .find(|o| o.name == name)
+        .and_then(|o| o.resolved.as_ref())
+        .and_then(|v| match v {
+            CommandDataOptionValue::Channel(channel) => Some(channel.id),
+            _ => None,
+        })
+}
+
+pub fn option_role(options: &[CommandDataOption], name: &str) -> Option<RoleId> {
+    options
+        .iter()
+        .find(|o| o.name == name)
+        .and_then(|o| o.resolved.as_ref())
+        .and_then(|v| match v {
+            CommandDataOptionValue::Role(role) => Some(role.id),
+            _ => None,
+        })
+}
+
+pub fn option_attachment<'a>(options: &'a [CommandDataOption], name: &str) -> Option<&'a Attachment> {
+    options
+        .iter()
+        .find(|o| o.name == name)
+        .and_then(|o| o.resolved.as_ref())
+        .and_then(|v| match v {
+            CommandDataOptionValue::Attachment(attachment) => Some(attachment),
+            _ => None,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn command_names_are_unique() {
+        let registry = CommandRegistry::new();
+        let names: HashSet<&str> = registry.commands.iter().map(|c| c.name()).collect();
+        assert_eq!(names.len(), registry.commands.len());
+    }
+
+    #[test]
+    fn cooldowns_belong_to_commands() {
+        let registry = CommandRegistry::new();
+        for (name, _) in COOLDOWNS {
+            let slash = registry.commands.iter().any(|c| c.name() == *name);
+            let typed = prefix::NAMES.contains(name);
+            assert!(slash != typed, "{}", name);
+        }
+    }
+}
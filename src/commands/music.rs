@@ -0,0 +1,118 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::{
+        application::{
+            command::CommandOptionType,
+            interaction::application_command::ApplicationCommandInteraction,
+        },
+        guild::PremiumTier,
+    },
+    prelude::*,
+};
+use tracing::warn;
+
+use super::{option_bool, respond, SlashCommand};
+use crate::{
+    error::BotError,
+    handler::{ChangeAction, ChannelMode, Handler},
+    utils::set_voice_status,
+};
+
+const MUSIC_STATUS: &str = "🎵 Music";
+
+pub struct MusicCommand;
+
+#[async_trait]
+impl SlashCommand for MusicCommand {
+    fn name(&self) -> &'static str {
+        "music"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Switch your channel to music mode with the highest bitrate the server allows")
+            .dm_permission(false)
+            .create_option(|o| {
+                o.name("invite_bot")
+                    .description("Move the server's music bot from the waiting room (default: yes)")
+                    .kind(CommandOptionType::Boolean)
+            });
+        command
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+/// Highest voice bitrate a guild can use at its boost level, in bits per second.
+pub fn max_bitrate(tier: PremiumTier) -> u64 {
+    match tier {
+        PremiumTier::Tier1 => 128_000,
+        PremiumTier::Tier2 => 256_000,
+        PremiumTier::Tier3 => 384_000,
+        _ => 96_000,
+    }
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let guild = match command.guild_id.and_then(|id| id.to_guild_cached(&ctx.cache)) {
+        Some(guild) => guild,
+        None => return respond(ctx, command, "This command only works in a server.", true).await,
+    };
+
+    let channel_id = match handler.get_user_channel(command.user.id).await {
+        Some(id) => id,
+        None => return respond(ctx, command, "You don't own a temporary channel.", true).await,
+    };
+    if handler.is_stage_channel(channel_id) {
+        return respond(ctx, command, "Stage channels can't use music mode.", true).await;
+    }
+    if handler.channel_mode(channel_id) == Some(ChannelMode::Music) {
+        return respond(ctx, command, "Your channel is already in music mode.", true).await;
+    }
+
+    let bitrate = max_bitrate(guild.premium_tier);
+    channel_id.edit(&ctx.http, |c| c.bitrate(bitrate)).await?;
+    handler.set_channel_mode(channel_id, ChannelMode::Music);
+    handler.record_change(channel_id, command.user.id, ChangeAction::MusicMode);
+
+    match set_voice_status(handler.http_client(), &ctx.http, channel_id, Some(MUSIC_STATUS)).await {
+        Ok(_) => handler.set_channel_status(channel_id, Some(MUSIC_STATUS.to_string())).await,
+        Err(e) => warn!("Kon muziekstatus van {} niet zetten: {:?}", channel_id, e),
+    }
+
+    let mut reply = format!("Music mode on, bitrate set to {} kbps.", bitrate / 1000);
+
+    let music_bot = handler.guild_config(guild.id).await.music_bot_id;
+    let invite = option_bool(&command.data.options, "invite_bot").unwrap_or(true);
+    if let (Some(bot_id), true) = (music_bot, invite) {
+        let bot_channel = guild.voice_states.get(&bot_id).and_then(|v| v.channel_id);
+        let waiting_room = handler.waiting_room(guild.id).await;
+
+        if bot_channel.is_some() && bot_channel == waiting_room {
+            match guild.id.move_member(&ctx.http, bot_id, channel_id).await {
+                Ok(_) => reply.push_str(&format!("\n<@{}> joined your channel.", bot_id)),
+                Err(e) => {
+                    warn!("Kon muziekbot {} niet verplaatsen: {:?}", bot_id, e);
+                    reply.push_str(&format!("\n<@{}> could not be moved.", bot_id));
+                }
+            }
+        } else {
+            reply.push_str(&format!("\n<@{}> is not in the waiting room.", bot_id));
+        }
+    }
+
+    respond(ctx, command, reply, false).await
+}
@@ -0,0 +1,93 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::{
+        application::{
+            command::CommandOptionType,
+            interaction::{application_command::ApplicationCommandInteraction, InteractionResponseType},
+        },
+        channel::ChannelType,
+        permissions::Permissions,
+    },
+    prelude::*,
+};
+use std::time::Instant;
+
+use super::{option_channel, respond, SlashCommand};
+use crate::{error::BotError, handler::Handler, utils::create_embed};
+
+pub struct NameHistoryCommand;
+
+#[async_trait]
+impl SlashCommand for NameHistoryCommand {
+    fn name(&self) -> &'static str {
+        "name_history"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("See every name a temporary channel has had")
+            .dm_permission(false)
+            .create_option(|o| {
+                o.name("channel")
+                    .description("The temporary channel")
+                    .kind(CommandOptionType::Channel)
+                    .channel_types(&[ChannelType::Voice, ChannelType::Stage])
+                    .required(true)
+            });
+        command
+    }
+
+    fn required_permissions(&self) -> Permissions {
+        Permissions::MANAGE_GUILD
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let guild_id = match command.guild_id {
+        Some(id) => id,
+        None => return respond(ctx, command, "This command only works in a server.", true).await,
+    };
+    let channel_id = match option_channel(&command.data.options, "channel") {
+        Some(id) => id,
+        None => return respond(ctx, command, "Please pick a channel.", true).await,
+    };
+    let history = match handler.name_history(channel_id) {
+        Some(history) => history,
+        None => return respond(ctx, command, format!("<#{}> is not a temporary channel.", channel_id), true).await,
+    };
+    if history.is_empty() {
+        return respond(ctx, command, format!("<#{}> hasn't been renamed.", channel_id), true).await;
+    }
+
+    let now = Instant::now();
+    let lines: Vec<String> = history.iter().rev().map(|change| change.line(now)).collect();
+    let mut embed = create_embed(&handler.guild_config(guild_id).await);
+    embed
+        .title("Name history")
+        .description(format!("<#{}>, newest first\n\n{}", channel_id, lines.join("\n")));
+    command
+        .create_interaction_response(&ctx.http, |r| {
+            r.kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|d| {
+                    d.set_embed(embed)
+                        .ephemeral(true)
+                        .allowed_mentions(|a| a.empty_parse())
+                })
+        })
+        .await
+}
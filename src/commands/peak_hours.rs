@@ -0,0 +1,174 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::{
+        application::interaction::application_command::ApplicationCommandInteraction,
+        permissions::Permissions, Timestamp,
+    },
+    prelude::*,
+};
+
+use super::{respond, SlashCommand};
+use crate::{
+    error::BotError,
+    handler::Handler,
+    utils::{create_embed, format_utc_offset},
+};
+
+/// How far back `/peak_hours` looks.
+const PERIOD_DAYS: i64 = 30;
+
+const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+const DAY_NAMES: [&str; 7] = ["Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday", "Sunday"];
+
+pub struct PeakHoursCommand;
+
+#[async_trait]
+impl SlashCommand for PeakHoursCommand {
+    fn name(&self) -> &'static str {
+        "peak_hours"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("See at what time of day channels were created in the last 30 days")
+            .dm_permission(false);
+        command
+    }
+
+    fn required_permissions(&self) -> Permissions {
+        Permissions::MANAGE_GUILD
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        run(handler, ctx, command).await
+    }
+}
+
+/// Seconds since the epoch in the guild's timezone.
+fn local_secs(unix_secs: i64, utc_offset_minutes: i16) -> i64 {
+    unix_secs + i64::from(utc_offset_minutes) * 60
+}
+
+/// Creations per local hour of the day.
+pub fn hour_counts(timestamps: &[i64], utc_offset_minutes: i16) -> [u32; 24] {
+    let mut counts = [0; 24];
+    for &at in timestamps {
+        let hour = local_secs(at, utc_offset_minutes).rem_euclid(24 * 60 * 60) / (60 * 60);
+        counts[hour as usize] += 1;
+    }
+    counts
+}
+
+/// Creations per local day of the week, Monday first.
+pub fn weekday_counts(timestamps: &[i64], utc_offset_minutes: i16) -> [u32; 7] {
+    let mut counts = [0; 7];
+    for &at in timestamps {
+        let days = local_secs(at, utc_offset_minutes).div_euclid(24 * 60 * 60);
+        // 1970-01-01 was a Thursday
+        counts[(days + 3).rem_euclid(7) as usize] += 1;
+    }
+    counts
+}
+
+/// One line per hour with a bar as high as its share of the busiest hour, and the count.
+pub fn bar_chart(hours: &[u32; 24]) -> String {
+    let max = hours.iter().copied().max().unwrap_or(0);
+    hours
+        .iter()
+        .enumerate()
+        .map(|(hour, &count)| {
+            let bar = match count {
+                0 => ' ',
+                _ => BLOCKS[((count * 8).div_ceil(max) - 1) as usize],
+            };
+            format!("{:02}h {} {}", hour, bar, count)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The day with the most creations, the earliest in the week on a tie.
+pub fn busiest_day(weekdays: &[u32; 7]) -> Option<&'static str> {
+    let max = weekdays.iter().copied().max().filter(|&m| m > 0)?;
+    weekdays.iter().position(|&c| c == max).map(|i| DAY_NAMES[i])
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), BotError> {
+    let guild_id = match command.guild_id {
+        Some(id) => id,
+        None => return Ok(respond(ctx, command, "This command only works in a server.", true).await?),
+    };
+
+    command.defer(&ctx.http).await?;
+    let since = Timestamp::now().unix_timestamp() - PERIOD_DAYS * 24 * 60 * 60;
+    let created = handler.channel_creations(guild_id, since).await?;
+    let config = handler.guild_config(guild_id).await;
+
+    let mut embed = create_embed(&config);
+    embed.title(format!("Channel creations in the last {} days", PERIOD_DAYS));
+    if created.is_empty() {
+        embed.description("No channels were created in this period.");
+    } else {
+        let hours = hour_counts(&created, config.utc_offset_minutes);
+        let weekdays = weekday_counts(&created, config.utc_offset_minutes);
+        embed
+            .description(format!("```\n{}\n```", bar_chart(&hours)))
+            .field("Total", created.len().to_string(), true)
+            .field("Busiest day", busiest_day(&weekdays).unwrap_or("None"), true)
+            .footer(|f| f.text(format!("Times in UTC{}, change it with /config utc_offset", format_utc_offset(config.utc_offset_minutes))));
+    }
+
+    command
+        .edit_original_interaction_response(&ctx.http, |r| r.set_embed(embed))
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2026-10-12 was a Monday
+    const MONDAY_NOON: i64 = 1_791_806_400;
+
+    #[test]
+    fn counts_in_local_time() {
+        let times = [MONDAY_NOON, MONDAY_NOON + 60, MONDAY_NOON + 12 * 60 * 60];
+
+        let hours = hour_counts(&times, 0);
+        assert_eq!((hours[12], hours[0]), (2, 1));
+        let hours = hour_counts(&times, -120);
+        assert_eq!((hours[10], hours[22]), (2, 1));
+
+        assert_eq!(busiest_day(&weekday_counts(&times, 0)), Some("Monday"));
+        // Midnight UTC on Tuesday is still Monday two hours behind
+        assert_eq!(weekday_counts(&times, -120)[0], 3);
+        assert_eq!(weekday_counts(&times, 13 * 60)[1], 3);
+        assert_eq!(busiest_day(&[0; 7]), None);
+    }
+
+    #[test]
+    fn bars_scale_to_the_busiest_hour() {
+        let mut hours = [0; 24];
+        hours[3] = 1;
+        hours[18] = 8;
+        let chart = bar_chart(&hours);
+        let lines: Vec<&str> = chart.lines().collect();
+        assert_eq!(lines.len(), 24);
+        assert_eq!(lines[0], "00h   0");
+        assert_eq!(lines[3], "03h ▁ 1");
+        assert_eq!(lines[18], "18h █ 8");
+    }
+}
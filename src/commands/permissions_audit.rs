@@ -0,0 +1,337 @@
+use serenity::{
+    async_trait,
+    builder::{CreateApplicationCommand, CreateComponents, CreateEmbed},
+    model::{
+        application::{
+            component::ButtonStyle,
+            interaction::{application_command::ApplicationCommandInteraction, InteractionResponseType},
+        },
+        prelude::*,
+    },
+    prelude::*,
+};
+use std::time::Duration;
+
+use super::{respond, SlashCommand};
+use crate::{config::GuildConfig, error::BotError, handler::Handler, utils::create_embed};
+
+pub const PREVIOUS_ID: &str = "permissions_audit_previous";
+pub const NEXT_ID: &str = "permissions_audit_next";
+
+/// Channels per page, each gets its own embed so it can have its own color.
+const CHANNELS_PER_PAGE: usize = 5;
+/// How long the page buttons stay usable after the last click.
+const PAGE_TIMEOUT: Duration = Duration::from_secs(120);
+/// Color of channels with overwrites the bot didn't make.
+const UNMANAGED_COLOR: u32 = 0xE74C3C;
+/// Color of channels everyone can connect to.
+const OPEN_COLOR: u32 = 0xE67E22;
+
+pub struct PermissionsAuditCommand;
+
+#[async_trait]
+impl SlashCommand for PermissionsAuditCommand {
+    fn name(&self) -> &'static str {
+        "permissions_audit"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("List who was given access to temporary channels and why")
+            .dm_permission(false);
+        command
+    }
+
+    fn required_permissions(&self) -> Permissions {
+        Permissions::MANAGE_GUILD
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+/// Why a member can connect to a temp channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Owner,
+    CoOwner,
+    /// A running `/visit` grant.
+    Visit,
+    /// An overwrite the bot doesn't know about, added by hand in Discord.
+    Unmanaged,
+}
+
+impl Access {
+    fn label(self) -> &'static str {
+        match self {
+            Access::Owner => "owner",
+            Access::CoOwner => "co-owner",
+            Access::Visit => "visit",
+            Access::Unmanaged => "⚠️ unmanaged",
+        }
+    }
+}
+
+/// The bot's state of a temp channel, to tell its own overwrites from others.
+pub struct Managed {
+    pub owner: Option<UserId>,
+    pub co_owners: Vec<UserId>,
+    pub visitors: Vec<UserId>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ChannelAudit {
+    pub channel_id: ChannelId,
+    /// Members with a `CONNECT` overwrite, the bot's own left out.
+    pub grants: Vec<(UserId, Access)>,
+    /// Whether @everyone is denied `CONNECT`, without it anybody can join.
+    pub everyone_denied: bool,
+}
+
+impl ChannelAudit {
+    fn has_unmanaged(&self) -> bool {
+        self.grants.iter().any(|(_, access)| *access == Access::Unmanaged)
+    }
+}
+
+pub fn audit_channel(
+    channel_id: ChannelId,
+    overwrites: &[PermissionOverwrite],
+    guild_id: GuildId,
+    bot_id: UserId,
+    managed: &Managed,
+) -> ChannelAudit {
+    let everyone = PermissionOverwriteType::Role(guild_id.0.into());
+    let everyone_denied = overwrites
+        .iter()
+        .any(|o| o.kind == everyone && o.deny.contains(Permissions::CONNECT));
+
+    let grants = overwrites
+        .iter()
+        .filter(|o| o.allow.contains(Permissions::CONNECT))
+        .filter_map(|o| match o.kind {
+            PermissionOverwriteType::Member(id) if id != bot_id => Some(id),
+            _ => None,
+        })
+        .map(|id| {
+            let access = if managed.owner == Some(id) {
+                Access::Owner
+            } else if managed.co_owners.contains(&id) {
+                Access::CoOwner
+            } else if managed.visitors.contains(&id) {
+                Access::Visit
+            } else {
+                Access::Unmanaged
+            };
+            (id, access)
+        })
+        .collect();
+
+    ChannelAudit { channel_id, grants, everyone_denied }
+}
+
+fn channel_embed(config: &GuildConfig, audit: &ChannelAudit) -> CreateEmbed {
+    let mut embed = create_embed(config);
+    let mut lines: Vec<String> = audit
+        .grants
+        .iter()
+        .map(|(id, access)| format!("<@{}> — {}", id, access.label()))
+        .collect();
+    if lines.is_empty() {
+        lines.push("No member overwrites.".to_string());
+    }
+    if !audit.everyone_denied {
+        lines.push("⚠️ @everyone isn't denied Connect, anybody can join.".to_string());
+    }
+    embed.description(format!("<#{}>\n{}", audit.channel_id, lines.join("\n")));
+    if audit.has_unmanaged() {
+        embed.colour(UNMANAGED_COLOR);
+    } else if !audit.everyone_denied {
+        embed.colour(OPEN_COLOR);
+    }
+    embed
+}
+
+fn page_embeds(config: &GuildConfig, audits: &[ChannelAudit], page: usize) -> Vec<CreateEmbed> {
+    let pages = audits.len().div_ceil(CHANNELS_PER_PAGE);
+    let mut embeds: Vec<CreateEmbed> = audits
+        .chunks(CHANNELS_PER_PAGE)
+        .nth(page)
+        .unwrap_or_default()
+        .iter()
+        .map(|audit| channel_embed(config, audit))
+        .collect();
+    if let Some(last) = embeds.last_mut() {
+        last.footer(|f| f.text(format!("Page {}/{}", page + 1, pages)));
+    }
+    embeds
+}
+
+fn page_buttons(components: &mut CreateComponents, page: usize, pages: usize) -> &mut CreateComponents {
+    components.create_action_row(|row| {
+        row.create_button(|b| {
+            b.custom_id(PREVIOUS_ID)
+                .label("Previous")
+                .style(ButtonStyle::Secondary)
+                .disabled(page == 0)
+        })
+        .create_button(|b| {
+            b.custom_id(NEXT_ID)
+                .label("Next")
+                .style(ButtonStyle::Secondary)
+                .disabled(page + 1 >= pages)
+        })
+    })
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let guild = match command.guild_id.and_then(|id| id.to_guild_cached(&ctx.cache)) {
+        Some(guild) => guild,
+        None => return respond(ctx, command, "This command only works in a server.", true).await,
+    };
+    let mut channel_ids: Vec<ChannelId> = guild
+        .channels
+        .keys()
+        .filter(|id| handler.is_tracked(**id))
+        .copied()
+        .collect();
+    if channel_ids.is_empty() {
+        return respond(ctx, command, "There are no temporary channels right now.", true).await;
+    }
+    channel_ids.sort();
+    command.defer_ephemeral(&ctx.http).await?;
+
+    // The cache can lag behind, the overwrites are what Discord has right now
+    let bot_id = ctx.cache.current_user_id();
+    let mut audits = Vec::new();
+    for channel_id in channel_ids {
+        let overwrites = match channel_id.to_channel(&ctx.http).await {
+            Ok(Channel::Guild(channel)) => channel.permission_overwrites,
+            _ => continue,
+        };
+        let managed = Managed {
+            owner: handler.channel_owner(channel_id),
+            co_owners: handler.co_owners(channel_id),
+            visitors: handler.visitors(channel_id),
+        };
+        audits.push(audit_channel(channel_id, &overwrites, guild.id, bot_id, &managed));
+    }
+
+    let config = handler.guild_config(guild.id).await;
+    let pages = audits.len().div_ceil(CHANNELS_PER_PAGE);
+    let mut page = 0;
+    let unmanaged = audits.iter().filter(|a| a.has_unmanaged()).count();
+    let open = audits.iter().filter(|a| !a.everyone_denied).count();
+    let summary = format!(
+        "{} temporary channel(s), {} with unmanaged overwrites, {} open to everyone.",
+        audits.len(),
+        unmanaged,
+        open
+    );
+
+    let message = command
+        .edit_original_interaction_response(&ctx.http, |r| {
+            r.content(&summary).set_embeds(page_embeds(&config, &audits, page));
+            if pages > 1 {
+                r.components(|c| page_buttons(c, page, pages));
+            }
+            r
+        })
+        .await?;
+    if pages <= 1 {
+        return Ok(());
+    }
+
+    while let Some(click) = message
+        .await_component_interaction(ctx)
+        .author_id(command.user.id)
+        .timeout(PAGE_TIMEOUT)
+        .await
+    {
+        page = match click.data.custom_id.as_str() {
+            PREVIOUS_ID => page.saturating_sub(1),
+            _ => (page + 1).min(pages - 1),
+        };
+        click
+            .create_interaction_response(&ctx.http, |r| {
+                r.kind(InteractionResponseType::UpdateMessage).interaction_response_data(|d| {
+                    d.set_embeds(page_embeds(&config, &audits, page))
+                        .components(|c| page_buttons(c, page, pages))
+                })
+            })
+            .await?;
+    }
+
+    command
+        .edit_original_interaction_response(&ctx.http, |r| r.components(|c| c))
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(id: u64, allow: Permissions) -> PermissionOverwrite {
+        PermissionOverwrite {
+            kind: PermissionOverwriteType::Member(UserId(id)),
+            allow,
+            deny: Permissions::empty(),
+        }
+    }
+
+    #[test]
+    fn grants_are_explained_or_flagged() {
+        let everyone = PermissionOverwrite {
+            kind: PermissionOverwriteType::Role(RoleId(1)),
+            allow: Permissions::empty(),
+            deny: Permissions::CONNECT,
+        };
+        let overwrites = [
+            everyone,
+            member(10, Permissions::CONNECT | Permissions::MOVE_MEMBERS),
+            member(11, Permissions::CONNECT),
+            member(12, Permissions::CONNECT),
+            member(13, Permissions::CONNECT),
+            member(14, Permissions::SPEAK),
+            member(99, Permissions::CONNECT),
+        ];
+        let managed = Managed {
+            owner: Some(UserId(10)),
+            co_owners: vec![UserId(11)],
+            visitors: vec![UserId(12)],
+        };
+
+        let audit = audit_channel(ChannelId(5), &overwrites, GuildId(1), UserId(99), &managed);
+        assert!(audit.everyone_denied);
+        assert_eq!(
+            audit.grants,
+            [
+                (UserId(10), Access::Owner),
+                (UserId(11), Access::CoOwner),
+                (UserId(12), Access::Visit),
+                (UserId(13), Access::Unmanaged),
+            ]
+        );
+        assert!(audit.has_unmanaged());
+    }
+
+    #[test]
+    fn missing_everyone_deny_is_noticed() {
+        let managed = Managed { owner: Some(UserId(10)), co_owners: Vec::new(), visitors: Vec::new() };
+        let audit = audit_channel(ChannelId(5), &[member(10, Permissions::CONNECT)], GuildId(1), UserId(99), &managed);
+        assert!(!audit.everyone_denied);
+        assert!(!audit.has_unmanaged());
+    }
+}
@@ -0,0 +1,134 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::{
+        application::{
+            command::CommandOptionType,
+            interaction::application_command::ApplicationCommandInteraction,
+        },
+        id::MessageId,
+    },
+    prelude::*,
+};
+
+use super::{option_str, respond, SlashCommand};
+use crate::{error::BotError, handler::{ChangeAction, Handler}, utils::is_not_found};
+
+/// Discord allows at most this many pinned messages per channel.
+const MAX_PINS: usize = 50;
+
+pub struct PinCommand;
+
+#[async_trait]
+impl SlashCommand for PinCommand {
+    fn name(&self) -> &'static str {
+        "pin"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Pin a message in your channel's text channel")
+            .dm_permission(false)
+            .create_option(|o| {
+                o.name("message_id")
+                    .description("ID of the message to pin")
+                    .kind(CommandOptionType::String)
+                    .required(true)
+            });
+        command
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+pub struct UnpinCommand;
+
+#[async_trait]
+impl SlashCommand for UnpinCommand {
+    fn name(&self) -> &'static str {
+        "unpin"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Unpin a message in your channel's text channel")
+            .dm_permission(false)
+            .create_option(|o| {
+                o.name("message_id")
+                    .description("ID of the message to unpin")
+                    .kind(CommandOptionType::String)
+                    .required(true)
+            });
+        command
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let channel_id = match handler.get_user_channel(command.user.id).await {
+        Some(id) => id,
+        None => return respond(ctx, command, "You don't own or co-own a temporary channel.", true).await,
+    };
+    let text_channel_id = match handler.text_channel(channel_id) {
+        Some(id) => id,
+        None => return respond(ctx, command, "Your channel has no text channel.", true).await,
+    };
+
+    let message_id = match option_str(&command.data.options, "message_id")
+        .and_then(|id| id.trim().parse::<u64>().ok())
+    {
+        Some(id) => MessageId(id),
+        None => return respond(ctx, command, "That is not a valid message ID.", true).await,
+    };
+
+    // Looked up in the text channel only, so messages elsewhere can't be pinned
+    let message = match text_channel_id.message(&ctx.http, message_id).await {
+        Ok(message) => message,
+        Err(e) if is_not_found(&e) => {
+            let reply = format!("That message isn't in <#{}>.", text_channel_id);
+            return respond(ctx, command, reply, true).await;
+        }
+        Err(e) => return Err(e),
+    };
+
+    if command.data.name == "unpin" {
+        if !message.pinned {
+            return respond(ctx, command, "That message isn't pinned.", true).await;
+        }
+        message.unpin(&ctx.http).await?;
+        handler.record_change(channel_id, command.user.id, ChangeAction::Unpin);
+        return respond(ctx, command, "Message unpinned.", true).await;
+    }
+
+    if message.pinned {
+        return respond(ctx, command, "That message is already pinned.", true).await;
+    }
+    if text_channel_id.pins(&ctx.http).await?.len() >= MAX_PINS {
+        let reply = format!("<#{}> already has {} pins, unpin one first.", text_channel_id, MAX_PINS);
+        return respond(ctx, command, reply, true).await;
+    }
+    message.pin(&ctx.http).await?;
+    handler.record_change(channel_id, command.user.id, ChangeAction::Pin);
+    respond(ctx, command, "Message pinned.", true).await
+}
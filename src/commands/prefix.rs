@@ -0,0 +1,241 @@
+//! Text commands (`!rename`, `!lock`, `!kick`, `!info`) for servers that prefer them
+//! over slash commands. Turned on with `PREFIX_COMMANDS_ENABLED=true`, the prefix is
+//! `COMMAND_PREFIX` (default `!`). Built with the `framework` feature, serenity's
+//! StandardFramework dispatches them instead and adds `!help` (see `commands::framework`).
+//!
+//! Reading messages needs the privileged Message Content intent. Enable it in the
+//! Discord Developer Portal under Applications → your bot → Bot → Privileged Gateway
+//! Intents → Message Content Intent. If it's requested without being enabled there,
+//! Discord refuses the gateway connection with "Disallowed intent(s)". Bots in 100 or
+//! more servers also need Discord to approve the intent during verification.
+
+use serenity::{
+    model::{
+        channel::{Channel, Message},
+        id::{ChannelId, GuildId, UserId},
+    },
+    prelude::*,
+};
+use tracing::error;
+
+use crate::{
+    handler::{boost_label, ChangeAction, Handler},
+    utils::{filter_channel_name, AuditReason},
+};
+
+/// Longest channel name Discord accepts.
+const MAX_NAME_LEN: usize = 100;
+
+/// Names of the prefix commands, as `PrefixCommand::name` gives them.
+pub const NAMES: &[&str] = &["rename", "lock", "kick", "info"];
+
+#[derive(Debug, PartialEq)]
+pub enum PrefixCommand {
+    Rename(String),
+    Lock,
+    Kick(UserId),
+    Info,
+}
+
+impl PrefixCommand {
+    /// The name it's typed as, also its key in `COOLDOWNS`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            PrefixCommand::Rename(_) => "rename",
+            PrefixCommand::Lock => "lock",
+            PrefixCommand::Kick(_) => "kick",
+            PrefixCommand::Info => "info",
+        }
+    }
+}
+
+/// Parses a message into a prefix command. `None` means the message isn't meant for
+/// us, `Some(Err(usage))` is one of our commands used wrong, `usage` without the prefix.
+pub fn parse(content: &str, prefix: &str) -> Option<Result<PrefixCommand, &'static str>> {
+    let rest = content.trim().strip_prefix(prefix)?;
+    let (name, args) = match rest.split_once(char::is_whitespace) {
+        Some((name, args)) => (name, args.trim()),
+        None => (rest, ""),
+    };
+
+    let command = match name.to_lowercase().as_str() {
+        "rename" if args.is_empty() => Err("rename <name>"),
+        "rename" => Ok(PrefixCommand::Rename(args.to_string())),
+        "lock" => Ok(PrefixCommand::Lock),
+        "kick" => parse_user(args).map(PrefixCommand::Kick).ok_or("kick <@user>"),
+        "info" => Ok(PrefixCommand::Info),
+        _ => return None,
+    };
+    Some(command)
+}
+
+/// Accepts a mention (`<@id>` or `<@!id>`) or a bare user ID.
+fn parse_user(arg: &str) -> Option<UserId> {
+    let id = arg
+        .strip_prefix("<@")
+        .and_then(|a| a.strip_suffix('>'))
+        .map(|a| a.trim_start_matches('!'))
+        .unwrap_or(arg);
+    id.parse().ok().map(UserId)
+}
+
+pub async fn handle_message(handler: &Handler, ctx: &Context, msg: &Message, prefix: &str) {
+    if msg.author.bot {
+        return;
+    }
+    let guild_id = match msg.guild_id {
+        Some(id) => id,
+        None => return,
+    };
+
+    let reply = match parse(&msg.content, prefix) {
+        None => return,
+        Some(Err(usage)) => format!("Usage: {}{}", prefix, usage),
+        Some(Ok(command)) => match handler.cooldowns().check_and_set(msg.author.id, command.name()) {
+            Some(remaining) => format!(
+                "Slow down, you can use `{}{}` again in {}s.",
+                prefix,
+                command.name(),
+                remaining.as_secs().max(1)
+            ),
+            None => match run(handler, ctx, msg, guild_id, command).await {
+                Ok(reply) => reply,
+                Err(e) => {
+                    error!("Fout bij prefix command '{}': {:?}", msg.content, e);
+                    return;
+                }
+            },
+        },
+    };
+
+    if let Err(e) = msg.reply(&ctx.http, reply).await {
+        error!("Kon niet antwoorden op prefix command: {:?}", e);
+    }
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    msg: &Message,
+    guild_id: GuildId,
+    command: PrefixCommand,
+) -> Result<String, SerenityError> {
+    let channel_id = match handler.get_user_channel(msg.author.id).await {
+        Some(id) => id,
+        None => return Ok("You don't own a temporary channel.".to_string()),
+    };
+
+    match command {
+        PrefixCommand::Rename(name) => {
+            if name.chars().count() > MAX_NAME_LEN {
+                return Ok("Channel names can be at most 100 characters.".to_string());
+            }
+            let config = handler.guild_config(guild_id).await;
+            if filter_channel_name(&name, &config.name_filter) {
+                return Ok("That name contains a blocked word.".to_string());
+            }
+            channel_id.edit(&ctx.http, |c| c.name(&name)).await?;
+            handler.record_change(channel_id, msg.author.id, ChangeAction::Rename(name.clone()));
+            handler.record_name(channel_id, Some(msg.author.id), &name);
+            Ok(format!("Channel renamed to **{}**.", name))
+        }
+        PrefixCommand::Lock => {
+            handler
+                .lock_channel(ctx, guild_id, channel_id, &AuditReason::Command("!lock"))
+                .await?;
+            handler.record_change(channel_id, msg.author.id, ChangeAction::Lock);
+            Ok("Your channel is locked.".to_string())
+        }
+        PrefixCommand::Kick(target) => {
+            if target == msg.author.id {
+                return Ok("You can't kick yourself.".to_string());
+            }
+            if voice_channel_of(ctx, guild_id, target) != Some(channel_id) {
+                return Ok("That user is not in your channel.".to_string());
+            }
+            guild_id.disconnect_member(&ctx.http, target).await?;
+            handler.record_change(channel_id, msg.author.id, ChangeAction::Kick(target));
+            Ok(format!("<@{}> was kicked from your channel.", target))
+        }
+        PrefixCommand::Info => Ok(info(handler, ctx, guild_id, channel_id, msg.author.id)),
+    }
+}
+
+fn info(
+    handler: &Handler,
+    ctx: &Context,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+    owner_id: UserId,
+) -> String {
+    let (name, members) = guild_id
+        .to_guild_cached(&ctx.cache)
+        .map(|g| {
+            let name = match g.channels.get(&channel_id) {
+                Some(Channel::Guild(gc)) => gc.name.clone(),
+                _ => channel_id.to_string(),
+            };
+            let members = g
+                .voice_states
+                .values()
+                .filter(|v| v.channel_id == Some(channel_id))
+                .count();
+            (name, members)
+        })
+        .unwrap_or_else(|| (channel_id.to_string(), 0));
+
+    let mut lines = vec![
+        format!("**{}**", name),
+        format!("Owner: <@{}>", owner_id),
+        format!("Members: {}", members),
+    ];
+    if let Some(mode) = handler.channel_mode(channel_id) {
+        lines.push(format!("Mode: {:?}", mode));
+    }
+    if let Some(remaining) = handler.boost_remaining(channel_id) {
+        lines.push(boost_label(remaining));
+    }
+    let tags = handler.channel_tags(channel_id);
+    if !tags.is_empty() {
+        lines.push(format!("Tags: {}", tags.join(", ")));
+    }
+    if let Some(server) = handler.voice_server(channel_id) {
+        lines.push(format!("Voice server: {}", server));
+    }
+    lines.join("\n")
+}
+
+fn voice_channel_of(ctx: &Context, guild_id: GuildId, user_id: UserId) -> Option<ChannelId> {
+    guild_id
+        .to_guild_cached(&ctx.cache)
+        .and_then(|g| g.voice_states.get(&user_id).and_then(|v| v.channel_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_commands_and_ignores_others() {
+        assert_eq!(
+            parse("!rename  Late night games ", "!"),
+            Some(Ok(PrefixCommand::Rename("Late night games".to_string())))
+        );
+        assert_eq!(parse("!kick <@!42>", "!"), Some(Ok(PrefixCommand::Kick(UserId(42)))));
+        assert_eq!(parse("!INFO", "!"), Some(Ok(PrefixCommand::Info)));
+        assert!(matches!(parse("!kick someone", "!"), Some(Err(_))));
+        assert_eq!(parse("!play song", "!"), None);
+        assert_eq!(parse("lock", "!"), None);
+    }
+
+    #[test]
+    fn names_match_the_commands() {
+        let commands = [
+            PrefixCommand::Rename(String::new()),
+            PrefixCommand::Lock,
+            PrefixCommand::Kick(UserId(1)),
+            PrefixCommand::Info,
+        ];
+        assert_eq!(commands.map(|c| c.name()), NAMES);
+    }
+}
@@ -0,0 +1,57 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::application::interaction::application_command::ApplicationCommandInteraction,
+    prelude::*,
+};
+
+use super::{respond, SlashCommand};
+use crate::{error::BotError, handler::Handler};
+
+/// Runs the temp channel reconciliation right away instead of waiting for the
+/// background task. A developer command like `/echo`, for the bot owner only.
+pub struct PurgeOrphansCommand;
+
+#[async_trait]
+impl SlashCommand for PurgeOrphansCommand {
+    fn name(&self) -> &'static str {
+        "purge_orphans"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command.description("Forget deleted temp channels and repair owner permissions (bot owner only)");
+        command
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    if !handler.is_bot_owner(command.user.id) {
+        return respond(ctx, command, "This command is only for the bot owner.", true).await;
+    }
+
+    let result = handler.reconcile_state(ctx).await;
+    respond(
+        ctx,
+        command,
+        format!(
+            "Forgot {} deleted channel(s), repaired the permissions of {}, {} with members out of sync (see the logs).",
+            result.removed, result.repaired, result.member_mismatches
+        ),
+        true,
+    )
+    .await
+}
@@ -0,0 +1,96 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::{
+        application::{
+            command::CommandOptionType,
+            interaction::application_command::ApplicationCommandInteraction,
+        },
+        channel::VideoQualityMode,
+        guild::PremiumTier,
+    },
+    prelude::*,
+};
+
+use super::{option_str, respond, SlashCommand};
+use crate::{error::BotError, handler::{ChangeAction, Handler}};
+
+pub struct QualityCommand;
+
+#[async_trait]
+impl SlashCommand for QualityCommand {
+    fn name(&self) -> &'static str {
+        "quality"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Set the video quality of your channel")
+            .dm_permission(false)
+            .create_option(|o| {
+                o.name("mode")
+                    .description("auto lets Discord pick, full needs a boosted server")
+                    .kind(CommandOptionType::String)
+                    .add_string_choice("auto", "auto")
+                    .add_string_choice("full", "full")
+                    .required(true)
+            });
+        command
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let guild = match command.guild_id.and_then(|id| id.to_guild_cached(&ctx.cache)) {
+        Some(guild) => guild,
+        None => return respond(ctx, command, "This command only works in a server.", true).await,
+    };
+
+    let channel_id = match handler.get_user_channel(command.user.id).await {
+        Some(id) => id,
+        None => return respond(ctx, command, "You don't own a temporary channel.", true).await,
+    };
+
+    let mode = match option_str(&command.data.options, "mode") {
+        Some("full") => VideoQualityMode::Full,
+        _ => VideoQualityMode::Auto,
+    };
+
+    // Full quality is a perk of boosted servers
+    let boosted = !matches!(guild.premium_tier, PremiumTier::Tier0 | PremiumTier::Unknown);
+    if mode == VideoQualityMode::Full && !boosted {
+        return respond(
+            ctx,
+            command,
+            "Full video quality needs a server with at least boost level 1.",
+            true,
+        )
+        .await;
+    }
+
+    if handler.video_quality(channel_id) == Some(mode) {
+        return respond(ctx, command, "Your channel already uses that video quality.", true).await;
+    }
+
+    channel_id
+        .edit(&ctx.http, |c| c.video_quality_mode(mode))
+        .await?;
+    handler.set_video_quality(channel_id, mode);
+
+    let label = if mode == VideoQualityMode::Full { "full" } else { "auto" };
+    handler.record_change(channel_id, command.user.id, ChangeAction::VideoQuality(label));
+    respond(ctx, command, format!("Video quality set to **{}**.", label), true).await
+}
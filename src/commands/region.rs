@@ -0,0 +1,130 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::application::{
+        command::CommandOptionType,
+        interaction::{
+            application_command::ApplicationCommandInteraction,
+            autocomplete::AutocompleteInteraction,
+        },
+    },
+    prelude::*,
+};
+
+use super::{option_str, respond, KNOWN_REGIONS, SlashCommand};
+use crate::{error::BotError, handler::{ChangeAction, Handler}};
+
+/// Discord shows at most 25 autocomplete suggestions.
+const MAX_SUGGESTIONS: usize = 25;
+
+/// Slug that hands the region choice back to Discord.
+const AUTOMATIC: &str = "automatic";
+
+pub struct RegionCommand;
+
+#[async_trait]
+impl SlashCommand for RegionCommand {
+    fn name(&self) -> &'static str {
+        "region"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Set the voice region of your channel")
+            .dm_permission(false)
+            .create_option(|o| {
+                o.name("region")
+                    .description("Region to use, or `automatic` to let Discord pick")
+                    .kind(CommandOptionType::String)
+                    .set_autocomplete(true)
+                    .required(true)
+            });
+        command
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+/// Known regions whose slug or name contains `partial`, case-insensitive.
+pub fn matching_regions(partial: &str) -> Vec<(&'static str, &'static str)> {
+    let partial = partial.trim().to_lowercase();
+    KNOWN_REGIONS
+        .iter()
+        .filter(|(slug, name)| slug.contains(&partial) || name.to_lowercase().contains(&partial))
+        .take(MAX_SUGGESTIONS)
+        .copied()
+        .collect()
+}
+
+pub async fn autocomplete(
+    ctx: &Context,
+    interaction: &AutocompleteInteraction,
+) -> Result<(), SerenityError> {
+    let partial = interaction
+        .data
+        .options
+        .iter()
+        .find(|o| o.focused)
+        .and_then(|o| o.value.as_ref())
+        .and_then(|v| v.as_str())
+        .unwrap_or_default();
+
+    let regions = matching_regions(partial);
+    interaction
+        .create_autocomplete_response(&ctx.http, |r| {
+            for (slug, name) in regions {
+                r.add_string_choice(name, slug);
+            }
+            r
+        })
+        .await
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let channel_id = match handler.get_user_channel(command.user.id).await {
+        Some(id) => id,
+        None => return respond(ctx, command, "You don't own a temporary channel.", true).await,
+    };
+
+    let input = option_str(&command.data.options, "region").unwrap_or_default().trim();
+    let (slug, name) = match KNOWN_REGIONS.iter().find(|(slug, _)| slug.eq_ignore_ascii_case(input)) {
+        Some(region) => *region,
+        None => return respond(ctx, command, "Unknown region, pick one from the list.", true).await,
+    };
+
+    let region = (slug != AUTOMATIC).then(|| slug.to_string());
+    channel_id
+        .edit(&ctx.http, |c| c.voice_region(region))
+        .await?;
+    handler.record_change(channel_id, command.user.id, ChangeAction::Region(name));
+
+    respond(ctx, command, format!("Voice region set to **{}**.", name), true).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_by_slug_and_name() {
+        let slugs = |partial| -> Vec<&str> {
+            matching_regions(partial).into_iter().map(|(slug, _)| slug).collect()
+        };
+
+        assert_eq!(slugs("us-"), ["us-central", "us-east", "us-south", "us-west"]);
+        assert_eq!(slugs("HONG"), ["hongkong"]);
+        assert_eq!(slugs("").len(), KNOWN_REGIONS.len());
+    }
+}
@@ -0,0 +1,91 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::{
+        application::{
+            command::CommandOptionType,
+            interaction::application_command::ApplicationCommandInteraction,
+        },
+        permissions::Permissions,
+    },
+    prelude::*,
+};
+
+use super::{cooldown_label, option_user, respond, SlashCommand};
+use crate::{error::BotError, handler::Handler};
+
+pub struct ResetCooldownsCommand;
+
+#[async_trait]
+impl SlashCommand for ResetCooldownsCommand {
+    fn name(&self) -> &'static str {
+        "reset_cooldowns"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Clear someone's command cooldowns")
+            .dm_permission(false)
+            .create_option(|o| {
+                o.name("user")
+                    .description("Whose cooldowns to clear")
+                    .kind(CommandOptionType::User)
+                    .required(true)
+            });
+        command
+    }
+
+    fn required_permissions(&self) -> Permissions {
+        Permissions::MANAGE_GUILD
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let guild_id = match command.guild_id {
+        Some(id) => id,
+        None => return respond(ctx, command, "This command only works in a server.", true).await,
+    };
+    let target = match option_user(&command.data.options, "user") {
+        Some(id) => id,
+        None => return respond(ctx, command, "Please pick a user.", true).await,
+    };
+
+    let cleared = handler.cooldowns().reset(target);
+    if cleared.is_empty() {
+        return respond(ctx, command, format!("<@{}> has no running cooldowns.", target), true).await;
+    }
+
+    let lines: Vec<String> = cleared
+        .iter()
+        .map(|(name, remaining)| format!("{} ({}s left)", cooldown_label(name, handler.command_prefix()), remaining.as_secs().max(1)))
+        .collect();
+    handler
+        .audit_log(
+            ctx,
+            guild_id,
+            &format!("<@{}> cleared the cooldowns of <@{}>: {}", command.user.id, target, lines.join(", ")),
+        )
+        .await;
+
+    respond(
+        ctx,
+        command,
+        format!("Cleared the cooldowns of <@{}>:\n{}", target, lines.join("\n")),
+        true,
+    )
+    .await
+}
@@ -0,0 +1,175 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::{
+        application::{
+            command::CommandOptionType,
+            interaction::{
+                application_command::ApplicationCommandInteraction, InteractionResponseType,
+            },
+        },
+        channel::Channel,
+        guild::Guild,
+        id::{ChannelId, RoleId, UserId},
+        permissions::Permissions,
+    },
+    prelude::*,
+};
+use std::collections::BTreeMap;
+
+use super::{option_role, respond, SlashCommand};
+use crate::{
+    error::BotError,
+    handler::Handler,
+    utils::{create_embed, fit_list, MAX_FIELD_LEN},
+};
+
+/// Embeds take 25 fields, one is kept for the other voice channels.
+const MAX_CHANNEL_FIELDS: usize = 24;
+
+pub struct RosterCommand;
+
+#[async_trait]
+impl SlashCommand for RosterCommand {
+    fn name(&self) -> &'static str {
+        "roster"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("See which members of a role are in voice")
+            .dm_permission(false)
+            .create_option(|o| {
+                o.name("role")
+                    .description("Role to look for")
+                    .kind(CommandOptionType::Role)
+                    .required(true)
+            });
+        command
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+/// Mentions of `users`, cut off with "and N more" so they fit in `max_len`.
+pub fn mention_list(users: &[UserId], max_len: usize) -> String {
+    let mentions: Vec<String> = users.iter().map(|id| format!("<@{}>", id)).collect();
+    fit_list(&mentions, ", ", max_len)
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let guild = match command.guild_id.and_then(|id| id.to_guild_cached(&ctx.cache)) {
+        Some(guild) => guild,
+        None => return respond(ctx, command, "This command only works in a server.", true).await,
+    };
+    let role_id = match option_role(&command.data.options, "role") {
+        Some(id) => id,
+        None => return respond(ctx, command, "Please pick a role.", true).await,
+    };
+
+    let mut temp: BTreeMap<ChannelId, Vec<UserId>> = BTreeMap::new();
+    let mut other = Vec::new();
+    let mut hidden = 0;
+    for voice_state in guild.voice_states.values() {
+        let channel_id = match voice_state.channel_id {
+            Some(id) => id,
+            None => continue,
+        };
+        let has_role = guild
+            .members
+            .get(&voice_state.user_id)
+            .is_some_and(|m| m.roles.contains(&role_id));
+        if !has_role {
+            continue;
+        }
+
+        if !visible_to(handler, &guild, channel_id, command.user.id) {
+            hidden += 1;
+        } else if handler.is_tracked(channel_id) {
+            temp.entry(channel_id).or_default().push(voice_state.user_id);
+        } else {
+            other.push(voice_state.user_id);
+        }
+    }
+
+    let mut embed = create_embed(&handler.guild_config(guild.id).await);
+    embed.title(format!("Roster: {}", role_name(&guild, role_id)));
+    if temp.is_empty() && other.is_empty() {
+        embed.description("Nobody with this role is in a channel you can see.");
+    }
+    for (channel_id, mut members) in temp.into_iter().take(MAX_CHANNEL_FIELDS) {
+        members.sort();
+        embed.field(channel_name(&guild, channel_id), mention_list(&members, MAX_FIELD_LEN), false);
+    }
+    if !other.is_empty() {
+        other.sort();
+        embed.field("Other voice channels", mention_list(&other, MAX_FIELD_LEN), false);
+    }
+    if hidden > 0 {
+        embed.footer(|f| f.text(format!("{} member(s) are in channels you can't see.", hidden)));
+    }
+
+    command
+        .create_interaction_response(&ctx.http, |r| {
+            r.kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|d| d.set_embed(embed).ephemeral(true))
+        })
+        .await
+}
+
+/// Locked and ghosted channels are only shown to the people in them and their owner.
+fn visible_to(handler: &Handler, guild: &Guild, channel_id: ChannelId, user_id: UserId) -> bool {
+    let inside = guild.voice_states.get(&user_id).and_then(|v| v.channel_id) == Some(channel_id);
+    if inside || handler.channel_owner(channel_id) == Some(user_id) {
+        return true;
+    }
+    if handler.in_ghost_period(channel_id) {
+        return false;
+    }
+
+    let (channel, member) = match (guild.channels.get(&channel_id), guild.members.get(&user_id)) {
+        (Some(Channel::Guild(channel)), Some(member)) => (channel, member),
+        _ => return false,
+    };
+    guild
+        .user_permissions_in(channel, member)
+        .is_ok_and(|p| p.contains(Permissions::VIEW_CHANNEL | Permissions::CONNECT))
+}
+
+fn channel_name(guild: &Guild, channel_id: ChannelId) -> String {
+    match guild.channels.get(&channel_id) {
+        Some(Channel::Guild(gc)) => gc.name.clone(),
+        _ => channel_id.to_string(),
+    }
+}
+
+fn role_name(guild: &Guild, role_id: RoleId) -> String {
+    guild.roles.get(&role_id).map_or_else(|| role_id.to_string(), |r| r.name.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mention_list_fits_max_len() {
+        let users: Vec<UserId> = (1..=5).map(UserId).collect();
+        assert_eq!(mention_list(&users[..2], 100), "<@1>, <@2>");
+
+        let list = mention_list(&users, 30);
+        assert!(list.len() <= 30, "{}", list);
+        assert_eq!(list, "<@1>, <@2>, <@3> and 2 more");
+    }
+}
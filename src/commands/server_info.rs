@@ -0,0 +1,185 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::{
+        application::interaction::{
+            application_command::ApplicationCommandInteraction, InteractionResponseType,
+        },
+        guild::PremiumTier,
+        id::ChannelId,
+        permissions::Permissions,
+    },
+    prelude::*,
+};
+
+use super::{music::max_bitrate, respond, SlashCommand};
+use crate::{
+    config::GuildConfig,
+    error::BotError,
+    handler::{Handler, DEFAULT_OWNERSHIP_EXPIRY_SECS},
+    utils::{create_embed, format_duration, late_night_label},
+};
+
+pub struct ServerInfoCommand;
+
+#[async_trait]
+impl SlashCommand for ServerInfoCommand {
+    fn name(&self) -> &'static str {
+        "server_info"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Show all of the bot's settings for this server")
+            .dm_permission(false);
+        command
+    }
+
+    fn required_permissions(&self) -> Permissions {
+        Permissions::MANAGE_GUILD
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+/// "Level 2 (256 kbps, 720p video)"
+pub fn premium_label(tier: PremiumTier) -> String {
+    let level = match tier {
+        PremiumTier::Tier1 => 1,
+        PremiumTier::Tier2 => 2,
+        PremiumTier::Tier3 => 3,
+        _ => 0,
+    };
+    let video = if level == 0 { "" } else { ", 720p video" };
+    format!("Level {} ({} kbps{})", level, max_bitrate(tier) / 1000, video)
+}
+
+/// One ✅/❌ line per feature, the same order as `/config`.
+pub fn feature_flags(config: &GuildConfig) -> String {
+    let flags = [
+        ("Activity names", config.use_activity_name),
+        ("Popular game names", config.use_popular_game_name),
+        ("Paired text channels", config.paired_text_channel),
+        ("Archive text channels", config.archive_text_channel),
+        ("Join sounds", config.join_sound_enabled),
+        ("Session summaries", config.session_summary),
+        ("Access log", config.access_log),
+        ("Reorder on delete", config.reorder_on_delete),
+    ];
+    flags
+        .iter()
+        .map(|(name, on)| format!("{} {}", if *on { "✅" } else { "❌" }, name))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn channel(id: Option<ChannelId>) -> String {
+    id.map_or_else(|| "Not set".to_string(), |id| format!("<#{}>", id))
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let guild = match command.guild_id.and_then(|id| id.to_guild_cached(&ctx.cache)) {
+        Some(guild) => guild,
+        None => return respond(ctx, command, "This command only works in a server.", true).await,
+    };
+    let config = handler.guild_config(guild.id).await;
+    let active = guild.channels.keys().filter(|id| handler.is_tracked(**id)).count();
+    let logs = match (&config.log_webhook_url, config.log_channel_id) {
+        (Some(_), _) => "Webhook".to_string(),
+        (None, id) => channel(id),
+    };
+    let color = match config.embed_color {
+        0 => "Default".to_string(),
+        color => format!("#{:06X}", color),
+    };
+
+    let mut embed = create_embed(&config);
+    embed
+        .title(format!("Settings for {}", guild.name))
+        .field("Creator channel", channel(handler.creator_channel(guild.id)), true)
+        .field("Waiting room", channel(handler.waiting_room(guild.id).await), true)
+        .field("Audit logs", logs, true)
+        .field("Overflow category", channel(config.overflow_category_id), true)
+        .field("Archive category", channel(config.archive_category_id), true)
+        .field("Diagnostics category", channel(config.diagnostics_category_id), true)
+        .field(
+            "Music bot",
+            config.music_bot_id.map_or_else(|| "Not set".to_string(), |id| format!("<@{}>", id)),
+            true,
+        )
+        .field(
+            "DM admin",
+            config.admin_user_id.map_or_else(|| "Not set".to_string(), |id| format!("<@{}>", id)),
+            true,
+        )
+        .field(
+            "Debug role",
+            config.debug_role_id.map_or_else(|| "Not set".to_string(), |id| format!("<@&{}>", id)),
+            true,
+        )
+        .field("Forum", channel(config.forum_creator_id), true)
+        .field(
+            "Test mode",
+            config.test_mode_user_id.map_or_else(|| "Off".to_string(), |id| format!("Only <@{}>", id)),
+            true,
+        )
+        .field("Features", feature_flags(&config), false)
+        .field(
+            "Ownership expiry",
+            format!("{}s", config.ownership_expiry_secs.unwrap_or(DEFAULT_OWNERSHIP_EXPIRY_SECS)),
+            true,
+        )
+        .field("Ghost period", format!("{}s", config.ghost_period_secs), true)
+        .field(
+            "AFK kick",
+            config.afk_kick_threshold_secs.map_or_else(|| "Off".to_string(), |secs| format!("{}s", secs)),
+            true,
+        )
+        .field("Embed color", color, true)
+        .field("Name filter", format!("{} word(s)", config.name_filter.len()), true)
+        .field("Channel tags", format!("{} tag(s)", config.allowed_tags.len()), true)
+        .field("Role quotas", format!("{} role(s)", config.role_quotas.len()), true)
+        .field("Late-night mode", late_night_label(&config), true)
+        .field("Active channels", active.to_string(), true)
+        .field("Server boost", premium_label(guild.premium_tier), true)
+        .field("Uptime", format_duration(handler.uptime()), true)
+        .footer(|f| f.text(format!("Bot version {}", env!("CARGO_PKG_VERSION"))));
+
+    command
+        .create_interaction_response(&ctx.http, |r| {
+            r.kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|d| d.set_embed(embed).ephemeral(true))
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labels_boost_level_and_features() {
+        assert_eq!(premium_label(PremiumTier::Tier0), "Level 0 (96 kbps)");
+        assert_eq!(premium_label(PremiumTier::Tier3), "Level 3 (384 kbps, 720p video)");
+
+        let config = GuildConfig {
+            join_sound_enabled: true,
+            ..GuildConfig::default()
+        };
+        let flags = feature_flags(&config);
+        assert!(flags.contains("✅ Join sounds"));
+        assert!(flags.contains("❌ Access log"));
+    }
+}
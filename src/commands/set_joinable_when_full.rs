@@ -0,0 +1,78 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::application::{
+        command::CommandOptionType,
+        interaction::application_command::ApplicationCommandInteraction,
+    },
+    prelude::*,
+};
+
+use super::{respond, SlashCommand};
+use crate::{
+    error::BotError,
+    handler::{ChangeAction, Handler},
+};
+
+pub struct SetJoinableWhenFullCommand;
+
+#[async_trait]
+impl SlashCommand for SetJoinableWhenFullCommand {
+    fn name(&self) -> &'static str {
+        "set_joinable_when_full"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Let people who drop out of your full channel queue to get back in")
+            .dm_permission(false)
+            .create_option(|o| {
+                o.name("on")
+                    .description("Queue them when they join the creator channel, they're moved in when a spot opens")
+                    .kind(CommandOptionType::SubCommand)
+            })
+            .create_option(|o| {
+                o.name("off")
+                    .description("Turn the queue off and empty it")
+                    .kind(CommandOptionType::SubCommand)
+            });
+        command
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let enabled = match command.data.options.first().map(|s| s.name.as_str()) {
+        Some("on") => true,
+        Some("off") => false,
+        _ => return Ok(()),
+    };
+    let channel_id = match handler.get_user_channel(command.user.id).await {
+        Some(id) => id,
+        None => return respond(ctx, command, "You don't own a temporary channel.", true).await,
+    };
+
+    if !handler.set_queue_enabled(channel_id, enabled) {
+        return respond(ctx, command, "Your channel is gone.", true).await;
+    }
+    handler.record_change(channel_id, command.user.id, ChangeAction::Flag("the queue", enabled));
+    let reply = if enabled {
+        "When your channel is full, people who were in it and join the creator channel wait in a queue and are moved in when a spot opens."
+    } else {
+        "Your channel's queue is off and empty."
+    };
+    respond(ctx, command, reply, true).await
+}
@@ -0,0 +1,75 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::application::{
+        command::CommandOptionType,
+        interaction::application_command::ApplicationCommandInteraction,
+    },
+    prelude::*,
+};
+
+use super::{option_str, respond, SlashCommand};
+use crate::{
+    error::BotError,
+    handler::{ChangeAction, Handler},
+    utils::{is_custom_emoji, is_unicode_emoji},
+};
+
+pub struct SetOwnerEmojiCommand;
+
+#[async_trait]
+impl SlashCommand for SetOwnerEmojiCommand {
+    fn name(&self) -> &'static str {
+        "set_owner_emoji"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Put an emoji in front of your channel's name, or `clear` to remove it")
+            .dm_permission(false)
+            .create_option(|o| {
+                o.name("emoji")
+                    .description("A single emoji, or `clear`")
+                    .kind(CommandOptionType::String)
+                    .max_length(64)
+                    .required(true)
+            });
+        command
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let channel_id = match handler.get_user_channel(command.user.id).await {
+        Some(id) => id,
+        None => return respond(ctx, command, "You don't own a temporary channel.", true).await,
+    };
+
+    let emoji = option_str(&command.data.options, "emoji").unwrap_or_default().trim();
+    let (emoji, reply) = if emoji.eq_ignore_ascii_case("clear") {
+        (None, "The emoji will be removed from your channel's name in a few seconds.".to_string())
+    } else if is_unicode_emoji(emoji) {
+        (Some(emoji.to_string()), format!("Your channel's name will start with {} in a few seconds.", emoji))
+    } else if is_custom_emoji(emoji) {
+        return respond(ctx, command, "Channel names can't show custom emojis, pick a standard one.", true).await;
+    } else {
+        return respond(ctx, command, "That's not a single emoji.", true).await;
+    };
+
+    handler.set_owner_emoji(ctx, channel_id, emoji.clone());
+    handler.record_change(channel_id, command.user.id, ChangeAction::OwnerEmoji(emoji));
+    respond(ctx, command, reply, true).await
+}
@@ -0,0 +1,272 @@
+use serenity::{
+    async_trait,
+    builder::{CreateApplicationCommand, CreateComponents},
+    model::{
+        application::{
+            component::ButtonStyle,
+            interaction::{
+                application_command::ApplicationCommandInteraction,
+                message_component::MessageComponentInteraction,
+                InteractionResponseType,
+            },
+        },
+        channel::{Channel, ChannelType},
+        id::{ChannelId, GuildId},
+        permissions::Permissions,
+    },
+    prelude::*,
+};
+
+use super::{respond, SlashCommand};
+use crate::{error::BotError, handler::Handler};
+
+pub const CREATOR_MENU_ID: &str = "setup_creator";
+pub const WAITING_ROOM_MENU_ID: &str = "setup_waiting_room";
+/// Page buttons of the menus, followed by `:` and the page they go to.
+pub const PAGE_PREFIX: &str = "setup_page";
+
+/// Discord allows at most 25 options in a select menu, servers with more voice
+/// channels get them in pages.
+const MAX_MENU_OPTIONS: usize = 25;
+
+pub struct SetupCommand;
+
+#[async_trait]
+impl SlashCommand for SetupCommand {
+    fn name(&self) -> &'static str {
+        "setup"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Pick the creator channel and waiting room for this server")
+            .dm_permission(false);
+        command
+    }
+
+    fn required_permissions(&self) -> Permissions {
+        Permissions::MANAGE_GUILD
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let guild_id = match command.guild_id {
+        Some(id) => id,
+        None => return respond(ctx, command, "This command only works in a server.", true).await,
+    };
+
+    let channels = voice_channels(ctx, guild_id);
+    if channels.is_empty() {
+        return respond(ctx, command, "This server has no voice or stage channels to pick from.", true).await;
+    }
+
+    let content = summary(handler, guild_id).await;
+    command
+        .create_interaction_response(&ctx.http, |r| {
+            r.kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|d| {
+                    d.content(content)
+                        .ephemeral(true)
+                        .components(|c| menus(c, &channels, 0))
+                })
+        })
+        .await
+}
+
+/// Stores the channel picked in one of the `/setup` menus and shows the updated state.
+pub async fn handle_select(
+    handler: &Handler,
+    ctx: &Context,
+    component: &MessageComponentInteraction,
+) -> Result<(), SerenityError> {
+    let guild_id = match component.guild_id {
+        Some(id) => id,
+        None => return Ok(()),
+    };
+
+    let channel_id = match component
+        .data
+        .values
+        .first()
+        .and_then(|v| v.parse::<u64>().ok())
+    {
+        Some(id) => ChannelId(id),
+        None => return Ok(()),
+    };
+
+    let custom_id = component.data.custom_id.as_str();
+    handler
+        .update_guild_config(guild_id, |c| match custom_id {
+            CREATOR_MENU_ID => c.creator_channel_id = Some(channel_id),
+            WAITING_ROOM_MENU_ID => c.waiting_room_id = Some(channel_id),
+            _ => {}
+        })
+        .await;
+
+    // Stay on the page of the channel that was just picked
+    let channels = voice_channels(ctx, guild_id);
+    let page = channels
+        .iter()
+        .position(|(id, _)| *id == channel_id)
+        .map_or(0, |i| i / MAX_MENU_OPTIONS);
+    update(handler, ctx, component, guild_id, &channels, page).await
+}
+
+/// Shows another page of the `/setup` menus.
+pub async fn handle_page(
+    handler: &Handler,
+    ctx: &Context,
+    component: &MessageComponentInteraction,
+) -> Result<(), SerenityError> {
+    let (guild_id, page) = match (component.guild_id, parse_page_id(&component.data.custom_id)) {
+        (Some(guild_id), Some(page)) => (guild_id, page),
+        _ => return Ok(()),
+    };
+    let channels = voice_channels(ctx, guild_id);
+    // Channels may have been deleted since the buttons were made
+    let page = page.min(page_count(channels.len()).saturating_sub(1));
+    update(handler, ctx, component, guild_id, &channels, page).await
+}
+
+async fn update(
+    handler: &Handler,
+    ctx: &Context,
+    component: &MessageComponentInteraction,
+    guild_id: GuildId,
+    channels: &[(ChannelId, String)],
+    page: usize,
+) -> Result<(), SerenityError> {
+    let content = summary(handler, guild_id).await;
+    component
+        .create_interaction_response(&ctx.http, |r| {
+            r.kind(InteractionResponseType::UpdateMessage)
+                .interaction_response_data(|d| d.content(content).components(|c| menus(c, channels, page)))
+        })
+        .await
+}
+
+pub fn page_id(page: usize) -> String {
+    format!("{}:{}", PAGE_PREFIX, page)
+}
+
+pub fn parse_page_id(id: &str) -> Option<usize> {
+    id.strip_prefix(PAGE_PREFIX)?.strip_prefix(':')?.parse().ok()
+}
+
+fn page_count(channels: usize) -> usize {
+    channels.div_ceil(MAX_MENU_OPTIONS)
+}
+
+fn voice_channels(ctx: &Context, guild_id: GuildId) -> Vec<(ChannelId, String)> {
+    let mut channels: Vec<(i64, ChannelId, String)> = guild_id
+        .to_guild_cached(&ctx.cache)
+        .map(|g| {
+            g.channels
+                .values()
+                .filter_map(|c| match c {
+                    Channel::Guild(gc) if matches!(gc.kind, ChannelType::Voice | ChannelType::Stage) => {
+                        Some((gc.position, gc.id, gc.name.clone()))
+                    }
+                    _ => None,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    channels.sort();
+    channels.into_iter().map(|(_, id, name)| (id, name)).collect()
+}
+
+/// The two menus with `page` of `channels`, plus page buttons when they don't fit on one.
+fn menus<'a>(
+    components: &'a mut CreateComponents,
+    channels: &[(ChannelId, String)],
+    page: usize,
+) -> &'a mut CreateComponents {
+    let pages = page_count(channels.len());
+    let shown = channels.chunks(MAX_MENU_OPTIONS).nth(page).unwrap_or_default();
+    for (custom_id, name) in [
+        (CREATOR_MENU_ID, "Creator channel"),
+        (WAITING_ROOM_MENU_ID, "Waiting room"),
+    ] {
+        let placeholder = match pages {
+            0 | 1 => name.to_string(),
+            _ => format!("{} (page {}/{})", name, page + 1, pages),
+        };
+        components.create_action_row(|row| {
+            row.create_select_menu(|menu| {
+                menu.custom_id(custom_id)
+                    .placeholder(placeholder)
+                    .options(|options| {
+                        for (id, name) in shown {
+                            options.create_option(|o| o.label(name).value(id.0));
+                        }
+                        options
+                    })
+            })
+        });
+    }
+    if pages > 1 {
+        components.create_action_row(|row| {
+            row.create_button(|b| {
+                b.custom_id(page_id(page.saturating_sub(1)))
+                    .label("Previous")
+                    .style(ButtonStyle::Secondary)
+                    .disabled(page == 0)
+            })
+            .create_button(|b| {
+                b.custom_id(page_id((page + 1).min(pages - 1)))
+                    .label("Next")
+                    .style(ButtonStyle::Secondary)
+                    .disabled(page + 1 >= pages)
+            })
+        });
+    }
+    components
+}
+
+async fn summary(handler: &Handler, guild_id: GuildId) -> String {
+    let show = |id: Option<ChannelId>| id.map_or("not set".to_string(), |id| format!("<#{}>", id));
+    let creator = handler.creator_channel(guild_id);
+    let waiting_room = handler.waiting_room(guild_id).await;
+
+    let mut content = format!(
+        "**Creator channel:** {}\n**Waiting room:** {}",
+        show(creator),
+        show(waiting_room)
+    );
+    if creator.is_some() && waiting_room.is_some() {
+        content.push_str("\n✓ Setup complete. Join the creator channel to get your own channel.");
+    } else {
+        content.push_str("\nPick a channel from each menu below.");
+    }
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn page_ids_round_trip() {
+        assert_eq!(parse_page_id(&page_id(3)), Some(3));
+        assert_eq!(parse_page_id(CREATOR_MENU_ID), None);
+        assert_eq!(parse_page_id("setup_page:x"), None);
+        assert_eq!(page_count(25), 1);
+        assert_eq!(page_count(26), 2);
+    }
+}
@@ -0,0 +1,205 @@
+use rand::seq::SliceRandom;
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::{
+        application::interaction::application_command::ApplicationCommandInteraction,
+        channel::Channel,
+        id::{ChannelId, UserId},
+        permissions::Permissions,
+    },
+    prelude::*,
+};
+use std::collections::HashMap;
+use tracing::warn;
+
+use super::{confirm, finish, respond, Prompt, SlashCommand, CONFIRM_TIMEOUT};
+use crate::{error::BotError, handler::Handler};
+
+#[derive(Debug, Default, PartialEq)]
+pub struct ShufflePlan {
+    /// Where every member ends up.
+    pub assignments: Vec<(UserId, ChannelId)>,
+    /// Members that didn't fit anywhere because of user limits, they stay put.
+    pub unassigned: Vec<UserId>,
+    /// User limits made an even split impossible.
+    pub uneven: bool,
+}
+
+/// Deals `members` (already shuffled) round-robin over `channels`, skipping channels
+/// that hit their user limit. A limit of `None` or `0` means unlimited, like Discord.
+pub fn plan_shuffle(members: &[UserId], channels: &[(ChannelId, Option<u64>)]) -> ShufflePlan {
+    if channels.is_empty() {
+        return ShufflePlan::default();
+    }
+
+    let capacity = |limit: Option<u64>| match limit {
+        Some(0) | None => usize::MAX,
+        Some(limit) => limit as usize,
+    };
+    let even_share = members.len().div_ceil(channels.len());
+
+    let mut counts = vec![0; channels.len()];
+    let mut plan = ShufflePlan {
+        uneven: channels.iter().any(|(_, limit)| capacity(*limit) < even_share),
+        ..Default::default()
+    };
+
+    let mut next = 0;
+    for member in members {
+        let slot = (0..channels.len())
+            .map(|offset| (next + offset) % channels.len())
+            .find(|i| counts[*i] < capacity(channels[*i].1));
+
+        match slot {
+            Some(i) => {
+                counts[i] += 1;
+                plan.assignments.push((*member, channels[i].0));
+                next = i + 1;
+            }
+            None => plan.unassigned.push(*member),
+        }
+    }
+
+    plan
+}
+
+pub struct ShuffleCommand;
+
+#[async_trait]
+impl SlashCommand for ShuffleCommand {
+    fn name(&self) -> &'static str {
+        "shuffle"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Randomly spread everyone in temporary channels over those channels")
+            .dm_permission(false);
+        command
+    }
+
+    fn required_permissions(&self) -> Permissions {
+        Permissions::MANAGE_GUILD
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let guild = match command.guild_id.and_then(|id| id.to_guild_cached(&ctx.cache)) {
+        Some(guild) => guild,
+        None => return respond(ctx, command, "This command only works in a server.", true).await,
+    };
+
+    let mut channels: Vec<(ChannelId, Option<u64>)> = guild
+        .channels
+        .values()
+        .filter_map(|c| match c {
+            Channel::Guild(gc) if handler.is_tracked(gc.id) => Some((gc.id, gc.user_limit)),
+            _ => None,
+        })
+        .collect();
+    channels.sort();
+
+    let current: HashMap<UserId, ChannelId> = guild
+        .voice_states
+        .values()
+        .filter(|v| !v.member.as_ref().is_some_and(|m| m.user.bot))
+        .filter_map(|v| v.channel_id.map(|c| (v.user_id, c)))
+        .filter(|(_, c)| channels.iter().any(|(id, _)| id == c))
+        .collect();
+
+    if channels.len() < 2 || current.is_empty() {
+        return respond(
+            ctx,
+            command,
+            "There need to be at least two active temporary channels with people in them.",
+            true,
+        )
+        .await;
+    }
+
+    let mut members: Vec<UserId> = current.keys().copied().collect();
+    members.shuffle(&mut rand::thread_rng());
+    let plan = plan_shuffle(&members, &channels);
+
+    let prompt = Prompt::text(preview(&plan, &channels));
+    let Some(interaction) = confirm(ctx, command, "Shuffle", prompt).await? else { return Ok(()) };
+
+    let mut failed = 0;
+    for (user_id, channel_id) in &plan.assignments {
+        if current.get(user_id) == Some(channel_id) {
+            continue;
+        }
+        let moved = match guild.members.get(user_id) {
+            Some(member) => member.move_to_voice_channel(&ctx.http, *channel_id).await.map(|_| ()),
+            None => guild.id.move_member(&ctx.http, *user_id, *channel_id).await.map(|_| ()),
+        };
+        if let Err(e) = moved {
+            warn!("Kon {} niet verplaatsen tijdens shuffle: {:?}", user_id, e);
+            failed += 1;
+        }
+    }
+
+    let content = match failed {
+        0 => "Shuffled!".to_string(),
+        n => format!("Shuffled, but {} member(s) could not be moved.", n),
+    };
+    finish(ctx, &interaction, content).await
+}
+
+fn preview(plan: &ShufflePlan, channels: &[(ChannelId, Option<u64>)]) -> String {
+    let mut lines = vec!["**Proposed shuffle:**".to_string()];
+    for (channel_id, _) in channels {
+        let members: Vec<String> = plan
+            .assignments
+            .iter()
+            .filter(|(_, c)| c == channel_id)
+            .map(|(u, _)| format!("<@{}>", u))
+            .collect();
+        lines.push(format!("<#{}>: {}", channel_id, members.join(", ")));
+    }
+
+    if plan.uneven {
+        lines.push("⚠️ User limits prevent an even split.".to_string());
+    }
+    if !plan.unassigned.is_empty() {
+        lines.push(format!(
+            "⚠️ {} member(s) don't fit anywhere and will stay where they are.",
+            plan.unassigned.len()
+        ));
+    }
+    lines.push(format!("Confirm within {} seconds.", CONFIRM_TIMEOUT.as_secs()));
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn respects_user_limits() {
+        let members: Vec<UserId> = (1..=5).map(UserId).collect();
+        let channels = [(ChannelId(10), Some(1)), (ChannelId(20), None)];
+
+        let plan = plan_shuffle(&members, &channels);
+
+        let in_first = plan.assignments.iter().filter(|(_, c)| *c == ChannelId(10)).count();
+        assert_eq!(in_first, 1);
+        assert_eq!(plan.assignments.len(), 5);
+        assert!(plan.uneven);
+    }
+}
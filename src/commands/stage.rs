@@ -0,0 +1,265 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::{
+        application::{
+            command::CommandOptionType,
+            component::ButtonStyle,
+            interaction::{
+                application_command::ApplicationCommandInteraction,
+                message_component::MessageComponentInteraction, InteractionResponseType,
+            },
+        },
+        channel::Channel,
+        id::{ChannelId, GuildId, UserId},
+    },
+    prelude::*,
+};
+
+use super::{option_user, respond, SlashCommand};
+use crate::{error::BotError, handler::Handler};
+
+/// Buttons on a speak request DM, followed by `:<channel_id>:<user_id>`.
+pub const APPROVE_PREFIX: &str = "speaker_approve";
+pub const DENY_PREFIX: &str = "speaker_deny";
+
+pub struct SpeakerCommand;
+
+#[async_trait]
+impl SlashCommand for SpeakerCommand {
+    fn name(&self) -> &'static str {
+        "speaker"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Invite someone in your Stage channel to speak")
+            .dm_permission(false)
+            .create_option(|o| {
+                o.name("user")
+                    .description("Who may speak")
+                    .kind(CommandOptionType::User)
+                    .required(true)
+            });
+        command
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+pub struct AudienceCommand;
+
+#[async_trait]
+impl SlashCommand for AudienceCommand {
+    fn name(&self) -> &'static str {
+        "audience"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Move a speaker in your Stage channel back to the audience")
+            .dm_permission(false)
+            .create_option(|o| {
+                o.name("user")
+                    .description("Who to move back")
+                    .kind(CommandOptionType::User)
+                    .required(true)
+            });
+        command
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+pub struct SpeakerRequestCommand;
+
+#[async_trait]
+impl SlashCommand for SpeakerRequestCommand {
+    fn name(&self) -> &'static str {
+        "speaker_request"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Ask the owner of this Stage channel if you can speak")
+            .dm_permission(false);
+        command
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let guild_id = match command.guild_id {
+        Some(id) => id,
+        None => return respond(ctx, command, "This command only works in a server.", true).await,
+    };
+
+    if command.data.name == "speaker_request" {
+        return request_to_speak(handler, ctx, command, guild_id).await;
+    }
+
+    let channel_id = match handler.get_user_channel(command.user.id).await {
+        Some(id) => id,
+        None => return respond(ctx, command, "You don't own a temporary channel.", true).await,
+    };
+    if !handler.is_stage_channel(channel_id) {
+        return respond(ctx, command, "Your channel is not a Stage channel.", true).await;
+    }
+
+    let target = match option_user(&command.data.options, "user") {
+        Some(id) => id,
+        None => return respond(ctx, command, "Please pick a user.", true).await,
+    };
+    if voice_channel_of(ctx, guild_id, target) != Some(channel_id) {
+        return respond(ctx, command, "That user is not in your channel.", true).await;
+    }
+
+    let speaker = command.data.name == "speaker";
+    set_suppressed(ctx, channel_id, target, !speaker).await?;
+
+    let reply = if speaker {
+        format!("<@{}> can now speak.", target)
+    } else {
+        format!("<@{}> is back in the audience.", target)
+    };
+    respond(ctx, command, reply, false).await
+}
+
+async fn request_to_speak(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+    guild_id: GuildId,
+) -> Result<(), SerenityError> {
+    let channel_id = match voice_channel_of(ctx, guild_id, command.user.id) {
+        Some(id) if handler.is_stage_channel(id) => id,
+        _ => return respond(ctx, command, "You're not in a temporary Stage channel.", true).await,
+    };
+
+    let owner = match handler.channel_owner(channel_id) {
+        Some(owner) if owner != command.user.id => owner,
+        _ => return respond(ctx, command, "You own this channel, use `/speaker`.", true).await,
+    };
+
+    let requester = command.user.id;
+    let sent = handler
+        .send_dm_message(ctx, owner, |m| {
+            m.content(format!("<@{}> wants to speak in <#{}>.", requester, channel_id))
+                .components(|c| {
+                    c.create_action_row(|row| {
+                        row.create_button(|b| {
+                            b.custom_id(format!("{}:{}:{}", APPROVE_PREFIX, channel_id, requester))
+                                .label("Approve")
+                                .style(ButtonStyle::Success)
+                        })
+                        .create_button(|b| {
+                            b.custom_id(format!("{}:{}:{}", DENY_PREFIX, channel_id, requester))
+                                .label("Deny")
+                                .style(ButtonStyle::Danger)
+                        })
+                    })
+                })
+        })
+        .await;
+
+    let reply = if sent {
+        "Your request was sent to the channel owner."
+    } else {
+        "Couldn't reach the channel owner, they may have DMs turned off or got too many \
+         requests. Ask them in the channel instead."
+    };
+    respond(ctx, command, reply, true).await
+}
+
+/// Handles the Approve/Deny buttons in the DM sent by `/speaker_request`.
+pub async fn handle_request_button(
+    handler: &Handler,
+    ctx: &Context,
+    component: &MessageComponentInteraction,
+) -> Result<(), SerenityError> {
+    let mut parts = component.data.custom_id.split(':');
+    let (action, channel_id, requester) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(action), Some(channel), Some(user)) => match (channel.parse(), user.parse()) {
+            (Ok(channel), Ok(user)) => (action, ChannelId(channel), UserId(user)),
+            _ => return Ok(()),
+        },
+        _ => return Ok(()),
+    };
+
+    let content = if handler.channel_owner(channel_id) != Some(component.user.id) {
+        "You no longer own that channel.".to_string()
+    } else if action == DENY_PREFIX {
+        format!("Denied <@{}>'s request to speak.", requester)
+    } else {
+        let guild_id = channel_id
+            .to_channel_cached(&ctx.cache)
+            .and_then(|c| c.guild())
+            .map(|gc| gc.guild_id);
+
+        match guild_id {
+            Some(guild_id) if voice_channel_of(ctx, guild_id, requester) == Some(channel_id) => {
+                set_suppressed(ctx, channel_id, requester, false).await?;
+                format!("<@{}> can now speak.", requester)
+            }
+            _ => format!("<@{}> is no longer in the channel.", requester),
+        }
+    };
+
+    component
+        .create_interaction_response(&ctx.http, |r| {
+            r.kind(InteractionResponseType::UpdateMessage)
+                .interaction_response_data(|d| d.content(content).components(|c| c))
+        })
+        .await
+}
+
+fn voice_channel_of(ctx: &Context, guild_id: GuildId, user_id: UserId) -> Option<ChannelId> {
+    guild_id
+        .to_guild_cached(&ctx.cache)
+        .and_then(|g| g.voice_states.get(&user_id).and_then(|v| v.channel_id))
+}
+
+async fn set_suppressed(
+    ctx: &Context,
+    channel_id: ChannelId,
+    user_id: UserId,
+    suppress: bool,
+) -> Result<(), SerenityError> {
+    match channel_id.to_channel(ctx).await? {
+        Channel::Guild(channel) => {
+            channel
+                .edit_voice_state(&ctx.http, user_id, |v| v.suppress(suppress))
+                .await
+        }
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,83 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::{
+        application::interaction::{
+            application_command::ApplicationCommandInteraction, InteractionResponseType,
+        },
+        permissions::Permissions,
+    },
+    prelude::*,
+};
+
+use super::{respond, SlashCommand};
+use crate::{
+    db::stats::Stat,
+    error::BotError,
+    handler::Handler,
+    utils::{create_embed, format_duration},
+};
+
+pub struct StatsCommand;
+
+#[async_trait]
+impl SlashCommand for StatsCommand {
+    fn name(&self) -> &'static str {
+        "stats"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Show how much the bot has done, all-time and since it started")
+            .dm_permission(false);
+        command
+    }
+
+    fn required_permissions(&self) -> Permissions {
+        Permissions::MANAGE_GUILD
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        run(handler, ctx, command).await
+    }
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), BotError> {
+    let guild_id = match command.guild_id {
+        Some(id) => id,
+        None => return Ok(respond(ctx, command, "This command only works in a server.", true).await?),
+    };
+
+    let all_time = handler.all_time_stats().await?;
+    let session = handler.session_stats();
+
+    let mut embed = create_embed(&handler.guild_config(guild_id).await);
+    embed
+        .title("Bot stats")
+        .description(format!("Up for {}", format_duration(handler.uptime())));
+    for stat in Stat::ALL {
+        embed.field(
+            stat.label(),
+            format!("{} all-time\n{} this session", all_time.get(stat), session.get(stat)),
+            true,
+        );
+    }
+
+    command
+        .create_interaction_response(&ctx.http, |r| {
+            r.kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|d| d.set_embed(embed).ephemeral(true))
+        })
+        .await?;
+    Ok(())
+}
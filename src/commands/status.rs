@@ -0,0 +1,106 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::application::{
+        command::CommandOptionType,
+        interaction::application_command::ApplicationCommandInteraction,
+    },
+    prelude::*,
+};
+
+use super::{option_str, respond, SlashCommand};
+use crate::{
+    error::BotError,
+    handler::{ChangeAction, Handler},
+    utils::{filter_channel_name, set_voice_status},
+};
+
+/// Longest status Discord accepts on a voice channel.
+pub const MAX_STATUS_LEN: u16 = 500;
+
+const VISIBILITY_WARNING: &str =
+    "Note: channel statuses are shown in the sidebar to everyone in the server, even when your channel is locked or hidden.";
+
+pub struct StatusCommand;
+
+#[async_trait]
+impl SlashCommand for StatusCommand {
+    fn name(&self) -> &'static str {
+        "status"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Set the status of your voice channel, or `clear` to remove it")
+            .dm_permission(false)
+            .create_option(|o| {
+                o.name("message")
+                    .description("Status text (max 500 characters), or `clear`")
+                    .kind(CommandOptionType::String)
+                    .max_length(MAX_STATUS_LEN)
+                    .required(true)
+            });
+        command
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let guild_id = match command.guild_id {
+        Some(id) => id,
+        None => return respond(ctx, command, "This command only works in a server.", true).await,
+    };
+
+    let channel_id = match handler.get_user_channel(command.user.id).await {
+        Some(id) => id,
+        None => return respond(ctx, command, "You don't own a temporary channel.", true).await,
+    };
+
+    let message = option_str(&command.data.options, "message").unwrap_or_default().trim();
+    if message.is_empty() {
+        return respond(ctx, command, "Please provide a status message.", true).await;
+    }
+
+    if message.eq_ignore_ascii_case("clear") {
+        set_voice_status(handler.http_client(), &ctx.http, channel_id, None).await?;
+        handler.set_channel_status(channel_id, None).await;
+        handler.record_change(channel_id, command.user.id, ChangeAction::Status(None));
+        return respond(ctx, command, "Channel status cleared.", true).await;
+    }
+
+    if message.chars().count() > MAX_STATUS_LEN as usize {
+        return respond(ctx, command, "Status can be at most 500 characters.", true).await;
+    }
+
+    let config = handler.guild_config(guild_id).await;
+    if filter_channel_name(message, &config.name_filter) {
+        return respond(ctx, command, "That status contains a blocked word.", true).await;
+    }
+
+    set_voice_status(handler.http_client(), &ctx.http, channel_id, Some(message)).await?;
+    handler
+        .set_channel_status(channel_id, Some(message.to_string()))
+        .await;
+    handler.record_change(channel_id, command.user.id, ChangeAction::Status(Some(message.to_string())));
+
+    respond(
+        ctx,
+        command,
+        format!("Channel status set to: {}\n{}", message, VISIBILITY_WARNING),
+        true,
+    )
+    .await
+}
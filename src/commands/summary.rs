@@ -0,0 +1,62 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::application::interaction::{
+        application_command::ApplicationCommandInteraction, InteractionResponseType,
+    },
+    prelude::*,
+};
+
+use super::{respond, SlashCommand};
+use crate::{error::BotError, handler::Handler, utils::create_embed};
+
+pub struct SummaryCommand;
+
+#[async_trait]
+impl SlashCommand for SummaryCommand {
+    fn name(&self) -> &'static str {
+        "summary"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Show the summary of the last session you were in")
+            .dm_permission(false);
+        command
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let guild_id = match command.guild_id {
+        Some(id) => id,
+        None => return respond(ctx, command, "This command only works in a server.", true).await,
+    };
+
+    let summary = match handler.last_summary(guild_id, command.user.id).await {
+        Some(summary) => summary,
+        None => return respond(ctx, command, "No ended session found for you.", true).await,
+    };
+
+    let mut embed = create_embed(&handler.guild_config(guild_id).await);
+    summary.embed(&mut embed);
+    command
+        .create_interaction_response(&ctx.http, |r| {
+            r.kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|d| d.set_embed(embed).ephemeral(true))
+        })
+        .await
+}
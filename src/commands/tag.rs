@@ -0,0 +1,143 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::application::{
+        command::CommandOptionType,
+        interaction::application_command::ApplicationCommandInteraction,
+    },
+    prelude::*,
+};
+
+use super::{option_str, respond, SlashCommand};
+use crate::{error::BotError, handler::{ChangeAction, Handler}};
+
+/// Tags a channel can have at once.
+pub const MAX_CHANNEL_TAGS: usize = 3;
+pub const MAX_TAG_LEN: usize = 20;
+/// Tags a guild can allow, so the list still fits in one error message.
+pub const MAX_ALLOWED_TAGS: usize = 25;
+
+const TAG_OPTIONS: [&str; MAX_CHANNEL_TAGS] = ["tag1", "tag2", "tag3"];
+
+pub struct TagCommand;
+
+#[async_trait]
+impl SlashCommand for TagCommand {
+    fn name(&self) -> &'static str {
+        "tag"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Label your channel, e.g. Gaming or Study")
+            .dm_permission(false);
+        for (i, name) in TAG_OPTIONS.into_iter().enumerate() {
+            command.create_option(|o| {
+                o.name(name)
+                    .description("One of the server's tags")
+                    .kind(CommandOptionType::String)
+                    .max_length(MAX_TAG_LEN as u16)
+                    .required(i == 0)
+            });
+        }
+        command
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+/// The comma separated tag list of `/config allowed_tags`, duplicates dropped.
+pub fn parse_allowed_tags(input: &str) -> Result<Vec<String>, String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in input.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+        if tag.chars().count() > MAX_TAG_LEN {
+            return Err(format!("Tags can be at most {} characters, `{}` is longer.", MAX_TAG_LEN, tag));
+        }
+        if !tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            tags.push(tag.to_string());
+        }
+    }
+    if tags.len() > MAX_ALLOWED_TAGS {
+        return Err(format!("A server can have at most {} tags.", MAX_ALLOWED_TAGS));
+    }
+    Ok(tags)
+}
+
+/// Matches `input` against the allowed tags, ignoring case, in the spelling the admins
+/// picked. The error lists the tags that are available.
+pub fn resolve_tags(input: &[&str], allowed: &[String]) -> Result<Vec<String>, String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in input.iter().map(|t| t.trim()) {
+        let known = match allowed.iter().find(|a| a.eq_ignore_ascii_case(tag)) {
+            Some(known) => known,
+            None => return Err(format!("`{}` is not a tag here. Available tags: {}", tag, allowed.join(", "))),
+        };
+        if !tags.contains(known) {
+            tags.push(known.clone());
+        }
+    }
+    Ok(tags)
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let guild_id = match command.guild_id {
+        Some(id) => id,
+        None => return respond(ctx, command, "This command only works in a server.", true).await,
+    };
+    let channel_id = match handler.get_user_channel(command.user.id).await {
+        Some(id) => id,
+        None => return respond(ctx, command, "You don't own a temporary channel.", true).await,
+    };
+
+    let allowed = handler.guild_config(guild_id).await.allowed_tags;
+    if allowed.is_empty() {
+        return respond(
+            ctx,
+            command,
+            "This server has no tags yet, an admin can add them with `/config allowed_tags`.",
+            true,
+        )
+        .await;
+    }
+
+    let input: Vec<&str> = TAG_OPTIONS
+        .iter()
+        .filter_map(|name| option_str(&command.data.options, name))
+        .collect();
+    let tags = match resolve_tags(&input, &allowed) {
+        Ok(tags) => tags,
+        Err(problem) => return respond(ctx, command, problem, true).await,
+    };
+
+    handler.set_tags(channel_id, tags.clone());
+    handler.record_change(channel_id, command.user.id, ChangeAction::Tags(tags.clone()));
+    respond(ctx, command, format!("Your channel is tagged **{}**.", tags.join("**, **")), true).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_resolves_tags() {
+        let allowed = parse_allowed_tags(" Gaming, Study,,gaming, Chill ").unwrap();
+        assert_eq!(allowed, ["Gaming", "Study", "Chill"]);
+        assert!(parse_allowed_tags("a-tag-that-is-too-long").is_err());
+
+        assert_eq!(resolve_tags(&["chill", "GAMING", "Chill"], &allowed), Ok(vec!["Chill".to_string(), "Gaming".to_string()]));
+        let error = resolve_tags(&["Sleep"], &allowed).unwrap_err();
+        assert!(error.contains("Gaming, Study, Chill"), "{}", error);
+    }
+}
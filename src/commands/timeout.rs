@@ -0,0 +1,125 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::application::{
+        command::CommandOptionType,
+        interaction::application_command::ApplicationCommandInteraction,
+    },
+    prelude::*,
+};
+use std::time::Duration;
+
+use super::{option_int, option_user, respond, SlashCommand};
+use crate::{error::BotError, handler::{ChangeAction, Handler}};
+
+/// Longest timeout an owner can hand out, in seconds.
+pub const MAX_TIMEOUT_SECS: u64 = 300;
+
+pub struct TimeoutCommand;
+
+#[async_trait]
+impl SlashCommand for TimeoutCommand {
+    fn name(&self) -> &'static str {
+        "timeout"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Temporarily mute and deafen someone in your channel")
+            .dm_permission(false)
+            .create_option(|o| {
+                o.name("user")
+                    .description("Who to time out")
+                    .kind(CommandOptionType::User)
+                    .required(true)
+            })
+            .create_option(|o| {
+                o.name("seconds")
+                    .description("Duration in seconds (max 300)")
+                    .kind(CommandOptionType::Integer)
+                    .min_int_value(1)
+                    .max_int_value(MAX_TIMEOUT_SECS)
+                    .required(true)
+            });
+        command
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let guild_id = match command.guild_id {
+        Some(id) => id,
+        None => return respond(ctx, command, "This command only works in a server.", true).await,
+    };
+
+    let channel_id = match handler.get_user_channel(command.user.id).await {
+        Some(id) => id,
+        None => return respond(ctx, command, "You don't own a temporary channel.", true).await,
+    };
+
+    let options = &command.data.options;
+    let target = match option_user(options, "user") {
+        Some(id) => id,
+        None => return respond(ctx, command, "Please pick a user.", true).await,
+    };
+    if target == command.user.id {
+        return respond(ctx, command, "You can't time yourself out.", true).await;
+    }
+
+    let in_channel = guild_id
+        .to_guild_cached(&ctx.cache)
+        .and_then(|g| g.voice_states.get(&target).and_then(|v| v.channel_id))
+        == Some(channel_id);
+    if !in_channel {
+        return respond(ctx, command, "That user is not in your channel.", true).await;
+    }
+
+    let seconds = option_int(options, "seconds")
+        .unwrap_or(0)
+        .clamp(1, MAX_TIMEOUT_SECS as i64) as u64;
+
+    guild_id
+        .edit_member(&ctx.http, target, |m| m.mute(true).deafen(true))
+        .await?;
+
+    handler
+        .start_timeout(ctx.clone(), guild_id, channel_id, target, Duration::from_secs(seconds))
+        .await;
+    handler.record_change(
+        channel_id,
+        command.user.id,
+        ChangeAction::Timeout(target, Duration::from_secs(seconds)),
+    );
+
+    let guild_name = guild_id
+        .name(&ctx.cache)
+        .unwrap_or_else(|| "a server".to_string());
+    handler
+        .send_dm(
+            ctx,
+            target,
+            format!("You were timed out in {} for {} seconds.", guild_name, seconds),
+        )
+        .await;
+
+    respond(
+        ctx,
+        command,
+        format!("<@{}> is muted and deafened for {} seconds.", target, seconds),
+        false,
+    )
+    .await
+}
@@ -0,0 +1,334 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::{
+        application::{
+            command::CommandOptionType,
+            interaction::application_command::ApplicationCommandInteraction,
+        },
+        channel::{ChannelType, PermissionOverwrite, PermissionOverwriteType},
+        guild::Guild,
+        id::{ChannelId, RoleId, UserId},
+        permissions::Permissions,
+    },
+    prelude::*,
+};
+use tracing::{error, warn};
+
+use super::{option_str, respond, SlashCommand};
+use crate::{
+    error::BotError,
+    handler::{match_name, pair_teams, Handler, Tournament, MAX_TEAMS},
+    utils::{create_channel, delete_channel, is_member_gone, AuditReason},
+};
+
+pub struct TournamentCommand;
+
+#[async_trait]
+impl SlashCommand for TournamentCommand {
+    fn name(&self) -> &'static str {
+        "tournament"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Run a tournament with a voice channel per match")
+            .dm_permission(false)
+            .create_option(|o| {
+                o.name("create")
+                    .description("Start a tournament, teams are paired in the order given")
+                    .kind(CommandOptionType::SubCommand)
+                    .create_sub_option(|s| {
+                        s.name("name")
+                            .description("Name of the tournament, also used for its category")
+                            .kind(CommandOptionType::String)
+                            .max_length(100)
+                            .required(true)
+                    })
+                    .create_sub_option(|s| {
+                        s.name("teams")
+                            .description("Team roles, e.g. @Red @Blue @Green @Yellow")
+                            .kind(CommandOptionType::String)
+                            .required(true)
+                    })
+            })
+            .create_option(|o| {
+                o.name("advance")
+                    .description("Start the next round with the winners of this one")
+                    .kind(CommandOptionType::SubCommand)
+                    .create_sub_option(|s| {
+                        s.name("winners")
+                            .description("The winning team role of every match")
+                            .kind(CommandOptionType::String)
+                            .required(true)
+                    })
+            })
+            .create_option(|o| {
+                o.name("end")
+                    .description("Stop the tournament and delete its channels")
+                    .kind(CommandOptionType::SubCommand)
+            });
+        command
+    }
+
+    fn required_permissions(&self) -> Permissions {
+        Permissions::MANAGE_CHANNELS
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+/// Role mentions (`<@&id>`) or bare role IDs, separated by spaces or commas.
+pub fn parse_roles(input: &str) -> Option<Vec<RoleId>> {
+    input
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let id = part.strip_prefix("<@&").and_then(|p| p.strip_suffix('>')).unwrap_or(part);
+            id.parse().ok().map(RoleId)
+        })
+        .collect()
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let guild = match command.guild_id.and_then(|id| id.to_guild_cached(&ctx.cache)) {
+        Some(guild) => guild,
+        None => return respond(ctx, command, "This command only works in a server.", true).await,
+    };
+    let subcommand = match command.data.options.first() {
+        Some(s) => s,
+        None => return Ok(()),
+    };
+    let options = &subcommand.options;
+    let running = handler.tournament(guild.id).await;
+
+    match (subcommand.name.as_str(), running) {
+        ("create", Some(t)) => {
+            respond(ctx, command, format!("**{}** is still running, end it first.", t.name), true).await
+        }
+        ("create", None) => {
+            let name = option_str(options, "name").unwrap_or_default().trim().to_string();
+            let teams = match parse_roles(option_str(options, "teams").unwrap_or_default()) {
+                Some(teams) => teams,
+                None => return respond(ctx, command, "Mention the teams as roles, e.g. `@Red @Blue`.", true).await,
+            };
+            if let Some(problem) = team_problem(&guild, &teams) {
+                return respond(ctx, command, problem, true).await;
+            }
+
+            // Creating a channel per match takes a while
+            command.defer(&ctx.http).await?;
+            let reason = AuditReason::Tournament(name.clone());
+            let category = create_channel(&ctx.http, guild.id, &reason, |c| {
+                c.name(&name).kind(ChannelType::Category)
+            })
+            .await?;
+            let mut tournament = Tournament {
+                name,
+                category_id: category.id,
+                round: 1,
+                matches: pair_teams(&teams),
+            };
+            if let Err(e) = create_match_channels(ctx, &guild, &mut tournament).await {
+                delete_channels(ctx, [category.id].into_iter(), &reason).await;
+                return Err(e);
+            }
+            let content = format!("**{}** has started!\n{}", tournament.name, bracket(&tournament));
+            handler
+                .audit_log(ctx, guild.id, &format!("<@{}> started the tournament **{}**", command.user.id, tournament.name))
+                .await;
+            handler.set_tournament(guild.id, Some(tournament)).await;
+            command.edit_original_interaction_response(&ctx.http, |r| r.content(content)).await?;
+            Ok(())
+        }
+        (_, None) => respond(ctx, command, "There is no tournament running.", true).await,
+        ("advance", Some(mut tournament)) => {
+            let winners = parse_roles(option_str(options, "winners").unwrap_or_default()).unwrap_or_default();
+            let through = match tournament.next_round(&winners) {
+                Ok(through) => through,
+                Err(problem) => return respond(ctx, command, problem, true).await,
+            };
+
+            command.defer(&ctx.http).await?;
+            let previous = tournament.channels().collect::<Vec<_>>();
+            let reason = AuditReason::Tournament(tournament.name.clone());
+            let content = if let [champion] = through[..] {
+                delete_channels(ctx, previous.into_iter().chain([tournament.category_id]), &reason).await;
+                handler.set_tournament(guild.id, None).await;
+                format!("🏆 <@&{}> won **{}**!", champion, tournament.name)
+            } else {
+                tournament.round += 1;
+                tournament.matches = pair_teams(&through);
+                create_match_channels(ctx, &guild, &mut tournament).await?;
+                move_winners(ctx, &guild, &previous, &tournament).await;
+                delete_channels(ctx, previous.into_iter(), &reason).await;
+                let content = format!("Round {} of **{}**\n{}", tournament.round, tournament.name, bracket(&tournament));
+                handler.set_tournament(guild.id, Some(tournament)).await;
+                content
+            };
+            command.edit_original_interaction_response(&ctx.http, |r| r.content(content)).await?;
+            Ok(())
+        }
+        ("end", Some(tournament)) => {
+            let reason = AuditReason::Tournament(tournament.name.clone());
+            delete_channels(ctx, tournament.channels().chain([tournament.category_id]), &reason).await;
+            handler.set_tournament(guild.id, None).await;
+            handler
+                .audit_log(ctx, guild.id, &format!("<@{}> ended the tournament **{}**", command.user.id, tournament.name))
+                .await;
+            respond(ctx, command, format!("**{}** has ended.", tournament.name), false).await
+        }
+        _ => Ok(()),
+    }
+}
+
+fn team_problem(guild: &Guild, teams: &[RoleId]) -> Option<String> {
+    if teams.len() < 2 || teams.len() > MAX_TEAMS {
+        return Some(format!("A tournament needs 2 to {} teams.", MAX_TEAMS));
+    }
+    if let Some(team) = teams.iter().find(|t| !guild.roles.contains_key(t)) {
+        return Some(format!("`{}` is not a role in this server.", team));
+    }
+    if teams.iter().enumerate().any(|(i, t)| teams[..i].contains(t)) {
+        return Some("Every team can only be in the tournament once.".to_string());
+    }
+    None
+}
+
+fn role_name(guild: &Guild, role_id: RoleId) -> String {
+    guild.roles.get(&role_id).map_or_else(|| role_id.to_string(), |r| r.name.clone())
+}
+
+/// Creates the channels of the current round, only the two teams of a match can join.
+/// If one fails the ones created so far are deleted again, the tournament isn't stored
+/// yet so `/tournament end` wouldn't find them.
+async fn create_match_channels(
+    ctx: &Context,
+    guild: &Guild,
+    tournament: &mut Tournament,
+) -> Result<(), SerenityError> {
+    let reason = AuditReason::Tournament(tournament.name.clone());
+    let bot_id = ctx.cache.current_user_id();
+    let mut created = Vec::new();
+    for (i, m) in tournament.matches.iter_mut().enumerate() {
+        let away = match m.away {
+            Some(away) => away,
+            None => continue,
+        };
+        let name = match_name(i + 1, &role_name(guild, m.home), &role_name(guild, away));
+        let permissions = match_overwrites(guild, [m.home, away], bot_id);
+        let channel = create_channel(&ctx.http, guild.id, &reason, |c| {
+            c.name(name)
+                .kind(ChannelType::Voice)
+                .category(tournament.category_id)
+                .permissions(permissions)
+        })
+        .await;
+        let channel = match channel {
+            Ok(channel) => channel,
+            Err(e) => {
+                delete_channels(ctx, created.into_iter(), &reason).await;
+                return Err(e);
+            }
+        };
+        created.push(channel.id);
+        m.channel_id = Some(channel.id);
+    }
+    Ok(())
+}
+
+fn match_overwrites(guild: &Guild, teams: [RoleId; 2], bot_id: UserId) -> Vec<PermissionOverwrite> {
+    let mut overwrites = vec![
+        PermissionOverwrite {
+            kind: PermissionOverwriteType::Role(guild.id.0.into()),
+            allow: Permissions::empty(),
+            deny: Permissions::CONNECT,
+        },
+        PermissionOverwrite {
+            kind: PermissionOverwriteType::Member(bot_id),
+            allow: Permissions::VIEW_CHANNEL
+                | Permissions::CONNECT
+                | Permissions::MOVE_MEMBERS
+                | Permissions::MANAGE_CHANNELS,
+            deny: Permissions::empty(),
+        },
+    ];
+    overwrites.extend(teams.into_iter().map(|team| PermissionOverwrite {
+        kind: PermissionOverwriteType::Role(team),
+        allow: Permissions::VIEW_CHANNEL | Permissions::CONNECT,
+        deny: Permissions::empty(),
+    }));
+    overwrites
+}
+
+/// Moves the members of winning teams from last round's channels to their new match.
+async fn move_winners(ctx: &Context, guild: &Guild, previous: &[ChannelId], tournament: &Tournament) {
+    for voice_state in guild.voice_states.values() {
+        if !voice_state.channel_id.is_some_and(|c| previous.contains(&c)) {
+            continue;
+        }
+        let roles = match guild.members.get(&voice_state.user_id) {
+            Some(member) => &member.roles,
+            None => continue,
+        };
+        let target = tournament
+            .matches
+            .iter()
+            .find(|m| roles.iter().any(|r| m.has_team(*r)))
+            .and_then(|m| m.channel_id);
+        if let Some(target) = target {
+            match guild.id.move_member(&ctx.http, voice_state.user_id, target).await {
+                Ok(_) => {}
+                Err(e) if is_member_gone(&e) => {}
+                Err(e) => warn!("Kon {} niet verplaatsen naar {}: {:?}", voice_state.user_id, target, e),
+            }
+        }
+    }
+}
+
+async fn delete_channels(ctx: &Context, channels: impl Iterator<Item = ChannelId>, reason: &AuditReason) {
+    for channel_id in channels {
+        if let Err(e) = delete_channel(&ctx.http, channel_id, reason).await {
+            error!("Kon toernooi kanaal {} niet verwijderen: {:?}", channel_id, e);
+        }
+    }
+}
+
+fn bracket(tournament: &Tournament) -> String {
+    tournament
+        .matches
+        .iter()
+        .enumerate()
+        .map(|(i, m)| match (m.away, m.channel_id) {
+            (Some(away), Some(channel_id)) => {
+                format!("Match {}: <@&{}> vs <@&{}> in <#{}>", i + 1, m.home, away, channel_id)
+            }
+            _ => format!("<@&{}> has a bye", m.home),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_role_mentions_and_ids() {
+        assert_eq!(parse_roles("<@&1> <@&2>,3"), Some(vec![RoleId(1), RoleId(2), RoleId(3)]));
+        assert_eq!(parse_roles("<@&1> red"), None);
+        assert_eq!(parse_roles(""), Some(vec![]));
+    }
+}
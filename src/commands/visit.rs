@@ -0,0 +1,77 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::application::{
+        command::CommandOptionType,
+        interaction::application_command::ApplicationCommandInteraction,
+    },
+    prelude::*,
+};
+
+use super::{option_user, respond, SlashCommand};
+use crate::{error::BotError, handler::{ChangeAction, Handler}};
+
+pub struct VisitCommand;
+
+#[async_trait]
+impl SlashCommand for VisitCommand {
+    fn name(&self) -> &'static str {
+        "visit"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Let someone join your channel once, access ends when they leave")
+            .dm_permission(false)
+            .create_option(|o| {
+                o.name("user")
+                    .description("Who may visit")
+                    .kind(CommandOptionType::User)
+                    .required(true)
+            });
+        command
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let channel_id = match handler.get_user_channel(command.user.id).await {
+        Some(id) => id,
+        None => return respond(ctx, command, "You don't own a temporary channel.", true).await,
+    };
+
+    let target = match option_user(&command.data.options, "user") {
+        Some(id) => id,
+        None => return respond(ctx, command, "Please pick a user.", true).await,
+    };
+    if target == command.user.id {
+        return respond(ctx, command, "You can already join your own channel.", true).await;
+    }
+
+    handler.start_visit(ctx, channel_id, target, "/visit").await?;
+    handler.record_change(channel_id, command.user.id, ChangeAction::Visit(target));
+
+    respond(
+        ctx,
+        command,
+        format!(
+            "<@{}> can join <#{}> once. Access ends when they leave, or in 5 minutes if they don't join.",
+            target, channel_id
+        ),
+        false,
+    )
+    .await
+}
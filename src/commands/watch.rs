@@ -0,0 +1,111 @@
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::{
+        application::{
+            command::CommandOptionType,
+            interaction::application_command::ApplicationCommandInteraction,
+        },
+        permissions::Permissions,
+    },
+    prelude::*,
+};
+
+use super::{option_user, respond, SlashCommand};
+use crate::{error::BotError, handler::Handler};
+
+pub struct WatchCommand;
+
+#[async_trait]
+impl SlashCommand for WatchCommand {
+    fn name(&self) -> &'static str {
+        "watch"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Report someone's voice activity to the log channel")
+            .dm_permission(false)
+            .create_option(|o| {
+                o.name("user")
+                    .description("Who to watch")
+                    .kind(CommandOptionType::User)
+                    .required(true)
+            });
+        command
+    }
+
+    fn required_permissions(&self) -> Permissions {
+        Permissions::MANAGE_GUILD
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+pub struct UnwatchCommand;
+
+#[async_trait]
+impl SlashCommand for UnwatchCommand {
+    fn name(&self) -> &'static str {
+        "unwatch"
+    }
+
+    fn definition(&self) -> CreateApplicationCommand {
+        let mut command = CreateApplicationCommand::default();
+        command
+            .description("Stop reporting someone's voice activity")
+            .dm_permission(false)
+            .create_option(|o| {
+                o.name("user")
+                    .description("Who to stop watching")
+                    .kind(CommandOptionType::User)
+                    .required(true)
+            });
+        command
+    }
+
+    fn required_permissions(&self) -> Permissions {
+        Permissions::MANAGE_GUILD
+    }
+
+    async fn handle(
+        &self,
+        handler: &Handler,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), BotError> {
+        Ok(run(handler, ctx, command).await?)
+    }
+}
+
+async fn run(
+    handler: &Handler,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), SerenityError> {
+    let guild_id = match command.guild_id {
+        Some(id) => id,
+        None => return respond(ctx, command, "This command only works in a server.", true).await,
+    };
+    let target = match option_user(&command.data.options, "user") {
+        Some(id) => id,
+        None => return respond(ctx, command, "Please pick a user.", true).await,
+    };
+
+    let watch = command.data.name == "watch";
+    let count = handler.set_watched(guild_id, target, watch).await;
+    let reply = if watch {
+        format!("Watching <@{}>, {} user(s) are watched now.", target, count)
+    } else {
+        format!("Stopped watching <@{}>, {} user(s) are watched now.", target, count)
+    };
+    respond(ctx, command, reply, true).await
+}
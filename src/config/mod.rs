@@ -0,0 +1,530 @@
+use serde::{Deserialize, Serialize};
+use serenity::model::id::{ChannelId, GuildId, MessageId, RoleId, UserId};
+use std::{env, fmt, fs, io, path::Path, time::Duration};
+
+use crate::{db, utils::HTTP_TIMEOUT};
+
+/// Default location of the optional config file.
+pub const CONFIG_PATH: &str = "config.toml";
+
+/// Used when prefix commands are enabled without `COMMAND_PREFIX`.
+pub const DEFAULT_COMMAND_PREFIX: &str = "!";
+
+/// How `/global_mute` spreads its calls out, so a full channel doesn't hit the rate limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MuteBatch {
+    /// Members muted at the same time.
+    pub size: usize,
+    /// Pause between two batches.
+    pub delay: Duration,
+}
+
+/// Who may use the developer commands like `/echo`, and the one guild they're
+/// registered in. Guild commands show up right away, global ones can take an hour.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DevConfig {
+    /// `BOT_OWNER_ID`
+    pub bot_owner_id: Option<UserId>,
+    /// `DEV_GUILD_ID`, developer commands aren't registered without it.
+    pub guild_id: Option<GuildId>,
+    /// `ALLOW_EVENT_REPLAY`, stores voice events so `/event_replay` can feed them through
+    /// the handler again. Off in production, the table grows with every voice event.
+    pub allow_event_replay: bool,
+}
+
+impl Default for MuteBatch {
+    fn default() -> Self {
+        Self {
+            size: 5,
+            delay: Duration::from_millis(500),
+        }
+    }
+}
+
+/// Startup configuration. Values come from `config.toml` and are overridden by env vars.
+///
+/// The channel IDs are optional defaults; guilds can pick their own with `/setup`.
+#[derive(Clone)]
+pub struct Config {
+    pub token: String,
+    pub creator_channel_id: Option<ChannelId>,
+    pub waiting_room_id: Option<ChannelId>,
+    pub database_url: String,
+    /// Max connections in the SQLite pool.
+    pub db_pool_size: u32,
+    /// Prefix for text commands like `!rename`, `None` unless `PREFIX_COMMANDS_ENABLED=true`.
+    /// Needs the privileged Message Content intent, see `commands::prefix`.
+    pub command_prefix: Option<String>,
+    /// How long a Discord API call may hang before it's abandoned.
+    pub http_timeout: Duration,
+    /// `MUTE_BATCH_SIZE` and `MUTE_BATCH_DELAY_MS`.
+    pub mute_batch: MuteBatch,
+    pub dev: DevConfig,
+}
+
+/// Shape of `config.toml`; every key is optional because env vars can fill the gaps.
+#[derive(Debug, Default, Deserialize)]
+struct FileConfig {
+    token: Option<String>,
+    creator_channel_id: Option<u64>,
+    waiting_room_id: Option<u64>,
+    database_url: Option<String>,
+    database_path: Option<String>,
+    db_pool_size: Option<u32>,
+    prefix_commands_enabled: Option<bool>,
+    command_prefix: Option<String>,
+    http_timeout_secs: Option<u64>,
+    mute_batch_size: Option<usize>,
+    mute_batch_delay_ms: Option<u64>,
+    bot_owner_id: Option<u64>,
+    dev_guild_id: Option<u64>,
+    allow_event_replay: Option<bool>,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Read(io::Error),
+    Parse(toml::de::Error),
+    Missing(Vec<&'static str>),
+    Invalid { key: &'static str, value: String },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read(e) => write!(f, "Could not read {}: {}", CONFIG_PATH, e),
+            ConfigError::Parse(e) => write!(f, "Invalid {}: {}", CONFIG_PATH, e),
+            ConfigError::Missing(keys) => write!(
+                f,
+                "Missing required config (set in {} or as env var): {}",
+                CONFIG_PATH,
+                keys.join(", ")
+            ),
+            ConfigError::Invalid { key, value } => {
+                write!(f, "{} has an invalid value: '{}'", key, value)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl fmt::Debug for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Config")
+            .field("token", &"[redacted]")
+            .field("creator_channel_id", &self.creator_channel_id)
+            .field("waiting_room_id", &self.waiting_room_id)
+            .field("database_url", &self.database_url)
+            .field("db_pool_size", &self.db_pool_size)
+            .field("command_prefix", &self.command_prefix)
+            .field("http_timeout", &self.http_timeout)
+            .field("mute_batch", &self.mute_batch)
+            .field("dev", &self.dev)
+            .finish()
+    }
+}
+
+impl Config {
+    /// Reads `config.toml` if it exists, then overlays env vars on top of it.
+    pub fn load() -> Result<Self, ConfigError> {
+        let toml = if Path::new(CONFIG_PATH).exists() {
+            Some(fs::read_to_string(CONFIG_PATH).map_err(ConfigError::Read)?)
+        } else {
+            None
+        };
+
+        Self::from_sources(toml.as_deref(), |key| env::var(key).ok())
+    }
+
+    fn from_sources(
+        toml: Option<&str>,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, ConfigError> {
+        let file: FileConfig = match toml {
+            Some(contents) => toml::from_str(contents).map_err(ConfigError::Parse)?,
+            None => FileConfig::default(),
+        };
+
+        let token = env("DISCORD_TOKEN").or(file.token);
+        let creator_channel_id = channel_id(&env, "CREATOR_CHANNEL_ID", file.creator_channel_id)?;
+        let waiting_room_id = channel_id(&env, "WAITING_ROOM_ID", file.waiting_room_id)?;
+        // A full URL wins over a path, env vars over the file
+        let database_url = env("DATABASE_URL")
+            .or_else(|| env("DATABASE_PATH").map(|path| db::url_for_path(&path)))
+            .or(file.database_url)
+            .or_else(|| file.database_path.map(|path| db::url_for_path(&path)))
+            .unwrap_or_else(|| db::url_for_path(db::DEFAULT_DATABASE_PATH));
+        let db_pool_size = match env("DB_POOL_SIZE") {
+            Some(value) => match value.parse() {
+                Ok(size) if size > 0 => size,
+                _ => return Err(ConfigError::Invalid { key: "DB_POOL_SIZE", value }),
+            },
+            None => file.db_pool_size.unwrap_or(db::DEFAULT_POOL_SIZE),
+        };
+
+        let prefix_commands_enabled = match env("PREFIX_COMMANDS_ENABLED") {
+            Some(value) => value
+                .parse()
+                .map_err(|_| ConfigError::Invalid { key: "PREFIX_COMMANDS_ENABLED", value })?,
+            None => file.prefix_commands_enabled.unwrap_or(false),
+        };
+        let command_prefix = prefix_commands_enabled.then(|| {
+            env("COMMAND_PREFIX")
+                .or(file.command_prefix)
+                .unwrap_or_else(|| DEFAULT_COMMAND_PREFIX.to_string())
+        });
+
+        let http_timeout = match env("HTTP_TIMEOUT_SECS") {
+            Some(value) => match value.parse() {
+                Ok(secs) if secs > 0 => Duration::from_secs(secs),
+                _ => return Err(ConfigError::Invalid { key: "HTTP_TIMEOUT_SECS", value }),
+            },
+            None => file.http_timeout_secs.map_or(HTTP_TIMEOUT, Duration::from_secs),
+        };
+
+        let defaults = MuteBatch::default();
+        let mute_batch = MuteBatch {
+            size: match env("MUTE_BATCH_SIZE") {
+                Some(value) => match value.parse() {
+                    Ok(size) if size > 0 => size,
+                    _ => return Err(ConfigError::Invalid { key: "MUTE_BATCH_SIZE", value }),
+                },
+                None => file.mute_batch_size.unwrap_or(defaults.size),
+            },
+            delay: match env("MUTE_BATCH_DELAY_MS") {
+                Some(value) => value
+                    .parse()
+                    .map(Duration::from_millis)
+                    .map_err(|_| ConfigError::Invalid { key: "MUTE_BATCH_DELAY_MS", value })?,
+                None => file.mute_batch_delay_ms.map_or(defaults.delay, Duration::from_millis),
+            },
+        };
+
+        let dev = DevConfig {
+            bot_owner_id: snowflake(&env, "BOT_OWNER_ID", file.bot_owner_id)?.map(UserId),
+            guild_id: snowflake(&env, "DEV_GUILD_ID", file.dev_guild_id)?.map(GuildId),
+            allow_event_replay: match env("ALLOW_EVENT_REPLAY") {
+                Some(value) => value
+                    .parse()
+                    .map_err(|_| ConfigError::Invalid { key: "ALLOW_EVENT_REPLAY", value })?,
+                None => file.allow_event_replay.unwrap_or(false),
+            },
+        };
+
+        match token {
+            Some(token) => Ok(Self {
+                token,
+                creator_channel_id,
+                waiting_room_id,
+                database_url,
+                db_pool_size,
+                command_prefix,
+                http_timeout,
+                mute_batch,
+                dev,
+            }),
+            None => Err(ConfigError::Missing(vec!["DISCORD_TOKEN"])),
+        }
+    }
+}
+
+fn channel_id(
+    env: &impl Fn(&str) -> Option<String>,
+    key: &'static str,
+    file_value: Option<u64>,
+) -> Result<Option<ChannelId>, ConfigError> {
+    Ok(snowflake(env, key, file_value)?.map(ChannelId))
+}
+
+/// A Discord ID from the env var `key`, or else from the file.
+fn snowflake(
+    env: &impl Fn(&str) -> Option<String>,
+    key: &'static str,
+    file_value: Option<u64>,
+) -> Result<Option<u64>, ConfigError> {
+    match env(key) {
+        Some(value) => value
+            .parse()
+            .map(Some)
+            .map_err(|_| ConfigError::Invalid { key, value }),
+        None => Ok(file_value),
+    }
+}
+
+/// Per-guild settings that admins can change at runtime with `/config`. Stored in the
+/// database as JSON, fields missing from older rows fall back to their defaults.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GuildConfig {
+    /// Name new channels after the owner's current game instead of their display name.
+    pub use_activity_name: bool,
+    /// Name new channels after the game most members in the creator channel are
+    /// playing, ties go to the owner's game. Takes precedence over `use_activity_name`.
+    pub use_popular_game_name: bool,
+    /// Banned terms; activity-based names containing any of these are rejected.
+    pub name_filter: Vec<String>,
+    /// Category used for new channels once the creator channel's category is full.
+    pub overflow_category_id: Option<ChannelId>,
+    /// Creator channel picked with `/setup`, overrides `Config::creator_channel_id`.
+    pub creator_channel_id: Option<ChannelId>,
+    /// Waiting room picked with `/setup`, overrides `Config::waiting_room_id`.
+    pub waiting_room_id: Option<ChannelId>,
+    /// Channel that receives audit log embeds.
+    pub log_channel_id: Option<ChannelId>,
+    /// Channel other guilds' `/mirror` embeds are posted in.
+    pub announce_channel_id: Option<ChannelId>,
+    /// Guild whose announce channel lists this guild's active channels, see `/mirror`.
+    pub mirror_guild_id: Option<GuildId>,
+    /// That list, edited when the channels change.
+    pub mirror_message_id: Option<MessageId>,
+    /// Webhook that receives audit logs instead of the log channel, when set.
+    pub log_webhook_url: Option<String>,
+    /// Create a text channel next to every temp voice channel.
+    pub paired_text_channel: bool,
+    /// Archive paired text channels instead of deleting them when a session ends.
+    pub archive_text_channel: bool,
+    /// Category archived text channels are moved into.
+    pub archive_category_id: Option<ChannelId>,
+    /// Category `/diagnostics` creates its test channel in, else the creator channel's.
+    pub diagnostics_category_id: Option<ChannelId>,
+    /// Play a short sound in a temp channel when someone joins or leaves it.
+    pub join_sound_enabled: bool,
+    /// Post a summary when a session ends, in the text channel or else the log channel.
+    pub session_summary: bool,
+    /// Post every join and leave of a temp channel in its paired text channel.
+    pub access_log: bool,
+    /// Close the gap a deleted temp channel leaves in its category's channel positions.
+    pub reorder_on_delete: bool,
+    /// Music bot that `/music` moves from the waiting room into the channel.
+    pub music_bot_id: Option<UserId>,
+    /// Can change the settings from DMs with `/config_summary`, next to `/config` here.
+    pub admin_user_id: Option<UserId>,
+    /// Members with this role can use `/debug_voice_states`, next to the bot owner.
+    pub debug_role_id: Option<RoleId>,
+    /// While set only this user's voice events are handled, everyone else is ignored.
+    pub test_mode_user_id: Option<UserId>,
+    /// Forum channel where every new post is a temp session, next to the creator channel.
+    /// A session ends when the post is archived, by Discord after an hour of inactivity.
+    pub forum_creator_id: Option<ChannelId>,
+    /// Names of the forum's tags every new post in `forum_creator_id` gets.
+    pub forum_tags: Vec<String>,
+    /// How many channels members with these roles can own at once, highest role first.
+    /// Members without any of them get `DEFAULT_CHANNEL_QUOTA`.
+    pub role_quotas: Vec<(RoleId, u32)>,
+    /// Delete channels nobody but the owner joined after this many seconds once the owner
+    /// is gone too, `None` uses `DEFAULT_OWNERSHIP_EXPIRY_SECS`.
+    pub ownership_expiry_secs: Option<u64>,
+    /// Keep new channels hidden from @everyone for this many seconds so the owner can
+    /// set them up first, 0 turns it off.
+    pub ghost_period_secs: u32,
+    /// Accent color of the bot's embeds as `0xRRGGBB`, set with `/color`. 0 is Discord's default.
+    pub embed_color: u32,
+    /// Tags owners can pick from with `/tag`.
+    pub allowed_tags: Vec<String>,
+    /// Move members who stay self-muted and self-deafened in a temp channel this long
+    /// to the waiting room, `None` turns it off.
+    pub afk_kick_threshold_secs: Option<u64>,
+    /// Fewer channels and a longer wait before empty ones are deleted during the night,
+    /// set with `/config late_night`.
+    pub late_night_config: Option<LateNightConfig>,
+    /// The guild's timezone as minutes ahead of UTC, for `late_night_config`.
+    pub utc_offset_minutes: i16,
+    /// Schema version the config was saved with, see `db::migrations`. Set on save,
+    /// rows from before versioning don't have it and read as 0.
+    pub version: u32,
+}
+
+/// Late-night mode, see `utils::is_late_night`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LateNightConfig {
+    /// Local hour it starts, 0-23. Past `end_hour` means it runs over midnight.
+    pub start_hour: u8,
+    /// Local hour it ends, exclusive.
+    pub end_hour: u8,
+    /// Temp channels the guild can have at once, creator channel joins past it are refused.
+    pub max_channels: u32,
+    /// How long an empty channel is kept, instead of the usual 5 seconds.
+    pub deletion_delay_secs: u64,
+}
+
+/// Channels a member can own at once unless a role in `GuildConfig::role_quotas` allows more.
+/// Joining the creator channel at the limit replaces one of them.
+pub const DEFAULT_CHANNEL_QUOTA: u32 = 1;
+
+impl GuildConfig {
+    /// The highest quota among `roles`, or `DEFAULT_CHANNEL_QUOTA` if none of them has one.
+    pub fn channel_quota(&self, roles: &[RoleId]) -> u32 {
+        self.role_quotas
+            .iter()
+            .filter(|(role, _)| roles.contains(role))
+            .map(|(_, quota)| *quota)
+            .max()
+            .unwrap_or(DEFAULT_CHANNEL_QUOTA)
+    }
+
+    /// The settings that make sense in another guild, for `/clone_config`. Channel and
+    /// user IDs and the webhook only exist in this guild, so they're left out.
+    pub fn portable(&self) -> GuildConfig {
+        GuildConfig {
+            use_activity_name: self.use_activity_name,
+            use_popular_game_name: self.use_popular_game_name,
+            name_filter: self.name_filter.clone(),
+            paired_text_channel: self.paired_text_channel,
+            archive_text_channel: self.archive_text_channel,
+            join_sound_enabled: self.join_sound_enabled,
+            session_summary: self.session_summary,
+            access_log: self.access_log,
+            reorder_on_delete: self.reorder_on_delete,
+            ownership_expiry_secs: self.ownership_expiry_secs,
+            ghost_period_secs: self.ghost_period_secs,
+            embed_color: self.embed_color,
+            allowed_tags: self.allowed_tags.clone(),
+            forum_tags: self.forum_tags.clone(),
+            afk_kick_threshold_secs: self.afk_kick_threshold_secs,
+            late_night_config: self.late_night_config,
+            utc_offset_minutes: self.utc_offset_minutes,
+            ..GuildConfig::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    const TOML: &str = r#"
+        token = "file-token"
+        creator_channel_id = 1
+        waiting_room_id = 2
+    "#;
+
+    fn env_from(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |key| vars.get(key).cloned()
+    }
+
+    #[test]
+    fn highest_matching_role_quota_wins() {
+        let config = GuildConfig {
+            role_quotas: vec![(RoleId(1), 2), (RoleId(2), 5)],
+            ..Default::default()
+        };
+
+        assert_eq!(config.channel_quota(&[RoleId(1), RoleId(2)]), 5);
+        assert_eq!(config.channel_quota(&[RoleId(1), RoleId(3)]), 2);
+        assert_eq!(config.channel_quota(&[RoleId(3)]), DEFAULT_CHANNEL_QUOTA);
+    }
+
+    #[test]
+    fn env_overrides_toml() {
+        let config =
+            Config::from_sources(Some(TOML), env_from(&[("CREATOR_CHANNEL_ID", "10")])).unwrap();
+
+        assert_eq!(config.creator_channel_id, Some(ChannelId(10)));
+        assert_eq!(config.waiting_room_id, Some(ChannelId(2)));
+        assert_eq!(config.token, "file-token");
+    }
+
+    #[test]
+    fn only_token_is_required() {
+        let err = Config::from_sources(None, env_from(&[("WAITING_ROOM_ID", "3")])).unwrap_err();
+
+        match err {
+            ConfigError::Missing(keys) => assert_eq!(keys, ["DISCORD_TOKEN"]),
+            other => panic!("unexpected error: {:?}", other),
+        }
+
+        let config = Config::from_sources(None, env_from(&[("DISCORD_TOKEN", "t")])).unwrap();
+        assert_eq!(config.creator_channel_id, None);
+    }
+
+    #[test]
+    fn pool_size_defaults_and_validates() {
+        let config = Config::from_sources(Some(TOML), env_from(&[])).unwrap();
+        assert_eq!(config.db_pool_size, db::DEFAULT_POOL_SIZE);
+
+        let err = Config::from_sources(Some(TOML), env_from(&[("DB_POOL_SIZE", "0")])).unwrap_err();
+        assert!(matches!(err, ConfigError::Invalid { key: "DB_POOL_SIZE", .. }));
+    }
+
+    #[test]
+    fn database_path_becomes_url() {
+        let config = Config::from_sources(Some(TOML), env_from(&[])).unwrap();
+        assert_eq!(config.database_url, "sqlite:///data/bot.db");
+
+        let config = Config::from_sources(Some(TOML), env_from(&[("DATABASE_PATH", "/tmp/bot.db")])).unwrap();
+        assert_eq!(config.database_url, "sqlite:///tmp/bot.db");
+
+        let env = env_from(&[("DATABASE_PATH", "/tmp/bot.db"), ("DATABASE_URL", "sqlite://other.db")]);
+        let config = Config::from_sources(Some(TOML), env).unwrap();
+        assert_eq!(config.database_url, "sqlite://other.db");
+    }
+
+    #[test]
+    fn mute_batch_defaults_and_validates() {
+        let config = Config::from_sources(Some(TOML), env_from(&[])).unwrap();
+        assert_eq!(config.mute_batch, MuteBatch::default());
+
+        let env = env_from(&[("MUTE_BATCH_SIZE", "10"), ("MUTE_BATCH_DELAY_MS", "0")]);
+        let config = Config::from_sources(Some(TOML), env).unwrap();
+        assert_eq!(config.mute_batch, MuteBatch { size: 10, delay: Duration::ZERO });
+
+        let err = Config::from_sources(Some(TOML), env_from(&[("MUTE_BATCH_SIZE", "0")])).unwrap_err();
+        assert!(matches!(err, ConfigError::Invalid { key: "MUTE_BATCH_SIZE", .. }));
+    }
+
+    #[test]
+    fn prefix_commands_are_opt_in() {
+        let config = Config::from_sources(Some(TOML), env_from(&[])).unwrap();
+        assert_eq!(config.command_prefix, None);
+
+        let env = env_from(&[("PREFIX_COMMANDS_ENABLED", "true")]);
+        let config = Config::from_sources(Some(TOML), env).unwrap();
+        assert_eq!(config.command_prefix.as_deref(), Some("!"));
+    }
+
+    #[test]
+    fn portable_config_drops_guild_ids() {
+        let config = GuildConfig {
+            name_filter: vec!["spam".to_string()],
+            session_summary: true,
+            creator_channel_id: Some(ChannelId(1)),
+            log_webhook_url: Some("https://discord.com/api/webhooks/1/x".to_string()),
+            ..GuildConfig::default()
+        };
+
+        let portable = config.portable();
+        assert_eq!(portable.name_filter, ["spam"]);
+        assert!(portable.session_summary);
+        assert_eq!(portable.creator_channel_id, None);
+        assert_eq!(portable.log_webhook_url, None);
+    }
+
+    #[test]
+    fn dev_config_from_env() {
+        let config = Config::from_sources(
+            Some(TOML),
+            env_from(&[("BOT_OWNER_ID", "5"), ("DEV_GUILD_ID", "6"), ("ALLOW_EVENT_REPLAY", "true")]),
+        )
+        .unwrap();
+        assert_eq!(config.dev.bot_owner_id, Some(UserId(5)));
+        assert_eq!(config.dev.guild_id, Some(GuildId(6)));
+        assert!(config.dev.allow_event_replay);
+        assert!(!Config::from_sources(Some(TOML), env_from(&[])).unwrap().dev.allow_event_replay);
+
+        let invalid = Config::from_sources(Some(TOML), env_from(&[("BOT_OWNER_ID", "me")]));
+        assert!(matches!(invalid, Err(ConfigError::Invalid { key: "BOT_OWNER_ID", .. })));
+    }
+
+    #[test]
+    fn debug_redacts_token() {
+        let config = Config::from_sources(Some(TOML), env_from(&[])).unwrap();
+
+        assert!(!format!("{:?}", config).contains("file-token"));
+    }
+}
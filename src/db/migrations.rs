@@ -0,0 +1,93 @@
+//! Upgrades `GuildConfig` JSON stored by older versions of the bot. New fields that are
+//! fine with their default don't need a step here, `#[serde(default)]` fills them in.
+//! Anything else (renames, changed types, defaults that depend on other fields) bumps
+//! `CURRENT_VERSION` and gets a step in `MIGRATIONS`.
+
+use serde_json::{Map, Value};
+
+use crate::config::GuildConfig;
+
+/// Version of the configs this build writes.
+pub const CURRENT_VERSION: u32 = 1;
+
+type Migration = fn(&mut Map<String, Value>);
+
+/// `MIGRATIONS[n]` turns a version `n` config into a version `n + 1` config.
+const MIGRATIONS: [Migration; CURRENT_VERSION as usize] = [fill_defaults];
+
+/// Version a stored config was saved with, configs from before versioning are 0.
+pub fn stored_version(config: &Value) -> u32 {
+    config
+        .get("version")
+        .and_then(Value::as_u64)
+        .map_or(0, |v| v as u32)
+}
+
+/// Runs every migration from `version` up to `CURRENT_VERSION` on `old`. Configs that
+/// are already current, or newer than this build, are only deserialized.
+pub fn migrate_config(old: Value, version: u32) -> Result<GuildConfig, serde_json::Error> {
+    let mut fields = match old {
+        Value::Object(fields) => fields,
+        other => return serde_json::from_value(other),
+    };
+
+    for migration in MIGRATIONS.iter().skip(version as usize) {
+        migration(&mut fields);
+    }
+    fields.insert("version".to_string(), CURRENT_VERSION.max(version).into());
+
+    serde_json::from_value(Value::Object(fields))
+}
+
+/// 0 → 1: unversioned configs could hold `null` for fields that aren't optional, which
+/// fails to deserialize. Those get their default, like missing fields do.
+fn fill_defaults(fields: &mut Map<String, Value>) {
+    let defaults = match serde_json::to_value(GuildConfig::default()) {
+        Ok(Value::Object(defaults)) => defaults,
+        _ => return,
+    };
+    for (key, default) in defaults {
+        match fields.get(&key) {
+            Some(Value::Null) | None if !default.is_null() => {
+                fields.insert(key, default);
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn migrates_every_older_version_to_current() {
+        for version in 0..=CURRENT_VERSION {
+            let mut stored = json!({
+                "use_activity_name": true,
+                "name_filter": null,
+                "session_summary": true,
+            });
+            if version > 0 {
+                stored["version"] = version.into();
+                stored["name_filter"] = json!([]);
+            }
+
+            assert_eq!(stored_version(&stored), version);
+            let config = migrate_config(stored, version).unwrap();
+            assert_eq!(config.version, CURRENT_VERSION, "from version {}", version);
+            assert!(config.use_activity_name);
+            assert!(config.session_summary);
+            assert!(config.name_filter.is_empty());
+        }
+    }
+
+    #[test]
+    fn newer_versions_are_left_alone() {
+        let stored = json!({ "version": CURRENT_VERSION + 1, "embed_color": 0xFF0000 });
+        let config = migrate_config(stored, CURRENT_VERSION + 1).unwrap();
+        assert_eq!(config.version, CURRENT_VERSION + 1);
+        assert_eq!(config.embed_color, 0xFF0000);
+    }
+}
@@ -0,0 +1,539 @@
+use serenity::model::id::{ChannelId, GuildId, ScheduledEventId, UserId};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
+use std::{
+    collections::{HashMap, HashSet},
+    fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+use serde_json::Value;
+use tracing::{info, warn};
+
+use crate::{config::GuildConfig, error::BotError};
+
+pub mod migrations;
+pub mod stats;
+
+use migrations::{migrate_config, stored_version, CURRENT_VERSION};
+
+/// Where the database goes without `DATABASE_PATH`, a volume in the Docker image.
+pub const DEFAULT_DATABASE_PATH: &str = "/data/bot.db";
+pub const DEFAULT_POOL_SIZE: u32 = 5;
+
+/// How long a query waits for a free connection before giving up.
+const ACQUIRE_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub fn url_for_path(path: &str) -> String {
+    format!("sqlite://{}", path)
+}
+
+/// The file behind a SQLite URL, `None` for in-memory databases.
+fn database_file(url: &str) -> Option<&Path> {
+    let path = url.strip_prefix("sqlite://").or_else(|| url.strip_prefix("sqlite:"))?;
+    let path = path.split('?').next().unwrap_or(path);
+    (!path.is_empty() && path != ":memory:").then(|| Path::new(path))
+}
+
+/// Fails with a clear error when the database directory isn't writable, instead of
+/// the SQLite error a read-only filesystem gives on the first write.
+/// Returns the directory and the error when it isn't.
+fn check_writable(url: &str) -> Result<(), (PathBuf, io::Error)> {
+    let Some(file) = database_file(url) else { return Ok(()) };
+    let dir = match file.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let probe = dir.join(".write-test");
+    fs::write(&probe, b"")
+        .and_then(|_| fs::remove_file(&probe))
+        .map_err(|e| (dir, e))
+}
+
+/// Opens the connection pool, creating the database file if needed, and runs the
+/// migrations in `migrations/`.
+pub async fn init(url: &str, pool_size: u32) -> Result<SqlitePool, BotError> {
+    check_writable(url).map_err(|(dir, e)| BotError::NotWritable(dir, e))?;
+    let options = SqliteConnectOptions::from_str(url)?.create_if_missing(true);
+    let pool = SqlitePoolOptions::new()
+        .max_connections(pool_size)
+        .acquire_timeout(ACQUIRE_TIMEOUT)
+        .connect_with(options)
+        .await?;
+
+    sqlx::migrate!()
+        .run(&pool)
+        .await
+        .map_err(|e| sqlx::Error::Migrate(Box::new(e)))?;
+
+    Ok(pool)
+}
+
+/// Loads every stored config, migrating and re-saving the ones written by an older version.
+pub async fn load_guild_configs(pool: &SqlitePool) -> Result<HashMap<GuildId, GuildConfig>, BotError> {
+    let rows: Vec<(i64, String)> = sqlx::query_as("SELECT guild_id, config FROM guild_configs")
+        .fetch_all(pool)
+        .await?;
+
+    let mut configs = HashMap::new();
+    for (guild_id, json) in rows {
+        let guild_id = GuildId(guild_id as u64);
+        let loaded = serde_json::from_str::<Value>(&json).and_then(|value| {
+            let version = stored_version(&value);
+            migrate_config(value, version).map(|config| (config, version))
+        });
+
+        match loaded {
+            Ok((config, version)) => {
+                if version < CURRENT_VERSION {
+                    info!(
+                        "Config van guild {} gemigreerd van versie {} naar {}",
+                        guild_id, version, CURRENT_VERSION
+                    );
+                    if let Err(e) = save_guild_config(pool, guild_id, &config).await {
+                        warn!("Kon gemigreerde config van guild {} niet opslaan: {}", guild_id, e);
+                    }
+                }
+                configs.insert(guild_id, config);
+            }
+            Err(e) => warn!("Ongeldige config voor guild {} overgeslagen: {}", guild_id, e),
+        }
+    }
+    Ok(configs)
+}
+
+pub async fn save_guild_config(
+    pool: &SqlitePool,
+    guild_id: GuildId,
+    config: &GuildConfig,
+) -> Result<(), BotError> {
+    // A row written by a newer build keeps its version, so that build doesn't skip
+    // migrations it already ran
+    let config = GuildConfig {
+        version: config.version.max(CURRENT_VERSION),
+        ..config.clone()
+    };
+    let json = serde_json::to_string(&config).expect("GuildConfig always serializes");
+
+    sqlx::query(
+        "INSERT INTO guild_configs (guild_id, config) VALUES (?, ?)
+         ON CONFLICT(guild_id) DO UPDATE SET config = excluded.config",
+    )
+    .bind(guild_id.0 as i64)
+    .bind(json)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Stored temp channels of `guild_id` with their owners.
+pub async fn load_temp_channels(pool: &SqlitePool, guild_id: GuildId) -> Result<Vec<(ChannelId, UserId)>, BotError> {
+    let rows: Vec<(i64, i64)> = sqlx::query_as("SELECT channel_id, owner_id FROM temp_channels WHERE guild_id = ?")
+        .bind(guild_id.0 as i64)
+        .fetch_all(pool)
+        .await?;
+    Ok(rows
+        .into_iter()
+        .map(|(channel_id, owner_id)| (ChannelId(channel_id as u64), UserId(owner_id as u64)))
+        .collect())
+}
+
+/// Stores a temp channel, or its new owner if it's stored already.
+pub async fn save_temp_channel(
+    pool: &SqlitePool,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+    owner_id: UserId,
+) -> Result<(), BotError> {
+    sqlx::query(
+        "INSERT INTO temp_channels (channel_id, guild_id, owner_id) VALUES (?, ?, ?)
+         ON CONFLICT(channel_id) DO UPDATE SET owner_id = excluded.owner_id",
+    )
+    .bind(channel_id.0 as i64)
+    .bind(guild_id.0 as i64)
+    .bind(owner_id.0 as i64)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Removes a temp channel that's no longer tracked, with its `/auto_lock` threshold and
+/// the event it was for.
+pub async fn delete_temp_channel(pool: &SqlitePool, channel_id: ChannelId) -> Result<(), BotError> {
+    sqlx::query("DELETE FROM temp_channels WHERE channel_id = ?")
+        .bind(channel_id.0 as i64)
+        .execute(pool)
+        .await?;
+    sqlx::query("DELETE FROM auto_locks WHERE channel_id = ?")
+        .bind(channel_id.0 as i64)
+        .execute(pool)
+        .await?;
+    sqlx::query("DELETE FROM event_channels WHERE channel_id = ?")
+        .bind(channel_id.0 as i64)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Stored event channels of `guild_id` as `(event, channel, venue)`.
+pub async fn load_event_channels(
+    pool: &SqlitePool,
+    guild_id: GuildId,
+) -> Result<Vec<(ScheduledEventId, ChannelId, Option<ChannelId>)>, BotError> {
+    let rows: Vec<(i64, i64, Option<i64>)> =
+        sqlx::query_as("SELECT event_id, channel_id, venue_id FROM event_channels WHERE guild_id = ?")
+            .bind(guild_id.0 as i64)
+            .fetch_all(pool)
+            .await?;
+    Ok(rows
+        .into_iter()
+        .map(|(event_id, channel_id, venue_id)| {
+            (
+                ScheduledEventId(event_id as u64),
+                ChannelId(channel_id as u64),
+                venue_id.map(|id| ChannelId(id as u64)),
+            )
+        })
+        .collect())
+}
+
+pub async fn save_event_channel(
+    pool: &SqlitePool,
+    guild_id: GuildId,
+    event_id: ScheduledEventId,
+    channel_id: ChannelId,
+    venue_id: Option<ChannelId>,
+) -> Result<(), BotError> {
+    sqlx::query("INSERT OR REPLACE INTO event_channels (event_id, guild_id, channel_id, venue_id) VALUES (?, ?, ?, ?)")
+        .bind(event_id.0 as i64)
+        .bind(guild_id.0 as i64)
+        .bind(channel_id.0 as i64)
+        .bind(venue_id.map(|id| id.0 as i64))
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn delete_event_channel(pool: &SqlitePool, event_id: ScheduledEventId) -> Result<(), BotError> {
+    sqlx::query("DELETE FROM event_channels WHERE event_id = ?")
+        .bind(event_id.0 as i64)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Stored `/auto_lock` thresholds by channel.
+pub async fn load_auto_locks(pool: &SqlitePool) -> Result<HashMap<ChannelId, u32>, BotError> {
+    let rows: Vec<(i64, i64)> = sqlx::query_as("SELECT channel_id, threshold FROM auto_locks")
+        .fetch_all(pool)
+        .await?;
+    Ok(rows
+        .into_iter()
+        .map(|(channel_id, threshold)| (ChannelId(channel_id as u64), threshold as u32))
+        .collect())
+}
+
+/// Stores the `/auto_lock` threshold of `channel_id`, `None` removes it.
+pub async fn save_auto_lock(
+    pool: &SqlitePool,
+    channel_id: ChannelId,
+    threshold: Option<u32>,
+) -> Result<(), BotError> {
+    match threshold {
+        Some(threshold) => {
+            sqlx::query(
+                "INSERT INTO auto_locks (channel_id, threshold) VALUES (?, ?)
+                 ON CONFLICT(channel_id) DO UPDATE SET threshold = excluded.threshold",
+            )
+            .bind(channel_id.0 as i64)
+            .bind(threshold as i64)
+            .execute(pool)
+            .await?;
+        }
+        None => {
+            sqlx::query("DELETE FROM auto_locks WHERE channel_id = ?")
+                .bind(channel_id.0 as i64)
+                .execute(pool)
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// The `/watch` lists of every guild.
+pub async fn load_watched_users(pool: &SqlitePool) -> Result<HashMap<GuildId, HashSet<UserId>>, BotError> {
+    let rows: Vec<(i64, i64)> = sqlx::query_as("SELECT guild_id, user_id FROM watched_users")
+        .fetch_all(pool)
+        .await?;
+    let mut watched: HashMap<GuildId, HashSet<UserId>> = HashMap::new();
+    for (guild_id, user_id) in rows {
+        watched
+            .entry(GuildId(guild_id as u64))
+            .or_default()
+            .insert(UserId(user_id as u64));
+    }
+    Ok(watched)
+}
+
+pub async fn save_watched_user(
+    pool: &SqlitePool,
+    guild_id: GuildId,
+    user_id: UserId,
+    watched: bool,
+) -> Result<(), BotError> {
+    let query = if watched {
+        "INSERT OR IGNORE INTO watched_users (guild_id, user_id) VALUES (?, ?)"
+    } else {
+        "DELETE FROM watched_users WHERE guild_id = ? AND user_id = ?"
+    };
+    sqlx::query(query)
+        .bind(guild_id.0 as i64)
+        .bind(user_id.0 as i64)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Users who keep their channels out of announce channels, see `/auto_announce`.
+pub async fn load_announce_opt_outs(pool: &SqlitePool) -> Result<HashSet<UserId>, BotError> {
+    let rows: Vec<(i64,)> = sqlx::query_as("SELECT user_id FROM user_preferences WHERE announce_opt_out = 1")
+        .fetch_all(pool)
+        .await?;
+    Ok(rows.into_iter().map(|(user_id,)| UserId(user_id as u64)).collect())
+}
+
+pub async fn save_announce_opt_out(pool: &SqlitePool, user_id: UserId, opt_out: bool) -> Result<(), BotError> {
+    sqlx::query(
+        "INSERT INTO user_preferences (user_id, announce_opt_out) VALUES (?, ?)
+         ON CONFLICT (user_id) DO UPDATE SET announce_opt_out = excluded.announce_opt_out",
+    )
+    .bind(user_id.0 as i64)
+    .bind(opt_out)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// `kind` of the `channel_events` row written when a temp channel is created.
+pub const CHANNEL_CREATED: &str = "created";
+
+pub async fn record_channel_event(
+    pool: &SqlitePool,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+    kind: &str,
+    unix_secs: i64,
+) -> Result<(), BotError> {
+    sqlx::query("INSERT INTO channel_events (guild_id, channel_id, kind, created_at) VALUES (?, ?, ?, ?)")
+        .bind(guild_id.0 as i64)
+        .bind(channel_id.0 as i64)
+        .bind(kind)
+        .bind(unix_secs)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// `kind` of the `channel_events` rows holding a voice event for `/event_replay`.
+pub const VOICE_STATE: &str = "voice_state";
+
+/// Stores a voice event as JSON and returns its ID for `/event_replay`.
+pub async fn record_voice_state_event(
+    pool: &SqlitePool,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+    payload: &str,
+    unix_secs: i64,
+) -> Result<i64, BotError> {
+    let result = sqlx::query(
+        "INSERT INTO channel_events (guild_id, channel_id, kind, created_at, payload) VALUES (?, ?, ?, ?, ?)",
+    )
+    .bind(guild_id.0 as i64)
+    .bind(channel_id.0 as i64)
+    .bind(VOICE_STATE)
+    .bind(unix_secs)
+    .bind(payload)
+    .execute(pool)
+    .await?;
+    Ok(result.last_insert_rowid())
+}
+
+/// The JSON of the voice event stored as `id`, if there is one.
+pub async fn load_voice_state_event(pool: &SqlitePool, id: i64) -> Result<Option<String>, BotError> {
+    let row: Option<(String,)> = sqlx::query_as("SELECT payload FROM channel_events WHERE rowid = ? AND kind = ?")
+        .bind(id)
+        .bind(VOICE_STATE)
+        .fetch_optional(pool)
+        .await?;
+    Ok(row.map(|(payload,)| payload))
+}
+
+/// Unix timestamps of the `kind` events in `guild_id` since `since`, oldest first.
+pub async fn channel_events_since(
+    pool: &SqlitePool,
+    guild_id: GuildId,
+    kind: &str,
+    since: i64,
+) -> Result<Vec<i64>, BotError> {
+    let rows: Vec<(i64,)> = sqlx::query_as(
+        "SELECT created_at FROM channel_events
+         WHERE guild_id = ? AND kind = ? AND created_at >= ?
+         ORDER BY created_at",
+    )
+    .bind(guild_id.0 as i64)
+    .bind(kind)
+    .bind(since)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().map(|(at,)| at).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn guild_config_round_trip() {
+        let pool = init("sqlite::memory:", 1).await.unwrap();
+        let config = GuildConfig {
+            use_activity_name: true,
+            name_filter: vec!["foo".to_string()],
+            ..Default::default()
+        };
+
+        save_guild_config(&pool, GuildId(1), &config).await.unwrap();
+        let loaded = load_guild_configs(&pool).await.unwrap();
+
+        assert_eq!(loaded[&GuildId(1)].name_filter, ["foo"]);
+        assert!(loaded[&GuildId(1)].use_activity_name);
+        assert_eq!(loaded[&GuildId(1)].version, CURRENT_VERSION);
+    }
+
+    #[tokio::test]
+    async fn channel_events_filter_by_guild_and_time() {
+        let pool = init("sqlite::memory:", 1).await.unwrap();
+        for (guild, at) in [(1, 100), (1, 300), (2, 300), (1, 50)] {
+            record_channel_event(&pool, GuildId(guild), ChannelId(9), CHANNEL_CREATED, at).await.unwrap();
+        }
+
+        let events = channel_events_since(&pool, GuildId(1), CHANNEL_CREATED, 100).await.unwrap();
+        assert_eq!(events, [100, 300]);
+    }
+
+    #[tokio::test]
+    async fn voice_state_events_load_by_id() {
+        let pool = init("sqlite::memory:", 1).await.unwrap();
+        record_channel_event(&pool, GuildId(1), ChannelId(9), CHANNEL_CREATED, 100).await.unwrap();
+        let id = record_voice_state_event(&pool, GuildId(1), ChannelId(9), "{}", 100).await.unwrap();
+
+        assert_eq!(load_voice_state_event(&pool, id).await.unwrap().as_deref(), Some("{}"));
+        assert_eq!(load_voice_state_event(&pool, id - 1).await.unwrap(), None);
+        assert_eq!(channel_events_since(&pool, GuildId(1), CHANNEL_CREATED, 0).await.unwrap(), [100]);
+    }
+
+    #[tokio::test]
+    async fn unversioned_rows_are_migrated_on_load() {
+        let pool = init("sqlite::memory:", 1).await.unwrap();
+        sqlx::query("INSERT INTO guild_configs (guild_id, config) VALUES (1, ?)")
+            .bind(r#"{"join_sound_enabled": true, "name_filter": null}"#)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let loaded = load_guild_configs(&pool).await.unwrap();
+        assert!(loaded[&GuildId(1)].join_sound_enabled);
+
+        let (json,): (String,) = sqlx::query_as("SELECT config FROM guild_configs WHERE guild_id = 1")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(stored_version(&serde_json::from_str(&json).unwrap()), CURRENT_VERSION);
+    }
+
+    #[tokio::test]
+    async fn saving_keeps_a_newer_version() {
+        let pool = init("sqlite::memory:", 1).await.unwrap();
+        let config = GuildConfig { version: CURRENT_VERSION + 1, ..GuildConfig::default() };
+        save_guild_config(&pool, GuildId(1), &config).await.unwrap();
+
+        let (json,): (String,) = sqlx::query_as("SELECT config FROM guild_configs WHERE guild_id = 1")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(stored_version(&serde_json::from_str(&json).unwrap()), CURRENT_VERSION + 1);
+    }
+
+    #[test]
+    fn finds_the_database_file() {
+        assert_eq!(database_file("sqlite:///data/bot.db"), Some(Path::new("/data/bot.db")));
+        assert_eq!(database_file("sqlite://bot.db?mode=rwc"), Some(Path::new("bot.db")));
+        assert_eq!(database_file("sqlite::memory:"), None);
+        assert!(check_writable("sqlite:///proc/bot.db").is_err());
+    }
+
+    #[tokio::test]
+    async fn auto_lock_round_trip() {
+        let pool = init("sqlite::memory:", 1).await.unwrap();
+        save_auto_lock(&pool, ChannelId(1), Some(4)).await.unwrap();
+        save_auto_lock(&pool, ChannelId(1), Some(6)).await.unwrap();
+        save_auto_lock(&pool, ChannelId(2), Some(3)).await.unwrap();
+        save_auto_lock(&pool, ChannelId(2), None).await.unwrap();
+
+        let loaded = load_auto_locks(&pool).await.unwrap();
+        assert_eq!(loaded, HashMap::from([(ChannelId(1), 6)]));
+    }
+
+    #[tokio::test]
+    async fn temp_channels_round_trip() {
+        let pool = init("sqlite::memory:", 1).await.unwrap();
+        save_temp_channel(&pool, GuildId(1), ChannelId(10), UserId(5)).await.unwrap();
+        save_temp_channel(&pool, GuildId(1), ChannelId(10), UserId(6)).await.unwrap();
+        save_temp_channel(&pool, GuildId(2), ChannelId(20), UserId(5)).await.unwrap();
+        save_auto_lock(&pool, ChannelId(20), Some(4)).await.unwrap();
+        assert_eq!(load_temp_channels(&pool, GuildId(1)).await.unwrap(), [(ChannelId(10), UserId(6))]);
+
+        delete_temp_channel(&pool, ChannelId(20)).await.unwrap();
+        assert!(load_temp_channels(&pool, GuildId(2)).await.unwrap().is_empty());
+        assert!(load_auto_locks(&pool).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn event_channels_round_trip() {
+        let pool = init("sqlite::memory:", 1).await.unwrap();
+        save_event_channel(&pool, GuildId(1), ScheduledEventId(5), ChannelId(10), Some(ChannelId(3))).await.unwrap();
+        save_event_channel(&pool, GuildId(1), ScheduledEventId(6), ChannelId(11), None).await.unwrap();
+        save_event_channel(&pool, GuildId(2), ScheduledEventId(7), ChannelId(20), None).await.unwrap();
+
+        delete_event_channel(&pool, ScheduledEventId(6)).await.unwrap();
+        delete_temp_channel(&pool, ChannelId(20)).await.unwrap();
+        assert_eq!(
+            load_event_channels(&pool, GuildId(1)).await.unwrap(),
+            [(ScheduledEventId(5), ChannelId(10), Some(ChannelId(3)))]
+        );
+        assert!(load_event_channels(&pool, GuildId(2)).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn watched_users_round_trip() {
+        let pool = init("sqlite::memory:", 1).await.unwrap();
+        save_watched_user(&pool, GuildId(1), UserId(10), true).await.unwrap();
+        save_watched_user(&pool, GuildId(1), UserId(10), true).await.unwrap();
+        save_watched_user(&pool, GuildId(1), UserId(11), true).await.unwrap();
+        save_watched_user(&pool, GuildId(1), UserId(11), false).await.unwrap();
+
+        let loaded = load_watched_users(&pool).await.unwrap();
+        assert_eq!(loaded, HashMap::from([(GuildId(1), HashSet::from([UserId(10)]))]));
+    }
+
+    #[tokio::test]
+    async fn announce_opt_outs_round_trip() {
+        let pool = init("sqlite::memory:", 1).await.unwrap();
+        save_announce_opt_out(&pool, UserId(10), true).await.unwrap();
+        save_announce_opt_out(&pool, UserId(11), true).await.unwrap();
+        save_announce_opt_out(&pool, UserId(11), false).await.unwrap();
+
+        let loaded = load_announce_opt_outs(&pool).await.unwrap();
+        assert_eq!(loaded, HashSet::from([UserId(10)]));
+    }
+}
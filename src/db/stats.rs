@@ -0,0 +1,174 @@
+//! Operational counters for `/stats`. The all-time totals live in the `bot_stats`
+//! table so they survive restarts, `SessionCounters` keeps the ones since startup and
+//! adds them to the table in batches.
+
+use sqlx::sqlite::SqlitePool;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::warn;
+
+use crate::error::BotError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stat {
+    ChannelsCreated,
+    ChannelsDeleted,
+    CommandsExecuted,
+    VoiceEventsProcessed,
+    Errors,
+}
+
+impl Stat {
+    pub const ALL: [Stat; 5] = [
+        Stat::ChannelsCreated,
+        Stat::ChannelsDeleted,
+        Stat::CommandsExecuted,
+        Stat::VoiceEventsProcessed,
+        Stat::Errors,
+    ];
+
+    fn column(self) -> &'static str {
+        match self {
+            Stat::ChannelsCreated => "channels_created_total",
+            Stat::ChannelsDeleted => "channels_deleted_total",
+            Stat::CommandsExecuted => "commands_executed_total",
+            Stat::VoiceEventsProcessed => "voice_events_processed_total",
+            Stat::Errors => "errors_total",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Stat::ChannelsCreated => "Channels created",
+            Stat::ChannelsDeleted => "Channels deleted",
+            Stat::CommandsExecuted => "Commands executed",
+            Stat::VoiceEventsProcessed => "Voice events",
+            Stat::Errors => "Errors",
+        }
+    }
+
+    fn index(self) -> usize {
+        Stat::ALL.iter().position(|s| *s == self).expect("every stat is in ALL")
+    }
+}
+
+/// All-time totals from the `bot_stats` table.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct BotStats {
+    pub channels_created_total: u64,
+    pub channels_deleted_total: u64,
+    pub commands_executed_total: u64,
+    pub voice_events_processed_total: u64,
+    pub errors_total: u64,
+}
+
+impl BotStats {
+    pub async fn load(pool: &SqlitePool) -> Result<Self, BotError> {
+        let (created, deleted, commands, voice_events, errors): (i64, i64, i64, i64, i64) = sqlx::query_as(
+            "SELECT channels_created_total, channels_deleted_total, commands_executed_total,
+                    voice_events_processed_total, errors_total
+             FROM bot_stats WHERE id = 1",
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(Self {
+            channels_created_total: created as u64,
+            channels_deleted_total: deleted as u64,
+            commands_executed_total: commands as u64,
+            voice_events_processed_total: voice_events as u64,
+            errors_total: errors as u64,
+        })
+    }
+
+    /// Adds the given amounts to the database totals. A single `UPDATE`, so concurrent
+    /// additions can't overwrite each other.
+    pub async fn add(pool: &SqlitePool, amounts: &[(Stat, u64)]) -> Result<(), BotError> {
+        if amounts.is_empty() {
+            return Ok(());
+        }
+        // The columns come from `Stat`, never from user input
+        let columns: Vec<String> = amounts.iter().map(|(stat, _)| format!("{0} = {0} + ?", stat.column())).collect();
+        let query = format!("UPDATE bot_stats SET {} WHERE id = 1", columns.join(", "));
+        let mut query = sqlx::query(&query);
+        for (_, amount) in amounts {
+            query = query.bind(*amount as i64);
+        }
+        query.execute(pool).await?;
+        Ok(())
+    }
+
+    pub fn get(&self, stat: Stat) -> u64 {
+        match stat {
+            Stat::ChannelsCreated => self.channels_created_total,
+            Stat::ChannelsDeleted => self.channels_deleted_total,
+            Stat::CommandsExecuted => self.commands_executed_total,
+            Stat::VoiceEventsProcessed => self.voice_events_processed_total,
+            Stat::Errors => self.errors_total,
+        }
+    }
+}
+
+/// Counters since this process started.
+#[derive(Debug, Default)]
+pub struct SessionCounters {
+    counts: [AtomicU64; Stat::ALL.len()],
+    /// Counted but not in the database yet, written by `flush`.
+    unsaved: [AtomicU64; Stat::ALL.len()],
+}
+
+impl SessionCounters {
+    /// Counts `stat` for this session, the next `flush` adds it to the database. Only
+    /// touches atomics, so it's fine on every voice event.
+    pub fn record(&self, stat: Stat) {
+        self.counts[stat.index()].fetch_add(1, Ordering::Relaxed);
+        self.unsaved[stat.index()].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Adds everything counted since the last flush to the database totals in one
+    /// `UPDATE`. On failure the counts are kept for the next try.
+    pub async fn flush(&self, pool: &SqlitePool) {
+        let amounts: Vec<(Stat, u64)> = Stat::ALL
+            .iter()
+            .map(|stat| (*stat, self.unsaved[stat.index()].swap(0, Ordering::Relaxed)))
+            .filter(|(_, amount)| *amount > 0)
+            .collect();
+        if let Err(e) = BotStats::add(pool, &amounts).await {
+            warn!("Kon statistieken niet opslaan: {}", e);
+            for (stat, amount) in amounts {
+                self.unsaved[stat.index()].fetch_add(amount, Ordering::Relaxed);
+            }
+        }
+    }
+
+    pub fn get(&self, stat: Stat) -> u64 {
+        self.counts[stat.index()].load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::init;
+
+    #[tokio::test]
+    async fn flush_adds_to_persisted_totals() {
+        let pool = init("sqlite::memory:", 1).await.unwrap();
+        assert_eq!(BotStats::load(&pool).await.unwrap(), BotStats::default());
+
+        let counters = SessionCounters::default();
+        counters.record(Stat::ChannelsCreated);
+        counters.record(Stat::ChannelsCreated);
+        counters.record(Stat::Errors);
+        assert_eq!(BotStats::load(&pool).await.unwrap(), BotStats::default());
+
+        counters.flush(&pool).await;
+        counters.record(Stat::ChannelsCreated);
+        counters.flush(&pool).await;
+        counters.flush(&pool).await;
+
+        let stats = BotStats::load(&pool).await.unwrap();
+        assert_eq!(stats.get(Stat::ChannelsCreated), 3);
+        assert_eq!(stats.get(Stat::Errors), 1);
+        assert_eq!(stats.get(Stat::ChannelsDeleted), 0);
+        assert_eq!(counters.get(Stat::ChannelsCreated), 3);
+    }
+}
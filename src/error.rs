@@ -0,0 +1,51 @@
+use serenity::Error as SerenityError;
+use std::{fmt, io, path::PathBuf};
+
+/// Errors from Discord or from the bot's own infrastructure.
+#[derive(Debug)]
+pub enum BotError {
+    /// A Discord API call or gateway action failed.
+    Discord(SerenityError),
+    /// A Discord API call got no response within `HTTP_TIMEOUT`.
+    Timeout,
+    /// The circuit breaker is open, Discord API calls are paused.
+    CircuitOpen,
+    /// A database query failed or no connection was available in time.
+    Database(sqlx::Error),
+    /// The directory the database lives in can't be written to.
+    NotWritable(PathBuf, io::Error),
+}
+
+impl fmt::Display for BotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BotError::Discord(e) => write!(f, "Discord fout: {}", e),
+            BotError::Timeout => write!(f, "Discord API reageerde niet op tijd"),
+            BotError::CircuitOpen => write!(f, "Circuit breaker staat open, Discord API calls gepauzeerd"),
+            BotError::Database(sqlx::Error::PoolTimedOut) => {
+                write!(f, "Database fout: geen verbinding vrij binnen de timeout")
+            }
+            BotError::Database(e) => write!(f, "Database fout: {}", e),
+            BotError::NotWritable(dir, e) => write!(
+                f,
+                "Database map {} is niet schrijfbaar ({}), mount een schrijfbaar volume of zet DATABASE_PATH",
+                dir.display(),
+                e
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BotError {}
+
+impl From<SerenityError> for BotError {
+    fn from(e: SerenityError) -> Self {
+        BotError::Discord(e)
+    }
+}
+
+impl From<sqlx::Error> for BotError {
+    fn from(e: sqlx::Error) -> Self {
+        BotError::Database(e)
+    }
+}
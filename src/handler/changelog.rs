@@ -0,0 +1,135 @@
+use serenity::model::id::{ChannelId, UserId};
+use std::{
+    collections::VecDeque,
+    fmt,
+    time::{Duration, Instant},
+};
+
+/// Changes kept per channel for `/changelog`, the oldest ones are dropped first.
+pub const MAX_CHANGES: usize = 20;
+
+/// A change made to a temp channel with one of the owner commands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChangeAction {
+    Rename(String),
+    Lock,
+    Kick(UserId),
+    Status(Option<String>),
+    VideoQuality(&'static str),
+    Region(&'static str),
+    Boost(Duration),
+    MusicMode,
+    Visit(UserId),
+    Timeout(UserId, Duration),
+    ResetPermissions,
+    Archive,
+    GlobalMute(usize),
+    GlobalUnmute(usize),
+    Merged(ChannelId),
+    Pin,
+    Unpin,
+    Tags(Vec<String>),
+    Flag(&'static str, bool),
+    JoinMe(UserId),
+    OwnerEmoji(Option<String>),
+}
+
+impl fmt::Display for ChangeAction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ChangeAction::Rename(name) => write!(f, "renamed the channel to **{}**", name),
+            ChangeAction::Lock => write!(f, "locked the channel"),
+            ChangeAction::Kick(user_id) => write!(f, "kicked <@{}>", user_id),
+            ChangeAction::Status(Some(status)) => write!(f, "set the status to \"{}\"", status),
+            ChangeAction::Status(None) => write!(f, "cleared the status"),
+            ChangeAction::VideoQuality(label) => write!(f, "set the video quality to {}", label),
+            ChangeAction::Region(name) => write!(f, "set the region to {}", name),
+            ChangeAction::Boost(duration) => write!(f, "boosted the bitrate for {}s", duration.as_secs()),
+            ChangeAction::MusicMode => write!(f, "turned on music mode"),
+            ChangeAction::Visit(user_id) => write!(f, "let <@{}> visit", user_id),
+            ChangeAction::Timeout(user_id, duration) => {
+                write!(f, "timed out <@{}> for {}s", user_id, duration.as_secs())
+            }
+            ChangeAction::ResetPermissions => write!(f, "reset the permissions"),
+            ChangeAction::Archive => write!(f, "marked the chat for archiving"),
+            ChangeAction::GlobalMute(count) => write!(f, "muted {} member(s)", count),
+            ChangeAction::GlobalUnmute(count) => write!(f, "unmuted {} member(s)", count),
+            ChangeAction::Merged(channel_id) => write!(f, "merged <#{}> into this channel", channel_id),
+            ChangeAction::Pin => write!(f, "pinned a message"),
+            ChangeAction::Unpin => write!(f, "unpinned a message"),
+            ChangeAction::Tags(tags) => write!(f, "tagged the channel {}", tags.join(", ")),
+            ChangeAction::Flag(flag, true) => write!(f, "turned on {}", flag),
+            ChangeAction::Flag(flag, false) => write!(f, "turned off {}", flag),
+            ChangeAction::JoinMe(user_id) => write!(f, "pulled <@{}> into the channel", user_id),
+            ChangeAction::OwnerEmoji(Some(emoji)) => write!(f, "put {} in front of the name", emoji),
+            ChangeAction::OwnerEmoji(None) => write!(f, "removed the emoji from the name"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ChangeEntry {
+    pub actor_id: UserId,
+    pub action: ChangeAction,
+    pub timestamp: Instant,
+}
+
+impl ChangeEntry {
+    /// `/changelog` line, e.g. "<@1> locked the channel, 5 minutes ago".
+    pub fn line(&self, now: Instant) -> String {
+        format!(
+            "<@{}> {}, {}",
+            self.actor_id,
+            self.action,
+            relative_time(now.duration_since(self.timestamp))
+        )
+    }
+}
+
+/// Adds `entry` to `log`, dropping the oldest entry past `MAX_CHANGES`.
+pub fn push_change(log: &mut VecDeque<ChangeEntry>, entry: ChangeEntry) {
+    log.push_back(entry);
+    while log.len() > MAX_CHANGES {
+        log.pop_front();
+    }
+}
+
+/// "just now", "5 minutes ago", "2 hours ago".
+pub fn relative_time(elapsed: Duration) -> String {
+    let (amount, unit) = match elapsed.as_secs() {
+        0..=59 => return "just now".to_string(),
+        secs @ 60..=3599 => (secs / 60, "minute"),
+        secs @ 3600..=86_399 => (secs / 3600, "hour"),
+        secs => (secs / 86_400, "day"),
+    };
+    let plural = if amount == 1 { "" } else { "s" };
+    format!("{} {}{} ago", amount, unit, plural)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_newest_changes() {
+        let now = Instant::now();
+        let mut log = VecDeque::new();
+        for i in 0..MAX_CHANGES + 5 {
+            let action = ChangeAction::GlobalMute(i);
+            push_change(&mut log, ChangeEntry { actor_id: UserId(1), action, timestamp: now });
+        }
+
+        assert_eq!(log.len(), MAX_CHANGES);
+        assert_eq!(log.front().unwrap().action, ChangeAction::GlobalMute(5));
+        assert_eq!(log.back().unwrap().action, ChangeAction::GlobalMute(MAX_CHANGES + 4));
+    }
+
+    #[test]
+    fn relative_times() {
+        assert_eq!(relative_time(Duration::from_secs(30)), "just now");
+        assert_eq!(relative_time(Duration::from_secs(60)), "1 minute ago");
+        assert_eq!(relative_time(Duration::from_secs(5 * 60 + 59)), "5 minutes ago");
+        assert_eq!(relative_time(Duration::from_secs(2 * 3600)), "2 hours ago");
+        assert_eq!(relative_time(Duration::from_secs(86_400)), "1 day ago");
+    }
+}
@@ -0,0 +1,41 @@
+/// Embed descriptions can be 4096 characters, some room is kept for the "and N more" line.
+const MAX_DESCRIPTION_LEN: usize = 4000;
+
+/// The `/mirror` embed's list, one line per channel with only its name and member
+/// count, nothing that could be used to join it.
+pub fn mirror_description(channels: &[(String, usize)]) -> String {
+    if channels.is_empty() {
+        return "No active channels right now.".to_string();
+    }
+
+    let mut description = String::new();
+    for (i, (name, members)) in channels.iter().enumerate() {
+        let line = format!("🔊 **{}** — {} member(s)\n", name, members);
+        if description.len() + line.len() > MAX_DESCRIPTION_LEN {
+            description.push_str(&format!("and {} more", channels.len() - i));
+            break;
+        }
+        description.push_str(&line);
+    }
+    description.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_names_and_member_counts() {
+        assert_eq!(mirror_description(&[]), "No active channels right now.");
+        let channels = [("Games".to_string(), 3), ("Study".to_string(), 1)];
+        assert_eq!(
+            mirror_description(&channels),
+            "🔊 **Games** — 3 member(s)\n🔊 **Study** — 1 member(s)"
+        );
+
+        let many: Vec<(String, usize)> = (0..200).map(|i| (format!("Lobby {}", i), 2)).collect();
+        let description = mirror_description(&many);
+        assert!(description.len() <= 4096);
+        assert!(description.ends_with("more"));
+    }
+}
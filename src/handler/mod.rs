@@ -1,226 +1,3489 @@
 use serenity::{
     async_trait,
+    builder::CreateMessage,
+    http::Http,
     model::{
-        gateway::Ready,
+        application::interaction::Interaction,
+        event::VoiceServerUpdateEvent,
+        gateway::{ActivityType, Presence, Ready},
         voice::VoiceState,
-        id::{ChannelId, GuildId, UserId},
-        channel::{Channel, ChannelType, PermissionOverwrite},
-        guild::Member,
+        id::{ChannelId, ForumTagId, GuildId, ScheduledEventId, UserId},
+        channel::{
+            Channel, ChannelType, GuildChannel, Message, PartialGuildChannel, PermissionOverwrite, VideoQualityMode,
+        },
+        guild::{Guild, Member, ScheduledEvent, ScheduledEventStatus, ScheduledEventType},
+        user::User,
         permissions::Permissions,
         prelude::PermissionOverwriteType,
+        Timestamp,
     },
     prelude::*,
 };
-use tokio::{sync::RwLock, time::sleep};
-use tracing::{error, info, warn};
-use std::{collections::HashMap, sync::Arc, time::Duration};
+use dashmap::{mapref::entry::Entry, DashMap, DashSet};
+use futures::future::join_all;
+use sentry::{Hub, SentryFutureExt};
+use serde_json::json;
+use sqlx::SqlitePool;
+use tokio::{
+    sync::{oneshot, RwLock},
+    time::sleep,
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, info_span, warn, Instrument, Span};
+use uuid::Uuid;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::{
+    commands::{self, CommandRegistry},
+    config::{DevConfig, GuildConfig, MuteBatch},
+    db::{
+        self,
+        stats::{BotStats, SessionCounters, Stat},
+    },
+    error::BotError,
+    utils::{
+        check_category_capacity, check_permissions, BotPermissionChecker, create_channel, create_permission, delete_channel,
+        delete_permission, AuditReason, BotUptime, create_embed, CircuitBreaker, Cooldown, DmThrottle, filter_channel_name, http_status, majority_game,
+        archive_text_channel, is_member_gone, is_not_found, new_correlation_id, notification_sound, play_notification_sound,
+        send_audit_log, set_voice_status, shard_for_guild, spawn_in_span, is_late_night, tag_sentry_scope, voice_channel_problem,
+    },
+};
+
+mod changelog;
+mod mirror;
+mod name_history;
+mod queue;
+mod replay;
+mod session;
+mod tournament;
+mod voice_filter;
+
+pub use changelog::{ChangeAction, ChangeEntry};
+pub use name_history::NameChange;
+pub use session::{SessionStats, SessionSummary};
+pub use tournament::{match_name, pair_teams, Tournament, MAX_TEAMS};
+use changelog::push_change;
+use mirror::mirror_description;
+use name_history::push_name;
+use queue::{enqueue, is_full};
+use replay::StoredVoiceEvent;
+use session::{access_joined, access_left};
 
 #[derive(Debug)]
 pub struct ChannelInfo {
     owner_id: UserId,
-    delete_task: Option<tokio::task::JoinHandle<()>>,
+    /// Pending deletion of the empty channel, cancelled when someone joins.
+    delete_task: Option<CancellationToken>,
+    /// Stage channel, its Stage instance has to be ended before the channel is deleted.
+    is_stage: bool,
+    /// Members, peak and voice time for the end-of-session summary.
+    session: SessionStats,
+    /// Video quality picked with `/quality`.
+    video_quality: VideoQualityMode,
+    mode: ChannelMode,
+    /// Custom voice channel status set with `/status`, re-applied after a reconnect.
+    status: Option<String>,
+    /// Text channel created next to the voice channel, see `GuildConfig::paired_text_channel`.
+    text_channel_id: Option<ChannelId>,
+    /// Archive the text channel instead of deleting it when the session ends.
+    archive: bool,
+    /// One-time `/visit` grants and their expiry tasks. The task is aborted once the
+    /// visitor joins, the grant itself is revoked when they leave again.
+    active_visits: HashMap<UserId, tokio::task::JoinHandle<()>>,
+    /// Someone other than the owner joined at some point.
+    had_guest: bool,
+    /// Deletes the channel if no guest ever joins, aborted on the first guest.
+    ownership_expiry: Option<CancellationToken>,
+    /// `/channel_boost`: the bitrate from before the boost and the task that restores it.
+    active_boost: Option<(u32, tokio::task::JoinHandle<()>)>,
+    /// When the active boost ends.
+    boost_ends_at: Option<Instant>,
+    /// Owners of channels merged into this one with `/link_channels`.
+    co_owners: Vec<UserId>,
+    /// Members muted with `/global_mute`, the only ones `/global_unmute` unmutes.
+    global_muted: Vec<UserId>,
+    /// Still hidden from @everyone, see `GuildConfig::ghost_period_secs`.
+    in_ghost_period: bool,
+    /// Changes made with the owner commands this session, oldest first, for `/changelog`.
+    change_log: VecDeque<ChangeEntry>,
+    /// Member count at which the channel locks itself, set with `/auto_lock`.
+    auto_lock_threshold: Option<u32>,
+    /// Whether the auto lock fired for the current threshold, it only locks once.
+    auto_locked: bool,
+    /// Voice server host the bot got while connected to the channel, for `!info`.
+    voice_server: Option<String>,
+    /// Labels picked with `/tag` from `GuildConfig::allowed_tags`.
+    tags: Vec<String>,
+    /// Whether each member is self-muted and self-deafened and since when, for
+    /// `GuildConfig::afk_kick_threshold_secs`.
+    member_states: HashMap<UserId, (bool, Instant)>,
+    /// Emoji the owner wants in front of the name, see `/set_owner_emoji`.
+    owner_emoji: Option<String>,
+    /// The emoji the name starts with right now, so it can be swapped out.
+    name_emoji: Option<String>,
+    /// Pending rename for the owner emoji, replaced by every newer one.
+    rename_task: Option<tokio::task::JoinHandle<()>>,
+    /// Names the channel had, oldest first, for `/name_history`.
+    name_history: VecDeque<NameChange>,
+    /// In the tracing spans of everything that happens to the channel, so its whole
+    /// life can be found in the logs.
+    correlation_id: Uuid,
+    /// Set for a post in `GuildConfig::forum_creator_id` instead of a voice channel.
+    thread_id: Option<ChannelId>,
+    /// `/set_joinable_when_full`: people trying to get back in while it's full wait in `queue`.
+    queue_enabled: bool,
+    /// Who gets moved in when a spot opens, first in line first.
+    queue: VecDeque<UserId>,
+}
+
+impl ChannelInfo {
+    fn new(owner_id: UserId) -> Self {
+        Self {
+            owner_id,
+            delete_task: None,
+            is_stage: false,
+            session: SessionStats::new(Instant::now()),
+            video_quality: VideoQualityMode::Auto,
+            mode: ChannelMode::Standard,
+            status: None,
+            text_channel_id: None,
+            archive: false,
+            active_visits: HashMap::new(),
+            had_guest: false,
+            ownership_expiry: None,
+            active_boost: None,
+            boost_ends_at: None,
+            co_owners: Vec::new(),
+            global_muted: Vec::new(),
+            in_ghost_period: false,
+            change_log: VecDeque::new(),
+            auto_lock_threshold: None,
+            auto_locked: false,
+            voice_server: None,
+            tags: Vec::new(),
+            member_states: HashMap::new(),
+            owner_emoji: None,
+            name_emoji: None,
+            rename_task: None,
+            name_history: VecDeque::new(),
+            correlation_id: new_correlation_id(),
+            thread_id: None,
+            queue_enabled: false,
+            queue: VecDeque::new(),
+        }
+    }
+
+    /// Notes whether `user_id` looks AFK, keeping when that last changed.
+    fn note_afk(&mut self, user_id: UserId, afk: bool, now: Instant) {
+        let state = self.member_states.entry(user_id).or_insert((afk, now));
+        if state.0 != afk {
+            *state = (afk, now);
+        }
+    }
+
+    /// Members that have looked AFK for at least `threshold`.
+    fn afk_for(&self, threshold: Duration, now: Instant) -> Vec<UserId> {
+        self.member_states
+            .iter()
+            .filter(|(_, (afk, since))| *afk && now.duration_since(*since) >= threshold)
+            .map(|(user_id, _)| *user_id)
+            .collect()
+    }
+
+    /// Stops everything still scheduled for the channel, for when it stops being tracked.
+    fn cancel_tasks(&self) {
+        for task in self.delete_task.iter().chain(&self.ownership_expiry) {
+            task.cancel();
+        }
+        self.active_visits.values().for_each(|expiry| expiry.abort());
+        if let Some((_, task)) = &self.active_boost {
+            task.abort();
+        }
+        if let Some(task) = &self.rename_task {
+            task.abort();
+        }
+    }
+}
+
+/// What a `reconcile_state` run found and fixed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Reconciliation {
+    /// Channels that no longer exist and were dropped from `temp_channels`.
+    pub removed: usize,
+    /// Channels whose owner overwrite was put back.
+    pub repaired: usize,
+    /// Channels whose members differ from the voice states, only logged.
+    pub member_mismatches: usize,
+}
+
+/// A temp channel made by `create_temp_channel`, not tracked yet.
+#[derive(Debug)]
+struct CreatedChannel {
+    id: ChannelId,
+    name: String,
+    /// Differs from the creator channel's category when the overflow category was used.
+    parent_id: Option<ChannelId>,
+    /// Paired text channel, if the guild wants one and creating it worked.
+    text_channel_id: Option<ChannelId>,
+    is_stage: bool,
+}
+
+/// Why a temp channel is scheduled for deletion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Deletion {
+    /// Everyone left.
+    Empty,
+    /// Nobody but the owner ever joined and the owner isn't in it anymore either.
+    Unclaimed,
+    /// The owner left the server and nobody was left to take over.
+    OwnerGone,
+    /// The scheduled event the channel was created for is over.
+    EventEnded,
 }
 
+/// What a temp channel is being used for, set by commands like `/music`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ChannelMode {
+    #[default]
+    Standard,
+    Music,
+    // Gaming and Study have no command that sets them yet
+    #[allow(dead_code)]
+    Gaming,
+    #[allow(dead_code)]
+    Study,
+}
+
+/// Summaries kept per guild for `/summary`.
+const RECENT_SUMMARIES: usize = 10;
+
+/// How long a creator channel join has to settle before a channel is created.
+const CREATION_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// How long a channel may go without guests, see `GuildConfig::ownership_expiry_secs`.
+pub const DEFAULT_OWNERSHIP_EXPIRY_SECS: u64 = 300;
+
+/// How long a `/visit` grant stays valid if the visitor never joins.
+const VISIT_EXPIRY: Duration = Duration::from_secs(5 * 60);
+
+/// Longest a `/channel_boost` may run, extensions included.
+pub const MAX_BOOST: Duration = Duration::from_secs(600);
+
+/// How often the countdown in the channel status of a boosted channel is updated.
+const BOOST_STATUS_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Bitrate Discord gives a voice channel when none is set.
+const DEFAULT_BITRATE: u32 = 64_000;
+
+/// Stage instance only visible to members of the guild.
+const STAGE_PRIVACY_GUILD_ONLY: u8 = 2;
+
+/// How long an empty temp channel is kept before it's deleted, outside late-night mode.
+const EMPTY_DELETION_DELAY: Duration = Duration::from_secs(5);
+
+/// How often `reconcile_state` runs in the background.
+const RECONCILE_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// How long channel positions are left alone after a deletion before the gaps are
+/// closed, so deleting several channels at once costs one reorder.
+const REORDER_DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// How long an owner emoji rename waits for another change. Discord allows two renames
+/// per channel every ten minutes, so a quick change of mind costs only one.
+const RENAME_DEBOUNCE: Duration = Duration::from_secs(5);
+
+/// How often members are checked against `GuildConfig::afk_kick_threshold_secs`.
+const AFK_CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// How often `/mirror` embeds are checked for changes, slow enough to stay clear of
+/// the message edit rate limit.
+const MIRROR_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How often the `/stats` counters are written to the database, whatever is counted
+/// after the last flush is lost on a crash.
+const STATS_FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Inactivity after which Discord archives a forum session post, in minutes.
+const FORUM_AUTO_ARCHIVE_MINUTES: u16 = 60;
+
+/// Tags Discord allows on a single forum post.
+const MAX_FORUM_TAGS: usize = 5;
+
+type TimeoutMap = HashMap<(ChannelId, UserId), tokio::task::JoinHandle<()>>;
+
+/// Temp channel of a running scheduled event.
+#[derive(Debug, Clone, Copy)]
+struct EventChannel {
+    /// The voice channel the event takes place in, members joining it are moved on.
+    venue: Option<ChannelId>,
+    /// `None` while the channel is being created.
+    channel_id: Option<ChannelId>,
+}
+
+/// A guild's debounced position cleanup, see `GuildConfig::reorder_on_delete`.
+#[derive(Default)]
+struct PendingReorder {
+    reorder_task: Option<tokio::task::JoinHandle<()>>,
+    /// Categories that lost a channel since the last cleanup.
+    categories: HashSet<ChannelId>,
+}
+
+/// Cloning is cheap and every clone shares the same state, so one set of temp channels
+/// is tracked no matter which shard an event comes in on.
+#[derive(Clone)]
 pub struct Handler {
-    temp_channels: Arc<RwLock<HashMap<ChannelId, ChannelInfo>>>,
-    creator_channel_id: ChannelId,
-    waiting_room_id: ChannelId,
+    temp_channels: Arc<DashMap<ChannelId, ChannelInfo>>,
+    guild_configs: Arc<RwLock<HashMap<GuildId, GuildConfig>>>,
+    /// `GuildConfig::creator_channel_id` of each guild that set one, so voice events
+    /// don't have to read `guild_configs`.
+    creator_channels: Arc<DashMap<GuildId, ChannelId>>,
+    timeouts: Arc<RwLock<TimeoutMap>>,
+    pending_creations: Arc<RwLock<HashMap<UserId, tokio::task::JoinHandle<()>>>>,
+    recent_summaries: Arc<RwLock<HashMap<GuildId, VecDeque<SessionSummary>>>>,
+    circuit_breaker: Arc<CircuitBreaker>,
+    dm_throttle: Arc<DmThrottle>,
+    uptime: Arc<BotUptime>,
+    cooldowns: Arc<Cooldown>,
+    /// Guilds where temp channels are off because the bot lacks permissions.
+    permission_checker: Arc<BotPermissionChecker>,
+    /// Users whose voice activity is reported to the log channel, see `/watch`.
+    watched_users: Arc<DashMap<GuildId, HashSet<UserId>>>,
+    /// Users whose channels aren't listed in announce channels, see `/auto_announce`.
+    announce_opt_outs: Arc<RwLock<HashSet<UserId>>>,
+    /// Counters for `/stats` since startup, the all-time ones are in the database.
+    stats: Arc<SessionCounters>,
+    /// The running `/tournament` of each guild.
+    tournaments: Arc<RwLock<HashMap<GuildId, Tournament>>>,
+    /// Set once the background tasks run, `ready` fires per shard.
+    background_started: Arc<AtomicBool>,
+    /// Last `/mirror` list sent per source guild, unchanged lists aren't edited again.
+    mirrored: Arc<RwLock<HashMap<GuildId, String>>>,
+    reorders: Arc<RwLock<HashMap<GuildId, PendingReorder>>>,
+    event_channels: Arc<DashMap<ScheduledEventId, EventChannel>>,
+    /// Server mutes from `/global_mute` and the channel each is for. The mute counts in
+    /// the whole guild, so it's lifted as soon as the member is in voice anywhere else.
+    server_mutes: Arc<DashMap<(GuildId, UserId), ChannelId>>,
+    /// Timeouts that ran out while the member wasn't in voice, Discord only lifts them
+    /// once they're back.
+    expired_timeouts: Arc<DashSet<(GuildId, UserId)>>,
+    /// Shared so webhook and voice status calls reuse one connection pool.
+    http_client: reqwest::Client,
+    commands: Arc<CommandRegistry>,
+    /// Guild configs are written through to here, see `update_guild_config`.
+    db: SqlitePool,
+    /// Prefix for text commands, `None` when they're turned off.
+    command_prefix: Option<String>,
+    mute_batch: MuteBatch,
+    dev: DevConfig,
+    /// Fallbacks from `Config` for guilds that haven't run `/setup`.
+    creator_channel_id: Option<ChannelId>,
+    waiting_room_id: Option<ChannelId>,
 }
 
 impl Handler {
-    pub fn new(creator_channel_id: ChannelId, waiting_room_id: ChannelId) -> Self {
+    pub fn new(
+        creator_channel_id: Option<ChannelId>,
+        waiting_room_id: Option<ChannelId>,
+        command_prefix: Option<String>,
+        http_timeout: Duration,
+        mute_batch: MuteBatch,
+        dev: DevConfig,
+        db: SqlitePool,
+    ) -> Self {
         Self {
-            temp_channels: Arc::new(RwLock::new(HashMap::new())),
+            temp_channels: Arc::new(DashMap::new()),
+            guild_configs: Arc::new(RwLock::new(HashMap::new())),
+            creator_channels: Arc::new(DashMap::new()),
+            timeouts: Arc::new(RwLock::new(HashMap::new())),
+            pending_creations: Arc::new(RwLock::new(HashMap::new())),
+            recent_summaries: Arc::new(RwLock::new(HashMap::new())),
+            circuit_breaker: Arc::new(CircuitBreaker::with_timeout(http_timeout)),
+            dm_throttle: Arc::new(DmThrottle::default()),
+            uptime: Arc::new(BotUptime::default()),
+            cooldowns: Arc::new(Cooldown::new(commands::COOLDOWNS)),
+            permission_checker: Arc::new(BotPermissionChecker::default()),
+            watched_users: Arc::new(DashMap::new()),
+            announce_opt_outs: Arc::new(RwLock::new(HashSet::new())),
+            stats: Arc::new(SessionCounters::default()),
+            tournaments: Arc::new(RwLock::new(HashMap::new())),
+            background_started: Arc::new(AtomicBool::new(false)),
+            mirrored: Arc::new(RwLock::new(HashMap::new())),
+            reorders: Arc::new(RwLock::new(HashMap::new())),
+            event_channels: Arc::new(DashMap::new()),
+            server_mutes: Arc::new(DashMap::new()),
+            expired_timeouts: Arc::new(DashSet::new()),
+            http_client: reqwest::Client::new(),
+            commands: Arc::new(CommandRegistry::new()),
+            db,
+            command_prefix,
+            mute_batch,
+            dev,
             creator_channel_id,
             waiting_room_id,
         }
     }
 
-    async fn user_has_channel(&self, user_id: UserId) -> bool {
-        let temp_channels = self.temp_channels.read().await;
-        temp_channels.values().any(|info| info.owner_id == user_id)
+    /// Loads the stored guild configs, call once before connecting.
+    pub async fn load_guild_configs(&self) -> Result<usize, BotError> {
+        let configs = db::load_guild_configs(&self.db).await?;
+        let count = configs.len();
+        for (guild_id, config) in &configs {
+            self.sync_creator_channel(*guild_id, config);
+        }
+        *self.guild_configs.write().await = configs;
+        Ok(count)
     }
 
-    async fn get_user_channel(&self, user_id: UserId) -> Option<ChannelId> {
-        let temp_channels = self.temp_channels.read().await;
-        temp_channels
-            .iter()
-            .find(|(_, info)| info.owner_id == user_id)
-            .map(|(channel_id, _)| *channel_id)
+    /// Loads the stored `/watch` lists, call once before connecting.
+    pub async fn load_watched_users(&self) -> Result<usize, BotError> {
+        let watched = db::load_watched_users(&self.db).await?;
+        let count = watched.values().map(HashSet::len).sum();
+        self.watched_users.clear();
+        for (guild_id, users) in watched {
+            self.watched_users.insert(guild_id, users);
+        }
+        Ok(count)
     }
 
-    async fn handle_creator_channel_join(
+    /// Loads who opted out with `/auto_announce`, call once before connecting.
+    pub async fn load_announce_opt_outs(&self) -> Result<usize, BotError> {
+        let opt_outs = db::load_announce_opt_outs(&self.db).await?;
+        let count = opt_outs.len();
+        *self.announce_opt_outs.write().await = opt_outs;
+        Ok(count)
+    }
+
+    pub(crate) async fn set_announce_opt_out(&self, user_id: UserId, opt_out: bool) -> Result<(), BotError> {
+        db::save_announce_opt_out(&self.db, user_id, opt_out).await?;
+        let mut opt_outs = self.announce_opt_outs.write().await;
+        if opt_out {
+            opt_outs.insert(user_id);
+        } else {
+            opt_outs.remove(&user_id);
+        }
+        Ok(())
+    }
+
+    pub(crate) async fn announce_opted_out(&self, user_id: UserId) -> bool {
+        self.announce_opt_outs.read().await.contains(&user_id)
+    }
+
+    /// Adds `user_id` to or removes them from the `/watch` list of `guild_id`. Returns
+    /// how many users the guild watches now.
+    pub(crate) async fn set_watched(&self, guild_id: GuildId, user_id: UserId, watched: bool) -> usize {
+        let count = {
+            let mut users = self.watched_users.entry(guild_id).or_default();
+            if watched {
+                users.insert(user_id);
+            } else {
+                users.remove(&user_id);
+            }
+            users.len()
+        };
+        // An empty list would keep voice events off the fast path
+        self.watched_users.remove_if(&guild_id, |_, users| users.is_empty());
+        if let Err(e) = db::save_watched_user(&self.db, guild_id, user_id, watched).await {
+            error!("Kon watch lijst van guild {} niet opslaan: {}", guild_id, e);
+        }
+        count
+    }
+
+    /// Reports a channel move of a `/watch`ed user to the log channel.
+    async fn report_watched_user(
         &self,
         ctx: &Context,
         guild_id: GuildId,
-        member: &Member,
-        parent_id: Option<ChannelId>,
-    ) -> Result<(), SerenityError> {
-        // First, remove existing channel if it exists
-        if self.user_has_channel(member.user.id).await {
-            if let Some(existing_channel) = self.get_user_channel(member.user.id).await {
-                // Delete the existing channel
-                if let Err(e) = existing_channel.delete(&ctx.http).await {
-                    error!("Error deleting existing channel: {:?}", e);
-                } else {
-                    info!("Successfully deleted existing channel");
-                    // Remove from tracking
-                    let mut temp_channels = self.temp_channels.write().await;
-                    temp_channels.remove(&existing_channel);
+        user_id: UserId,
+        left: Option<ChannelId>,
+        joined: Option<ChannelId>,
+    ) {
+        let watched = self
+            .watched_users
+            .get(&guild_id)
+            .is_some_and(|users| users.contains(&user_id));
+        if !watched {
+            return;
+        }
+
+        let moved = match (left, joined) {
+            (Some(left), Some(joined)) => format!("moved from <#{}> to <#{}>", left, joined),
+            (None, Some(joined)) => format!("joined <#{}>", joined),
+            (Some(left), None) => format!("left <#{}>", left),
+            (None, None) => return,
+        };
+        let owned: Vec<String> = self
+            .temp_channels
+            .iter()
+            .filter(|entry| entry.owner_id == user_id || entry.co_owners.contains(&user_id))
+            // Channels in other servers are none of this server's business
+            .filter(|entry| ctx.cache.guild_channel_field(*entry.key(), |c| c.guild_id) == Some(guild_id))
+            .map(|entry| format!("<#{}>", entry.key()))
+            .collect();
+        let cooldowns: Vec<String> = self
+            .cooldowns
+            .remaining(user_id)
+            .into_iter()
+            .map(|(name, left)| format!("{} {}s", commands::cooldown_label(name, self.command_prefix()), left.as_secs().max(1)))
+            .collect();
+
+        let or_none = |items: Vec<String>| {
+            if items.is_empty() {
+                "none".to_string()
+            } else {
+                items.join(", ")
+            }
+        };
+        let message = format!(
+            "👁 Watched user <@{}> {}\nOwns: {}\nCooldowns: {}",
+            user_id,
+            moved,
+            or_none(owned),
+            or_none(cooldowns)
+        );
+        self.audit_log(ctx, guild_id, &message).await;
+    }
+
+    pub(crate) async fn tournament(&self, guild_id: GuildId) -> Option<Tournament> {
+        self.tournaments.read().await.get(&guild_id).cloned()
+    }
+
+    /// Stores the state of the guild's tournament, `None` once it's over.
+    pub(crate) async fn set_tournament(&self, guild_id: GuildId, tournament: Option<Tournament>) {
+        let mut tournaments = self.tournaments.write().await;
+        match tournament {
+            Some(tournament) => tournaments.insert(guild_id, tournament),
+            None => tournaments.remove(&guild_id),
+        };
+    }
+
+    pub(crate) async fn guild_config(&self, guild_id: GuildId) -> GuildConfig {
+        let guild_configs = self.guild_configs.read().await;
+        guild_configs.get(&guild_id).cloned().unwrap_or_default()
+    }
+
+    /// Whether test mode is on in the guild and the event of `user_id` has to be ignored.
+    async fn skipped_by_test_mode(&self, guild_id: GuildId, user_id: UserId) -> bool {
+        let test_user = self.guild_configs.read().await.get(&guild_id).and_then(|c| c.test_mode_user_id);
+        match test_user {
+            Some(test_user) if test_user != user_id => {
+                warn!("Test mode actief in {}, event van {} overgeslagen", guild_id, user_id);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Changes the config of `guild_id` and saves it. The save happens under the lock,
+    /// so the database always ends up with the last change.
+    pub(crate) async fn update_guild_config<F, R>(&self, guild_id: GuildId, f: F) -> R
+    where
+        F: FnOnce(&mut GuildConfig) -> R,
+    {
+        let mut guild_configs = self.guild_configs.write().await;
+        let config = guild_configs.entry(guild_id).or_default();
+        let result = f(config);
+        self.sync_creator_channel(guild_id, config);
+        if let Err(e) = db::save_guild_config(&self.db, guild_id, config).await {
+            error!("Kon config van guild {} niet opslaan: {}", guild_id, e);
+        }
+        result
+    }
+
+    fn sync_creator_channel(&self, guild_id: GuildId, config: &GuildConfig) {
+        match config.creator_channel_id {
+            Some(channel_id) => self.creator_channels.insert(guild_id, channel_id),
+            None => self.creator_channels.remove(&guild_id).map(|(_, id)| id),
+        };
+    }
+
+    pub(crate) fn cooldowns(&self) -> &Cooldown {
+        &self.cooldowns
+    }
+
+    pub(crate) fn http_client(&self) -> &reqwest::Client {
+        &self.http_client
+    }
+
+    pub(crate) fn is_bot_owner(&self, user_id: UserId) -> bool {
+        self.dev.bot_owner_id == Some(user_id)
+    }
+
+    /// Every tracked temp channel as JSON for `/echo`, sorted by channel ID. Tasks only
+    /// show whether they're pending.
+    pub(crate) fn state_dump(&self, ctx: &Context) -> serde_json::Value {
+        let mut channels: Vec<(ChannelId, serde_json::Value)> = self
+            .temp_channels
+            .iter()
+            .map(|entry| {
+                let (channel_id, info) = (*entry.key(), entry.value());
+                let cached = channel_id.to_channel_cached(&ctx.cache).and_then(|c| c.guild());
+                let locked = cached.as_ref().map(|gc| {
+                    gc.permission_overwrites.iter().any(|o| {
+                        o.kind == PermissionOverwriteType::Role(gc.guild_id.0.into())
+                            && o.deny.contains(Permissions::CONNECT)
+                    })
+                });
+                let task_status = |task: Option<&CancellationToken>| match task {
+                    Some(token) if !token.is_cancelled() => "running",
+                    _ => "none",
+                };
+                let value = serde_json::json!({
+                    "channel_id": channel_id.0.to_string(),
+                    "guild_id": cached.as_ref().map(|gc| gc.guild_id.0.to_string()),
+                    "owner_id": info.owner_id.0.to_string(),
+                    "co_owners": info.co_owners.iter().map(|id| id.0.to_string()).collect::<Vec<_>>(),
+                    "locked": locked,
+                    "ghosted": info.in_ghost_period,
+                    "members": info.session.present_count(),
+                    "deletion_task": task_status(info.delete_task.as_ref()),
+                    "ownership_expiry": task_status(info.ownership_expiry.as_ref()),
+                    "is_stage": info.is_stage,
+                    "text_channel_id": info.text_channel_id.map(|id| id.0.to_string()),
+                    "mode": format!("{:?}", info.mode),
+                    "auto_lock_threshold": info.auto_lock_threshold,
+                    "tags": info.tags,
+                    "correlation_id": info.correlation_id.to_string(),
+                });
+                (channel_id, value)
+            })
+            .collect();
+        channels.sort_by_key(|(channel_id, _)| *channel_id);
+        serde_json::Value::Array(channels.into_iter().map(|(_, value)| value).collect())
+    }
+
+    pub(crate) fn command_prefix(&self) -> Option<&str> {
+        self.command_prefix.as_deref()
+    }
+
+    /// What the bot is keeping track of right now, for `!debug_state`.
+    #[cfg(feature = "framework")]
+    pub(crate) async fn debug_state(&self) -> String {
+        let watched: usize = self.watched_users.iter().map(|users| users.len()).sum();
+        [
+            format!("Temp channels: {}", self.temp_channels.len()),
+            format!("Pending creations: {}", self.pending_creations.read().await.len()),
+            format!("Timeouts: {}", self.timeouts.read().await.len()),
+            format!("Guild configs: {}", self.guild_configs.read().await.len()),
+            format!("Watched users: {}", watched),
+            format!("Tournaments: {}", self.tournaments.read().await.len()),
+            format!("Uptime: {}", crate::utils::format_duration(self.uptime())),
+        ]
+        .join("\n")
+    }
+
+    /// Counts `stat` for `/stats`, both for this session and all-time.
+    pub(crate) fn count(&self, stat: Stat) {
+        self.stats.record(stat);
+    }
+
+    /// How long the bot has been connected to Discord.
+    pub(crate) fn uptime(&self) -> Duration {
+        self.uptime.elapsed()
+    }
+
+    pub(crate) fn session_stats(&self) -> &SessionCounters {
+        &self.stats
+    }
+
+    pub(crate) async fn all_time_stats(&self) -> Result<BotStats, BotError> {
+        self.stats.flush(&self.db).await;
+        BotStats::load(&self.db).await
+    }
+
+    /// Stores when `channel_id` was created for `/peak_hours`, in the background.
+    fn record_creation(&self, guild_id: GuildId, channel_id: ChannelId) {
+        let pool = self.db.clone();
+        let now = Timestamp::now().unix_timestamp();
+        tokio::spawn(async move {
+            if let Err(e) = db::record_channel_event(&pool, guild_id, channel_id, db::CHANNEL_CREATED, now).await {
+                warn!("Kon aanmaak van kanaal {} niet opslaan: {}", channel_id, e);
+            }
+        });
+    }
+
+    /// Stores a voice event for `/event_replay` in the background, only with `ALLOW_EVENT_REPLAY`.
+    fn record_voice_event(&self, old: &Option<VoiceState>, new: &VoiceState) {
+        let Some(guild_id) = new.guild_id else { return };
+        let channel_id = new.channel_id.or_else(|| old.as_ref().and_then(|o| o.channel_id)).unwrap_or_default();
+        let payload = match serde_json::to_string(&StoredVoiceEvent { old: old.clone(), new: new.clone() }) {
+            Ok(payload) => payload,
+            Err(e) => {
+                warn!("Kon voice event niet serialiseren: {}", e);
+                return;
+            }
+        };
+        let pool = self.db.clone();
+        let now = Timestamp::now().unix_timestamp();
+        tokio::spawn(async move {
+            match db::record_voice_state_event(&pool, guild_id, channel_id, &payload, now).await {
+                Ok(id) => debug!("Voice event opgeslagen als {}", id),
+                Err(e) => warn!("Kon voice event niet opslaan: {}", e),
+            }
+        });
+    }
+
+    pub(crate) fn event_replay_allowed(&self) -> bool {
+        self.dev.allow_event_replay
+    }
+
+    /// Feeds the voice event stored as `id` through the handler again, with every log
+    /// line under a `[REPLAY]` span. Returns `false` if there's no such event.
+    pub(crate) async fn replay_voice_event(&self, ctx: &Context, id: i64) -> Result<bool, BotError> {
+        let Some(payload) = db::load_voice_state_event(&self.db, id).await? else {
+            return Ok(false);
+        };
+        let event: StoredVoiceEvent = serde_json::from_str(&payload).map_err(SerenityError::from)?;
+        let span = info_span!(
+            "[REPLAY]",
+            event_id = id,
+            guild = ?event.new.guild_id,
+            user = %event.new.user_id,
+            correlation_id = tracing::field::Empty
+        );
+        async {
+            info!("Voice event {} wordt opnieuw verwerkt", id);
+            self.handle_voice_state_update(ctx.clone(), event.old, event.new).await;
+            info!("Voice event {} opnieuw verwerkt", id);
+        }
+        .instrument(span)
+        .await;
+        Ok(true)
+    }
+
+    /// When temp channels in `guild_id` were created since `since`, as unix timestamps.
+    pub(crate) async fn channel_creations(&self, guild_id: GuildId, since: i64) -> Result<Vec<i64>, BotError> {
+        db::channel_events_since(&self.db, guild_id, db::CHANNEL_CREATED, since).await
+    }
+
+    /// Every DM the bot sends goes through here so it respects the [`DmThrottle`].
+    pub(crate) async fn send_dm(&self, ctx: &Context, user_id: UserId, content: impl std::fmt::Display) {
+        if let Err(e) = self.dm_throttle.send(&ctx.http, user_id, content).await {
+            warn!("Kon DM naar {} niet versturen: {:?}", user_id, e);
+        }
+    }
+
+    /// Returns whether the DM arrived, `false` if it was throttled or failed.
+    pub(crate) async fn send_dm_message<'a, F>(&self, ctx: &Context, user_id: UserId, f: F) -> bool
+    where
+        for<'b> F: FnOnce(&'b mut CreateMessage<'a>) -> &'b mut CreateMessage<'a>,
+    {
+        match self.dm_throttle.send_message(&ctx.http, user_id, f).await {
+            Ok(sent) => sent,
+            Err(e) => {
+                warn!("Kon DM naar {} niet versturen: {:?}", user_id, e);
+                false
+            }
+        }
+    }
+
+    /// Turns temp channels off in `guild_id` while the bot lacks any of
+    /// `required_permissions()`, telling the server owner when that happens. Returns
+    /// whether the guild can be used.
+    async fn check_bot_permissions(&self, ctx: &Context, guild_id: GuildId) -> bool {
+        let missing = match check_permissions(ctx, guild_id).await {
+            Some(missing) => missing,
+            None => return false,
+        };
+
+        if let Some(missing) = self.permission_checker.update(guild_id, missing) {
+            let names = missing.get_permission_names().join(", ");
+            error!("Bot mist permissies in guild {}: {}, tijdelijke kanalen uit", guild_id, names);
+
+            if let Some(guild) = guild_id.to_guild_cached(&ctx.cache) {
+                let message = format!(
+                    "I'm missing permissions in **{}**: {}. Temporary channels are turned off \
+                     there until they're granted.",
+                    guild.name, names
+                );
+                self.send_dm(ctx, guild.owner_id, message).await;
+            }
+        }
+        !self.permission_checker.is_disabled(guild_id)
+    }
+
+    pub(crate) async fn audit_log(&self, ctx: &Context, guild_id: GuildId, message: &str) {
+        Self::send_audit_log(ctx, &self.http_client, &self.db, &self.guild_configs, guild_id, message)
+            .await;
+    }
+
+    /// Sends an audit log entry and drops the webhook URL if Discord says it's gone.
+    async fn send_audit_log(
+        ctx: &Context,
+        client: &reqwest::Client,
+        db: &SqlitePool,
+        guild_configs: &RwLock<HashMap<GuildId, GuildConfig>>,
+        guild_id: GuildId,
+        message: &str,
+    ) {
+        let config = guild_configs.read().await.get(&guild_id).cloned().unwrap_or_default();
+
+        match send_audit_log(ctx, client, &config, message).await {
+            Ok(_) => {}
+            Err(e) if config.log_webhook_url.is_some() && is_not_found(&e) => {
+                error!("Log webhook van guild {} bestaat niet meer, URL verwijderd", guild_id);
+                let mut guild_configs = guild_configs.write().await;
+                if let Some(config) = guild_configs.get_mut(&guild_id) {
+                    config.log_webhook_url = None;
+                    if let Err(e) = db::save_guild_config(db, guild_id, config).await {
+                        error!("Kon config van guild {} niet opslaan: {}", guild_id, e);
+                    }
                 }
             }
+            Err(e) => warn!("Kon audit log niet versturen: {:?}", e),
         }
+    }
 
-        // Create a new channel
-        match self.create_temp_channel(ctx, guild_id, member, parent_id).await {
-            Ok(Channel::Guild(guild_channel)) => {
-                {
-                    let mut temp_channels = self.temp_channels.write().await;
-                    temp_channels.insert(
-                        guild_channel.id,
-                        ChannelInfo {
-                            owner_id: member.user.id,
-                            delete_task: None,
-                        },
-                    );
+    pub(crate) fn creator_channel(&self, guild_id: GuildId) -> Option<ChannelId> {
+        self.creator_channels
+            .get(&guild_id)
+            .map(|id| *id)
+            .or(self.creator_channel_id)
+    }
+
+    pub(crate) async fn waiting_room(&self, guild_id: GuildId) -> Option<ChannelId> {
+        self.guild_config(guild_id)
+            .await
+            .waiting_room_id
+            .or(self.waiting_room_id)
+    }
+
+    /// Tracked channels `user_id` owns, co-ownership doesn't count.
+    fn owned_channel_count(&self, user_id: UserId) -> usize {
+        self.temp_channels
+            .iter()
+            .filter(|entry| entry.owner_id == user_id && entry.thread_id.is_none())
+            .count()
+    }
+
+    /// Stops tracking the channel owned by `user_id` and hands it back. The lookup and
+    /// removal are one step, so two joins racing for the same channel can't both get it.
+    fn take_user_channel(&self, user_id: UserId) -> Option<(ChannelId, ChannelInfo)> {
+        loop {
+            let channel_id = self
+                .temp_channels
+                .iter()
+                .find(|entry| entry.owner_id == user_id && entry.thread_id.is_none())
+                .map(|entry| *entry.key())?;
+
+            // Somebody else removed it between the scan and here, look again
+            if let Some(taken) = self
+                .temp_channels
+                .remove_if(&channel_id, |_, info| info.owner_id == user_id && info.thread_id.is_none())
+            {
+                return Some(taken);
+            }
+        }
+    }
+
+    fn track_channel(&self, channel_id: ChannelId, owner_id: UserId) {
+        self.temp_channels.insert(channel_id, ChannelInfo::new(owner_id));
+    }
+
+    fn correlation_id(&self, channel_id: ChannelId) -> Option<Uuid> {
+        self.temp_channels.get(&channel_id).map(|info| info.correlation_id)
+    }
+
+    /// The channel `user_id` owns, or else one they co-own after a `/link_channels`.
+    /// With a quota above one, the channel they're in comes first, then the newest.
+    /// Forum posts aren't voice channels, commands can't use them.
+    pub(crate) async fn get_user_channel(&self, user_id: UserId) -> Option<ChannelId> {
+        self.temp_channels
+            .iter()
+            .filter(|entry| entry.thread_id.is_none())
+            .filter(|entry| entry.owner_id == user_id || entry.co_owners.contains(&user_id))
+            .max_by_key(|entry| (entry.session.is_present(user_id), entry.owner_id == user_id, *entry.key()))
+            .map(|entry| *entry.key())
+    }
+
+    pub(crate) async fn set_channel_status(&self, channel_id: ChannelId, status: Option<String>) {
+        if let Some(mut info) = self.temp_channels.get_mut(&channel_id) {
+            info.status = status;
+        }
+    }
+
+    /// Checks that the configured creator channels and waiting rooms exist and are voice
+    /// channels, logging every problem. Returns `true` if everything is valid.
+    pub async fn validate_channels(&self, http: &Http, guild_ids: &[GuildId]) -> Result<bool, SerenityError> {
+        let mut valid = true;
+
+        for guild_id in guild_ids {
+            let config = self.guild_config(*guild_id).await;
+            let configured = [
+                ("creator_channel_id", config.creator_channel_id),
+                ("waiting_room_id", config.waiting_room_id),
+            ];
+            if configured.iter().all(|(_, id)| id.is_none()) {
+                continue;
+            }
+
+            let channels = guild_id.channels(http).await?;
+            for (field, channel_id) in configured {
+                let Some(channel_id) = channel_id else { continue };
+                if let Some(problem) = voice_channel_problem(channels.get(&channel_id).map(|c| c.kind)) {
+                    error!("{} {} van guild {} {}", field, channel_id, guild_id, problem);
+                    valid = false;
+                }
+            }
+        }
+
+        // The fallbacks from the environment belong to whichever guild has that channel
+        let fallbacks = [
+            ("CREATOR_CHANNEL_ID", self.creator_channel_id),
+            ("WAITING_ROOM_ID", self.waiting_room_id),
+        ];
+        for (field, channel_id) in fallbacks {
+            let Some(channel_id) = channel_id else { continue };
+            let kind = match http.get_channel(channel_id.0).await {
+                Ok(Channel::Guild(gc)) if guild_ids.contains(&gc.guild_id) => Some(gc.kind),
+                Ok(_) => None,
+                Err(e) if is_not_found(&e) => None,
+                Err(e) => return Err(e),
+            };
+            if let Some(problem) = voice_channel_problem(kind) {
+                error!("{} {} {} in een van de guilds van de bot", field, channel_id, problem);
+                valid = false;
+            }
+        }
+
+        Ok(valid)
+    }
+
+    /// Puts the `/status` of every tracked channel back, Discord may drop it while we were gone.
+    async fn restore_channel_statuses(&self, ctx: &Context) {
+        let statuses: Vec<(ChannelId, String)> = self
+            .temp_channels
+            .iter()
+            .filter_map(|entry| entry.status.clone().map(|s| (*entry.key(), s)))
+            .collect();
+
+        for (channel_id, status) in statuses {
+            if let Err(e) = set_voice_status(&self.http_client, &ctx.http, channel_id, Some(&status)).await {
+                warn!("Kon status van kanaal {} niet herstellen: {:?}", channel_id, e);
+            }
+        }
+    }
+
+    /// Runs `reconcile_state`, `update_mirrors` and the stats flush on their intervals,
+    /// started on the first `ready`.
+    fn start_background_tasks(&self, ctx: Context) {
+        if self.background_started.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let (handler, reconcile_ctx) = (self.clone(), ctx.clone());
+        tokio::spawn(async move {
+            loop {
+                sleep(RECONCILE_INTERVAL).await;
+                handler.reconcile_state(&reconcile_ctx).await;
+            }
+        });
+        let (handler, afk_ctx) = (self.clone(), ctx.clone());
+        tokio::spawn(async move {
+            loop {
+                sleep(AFK_CHECK_INTERVAL).await;
+                handler.kick_afk_members(&afk_ctx).await;
+            }
+        });
+        let handler = self.clone();
+        tokio::spawn(async move {
+            loop {
+                sleep(MIRROR_INTERVAL).await;
+                handler.update_mirrors(&ctx).await;
+            }
+        });
+        let handler = self.clone();
+        tokio::spawn(async move {
+            loop {
+                sleep(STATS_FLUSH_INTERVAL).await;
+                handler.stats.flush(&handler.db).await;
+            }
+        });
+    }
+
+    /// Tracks who sits self-muted and self-deafened in a temp channel, bots don't count.
+    fn record_afk_state(&self, left: Option<ChannelId>, new: &VoiceState, is_bot: bool) {
+        if left != new.channel_id {
+            if let Some(mut info) = left.and_then(|id| self.temp_channels.get_mut(&id)) {
+                info.member_states.remove(&new.user_id);
+            }
+        }
+        if is_bot {
+            return;
+        }
+        if let Some(mut info) = new.channel_id.and_then(|id| self.temp_channels.get_mut(&id)) {
+            info.note_afk(new.user_id, new.self_deaf && new.self_mute, Instant::now());
+        }
+    }
+
+    /// Moves members who have looked AFK for longer than their guild allows from temp
+    /// channels to the waiting room, or disconnects them if there is none.
+    async fn kick_afk_members(&self, ctx: &Context) {
+        let guilds: Vec<(GuildId, u64)> = self
+            .guild_configs
+            .read()
+            .await
+            .iter()
+            .filter_map(|(guild_id, config)| config.afk_kick_threshold_secs.map(|secs| (*guild_id, secs)))
+            .collect();
+
+        for (guild_id, threshold) in guilds {
+            let Some(guild) = guild_id.to_guild_cached(&ctx.cache) else { continue };
+            let now = Instant::now();
+            let mut afk = Vec::new();
+            for voice_state in guild.voice_states.values() {
+                let Some(channel_id) = voice_state.channel_id else { continue };
+                let Some(mut info) = self.temp_channels.get_mut(&channel_id) else { continue };
+                // Covers members who were already there before a restart
+                if !voice_state.member.as_ref().is_some_and(|m| m.user.bot) {
+                    info.note_afk(voice_state.user_id, voice_state.self_deaf && voice_state.self_mute, now);
+                }
+                if info.afk_for(Duration::from_secs(threshold), now).contains(&voice_state.user_id) {
+                    info.member_states.remove(&voice_state.user_id);
+                    afk.push((voice_state.user_id, guild.channels.get(&channel_id).map(|c| c.to_string())));
                 }
+            }
 
-                if let Err(e) = member.move_to_voice_channel(&ctx.http, guild_channel.id).await {
-                    error!("Error moving user: {:?}", e);
-                } else {
-                    info!("✓ User moved to new channel");
+            let waiting_room = self.waiting_room(guild_id).await;
+            for (user_id, channel) in afk {
+                let moved = match waiting_room {
+                    Some(waiting_room_id) => guild_id.move_member(&ctx.http, user_id, waiting_room_id).await.map(|_| ()),
+                    None => guild_id.disconnect_member(&ctx.http, user_id).await.map(|_| ()),
+                };
+                match moved {
+                    Ok(()) => {
+                        info!("{} was AFK in guild {}, verplaatst", user_id, guild_id);
+                        let place = channel.unwrap_or_else(|| "a temporary channel".to_string());
+                        self.send_dm(
+                            ctx,
+                            user_id,
+                            format!(
+                                "You were moved out of {} because you were muted and deafened for over {} minutes.",
+                                place,
+                                threshold / 60
+                            ),
+                        )
+                        .await;
+                    }
+                    Err(e) if is_member_gone(&e) => {}
+                    Err(e) => warn!("Kon AFK lid {} niet verplaatsen: {:?}", user_id, e),
                 }
             }
-            _ => error!("Unexpected channel type created"),
         }
-        Ok(())
     }
 
-    async fn create_temp_channel(
-        &self,
-        ctx: &Context,
-        guild_id: GuildId,
-        member: &Member,
-        parent_id: Option<ChannelId>,
-    ) -> Result<Channel, SerenityError> {
-        let channel_name = if let Some(guild) = guild_id.to_guild_cached(&ctx.cache) {
-            if let Some(member_info) = guild.member(&ctx.http, member.user.id).await.ok() {
-                member_info.display_name().to_string()
-            } else {
-                member.user.name.clone()
+    async fn update_mirrors(&self, ctx: &Context) {
+        let sources: Vec<GuildId> = self
+            .guild_configs
+            .read()
+            .await
+            .iter()
+            .filter(|(_, config)| config.mirror_guild_id.is_some())
+            .map(|(guild_id, _)| *guild_id)
+            .collect();
+        for source in sources {
+            if let Err(e) = self.update_mirror(ctx, source).await {
+                warn!("Kon mirror van guild {} niet bijwerken: {:?}", source, e);
             }
-        } else {
-            member.user.name.clone()
+        }
+    }
+
+    /// Posts or edits the list of `source`'s active temp channels in the announce channel
+    /// of the guild it mirrors to. Ghosted channels and those of owners who opted out with
+    /// `/auto_announce` are left out.
+    pub(crate) async fn update_mirror(&self, ctx: &Context, source: GuildId) -> Result<(), SerenityError> {
+        let config = self.guild_config(source).await;
+        let Some(target) = config.mirror_guild_id else { return Ok(()) };
+        let Some(announce_channel_id) = self.guild_config(target).await.announce_channel_id else {
+            return Ok(());
         };
-        let bot_id = ctx.cache.current_user_id();
-        let waiting_room_id = self.waiting_room_id;
-
-        let guild_channel = guild_id.create_channel(&ctx.http, |c| {
-            let mut channel = c.name(&channel_name)
-                .kind(ChannelType::Voice)
-                .permissions(vec![
-                    PermissionOverwrite {
-                        kind: PermissionOverwriteType::Role(guild_id.0.into()),
-                        allow: Permissions::empty(),
-                        deny: Permissions::CONNECT | Permissions::MOVE_MEMBERS,
-                    },
-                    PermissionOverwrite {
-                        kind: PermissionOverwriteType::Member(member.user.id),
-                        allow: Permissions::CONNECT 
-                            | Permissions::MANAGE_CHANNELS
-                            | Permissions::MUTE_MEMBERS
-                            | Permissions::DEAFEN_MEMBERS,
-                        deny: Permissions::empty(),
-                    },
-                    PermissionOverwrite {
-                        kind: PermissionOverwriteType::Member(bot_id),
-                        allow: Permissions::CONNECT
-                            | Permissions::MOVE_MEMBERS
-                            | Permissions::MANAGE_CHANNELS,
-                        deny: Permissions::empty(),
-                    },
-                ]);
+        let Some(guild) = source.to_guild_cached(&ctx.cache) else { return Ok(()) };
+        let opt_outs = self.announce_opt_outs.read().await.clone();
 
-            if let Some(parent) = parent_id {
-                channel = channel.category(parent);
-            }
-            channel
-        })
-        .await?;
+        let mut channels: Vec<(String, usize)> = guild
+            .channels
+            .values()
+            .filter_map(|channel| match channel {
+                Channel::Guild(gc) if self.is_tracked(gc.id) && !self.in_ghost_period(gc.id) => Some(gc),
+                _ => None,
+            })
+            .filter(|gc| !self.channel_owner(gc.id).is_some_and(|owner| opt_outs.contains(&owner)))
+            .map(|gc| {
+                let members = guild.voice_states.values().filter(|v| v.channel_id == Some(gc.id)).count();
+                (gc.name.clone(), members)
+            })
+            .collect();
+        channels.sort();
+        let description = mirror_description(&channels);
+        if self.mirrored.read().await.get(&source) == Some(&description) {
+            return Ok(());
+        }
 
-        waiting_room_id.create_permission(
-            &ctx.http,
-            &PermissionOverwrite {
-                kind: PermissionOverwriteType::Member(member.user.id),
-                allow: Permissions::MOVE_MEMBERS,
+        let mut embed = create_embed(&config);
+        embed
+            .title(format!("Active channels in {}", guild.name))
+            .description(&description)
+            .timestamp(Timestamp::now());
+        let edited = match config.mirror_message_id {
+            Some(message_id) => {
+                let embed = embed.clone();
+                match announce_channel_id.edit_message(&ctx.http, message_id, |m| m.set_embed(embed)).await {
+                    Ok(_) => true,
+                    // Deleted, or the announce channel changed since
+                    Err(e) if is_not_found(&e) => false,
+                    Err(e) => return Err(e),
+                }
+            }
+            None => false,
+        };
+        if !edited {
+            let message = announce_channel_id.send_message(&ctx.http, |m| m.set_embed(embed)).await?;
+            self.update_guild_config(source, |c| c.mirror_message_id = Some(message.id)).await;
+        }
+        self.mirrored.write().await.insert(source, description);
+        Ok(())
+    }
+
+    /// Stops mirroring `source` and removes its list from the other guild.
+    pub(crate) async fn stop_mirror(&self, ctx: &Context, source: GuildId) {
+        let config = self.guild_config(source).await;
+        let announce_channel_id = match config.mirror_guild_id {
+            Some(target) => self.guild_config(target).await.announce_channel_id,
+            None => None,
+        };
+        if let (Some(channel_id), Some(message_id)) = (announce_channel_id, config.mirror_message_id) {
+            if let Err(e) = channel_id.delete_message(&ctx.http, message_id).await {
+                warn!("Kon mirror bericht {} niet verwijderen: {:?}", message_id, e);
+            }
+        }
+        self.update_guild_config(source, |c| {
+            c.mirror_guild_id = None;
+            c.mirror_message_id = None;
+        })
+        .await;
+        self.mirrored.write().await.remove(&source);
+    }
+
+    /// Cross-checks `temp_channels` with what Discord has. Channels that are gone are
+    /// forgotten and missing owner overwrites are put back, member lists that differ
+    /// from the voice states are only logged.
+    pub(crate) async fn reconcile_state(&self, ctx: &Context) -> Reconciliation {
+        // Threads aren't in the channel cache, forum posts are left to the thread events
+        let tracked: Vec<(ChannelId, UserId, HashSet<UserId>)> = self
+            .temp_channels
+            .iter()
+            .filter(|entry| entry.thread_id.is_none())
+            .map(|entry| (*entry.key(), entry.owner_id, entry.session.present().collect()))
+            .collect();
+
+        let mut result = Reconciliation::default();
+        for (channel_id, owner_id, members) in tracked {
+            let channel = match channel_id.to_channel_cached(&ctx.cache).and_then(|c| c.guild()) {
+                Some(channel) => channel,
+                None => {
+                    warn!("Reconciliatie: kanaal {} bestaat niet meer, wordt vergeten", channel_id);
+                    Self::handle_external_deletion(&self.temp_channels, &self.db, channel_id).await;
+                    result.removed += 1;
+                    continue;
+                }
+            };
+
+            let in_voice: HashSet<UserId> = channel
+                .guild_id
+                .to_guild_cached(&ctx.cache)
+                .map(|g| {
+                    g.voice_states
+                        .values()
+                        .filter(|v| v.channel_id == Some(channel_id))
+                        .filter(|v| !v.member.as_ref().is_some_and(|m| m.user.bot))
+                        .map(|v| v.user_id)
+                        .collect()
+                })
+                .unwrap_or_default();
+            if in_voice != members {
+                warn!(
+                    "Reconciliatie: leden van {} kloppen niet, niet getrackt: {:?}, weg: {:?}",
+                    channel_id,
+                    in_voice.difference(&members).collect::<Vec<_>>(),
+                    members.difference(&in_voice).collect::<Vec<_>>()
+                );
+                result.member_mismatches += 1;
+            }
+
+            let expected = Self::owner_overwrite(owner_id);
+            let intact = channel
+                .permission_overwrites
+                .iter()
+                .any(|o| o.kind == expected.kind && o.allow.contains(expected.allow));
+            if !intact {
+                warn!("Reconciliatie: eigenaar permissies van {} ontbreken, worden hersteld", channel_id);
+                match create_permission(&ctx.http, channel_id, &expected, &AuditReason::Reconciled).await {
+                    Ok(_) => result.repaired += 1,
+                    Err(e) => error!("Kon permissies van {} niet herstellen: {:?}", channel_id, e),
+                }
+            }
+        }
+        result
+    }
+
+    /// Every temp channel with the members the bot counts as in it, for `/debug_voice_states`.
+    pub(crate) fn tracked_members(&self) -> Vec<(ChannelId, HashSet<UserId>)> {
+        self.temp_channels
+            .iter()
+            .map(|entry| (*entry.key(), entry.session.present().collect()))
+            .collect()
+    }
+
+    /// Waits out [`CREATION_DEBOUNCE`] for `user_id`. Returns `false` if a newer join
+    /// from the same user came in meanwhile, that one takes over.
+    async fn debounce_creation(&self, user_id: UserId) -> bool {
+        let (fire, fired) = oneshot::channel();
+        let timer = tokio::spawn(async move {
+            sleep(CREATION_DEBOUNCE).await;
+            let _ = fire.send(());
+        });
+
+        if let Some(previous) = self.pending_creations.write().await.insert(user_id, timer) {
+            previous.abort();
+        }
+
+        if fired.await.is_err() {
+            return false;
+        }
+
+        let mut pending = self.pending_creations.write().await;
+        if pending.get(&user_id).is_some_and(|t| t.is_finished()) {
+            pending.remove(&user_id);
+        }
+        true
+    }
+
+    async fn handle_creator_channel_join(
+        &self,
+        ctx: &Context,
+        guild_id: GuildId,
+        member: &Member,
+        parent_id: Option<ChannelId>,
+    ) -> Result<(), BotError> {
+        let correlation_id = new_correlation_id();
+        let span = info_span!("temp_channel", %correlation_id);
+        self.create_channel_for(ctx, guild_id, member, parent_id, correlation_id)
+            .instrument(span)
+            .await
+    }
+
+    async fn create_channel_for(
+        &self,
+        ctx: &Context,
+        guild_id: GuildId,
+        member: &Member,
+        parent_id: Option<ChannelId>,
+        correlation_id: Uuid,
+    ) -> Result<(), BotError> {
+        // At their quota, one of the member's channels makes way for the new one
+        let quota = self.guild_config(guild_id).await.channel_quota(&member.roles);
+        let at_quota = self.owned_channel_count(member.user.id) >= quota as usize;
+        if let Some((existing_channel, info)) = at_quota.then(|| self.take_user_channel(member.user.id)).flatten() {
+            if info.is_stage {
+                Self::end_stage_instance(ctx, existing_channel).await;
+            }
+            if let Err(e) = delete_channel(&ctx.http, existing_channel, &AuditReason::Replaced).await {
+                error!("Error deleting existing channel: {:?}", e);
+                // Keep tracking it so the empty-channel cleanup still gets to it
+                self.temp_channels.insert(existing_channel, info);
+            } else {
+                info!("Successfully deleted existing channel");
+                Self::forget_channel(&self.db, existing_channel).await;
+                Self::end_text_channel(ctx, &self.guild_configs, guild_id, &info).await;
+            }
+        }
+
+        // Create a new channel
+        let created = self.create_temp_channel(ctx, guild_id, member, parent_id).await?;
+        self.track_channel(created.id, member.user.id);
+        self.remember_channel(guild_id, created.id, member.user.id).await;
+        self.record_name(created.id, Some(member.user.id), &created.name);
+        self.count(Stat::ChannelsCreated);
+        self.record_creation(guild_id, created.id);
+        if created.parent_id != parent_id {
+            info!("Kanaal {} staat in overflow categorie {:?}", created.name, created.parent_id);
+        }
+        if let Some(mut info) = self.temp_channels.get_mut(&created.id) {
+            info.correlation_id = correlation_id;
+            info.is_stage = created.is_stage;
+            info.text_channel_id = created.text_channel_id;
+        }
+
+        let ghost_period = self.guild_config(guild_id).await.ghost_period_secs;
+        if ghost_period > 0 {
+            self.start_ghost_period(ctx, created.id, Duration::from_secs(ghost_period.into()));
+        }
+
+        let expiry = self
+            .guild_config(guild_id)
+            .await
+            .ownership_expiry_secs
+            .unwrap_or(DEFAULT_OWNERSHIP_EXPIRY_SECS);
+        let expiry_task = self
+            .schedule_channel_deletion(
+                ctx.clone(),
+                guild_id,
+                created.id,
+                created.name.clone(),
+                Deletion::Unclaimed,
+                Duration::from_secs(expiry),
+            )
+            .await;
+        match self.temp_channels.get_mut(&created.id) {
+            Some(mut info) => info.ownership_expiry = Some(expiry_task),
+            None => expiry_task.cancel(),
+        }
+
+        self.audit_log(
+            ctx,
+            guild_id,
+            &format!("<@{}> created **{}**", member.user.id, created.name),
+        )
+        .await;
+
+        let moved = self
+            .circuit_breaker
+            .call(member.move_to_voice_channel(&ctx.http, created.id))
+            .await;
+        match moved {
+            Ok(_) => info!("✓ User moved to new channel"),
+            Err(BotError::Discord(e)) if is_member_gone(&e) => {
+                info!("User left before move, cleaning up channel");
+                if let Some((_, info)) = self.temp_channels.remove(&created.id) {
+                    Self::forget_channel(&self.db, created.id).await;
+                    if info.is_stage {
+                        Self::end_stage_instance(ctx, created.id).await;
+                    }
+                    if let Err(e) = delete_channel(&ctx.http, created.id, &AuditReason::OwnerLeft).await {
+                        error!("Fout bij verwijderen ongebruikt kanaal: {:?}", e);
+                    }
+                    Self::end_text_channel(ctx, &self.guild_configs, guild_id, &info).await;
+                }
+            }
+            Err(e) => error!("Error moving user: {:?}", e),
+        }
+        Ok(())
+    }
+
+    async fn create_temp_channel(
+        &self,
+        ctx: &Context,
+        guild_id: GuildId,
+        member: &Member,
+        parent_id: Option<ChannelId>,
+    ) -> Result<CreatedChannel, BotError> {
+        tag_sentry_scope(Some(guild_id), Some(member.user.id), parent_id);
+        let guild = guild_id.to_guild_cached(&ctx.cache);
+        let display_name = if let Some(guild) = &guild {
+            if let Ok(member_info) = guild.member(&ctx.http, member.user.id).await {
+                member_info.display_name().to_string()
+            } else {
+                member.user.name.clone()
+            }
+        } else {
+            member.user.name.clone()
+        };
+
+        let config = self.guild_config(guild_id).await;
+        let creator_channel_id = self.creator_channel(guild_id);
+        let activity_name = match &guild {
+            Some(g) if config.use_popular_game_name => {
+                // Everyone waiting in the creator channel, the owner included
+                let presences: HashMap<UserId, Presence> = g
+                    .voice_states
+                    .values()
+                    .filter(|v| v.channel_id.is_some() && v.channel_id == creator_channel_id)
+                    .filter_map(|v| g.presences.get(&v.user_id).map(|p| (v.user_id, p.clone())))
+                    .collect();
+                majority_game(&presences, member.user.id)
+            }
+            Some(g) if config.use_activity_name => g
+                .presences
+                .get(&member.user.id)
+                .and_then(|p| p.activities.iter().find(|a| a.kind == ActivityType::Playing))
+                .map(|a| a.name.clone()),
+            _ => None,
+        };
+
+        let channel_name = match activity_name {
+            Some(name) if filter_channel_name(&name, &config.name_filter) => {
+                warn!("Activity naam '{}' geblokkeerd door name filter, fallback naar display name", name);
+                display_name
+            }
+            Some(name) => name,
+            None => display_name,
+        };
+        let bot_id = ctx.cache.current_user_id();
+        let waiting_room_id = self.waiting_room(guild_id).await;
+
+        let mut permissions = Self::default_overwrites(guild_id, member.user.id, bot_id);
+        if config.ghost_period_secs > 0 {
+            permissions[0].deny |= Permissions::VIEW_CHANNEL;
+        }
+
+        // A Stage creator channel hands out Stage channels
+        let kind = creator_channel_id
+            .and_then(|id| id.to_channel_cached(&ctx.cache))
+            .and_then(|c| c.guild())
+            .map(|gc| gc.kind)
+            .filter(|kind| *kind == ChannelType::Stage)
+            .unwrap_or(ChannelType::Voice);
+
+        let reason = AuditReason::TempChannel(member.user.name.clone());
+        let breaker = &self.circuit_breaker;
+        let guild_channel = match breaker
+            .call(Self::create_voice_channel(ctx, guild_id, &channel_name, kind, &permissions, parent_id, &reason))
+            .await
+        {
+            Ok(channel) => channel,
+            Err(BotError::Discord(e)) if http_status(&e) == Some(400) && parent_id.is_some() => {
+                let category_id = parent_id.expect("checked above");
+                let (current, max) = check_category_capacity(ctx, guild_id, category_id);
+                if current < max {
+                    return Err(e.into());
+                }
+
+                warn!("Categorie {} zit vol ({}/{}), uitwijken naar overflow categorie", category_id, current, max);
+                let overflow_id = self.overflow_category(ctx, guild_id, category_id).await?;
+                breaker
+                    .call(Self::create_voice_channel(ctx, guild_id, &channel_name, kind, &permissions, Some(overflow_id), &reason))
+                    .await?
+            }
+            Err(e) => return Err(e),
+        };
+
+        if let Some(waiting_room_id) = waiting_room_id {
+            breaker.call(create_permission(
+                &ctx.http,
+                waiting_room_id,
+                &PermissionOverwrite {
+                    kind: PermissionOverwriteType::Member(member.user.id),
+                    allow: Permissions::MOVE_MEMBERS,
+                    deny: Permissions::empty(),
+                },
+                &reason,
+            )).await?;
+        }
+
+        if guild_channel.kind == ChannelType::Stage {
+            let instance = json!({
+                "channel_id": guild_channel.id,
+                "topic": channel_name,
+                "privacy_level": STAGE_PRIVACY_GUILD_ONLY,
+            });
+            if let Err(e) = breaker.call(ctx.http.create_stage_instance(&instance)).await {
+                error!("Fout bij starten stage instance voor {}: {:?}", channel_name, e);
+            }
+        }
+
+        info!("✓ Kanaal aangemaakt: {} met beperkte move permissions", channel_name);
+
+        let text_channel_id = if config.paired_text_channel {
+            match self.create_text_channel(ctx, guild_id, member, &guild_channel).await {
+                Ok(text_channel_id) => Some(text_channel_id),
+                Err(e) => {
+                    error!("Fout bij aanmaken tekstkanaal: {:?}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        Ok(CreatedChannel {
+            id: guild_channel.id,
+            name: guild_channel.name,
+            parent_id: guild_channel.parent_id,
+            text_channel_id,
+            is_stage: guild_channel.kind == ChannelType::Stage,
+        })
+    }
+
+    /// Overwrites every temp channel starts with, `/clearperms` resets to these.
+    pub(crate) fn default_overwrites(
+        guild_id: GuildId,
+        owner_id: UserId,
+        bot_id: UserId,
+    ) -> Vec<PermissionOverwrite> {
+        vec![
+            PermissionOverwrite {
+                kind: PermissionOverwriteType::Role(guild_id.0.into()),
+                allow: Permissions::empty(),
+                deny: Permissions::CONNECT | Permissions::MOVE_MEMBERS,
+            },
+            Self::owner_overwrite(owner_id),
+            PermissionOverwrite {
+                kind: PermissionOverwriteType::Member(bot_id),
+                allow: Permissions::VIEW_CHANNEL
+                    | Permissions::CONNECT
+                    | Permissions::MOVE_MEMBERS
+                    | Permissions::MANAGE_CHANNELS,
                 deny: Permissions::empty(),
             },
-        ).await?;
+        ]
+    }
+
+    /// What owners and co-owners of a temp channel get.
+    fn owner_overwrite(owner_id: UserId) -> PermissionOverwrite {
+        PermissionOverwrite {
+            kind: PermissionOverwriteType::Member(owner_id),
+            allow: Permissions::VIEW_CHANNEL
+                | Permissions::CONNECT
+                | Permissions::MANAGE_CHANNELS
+                | Permissions::MUTE_MEMBERS
+                | Permissions::DEAFEN_MEMBERS,
+            deny: Permissions::empty(),
+        }
+    }
+
+    async fn create_voice_channel(
+        ctx: &Context,
+        guild_id: GuildId,
+        name: &str,
+        kind: ChannelType,
+        permissions: &[PermissionOverwrite],
+        parent_id: Option<ChannelId>,
+        reason: &AuditReason,
+    ) -> Result<GuildChannel, SerenityError> {
+        create_channel(&ctx.http, guild_id, reason, |c| {
+            let channel = c.name(name)
+                .kind(kind)
+                .permissions(permissions.to_vec());
+
+            if let Some(parent) = parent_id {
+                channel.category(parent);
+            }
+            channel
+        })
+        .await
+    }
+
+    /// Creates a temp channel when a voice event starts, owned by whoever created the
+    /// event, and lets it go once the event is over.
+    async fn handle_scheduled_event(&self, ctx: &Context, event: &ScheduledEvent) {
+        if !matches!(event.kind, ScheduledEventType::Voice) {
+            return;
+        }
+        match event.status {
+            ScheduledEventStatus::Active => {
+                if let Err(e) = self.start_event_channel(ctx, event).await {
+                    error!("Fout bij aanmaken kanaal voor event {}: {:?}", event.name, e);
+                    self.count(Stat::Errors);
+                }
+            }
+            ScheduledEventStatus::Completed | ScheduledEventStatus::Canceled => self.end_event_channel(ctx, event).await,
+            _ => {}
+        }
+    }
+
+    async fn start_event_channel(&self, ctx: &Context, event: &ScheduledEvent) -> Result<(), BotError> {
+        // Only events from before October 2021 have no creator
+        let Some(owner_id) = event.creator_id else { return Ok(()) };
+        let guild_id = event.guild_id;
+        // Reserved before the first await, Discord can send the update twice
+        match self.event_channels.entry(event.id) {
+            Entry::Occupied(_) => return Ok(()),
+            Entry::Vacant(entry) => {
+                entry.insert(EventChannel { venue: event.channel_id, channel_id: None });
+            }
+        }
+        let channel = match self.create_event_channel(ctx, event, owner_id).await {
+            Ok(Some(channel)) => channel,
+            result => {
+                self.event_channels.remove(&event.id);
+                return result.map(|_| ());
+            }
+        };
+
+        self.track_channel(channel.id, owner_id);
+        self.remember_channel(guild_id, channel.id, owner_id).await;
+        self.count(Stat::ChannelsCreated);
+        self.record_creation(guild_id, channel.id);
+        info!("✓ Kanaal {} aangemaakt voor event {}", channel.name, event.name);
+        self.audit_log(
+            ctx,
+            guild_id,
+            &format!("Event **{}** started, created **{}** for <@{}>", event.name, channel.name, owner_id),
+        )
+        .await;
+
+        match self.event_channels.get_mut(&event.id) {
+            Some(mut event_channel) => event_channel.channel_id = Some(channel.id),
+            None => {
+                // The event ended while the channel was being created
+                self.release_event_channel(ctx, guild_id, channel.id, &event.name).await;
+                return Ok(());
+            }
+        }
+        if let Err(e) = db::save_event_channel(&self.db, guild_id, event.id, channel.id, event.channel_id).await {
+            warn!("Kon eventkanaal {} niet opslaan: {}", channel.id, e);
+        }
+
+        // Whoever was already waiting in the event's channel
+        let waiting: Vec<UserId> = match (event.channel_id, guild_id.to_guild_cached(&ctx.cache)) {
+            (Some(venue), Some(guild)) => guild
+                .voice_states
+                .values()
+                .filter(|v| v.channel_id == Some(venue))
+                .map(|v| v.user_id)
+                .collect(),
+            _ => Vec::new(),
+        };
+        for user_id in waiting {
+            self.move_to_event_channel(ctx, guild_id, user_id, channel.id).await;
+        }
+        Ok(())
+    }
+
+    /// Creates the channel of `event`, `None` when the bot lacks permissions.
+    async fn create_event_channel(
+        &self,
+        ctx: &Context,
+        event: &ScheduledEvent,
+        owner_id: UserId,
+    ) -> Result<Option<GuildChannel>, BotError> {
+        let guild_id = event.guild_id;
+        if !self.check_bot_permissions(ctx, guild_id).await {
+            return Ok(None);
+        }
+
+        let config = self.guild_config(guild_id).await;
+        let name = event_channel_name(&event.name, &config.name_filter);
+        let parent_id = match event.channel_id {
+            Some(venue) => Some(venue),
+            None => self.creator_channel(guild_id),
+        }
+        .and_then(|id| id.to_channel_cached(&ctx.cache))
+        .and_then(|c| c.guild())
+        .and_then(|gc| gc.parent_id);
+        let permissions = Self::default_overwrites(guild_id, owner_id, ctx.cache.current_user_id());
+        let reason = AuditReason::Event(event.name.clone());
+        let channel = self
+            .circuit_breaker
+            .call(Self::create_voice_channel(ctx, guild_id, &name, ChannelType::Voice, &permissions, parent_id, &reason))
+            .await?;
+
+        if let Some(waiting_room_id) = self.waiting_room(guild_id).await {
+            let overwrite = PermissionOverwrite {
+                kind: PermissionOverwriteType::Member(owner_id),
+                allow: Permissions::MOVE_MEMBERS,
+                deny: Permissions::empty(),
+            };
+            if let Err(e) = create_permission(&ctx.http, waiting_room_id, &overwrite, &reason).await {
+                warn!("Kon wachtkamer rechten voor event {} niet geven: {:?}", event.name, e);
+            }
+        }
+        Ok(Some(channel))
+    }
+
+    /// Moves members who join the channel of a running voice event on to its temp channel.
+    async fn follow_event(&self, ctx: &Context, left: Option<ChannelId>, new: &VoiceState) {
+        let (Some(guild_id), Some(joined)) = (new.guild_id, new.channel_id) else { return };
+        if left == Some(joined) || new.member.as_ref().is_some_and(|m| m.user.bot) {
+            return;
+        }
+        let target = self
+            .event_channels
+            .iter()
+            .find(|event| event.venue == Some(joined))
+            .and_then(|event| event.channel_id)
+            .filter(|channel_id| self.temp_channels.contains_key(channel_id));
+        if let Some(channel_id) = target {
+            self.move_to_event_channel(ctx, guild_id, new.user_id, channel_id).await;
+        }
+    }
+
+    async fn move_to_event_channel(&self, ctx: &Context, guild_id: GuildId, user_id: UserId, channel_id: ChannelId) {
+        let moved = self
+            .circuit_breaker
+            .call(guild_id.move_member(&ctx.http, user_id, channel_id))
+            .await;
+        if let Err(e) = moved {
+            warn!("Kon {} niet naar eventkanaal {} verplaatsen: {:?}", user_id, channel_id, e);
+        }
+    }
+
+    /// An event that's over stops pulling members in. Its channel is deleted after the
+    /// usual empty delay, or like any other once the last member leaves.
+    async fn end_event_channel(&self, ctx: &Context, event: &ScheduledEvent) {
+        let Some((_, event_channel)) = self.event_channels.remove(&event.id) else { return };
+        if let Err(e) = db::delete_event_channel(&self.db, event.id).await {
+            warn!("Kon opgeslagen event {} niet verwijderen: {}", event.id, e);
+        }
+        // Still being created, `start_event_channel` releases it when it's done
+        let Some(channel_id) = event_channel.channel_id else { return };
+        self.release_event_channel(ctx, event.guild_id, channel_id, &event.name).await;
+    }
+
+    async fn release_event_channel(&self, ctx: &Context, guild_id: GuildId, channel_id: ChannelId, event_name: &str) {
+        let name = match channel_id.to_channel_cached(&ctx.cache).and_then(|c| c.guild()) {
+            Some(gc) => gc.name,
+            None => return,
+        };
+        let occupied = guild_id
+            .to_guild_cached(&ctx.cache)
+            .is_some_and(|g| g.voice_states.values().any(|v| v.channel_id == Some(channel_id)));
+        if occupied || !self.temp_channels.contains_key(&channel_id) {
+            return;
+        }
+
+        let delay = self.empty_deletion_delay(guild_id).await;
+        info!("Event {} is voorbij, kanaal {} wordt over {} seconden verwijderd", event_name, name, delay.as_secs());
+        let delete_task = self
+            .schedule_channel_deletion(ctx.clone(), guild_id, channel_id, name, Deletion::EventEnded, delay)
+            .await;
+        match self.temp_channels.get_mut(&channel_id) {
+            Some(mut info) => {
+                if let Some(task) = info.delete_task.replace(delete_task) {
+                    task.cancel();
+                }
+            }
+            None => delete_task.cancel(),
+        }
+    }
+
+    /// Follows the events that were running before a restart again, call after
+    /// `readopt_channels`. Those that ended in the meantime are dropped, their channels
+    /// go like any other.
+    async fn readopt_event_channels(&self, ctx: &Context, guild_id: GuildId) {
+        let stored = match db::load_event_channels(&self.db, guild_id).await {
+            Ok(stored) => stored,
+            Err(e) => return warn!("Kon eventkanalen van guild {} niet laden: {}", guild_id, e),
+        };
+        for (event_id, channel_id, venue) in stored {
+            if self.event_channels.contains_key(&event_id) {
+                continue;
+            }
+            let running = match guild_id.scheduled_event(&ctx.http, event_id, false).await {
+                Ok(event) => matches!(event.status, ScheduledEventStatus::Active),
+                Err(e) if is_not_found(&e) => false,
+                Err(e) => {
+                    warn!("Kon event {} niet ophalen, het blijft lopen: {:?}", event_id, e);
+                    true
+                }
+            };
+            if running && self.is_tracked(channel_id) {
+                // An empty event channel waits for its event, not for the empty delay
+                let delete_task = self.temp_channels.get_mut(&channel_id).and_then(|mut info| info.delete_task.take());
+                if let Some(task) = delete_task {
+                    task.cancel();
+                }
+                self.event_channels.insert(event_id, EventChannel { venue, channel_id: Some(channel_id) });
+            } else if let Err(e) = db::delete_event_channel(&self.db, event_id).await {
+                warn!("Kon opgeslagen event {} niet verwijderen: {}", event_id, e);
+            }
+        }
+    }
+
+    /// Tracks a new post in the guild's `forum_creator_id` as a temp session and gives it
+    /// the configured forum tags.
+    async fn start_forum_post(&self, ctx: &Context, thread: &GuildChannel) {
+        let config = self.guild_config(thread.guild_id).await;
+        let (Some(forum_id), Some(owner_id)) = (config.forum_creator_id, thread.owner_id) else { return };
+        if thread.parent_id != Some(forum_id) || self.temp_channels.contains_key(&thread.id) {
+            return;
+        }
+        if owner_id == ctx.cache.current_user_id() {
+            return;
+        }
+
+        self.track_channel(thread.id, owner_id);
+        if let Some(mut info) = self.temp_channels.get_mut(&thread.id) {
+            info.thread_id = Some(thread.id);
+        }
+        self.record_name(thread.id, Some(owner_id), &thread.name);
+        self.record_creation(thread.guild_id, thread.id);
+        self.count(Stat::ChannelsCreated);
+        info!("Forum post {} van {} wordt getrackt", thread.name, owner_id);
+
+        let available: Vec<(ForumTagId, String)> = match forum_id.to_channel_cached(&ctx.cache) {
+            Some(Channel::Guild(forum)) => forum.available_tags.into_iter().map(|t| (t.id, t.name)).collect(),
+            _ => Vec::new(),
+        };
+        let tags = forum_tag_ids(&available, &config.forum_tags);
+        let result = thread
+            .id
+            .edit_thread(&ctx.http, |t| {
+                if !tags.is_empty() {
+                    t.0.insert("applied_tags", json!(tags));
+                }
+                t.auto_archive_duration(FORUM_AUTO_ARCHIVE_MINUTES)
+            })
+            .await;
+        if let Err(e) = result {
+            warn!("Kon tags van forum post {} niet zetten: {:?}", thread.id, e);
+        }
+        self.audit_log(ctx, thread.guild_id, &format!("<@{}> started forum session <#{}>", owner_id, thread.id))
+            .await;
+    }
+
+    /// Ends the session of an archived forum post and locks it. Forum posts have no
+    /// voice members to count, so a session ends when Discord archives the post after
+    /// `FORUM_AUTO_ARCHIVE_MINUTES` without activity, or when a moderator does.
+    async fn end_forum_post(&self, ctx: &Context, guild_id: GuildId, thread_id: ChannelId) {
+        let Some((_, info)) = self.temp_channels.remove_if(&thread_id, |_, info| info.thread_id.is_some()) else {
+            return;
+        };
+        info.cancel_tasks();
+        info!("Forum post {} is gearchiveerd, sessie afgelopen", thread_id);
+        if let Err(e) = thread_id.edit_thread(&ctx.http, |t| t.archived(true).locked(true)).await {
+            warn!("Kon forum post {} niet locken: {:?}", thread_id, e);
+        }
+        self.audit_log(ctx, guild_id, &format!("Forum session <#{}> ended and was archived", thread_id))
+            .await;
+    }
+
+    /// Returns the overflow category for `full_category_id`, creating the next one when
+    /// it doesn't exist yet, has been deleted or is full itself. They're numbered after
+    /// the full category: "Temp Channels (2)", "Temp Channels (3)" and so on.
+    async fn overflow_category(
+        &self,
+        ctx: &Context,
+        guild_id: GuildId,
+        full_category_id: ChannelId,
+    ) -> Result<ChannelId, SerenityError> {
+        let existing = self.guild_config(guild_id).await.overflow_category_id;
+        if let Some(id) = existing {
+            let (current, max) = check_category_capacity(ctx, guild_id, id);
+            if id.to_channel_cached(&ctx.cache).is_some() && current < max {
+                return Ok(id);
+            }
+        }
+
+        let base_name = full_category_id
+            .name(&ctx.cache)
+            .await
+            .unwrap_or_else(|| "Temp Channels".to_string());
+        let categories: Vec<String> = guild_id
+            .to_guild_cached(&ctx.cache)
+            .map(|g| {
+                g.channels
+                    .values()
+                    .filter_map(|c| match c {
+                        Channel::Guild(gc) if gc.kind == ChannelType::Category => Some(gc.name.clone()),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let name = overflow_category_name(&base_name, &categories);
+
+        let category = create_channel(&ctx.http, guild_id, &AuditReason::CategoryFull, |c| {
+            c.name(name).kind(ChannelType::Category)
+        })
+        .await?;
+
+        self.update_guild_config(guild_id, |c| c.overflow_category_id = Some(category.id))
+            .await;
+        info!("✓ Overflow categorie aangemaakt: {}", category.name);
+        Ok(category.id)
+    }
+
+    /// Lifts a `/timeout` after `duration`. The task is not tied to the channel, so the
+    /// mute and deafen are reversed even if the user has left it in the meantime.
+    pub(crate) async fn start_timeout(
+        &self,
+        ctx: Context,
+        guild_id: GuildId,
+        channel_id: ChannelId,
+        user_id: UserId,
+        duration: Duration,
+    ) {
+        let timeouts = Arc::clone(&self.timeouts);
+        let expired_timeouts = Arc::clone(&self.expired_timeouts);
+        let mut active = self.timeouts.write().await;
+
+        if let Some(previous) = active.remove(&(channel_id, user_id)) {
+            previous.abort();
+        }
+        self.expired_timeouts.remove(&(guild_id, user_id));
+
+        let task = tokio::spawn(async move {
+            sleep(duration).await;
+
+            match guild_id
+                .edit_member(&ctx.http, user_id, |m| m.mute(false).deafen(false))
+                .await
+            {
+                Ok(_) => info!("✓ Timeout van {} opgeheven", user_id),
+                Err(e) if is_member_gone(&e) => {
+                    info!("{} zit niet in voice, timeout wordt opgeheven bij terugkeer", user_id);
+                    expired_timeouts.insert((guild_id, user_id));
+                }
+                Err(e) => warn!("Kon timeout van {} niet opheffen: {:?}", user_id, e),
+            }
+
+            timeouts.write().await.remove(&(channel_id, user_id));
+        });
+
+        active.insert((channel_id, user_id), task);
+    }
+
+    /// Starts managing an existing voice channel as if `owner` had created it, see
+    /// `/convert`. `Ok(false)` if the channel is already tracked.
+    pub(crate) async fn convert_channel(
+        &self,
+        ctx: &Context,
+        guild_id: GuildId,
+        channel: &GuildChannel,
+        owner: &Member,
+    ) -> Result<bool, BotError> {
+        // Claimed before the Discord calls, so a second /convert can't register it too
+        match self.temp_channels.entry(channel.id) {
+            Entry::Occupied(_) => return Ok(false),
+            Entry::Vacant(entry) => {
+                let mut info = ChannelInfo::new(owner.user.id);
+                info.is_stage = channel.kind == ChannelType::Stage;
+                entry.insert(info);
+            }
+        }
+
+        let reason = AuditReason::Command("/convert");
+        let mut overwrites = Self::default_overwrites(guild_id, owner.user.id, ctx.cache.current_user_id())
+            .into_iter()
+            .map(|overwrite| (channel.id, overwrite))
+            .collect::<Vec<_>>();
+        if let Some(waiting_room_id) = self.waiting_room(guild_id).await {
+            overwrites.push((
+                waiting_room_id,
+                PermissionOverwrite {
+                    kind: PermissionOverwriteType::Member(owner.user.id),
+                    allow: Permissions::MOVE_MEMBERS,
+                    deny: Permissions::empty(),
+                },
+            ));
+        }
+        for (channel_id, overwrite) in &overwrites {
+            if let Err(e) = create_permission(&ctx.http, *channel_id, overwrite, &reason).await {
+                self.temp_channels.remove(&channel.id);
+                return Err(e.into());
+            }
+        }
+
+        if self.guild_config(guild_id).await.paired_text_channel {
+            match self.create_text_channel(ctx, guild_id, owner, channel).await {
+                Ok(text_channel_id) => {
+                    if let Some(mut info) = self.temp_channels.get_mut(&channel.id) {
+                        info.text_channel_id = Some(text_channel_id);
+                    }
+                }
+                Err(e) => error!("Fout bij aanmaken tekstkanaal: {:?}", e),
+            }
+        }
+        self.remember_channel(guild_id, channel.id, owner.user.id).await;
+        Ok(true)
+    }
+
+    /// Stops managing `channel_id` without deleting it and removes every permission
+    /// overwrite, so only the server's role permissions apply, see `/deconvert`.
+    /// `Ok(false)` if it wasn't tracked.
+    pub(crate) async fn deconvert_channel(
+        &self,
+        ctx: &Context,
+        guild_id: GuildId,
+        channel_id: ChannelId,
+    ) -> Result<bool, BotError> {
+        let info = match self.temp_channels.remove(&channel_id) {
+            Some((_, info)) => info,
+            None => return Ok(false),
+        };
+        info.cancel_tasks();
+        Self::forget_channel(&self.db, channel_id).await;
+        Self::end_text_channel(ctx, &self.guild_configs, guild_id, &info).await;
+        Self::release_waiting_room(ctx, &self.temp_channels, &self.guild_configs, self.waiting_room_id, guild_id, info.owner_id)
+            .await;
+        // Nobody can /global_unmute an unmanaged channel, the rest goes through `lift_stale_mute`
+        let unmuted = self.set_server_mute(ctx, guild_id, &info.global_muted, false).await;
+        self.remove_global_muted(guild_id, channel_id, &unmuted);
+
+        let mut overwrites = match channel_id.to_channel(ctx).await? {
+            Channel::Guild(gc) => gc.permission_overwrites,
+            _ => Vec::new(),
+        };
+        // The bot's own goes last, it may need it for the others
+        let bot = PermissionOverwriteType::Member(ctx.cache.current_user_id());
+        overwrites.sort_by_key(|overwrite| overwrite.kind == bot);
+        let reason = AuditReason::Command("/deconvert");
+        for overwrite in overwrites {
+            delete_permission(&ctx.http, channel_id, overwrite.kind, &reason).await?;
+        }
+        Ok(true)
+    }
+
+    /// Lets `user_id` connect to the locked channel once, see `/visit`. The audit log
+    /// names `command` as the reason.
+    pub(crate) async fn start_visit(
+        &self,
+        ctx: &Context,
+        channel_id: ChannelId,
+        user_id: UserId,
+        command: &'static str,
+    ) -> Result<(), SerenityError> {
+        create_permission(
+            &ctx.http,
+            channel_id,
+            &PermissionOverwrite {
+                kind: PermissionOverwriteType::Member(user_id),
+                allow: Permissions::CONNECT,
+                deny: Permissions::empty(),
+            },
+            &AuditReason::Command(command),
+        )
+        .await?;
+
+        let temp_channels = Arc::clone(&self.temp_channels);
+        let http = Arc::clone(&ctx.http);
+        let expiry = tokio::spawn(async move {
+            sleep(VISIT_EXPIRY).await;
+
+            let expired = temp_channels
+                .get_mut(&channel_id)
+                .and_then(|mut info| info.active_visits.remove(&user_id))
+                .is_some();
+            if expired {
+                info!("Visit van {} aan kanaal {} verlopen", user_id, channel_id);
+                Self::revoke_visit(&http, channel_id, user_id).await;
+            }
+        });
+        self.track_visit(channel_id, user_id, expiry);
+        Ok(())
+    }
+
+    fn track_visit(&self, channel_id: ChannelId, user_id: UserId, expiry: tokio::task::JoinHandle<()>) {
+        match self.temp_channels.get_mut(&channel_id) {
+            Some(mut info) => {
+                if let Some(previous) = info.active_visits.insert(user_id, expiry) {
+                    previous.abort();
+                }
+            }
+            None => expiry.abort(),
+        }
+    }
+
+    /// Keeps the visit going while the visitor is inside, and ends it once they leave.
+    /// Returns true if a visit ended and its permission has to be revoked.
+    fn update_visit(&self, user_id: UserId, left: Option<ChannelId>, joined: Option<ChannelId>) -> bool {
+        if let Some(info) = joined.and_then(|id| self.temp_channels.get(&id)) {
+            if let Some(expiry) = info.active_visits.get(&user_id) {
+                expiry.abort();
+            }
+        }
+        left.and_then(|id| self.temp_channels.get_mut(&id))
+            .and_then(|mut info| info.active_visits.remove(&user_id))
+            .is_some()
+    }
+
+    async fn revoke_visit(http: &Http, channel_id: ChannelId, user_id: UserId) {
+        match delete_permission(
+            http,
+            channel_id,
+            PermissionOverwriteType::Member(user_id),
+            &AuditReason::VisitEnded,
+        )
+        .await
+        {
+            Ok(_) => {}
+            Err(e) if is_not_found(&e) => {}
+            Err(e) => warn!("Kon visit van {} aan kanaal {} niet intrekken: {:?}", user_id, channel_id, e),
+        }
+    }
+
+    /// Shows `channel_id` to @everyone after `duration`, it's created hidden when the
+    /// guild has a ghost period. The CONNECT deny stays, the owner may have locked it.
+    fn start_ghost_period(&self, ctx: &Context, channel_id: ChannelId, duration: Duration) {
+        if let Some(mut info) = self.temp_channels.get_mut(&channel_id) {
+            info.in_ghost_period = true;
+        }
+
+        let temp_channels = Arc::clone(&self.temp_channels);
+        let ctx = ctx.clone();
+        tokio::spawn(async move {
+            sleep(duration).await;
+            if !temp_channels.contains_key(&channel_id) {
+                return;
+            }
+
+            // Fetched, the cached overwrites may lag behind a /lock from just now
+            let channel = match ctx.http.get_channel(channel_id.0).await {
+                Ok(channel) => channel,
+                Err(e) => {
+                    warn!("Kon kanaal {} niet ophalen om het zichtbaar te maken: {:?}", channel_id, e);
+                    return;
+                }
+            };
+            let everyone = channel.guild().and_then(|gc| {
+                let everyone = PermissionOverwriteType::Role(gc.guild_id.0.into());
+                gc.permission_overwrites.into_iter().find(|o| o.kind == everyone)
+            });
+            if let Some(mut overwrite) = everyone {
+                overwrite.deny.remove(Permissions::VIEW_CHANNEL);
+                match create_permission(&ctx.http, channel_id, &overwrite, &AuditReason::GhostPeriodEnded).await {
+                    Ok(_) => info!("✓ Kanaal {} is nu zichtbaar", channel_id),
+                    Err(e) => warn!("Kon kanaal {} niet zichtbaar maken: {:?}", channel_id, e),
+                }
+            }
+            if let Some(mut info) = temp_channels.get_mut(&channel_id) {
+                info.in_ghost_period = false;
+            }
+        });
+    }
+
+    /// Denies CONNECT to @everyone, keeping the channel hidden during its ghost period.
+    pub(crate) async fn lock_channel(
+        &self,
+        ctx: &Context,
+        guild_id: GuildId,
+        channel_id: ChannelId,
+        reason: &AuditReason,
+    ) -> Result<(), SerenityError> {
+        let mut deny = Permissions::CONNECT | Permissions::MOVE_MEMBERS;
+        if self.in_ghost_period(channel_id) {
+            deny |= Permissions::VIEW_CHANNEL;
+        }
+        let overwrite = PermissionOverwrite {
+            kind: PermissionOverwriteType::Role(guild_id.0.into()),
+            allow: Permissions::empty(),
+            deny,
+        };
+        create_permission(&ctx.http, channel_id, &overwrite, reason).await
+    }
+
+    pub(crate) async fn set_auto_lock(&self, channel_id: ChannelId, threshold: Option<u32>) {
+        if let Some(mut info) = self.temp_channels.get_mut(&channel_id) {
+            info.auto_lock_threshold = threshold;
+            info.auto_locked = false;
+        }
+        if let Err(e) = db::save_auto_lock(&self.db, channel_id, threshold).await {
+            error!("Kon auto lock van {} niet opslaan: {}", channel_id, e);
+        }
+    }
+
+    /// Stores a temp channel and its owner, so it's picked up again after a restart.
+    async fn remember_channel(&self, guild_id: GuildId, channel_id: ChannelId, owner_id: UserId) {
+        if let Err(e) = db::save_temp_channel(&self.db, guild_id, channel_id, owner_id).await {
+            warn!("Kon kanaal {} niet opslaan: {}", channel_id, e);
+        }
+    }
+
+    /// Drops the stored channel and `/auto_lock` threshold of a channel that's no
+    /// longer tracked.
+    async fn forget_channel(db: &SqlitePool, channel_id: ChannelId) {
+        if let Err(e) = db::delete_temp_channel(db, channel_id).await {
+            warn!("Kon opgeslagen kanaal {} niet verwijderen: {}", channel_id, e);
+        }
+    }
+
+    /// Tracks the stored temp channels of `guild` again after a restart, with their
+    /// owners and `/auto_lock` thresholds. Channels that are gone are forgotten, empty
+    /// ones are deleted like any other.
+    async fn readopt_channels(&self, ctx: &Context, guild: &Guild) {
+        let stored = match db::load_temp_channels(&self.db, guild.id).await {
+            Ok(stored) => stored,
+            Err(e) => return warn!("Kon kanalen van guild {} niet laden: {}", guild.id, e),
+        };
+        if stored.is_empty() {
+            return;
+        }
+        let thresholds = db::load_auto_locks(&self.db).await.unwrap_or_else(|e| {
+            warn!("Kon auto locks niet laden: {}", e);
+            HashMap::new()
+        });
+
+        let mut adopted = 0;
+        for (channel_id, owner_id) in stored {
+            // guild_create comes again after a reconnect, those are still tracked
+            if self.is_tracked(channel_id) {
+                continue;
+            }
+            let channel = match guild.channels.get(&channel_id) {
+                Some(Channel::Guild(channel)) => channel,
+                _ => {
+                    Self::forget_channel(&self.db, channel_id).await;
+                    continue;
+                }
+            };
+
+            let now = Instant::now();
+            let mut info = ChannelInfo::new(owner_id);
+            info.is_stage = channel.kind == ChannelType::Stage;
+            info.auto_lock_threshold = thresholds.get(&channel_id).copied();
+            // The ownership expiry was for the first minutes, that's long over
+            info.had_guest = true;
+            for voice in guild.voice_states.values().filter(|v| v.channel_id == Some(channel_id)) {
+                info.session.join(voice.user_id, now);
+            }
+            let empty = info.session.present_count() == 0;
+            self.temp_channels.insert(channel_id, info);
+            self.record_name(channel_id, None, &channel.name);
+            adopted += 1;
+
+            if empty {
+                let delay = self.empty_deletion_delay(guild.id).await;
+                let delete_task = self
+                    .schedule_channel_deletion(ctx.clone(), guild.id, channel_id, channel.name.clone(), Deletion::Empty, delay)
+                    .await;
+                match self.temp_channels.get_mut(&channel_id) {
+                    Some(mut info) => info.delete_task = Some(delete_task),
+                    None => delete_task.cancel(),
+                }
+            }
+        }
+        info!("✓ {} kanalen van voor de herstart overgenomen in guild {}", adopted, guild.id);
+    }
+
+    /// Locks `channel_id` once its member count reaches the `/auto_lock` threshold,
+    /// checked on every join and when the threshold is set. Bots don't count. Returns
+    /// whether it locked the channel.
+    pub(crate) async fn check_auto_lock(&self, ctx: &Context, guild_id: GuildId, channel_id: ChannelId) -> bool {
+        let members = guild_id
+            .to_guild_cached(&ctx.cache)
+            .map(|g| {
+                g.voice_states
+                    .values()
+                    .filter(|v| v.channel_id == Some(channel_id))
+                    .filter(|v| !v.member.as_ref().or_else(|| g.members.get(&v.user_id)).is_some_and(|m| m.user.bot))
+                    .count()
+            })
+            .unwrap_or(0);
+        let Some((threshold, owner_id)) = self.claim_auto_lock(channel_id, members) else {
+            return false;
+        };
+
+        if let Err(e) = self.lock_channel(ctx, guild_id, channel_id, &AuditReason::AutoLock(threshold)).await {
+            warn!("Kon kanaal {} niet automatisch locken: {:?}", channel_id, e);
+            // The next join tries again
+            if let Some(mut info) = self.temp_channels.get_mut(&channel_id) {
+                info.auto_locked = false;
+            }
+            return false;
+        }
+        info!("Kanaal {} automatisch gelockt bij {} members", channel_id, members);
+        self.record_change(channel_id, ctx.cache.current_user_id(), ChangeAction::Lock);
+        self.send_dm(
+            ctx,
+            owner_id,
+            format!("Your channel was automatically locked ({} members reached).", members),
+        )
+        .await;
+        true
+    }
+
+    /// Marks the auto lock of `channel_id` as fired if `members` reached its threshold
+    /// and it hasn't fired yet, in one step so two joins at once lock it only once.
+    /// Returns the threshold and the owner to tell.
+    fn claim_auto_lock(&self, channel_id: ChannelId, members: usize) -> Option<(u32, UserId)> {
+        let mut info = self.temp_channels.get_mut(&channel_id)?;
+        let threshold = info.auto_lock_threshold?;
+        if info.auto_locked || members < threshold as usize {
+            return None;
+        }
+        info.auto_locked = true;
+        Some((threshold, info.owner_id))
+    }
+
+    pub(crate) fn record_change(&self, channel_id: ChannelId, actor_id: UserId, action: ChangeAction) {
+        if let Some(mut info) = self.temp_channels.get_mut(&channel_id) {
+            let entry = ChangeEntry { actor_id, action, timestamp: Instant::now() };
+            push_change(&mut info.change_log, entry);
+        }
+    }
+
+    /// Sets or clears the emoji in front of the channel name. The rename itself waits for
+    /// `RENAME_DEBOUNCE`.
+    pub(crate) fn set_owner_emoji(&self, ctx: &Context, channel_id: ChannelId, emoji: Option<String>) {
+        if let Some(mut info) = self.temp_channels.get_mut(&channel_id) {
+            info.owner_emoji = emoji;
+        }
+        self.schedule_emoji_rename(ctx, channel_id);
+    }
+
+    fn schedule_emoji_rename(&self, ctx: &Context, channel_id: ChannelId) {
+        let temp_channels = Arc::clone(&self.temp_channels);
+        let ctx = ctx.clone();
+        let task = tokio::spawn(async move {
+            sleep(RENAME_DEBOUNCE).await;
+            let Some(current) = channel_id.to_channel_cached(&ctx.cache).and_then(|c| c.guild()).map(|gc| gc.name) else {
+                return;
+            };
+            let (applied, wanted) = match temp_channels.get(&channel_id) {
+                Some(info) if info.name_emoji != info.owner_emoji => (info.name_emoji.clone(), info.owner_emoji.clone()),
+                _ => return,
+            };
+            let name = owner_emoji_name(&current, applied.as_deref(), wanted.as_deref());
+            match channel_id.edit(&ctx.http, |c| c.name(&name)).await {
+                Ok(_) => {
+                    if let Some(mut info) = temp_channels.get_mut(&channel_id) {
+                        info.name_emoji = wanted;
+                        let bot_id = ctx.cache.current_user_id();
+                        let change = NameChange { name, actor_id: Some(bot_id), timestamp: Instant::now() };
+                        push_name(&mut info.name_history, change);
+                    }
+                }
+                Err(e) => warn!("Kon kanaal {} niet hernoemen naar {}: {:?}", channel_id, name, e),
+            }
+        });
+        match self.temp_channels.get_mut(&channel_id) {
+            Some(mut info) => {
+                if let Some(previous) = info.rename_task.replace(task) {
+                    previous.abort();
+                }
+            }
+            None => task.abort(),
+        }
+    }
+
+    /// Notes that `channel_id` is now called `name`, see `name_history::push_name`.
+    pub(crate) fn record_name(&self, channel_id: ChannelId, actor_id: Option<UserId>, name: &str) {
+        if let Some(mut info) = self.temp_channels.get_mut(&channel_id) {
+            let change = NameChange { name: name.to_string(), actor_id, timestamp: Instant::now() };
+            push_name(&mut info.name_history, change);
+        }
+    }
+
+    /// Names of `channel_id` oldest first, `None` if it isn't tracked.
+    pub(crate) fn name_history(&self, channel_id: ChannelId) -> Option<Vec<NameChange>> {
+        self.temp_channels
+            .get(&channel_id)
+            .map(|info| info.name_history.iter().cloned().collect())
+    }
+
+    /// The last `count` changes to `channel_id`, newest first.
+    pub(crate) fn recent_changes(&self, channel_id: ChannelId, count: usize) -> Vec<ChangeEntry> {
+        self.temp_channels
+            .get(&channel_id)
+            .map(|info| info.change_log.iter().rev().take(count).cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn set_tags(&self, channel_id: ChannelId, tags: Vec<String>) {
+        if let Some(mut info) = self.temp_channels.get_mut(&channel_id) {
+            info.tags = tags;
+        }
+    }
+
+    pub(crate) fn channel_tags(&self, channel_id: ChannelId) -> Vec<String> {
+        self.temp_channels
+            .get(&channel_id)
+            .map(|info| info.tags.clone())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn voice_server(&self, channel_id: ChannelId) -> Option<String> {
+        self.temp_channels
+            .get(&channel_id)
+            .and_then(|info| info.voice_server.clone())
+    }
+
+    /// Remembers the voice server of the temp channel the bot is connected to in the
+    /// update's guild. Discord only sends these for the bot's own voice connections.
+    fn record_voice_server(&self, ctx: &Context, update: &VoiceServerUpdateEvent) {
+        let (Some(guild_id), Some(endpoint)) = (update.guild_id, update.endpoint.as_deref()) else {
+            return;
+        };
+        let bot_channel = guild_id.to_guild_cached(&ctx.cache).and_then(|g| {
+            g.voice_states
+                .get(&ctx.cache.current_user_id())
+                .and_then(|v| v.channel_id)
+        });
+        let Some(channel_id) = update.channel_id.or(bot_channel) else { return };
+
+        if let Some(mut info) = self.temp_channels.get_mut(&channel_id) {
+            debug!("Kanaal {} gebruikt voice server {}", channel_id, endpoint);
+            info.voice_server = Some(voice_server_host(endpoint).to_string());
+        }
+    }
+
+    pub(crate) fn in_ghost_period(&self, channel_id: ChannelId) -> bool {
+        self.temp_channels
+            .get(&channel_id)
+            .is_some_and(|info| info.in_ghost_period)
+    }
+
+    /// Raises the bitrate of `channel_id` to `bitrate` for `duration`, or extends the
+    /// running boost. Returns how long the boost has left, at most `MAX_BOOST`.
+    pub(crate) async fn start_boost(
+        &self,
+        ctx: &Context,
+        channel_id: ChannelId,
+        bitrate: u32,
+        duration: Duration,
+    ) -> Result<Duration, SerenityError> {
+        let now = Instant::now();
+        let running = self.temp_channels.get(&channel_id).and_then(|info| {
+            info.active_boost
+                .as_ref()
+                .zip(info.boost_ends_at)
+                .map(|((original, _), ends_at)| (*original, ends_at))
+        });
+
+        let (original, ends_at) = match running {
+            Some((original, ends_at)) => (original, boost_end(now, Some(ends_at), duration)),
+            None => {
+                let original = channel_id
+                    .to_channel_cached(&ctx.cache)
+                    .and_then(|c| c.guild())
+                    .and_then(|gc| gc.bitrate)
+                    .map_or(DEFAULT_BITRATE, |b| b as u32);
+                channel_id.edit(&ctx.http, |c| c.bitrate(bitrate as u64)).await?;
+                (original, boost_end(now, None, duration))
+            }
+        };
+
+        let temp_channels = Arc::clone(&self.temp_channels);
+        let http = Arc::clone(&ctx.http);
+        let client = self.http_client.clone();
+        let task = tokio::spawn(async move {
+            loop {
+                let remaining = ends_at.saturating_duration_since(Instant::now());
+                if remaining.is_zero() {
+                    break;
+                }
+                let status = boost_label(remaining);
+                if let Err(e) = set_voice_status(&client, &http, channel_id, Some(&status)).await {
+                    warn!("Kon booststatus van {} niet zetten: {:?}", channel_id, e);
+                }
+                sleep(remaining.min(BOOST_STATUS_INTERVAL)).await;
+            }
+
+            let status = match temp_channels.get_mut(&channel_id) {
+                Some(mut info) => {
+                    info.active_boost = None;
+                    info.boost_ends_at = None;
+                    info.status.clone()
+                }
+                None => return,
+            };
+            match channel_id.edit(&http, |c| c.bitrate(original as u64)).await {
+                Ok(_) => info!("✓ Boost van {} afgelopen, bitrate hersteld", channel_id),
+                Err(e) => warn!("Kon bitrate van {} niet herstellen: {:?}", channel_id, e),
+            }
+            if let Err(e) = set_voice_status(&client, &http, channel_id, status.as_deref()).await {
+                warn!("Kon status van {} niet herstellen: {:?}", channel_id, e);
+            }
+        });
+
+        match self.temp_channels.get_mut(&channel_id) {
+            Some(mut info) => {
+                if let Some((_, previous)) = info.active_boost.replace((original, task)) {
+                    previous.abort();
+                }
+                info.boost_ends_at = Some(ends_at);
+            }
+            None => task.abort(),
+        }
+        Ok(ends_at.saturating_duration_since(now))
+    }
+
+    /// Moves everyone from `merged` into `kept` and deletes `merged`, see `/link_channels`.
+    /// The owners of `merged` become co-owners of `kept`.
+    pub(crate) async fn merge_channels(
+        &self,
+        ctx: &Context,
+        guild_id: GuildId,
+        kept: ChannelId,
+        merged: ChannelId,
+    ) -> Result<(), SerenityError> {
+        let owners: Vec<UserId> = match self.temp_channels.get(&merged) {
+            Some(info) => std::iter::once(info.owner_id).chain(info.co_owners.iter().copied()).collect(),
+            None => return Ok(()),
+        };
+        let reason = AuditReason::Command("/link_channels");
+        for owner in &owners {
+            create_permission(&ctx.http, kept, &Self::owner_overwrite(*owner), &reason).await?;
+        }
+
+        // Untracked before the moves, so it isn't scheduled for deletion once they empty it
+        let mut info = match self.temp_channels.remove(&merged) {
+            Some((_, info)) => info,
+            None => return Ok(()),
+        };
+        if let Some(task) = info.delete_task.take() {
+            task.cancel();
+        }
+
+        let members: Vec<UserId> = guild_id
+            .to_guild_cached(&ctx.cache)
+            .map(|g| {
+                g.voice_states
+                    .values()
+                    .filter(|v| v.channel_id == Some(merged))
+                    .map(|v| v.user_id)
+                    .collect()
+            })
+            .unwrap_or_default();
+        for user_id in members {
+            match guild_id.move_member(&ctx.http, user_id, kept).await {
+                Ok(_) => {}
+                Err(e) if is_member_gone(&e) => {}
+                Err(e) => warn!("Kon {} niet verplaatsen naar {}: {:?}", user_id, kept, e),
+            }
+        }
+
+        if info.is_stage {
+            Self::end_stage_instance(ctx, merged).await;
+        }
+        if let Err(e) = delete_channel(&ctx.http, merged, &AuditReason::Merged).await {
+            // Tracked again and deleted like any empty channel, instead of being left behind
+            let name = merged.name(&ctx.cache).await.unwrap_or_else(|| merged.to_string());
+            let delay = self.empty_deletion_delay(guild_id).await;
+            info.delete_task = Some(
+                self.schedule_channel_deletion(ctx.clone(), guild_id, merged, name, Deletion::Empty, delay)
+                    .await,
+            );
+            self.temp_channels.insert(merged, info);
+            return Err(e);
+        }
+
+        info.cancel_tasks();
+        Self::forget_channel(&self.db, merged).await;
+        if let Some(mut kept_info) = self.temp_channels.get_mut(&kept) {
+            kept_info.co_owners.extend(owners);
+        }
+        Self::end_text_channel(ctx, &self.guild_configs, guild_id, &info).await;
+        info!("✓ Kanaal {} samengevoegd met {}", merged, kept);
+        Ok(())
+    }
+
+    /// Hands `channel_id` to whoever has been in it the longest, for when the owner is
+    /// gone. Returns the new owner, `None` if nobody is left to take over.
+    pub(crate) async fn assign_next_owner(&self, ctx: &Context, guild_id: GuildId, channel_id: ChannelId) -> Option<UserId> {
+        let (old_owner, new_owner, had_emoji) = {
+            let mut info = self.temp_channels.get_mut(&channel_id)?;
+            let new_owner = info.session.longest_present().filter(|id| *id != info.owner_id)?;
+            let old_owner = std::mem::replace(&mut info.owner_id, new_owner);
+            info.co_owners.retain(|id| *id != new_owner && *id != old_owner);
+            // The emoji was the old owner's, the new one can pick their own
+            info.owner_emoji = None;
+            (old_owner, new_owner, info.name_emoji.is_some())
+        };
+        if had_emoji {
+            self.schedule_emoji_rename(ctx, channel_id);
+        }
+        self.remember_channel(guild_id, channel_id, new_owner).await;
+
+        let reason = AuditReason::OwnerLeft;
+        if let Err(e) = create_permission(&ctx.http, channel_id, &Self::owner_overwrite(new_owner), &reason).await {
+            warn!("Kon {} geen eigenaarsrechten geven in {}: {:?}", new_owner, channel_id, e);
+        }
+        match delete_permission(&ctx.http, channel_id, PermissionOverwriteType::Member(old_owner), &reason).await {
+            Ok(_) => {}
+            Err(e) if is_not_found(&e) => {}
+            Err(e) => warn!("Kon rechten van {} in {} niet weghalen: {:?}", old_owner, channel_id, e),
+        }
+
+        info!("Kanaal {} overgedragen van {} aan {}", channel_id, old_owner, new_owner);
+        self.audit_log(
+            ctx,
+            guild_id,
+            &format!("Ownership of <#{}> passed from <@{}> to <@{}>", channel_id, old_owner, new_owner),
+        )
+        .await;
+        Some(new_owner)
+    }
+
+    /// Cleans up after a member that left or was removed from `guild_id`: their
+    /// channels go to the next in line or are deleted when empty.
+    async fn handle_member_removal(&self, ctx: &Context, guild_id: GuildId, user_id: UserId) {
+        // Temp channels are tracked for all guilds together
+        let in_guild = |channel_id: ChannelId| {
+            channel_id
+                .to_channel_cached(&ctx.cache)
+                .and_then(|c| c.guild())
+                .is_some_and(|gc| gc.guild_id == guild_id)
+        };
+
+        // The voice state update for the disconnect isn't guaranteed to come first
+        let present_in = self
+            .temp_channels
+            .iter()
+            .find(|entry| entry.session.is_present(user_id))
+            .map(|entry| *entry.key())
+            .filter(|id| in_guild(*id));
+        if let Some(channel_id) = present_in {
+            let stay = self.record_session_move(user_id, Some(channel_id), None);
+            self.log_access(ctx, guild_id, user_id, Some(channel_id), None, stay).await;
+        }
+
+        let mut owned = Vec::new();
+        for mut entry in self.temp_channels.iter_mut() {
+            if !in_guild(*entry.key()) {
+                continue;
+            }
+            entry.co_owners.retain(|id| *id != user_id);
+            if entry.owner_id == user_id {
+                owned.push(*entry.key());
+            }
+        }
+
+        for channel_id in owned {
+            if self.assign_next_owner(ctx, guild_id, channel_id).await.is_some() {
+                continue;
+            }
+
+            let name = channel_id
+                .name(&ctx.cache)
+                .await
+                .unwrap_or_else(|| channel_id.to_string());
+            info!("Eigenaar van kanaal {} heeft de server verlaten, wordt verwijderd", name);
+            let delete_task = self
+                .schedule_channel_deletion(ctx.clone(), guild_id, channel_id, name, Deletion::OwnerGone, Duration::ZERO)
+                .await;
+            match self.temp_channels.get_mut(&channel_id) {
+                Some(mut info) => {
+                    if let Some(task) = info.delete_task.replace(delete_task) {
+                        task.cancel();
+                    }
+                }
+                None => delete_task.cancel(),
+            }
+        }
+    }
+
+    /// Server-mutes or unmutes `members` in batches, see `MuteBatch`. Returns the ones
+    /// that worked.
+    pub(crate) async fn set_server_mute(
+        &self,
+        ctx: &Context,
+        guild_id: GuildId,
+        members: &[UserId],
+        mute: bool,
+    ) -> Vec<UserId> {
+        let mut done = Vec::new();
+        for (i, batch) in members.chunks(self.mute_batch.size).enumerate() {
+            if i > 0 {
+                sleep(self.mute_batch.delay).await;
+            }
+            let results = join_all(
+                batch
+                    .iter()
+                    .map(|user_id| guild_id.edit_member(&ctx.http, *user_id, |m| m.mute(mute))),
+            )
+            .await;
+            for (user_id, result) in batch.iter().zip(results) {
+                match result {
+                    Ok(_) => done.push(*user_id),
+                    Err(e) => warn!("Kon mute van {} niet aanpassen: {:?}", user_id, e),
+                }
+            }
+        }
+        done
+    }
+
+    pub(crate) fn global_muted(&self, channel_id: ChannelId) -> Vec<UserId> {
+        self.temp_channels
+            .get(&channel_id)
+            .map(|info| info.global_muted.clone())
+            .unwrap_or_default()
+    }
+
+    /// Notes `members` as muted with `/global_mute` in `channel_id`.
+    pub(crate) fn add_global_muted(&self, guild_id: GuildId, channel_id: ChannelId, members: &[UserId]) {
+        if let Some(mut info) = self.temp_channels.get_mut(&channel_id) {
+            for user_id in members {
+                if !info.global_muted.contains(user_id) {
+                    info.global_muted.push(*user_id);
+                }
+            }
+        }
+        for user_id in members {
+            self.server_mutes.insert((guild_id, *user_id), channel_id);
+        }
+    }
+
+    /// Forgets the `/global_mute` of `members`, once it's lifted.
+    pub(crate) fn remove_global_muted(&self, guild_id: GuildId, channel_id: ChannelId, members: &[UserId]) {
+        if let Some(mut info) = self.temp_channels.get_mut(&channel_id) {
+            info.global_muted.retain(|id| !members.contains(id));
+        }
+        for user_id in members {
+            self.server_mutes.remove(&(guild_id, *user_id));
+        }
+    }
+
+    /// Lifts the `/global_mute` of a member who is in voice outside the channel it was
+    /// for. Discord can't unmute members that aren't connected, so someone who
+    /// disconnected, or whose channel was deleted, is unmuted when they're back.
+    async fn lift_stale_mute(&self, ctx: &Context, new: &VoiceState) {
+        let (Some(guild_id), Some(joined)) = (new.guild_id, new.channel_id) else { return };
+        let Some(channel_id) = self.server_mutes.get(&(guild_id, new.user_id)).map(|entry| *entry) else {
+            return;
+        };
+        if joined == channel_id && self.is_tracked(channel_id) {
+            return;
+        }
+        // Unmuted by someone else meanwhile
+        if !new.mute {
+            self.remove_global_muted(guild_id, channel_id, &[new.user_id]);
+            return;
+        }
+        match guild_id.edit_member(&ctx.http, new.user_id, |m| m.mute(false)).await {
+            Ok(_) => {
+                info!("✓ Mute van {} opgeheven na het verlaten van {}", new.user_id, channel_id);
+                self.remove_global_muted(guild_id, channel_id, &[new.user_id]);
+            }
+            Err(e) => warn!("Kon mute van {} niet opheffen, volgende keer opnieuw: {:?}", new.user_id, e),
+        }
+    }
+
+    /// Lifts a timeout that ran out while the member was out of voice, see `start_timeout`.
+    async fn lift_expired_timeout(&self, ctx: &Context, new: &VoiceState) {
+        let (Some(guild_id), Some(_)) = (new.guild_id, new.channel_id) else { return };
+        if !self.expired_timeouts.contains(&(guild_id, new.user_id)) {
+            return;
+        }
+        // Lifted by someone else meanwhile
+        if !new.mute && !new.deaf {
+            self.expired_timeouts.remove(&(guild_id, new.user_id));
+            return;
+        }
+        match guild_id.edit_member(&ctx.http, new.user_id, |m| m.mute(false).deafen(false)).await {
+            Ok(_) => {
+                info!("✓ Verlopen timeout van {} opgeheven", new.user_id);
+                self.expired_timeouts.remove(&(guild_id, new.user_id));
+            }
+            Err(e) => warn!("Kon verlopen timeout van {} niet opheffen, volgende keer opnieuw: {:?}", new.user_id, e),
+        }
+    }
+
+    /// Time left on the `/channel_boost` of `channel_id`, if one is running.
+    pub(crate) fn boost_remaining(&self, channel_id: ChannelId) -> Option<Duration> {
+        self.temp_channels
+            .get(&channel_id)
+            .and_then(|info| info.boost_ends_at)
+            .map(|ends_at| ends_at.saturating_duration_since(Instant::now()))
+    }
+
+    /// Deletes the channel after `delay` unless the returned token is cancelled first.
+    /// Cancelling needs no lock and doesn't wait for the task, so the task can also
+    /// cancel its own token while cleaning up `temp_channels`.
+    async fn schedule_channel_deletion(
+        &self,
+        ctx: Context,
+        guild_id: GuildId,
+        channel_id: ChannelId,
+        channel_name: String,
+        reason: Deletion,
+        delay: Duration,
+    ) -> CancellationToken {
+        let temp_channels = Arc::clone(&self.temp_channels);
+        let breaker = Arc::clone(&self.circuit_breaker);
+        let guild_configs = Arc::clone(&self.guild_configs);
+        let http_client = self.http_client.clone();
+        let db = self.db.clone();
+        let recent_summaries = Arc::clone(&self.recent_summaries);
+        let stats = Arc::clone(&self.stats);
+        let default_waiting_room = self.waiting_room_id;
+        let reorders = Arc::clone(&self.reorders);
+
+        let hub = Arc::new(Hub::new_from_top(Hub::current()));
+        let correlation_id = self.correlation_id(channel_id).map(|id| id.to_string()).unwrap_or_default();
+        let span = info_span!("channel_deletion", channel = %channel_id, %correlation_id);
+
+        let token = CancellationToken::new();
+        let cancelled = token.clone();
+        // Cancels the token once the task is done, so a finished deletion reads as cancelled
+        let guard = token.clone().drop_guard();
+
+        // Logged under the voice event that scheduled it
+        spawn_in_span(async move {
+            let _guard = guard;
+            tag_sentry_scope(Some(guild_id), None, Some(channel_id));
+            tokio::select! {
+                _ = cancelled.cancelled() => return,
+                _ = sleep(delay) => {}
+            }
+
+            if reason == Deletion::Unclaimed {
+                let owner_id = match temp_channels.get(&channel_id) {
+                    Some(info) if !info.had_guest => info.owner_id,
+                    _ => return,
+                };
+                // Owner-only sessions are fine, as long as the owner is still there
+                let owner_inside = guild_id
+                    .to_guild_cached(&ctx.cache)
+                    .and_then(|g| g.voice_states.get(&owner_id).and_then(|v| v.channel_id))
+                    == Some(channel_id);
+                if owner_inside {
+                    return;
+                }
+                info!("Kanaal {} heeft nooit gasten gehad, wordt verwijderd", channel_name);
+            }
+
+            // Nothing may happen to the channel until here, a join cancels all of it
+            if cancelled.is_cancelled() {
+                return;
+            }
+            let is_stage = temp_channels.get(&channel_id).is_some_and(|info| info.is_stage);
+            if is_stage {
+                Self::end_stage_instance(&ctx, channel_id).await;
+                // Someone may have joined while the Stage instance was being ended
+                if cancelled.is_cancelled() {
+                    return;
+                }
+            }
+
+            let audit_reason = match reason {
+                Deletion::Empty => AuditReason::Empty(delay),
+                Deletion::Unclaimed | Deletion::OwnerGone => AuditReason::OwnerLeft,
+                Deletion::EventEnded => AuditReason::Event(channel_name.clone()),
+            };
+            // Read before the delete, the cache forgets the channel once it's gone
+            let category_id = channel_id
+                .to_channel_cached(&ctx.cache)
+                .and_then(|c| c.guild())
+                .and_then(|gc| gc.parent_id);
+            match breaker.call(delete_channel(&ctx.http, channel_id, &audit_reason)).await {
+                Ok(_) => {
+                    info!("✓ Kanaal {} verwijderd", channel_name);
+                    stats.record(Stat::ChannelsDeleted);
+                    // Posted once the channel is really gone, the text channel goes after this
+                    let summary = temp_channels.get(&channel_id).map(|info| {
+                        let mut summary = info.session.summary(&channel_name, Instant::now());
+                        summary.names = info.name_history.iter().map(|change| change.name.clone()).collect();
+                        (summary, info.text_channel_id, info.archive)
+                    });
+                    if let Some((summary, text_channel_id, archive)) = summary {
+                        Self::post_summary(&ctx, &guild_configs, guild_id, text_channel_id, archive, &summary).await;
+                        let mut recent = recent_summaries.write().await;
+                        let recent = recent.entry(guild_id).or_default();
+                        recent.push_front(summary);
+                        recent.truncate(RECENT_SUMMARIES);
+                    }
+                    let reorder = guild_configs
+                        .read()
+                        .await
+                        .get(&guild_id)
+                        .is_some_and(|c| c.reorder_on_delete);
+                    if let (true, Some(category_id)) = (reorder, category_id) {
+                        Self::schedule_reorder(&ctx, &reorders, guild_id, category_id).await;
+                    }
+                    if let Some((_, info)) = temp_channels.remove(&channel_id) {
+                        info.cancel_tasks();
+                        Self::forget_channel(&db, channel_id).await;
+                        Self::end_text_channel(&ctx, &guild_configs, guild_id, &info).await;
+                        Self::release_waiting_room(
+                            &ctx,
+                            &temp_channels,
+                            &guild_configs,
+                            default_waiting_room,
+                            guild_id,
+                            info.owner_id,
+                        )
+                        .await;
+                    }
+                    let message = match reason {
+                        Deletion::Empty => format!("Channel **{}** deleted", channel_name),
+                        Deletion::Unclaimed => {
+                            format!("Channel **{}** deleted, nobody but the owner ever joined", channel_name)
+                        }
+                        Deletion::OwnerGone => {
+                            format!("Channel **{}** deleted, the owner left the server", channel_name)
+                        }
+                        Deletion::EventEnded => format!("Channel **{}** deleted, its event is over", channel_name),
+                    };
+                    Self::send_audit_log(
+                        &ctx,
+                        &http_client,
+                        &db,
+                        &guild_configs,
+                        guild_id,
+                        &message,
+                    )
+                    .await;
+                }
+                Err(BotError::Discord(e)) if is_not_found(&e) => {
+                    Self::handle_external_deletion(&temp_channels, &db, channel_id).await;
+                }
+                Err(e) => error!("Fout bij verwijderen kanaal {}: {:?}", channel_name, e),
+            }
+        }.bind_hub(hub).instrument(span));
+
+        token
+    }
+
+    async fn create_text_channel(
+        &self,
+        ctx: &Context,
+        guild_id: GuildId,
+        member: &Member,
+        voice_channel: &GuildChannel,
+    ) -> Result<ChannelId, SerenityError> {
+        let bot_id = ctx.cache.current_user_id();
+        let reason = AuditReason::TempChannel(member.user.name.clone());
+        let text_channel = create_channel(&ctx.http, guild_id, &reason, |c| {
+                let channel = c
+                    .name(&voice_channel.name)
+                    .kind(ChannelType::Text)
+                    .permissions(vec![
+                        PermissionOverwrite {
+                            kind: PermissionOverwriteType::Member(member.user.id),
+                            allow: Permissions::MANAGE_MESSAGES,
+                            deny: Permissions::empty(),
+                        },
+                        PermissionOverwrite {
+                            kind: PermissionOverwriteType::Member(bot_id),
+                            // MANAGE_MESSAGES for `/pin`
+                            allow: Permissions::SEND_MESSAGES
+                                | Permissions::MANAGE_CHANNELS
+                                | Permissions::MANAGE_MESSAGES,
+                            deny: Permissions::empty(),
+                        },
+                    ]);
+
+                if let Some(parent) = voice_channel.parent_id {
+                    channel.category(parent);
+                }
+                channel
+            })
+            .await?;
+
+        Ok(text_channel.id)
+    }
+
+    /// Posts the session summary in the paired text channel if it's archived, or else in
+    /// the log channel, a text channel that gets deleted would take the summary with it.
+    /// Only when the guild turned summaries on.
+    async fn post_summary(
+        ctx: &Context,
+        guild_configs: &RwLock<HashMap<GuildId, GuildConfig>>,
+        guild_id: GuildId,
+        text_channel_id: Option<ChannelId>,
+        archive: bool,
+        summary: &SessionSummary,
+    ) {
+        let config = guild_configs.read().await.get(&guild_id).cloned().unwrap_or_default();
+        if !config.session_summary {
+            return;
+        }
+
+        let kept_text_channel = text_channel_id.filter(|_| archive_category(&config, archive).is_some());
+        if let Some(target) = kept_text_channel.or(config.log_channel_id) {
+            if let Err(e) = target
+                .send_message(&ctx.http, |m| {
+                    let mut embed = create_embed(&config);
+                    summary.embed(&mut embed);
+                    m.set_embed(embed)
+                })
+                .await
+            {
+                warn!("Kon sessie samenvatting niet posten: {:?}", e);
+            }
+        }
+    }
+
+    /// Most recent session `user_id` took part in, for `/summary`.
+    pub(crate) async fn last_summary(&self, guild_id: GuildId, user_id: UserId) -> Option<SessionSummary> {
+        let recent = self.recent_summaries.read().await;
+        recent
+            .get(&guild_id)?
+            .iter()
+            .find(|s| s.participants.iter().any(|(id, _)| *id == user_id))
+            .cloned()
+    }
+
+    /// Keeps the session stats of the channels `user_id` left and joined up to date.
+    /// Returns how long they were in the temp channel they left.
+    fn record_session_move(
+        &self,
+        user_id: UserId,
+        left: Option<ChannelId>,
+        joined: Option<ChannelId>,
+    ) -> Option<Duration> {
+        let now = Instant::now();
+        let stay = left
+            .and_then(|id| self.temp_channels.get_mut(&id))
+            .and_then(|mut info| info.session.leave(user_id, now));
+        if let Some(mut info) = joined.and_then(|id| self.temp_channels.get_mut(&id)) {
+            info.session.join(user_id, now);
+            if user_id != info.owner_id && !info.had_guest {
+                info.had_guest = true;
+                if let Some(task) = info.ownership_expiry.take() {
+                    task.cancel();
+                }
+            }
+        }
+        stay
+    }
+
+    /// Posts a join or leave in the paired text channels, see `GuildConfig::access_log`.
+    async fn log_access(
+        &self,
+        ctx: &Context,
+        guild_id: GuildId,
+        user_id: UserId,
+        left: Option<ChannelId>,
+        joined: Option<ChannelId>,
+        stay: Option<Duration>,
+    ) {
+        if !self.guild_config(guild_id).await.access_log {
+            return;
+        }
+
+        let now = Timestamp::now().unix_timestamp();
+        let lines = [
+            (left, stay.map(|stay| access_left(user_id, now, stay))),
+            (joined, Some(access_joined(user_id, now))),
+        ];
+        for (channel_id, line) in lines {
+            let Some(text_channel_id) = channel_id.and_then(|id| self.text_channel(id)) else { continue };
+            let Some(line) = line else { continue };
+            let sent = text_channel_id
+                .send_message(&ctx.http, |m| m.content(line).allowed_mentions(|a| a.empty_parse()))
+                .await;
+            if let Err(e) = sent {
+                warn!("Kon toegang niet loggen in {}: {:?}", text_channel_id, e);
+            }
+        }
+    }
+
+    async fn end_stage_instance(ctx: &Context, channel_id: ChannelId) {
+        match channel_id.delete_stage_instance(&ctx.http).await {
+            Ok(_) => info!("✓ Stage instance van {} beëindigd", channel_id),
+            Err(e) if is_not_found(&e) => {}
+            Err(e) => warn!("Fout bij beëindigen stage instance van {}: {:?}", channel_id, e),
+        }
+    }
+
+    /// Closes the position gaps in `category_id` after `REORDER_DEBOUNCE`. Calls for the
+    /// same guild in the meantime push it back and are handled together.
+    async fn schedule_reorder(
+        ctx: &Context,
+        reorders: &Arc<RwLock<HashMap<GuildId, PendingReorder>>>,
+        guild_id: GuildId,
+        category_id: ChannelId,
+    ) {
+        let mut pending = reorders.write().await;
+        let pending = pending.entry(guild_id).or_default();
+        if let Some(previous) = pending.reorder_task.take() {
+            previous.abort();
+        }
+        pending.categories.insert(category_id);
+
+        let (ctx, reorders) = (ctx.clone(), Arc::clone(reorders));
+        pending.reorder_task = Some(tokio::spawn(async move {
+            sleep(REORDER_DEBOUNCE).await;
+            let Some(done) = reorders.write().await.remove(&guild_id) else { return };
+            for category_id in done.categories {
+                Self::collapse_positions(&ctx, guild_id, category_id).await;
+            }
+        }));
+    }
+
+    async fn collapse_positions(ctx: &Context, guild_id: GuildId, category_id: ChannelId) {
+        let Some(guild) = guild_id.to_guild_cached(&ctx.cache) else { return };
+        let children = guild
+            .channels
+            .values()
+            .filter_map(|channel| match channel {
+                Channel::Guild(gc) if gc.parent_id == Some(category_id) => Some((gc.id, gc.position)),
+                _ => None,
+            })
+            .collect();
+
+        let moves = collapsed_positions(children);
+        if moves.is_empty() {
+            return;
+        }
+        match guild_id.reorder_channels(&ctx.http, moves).await {
+            Ok(()) => info!("✓ Posities in categorie {} aangesloten", category_id),
+            Err(e) => warn!("Kon posities in categorie {} niet aansluiten: {:?}", category_id, e),
+        }
+    }
+
+    /// Removes the Move Members overwrite `create_temp_channel` gave `owner_id` on the
+    /// waiting room, unless another channel they own still needs it.
+    async fn release_waiting_room(
+        ctx: &Context,
+        temp_channels: &DashMap<ChannelId, ChannelInfo>,
+        guild_configs: &RwLock<HashMap<GuildId, GuildConfig>>,
+        default_waiting_room: Option<ChannelId>,
+        guild_id: GuildId,
+        owner_id: UserId,
+    ) {
+        if temp_channels.iter().any(|entry| entry.owner_id == owner_id) {
+            return;
+        }
+        let waiting_room_id = match guild_configs
+            .read()
+            .await
+            .get(&guild_id)
+            .and_then(|c| c.waiting_room_id)
+            .or(default_waiting_room)
+        {
+            Some(id) => id,
+            None => return,
+        };
+
+        let kind = PermissionOverwriteType::Member(owner_id);
+        match delete_permission(&ctx.http, waiting_room_id, kind, &AuditReason::SessionEnded).await {
+            Ok(_) => {}
+            Err(e) if is_not_found(&e) => {}
+            Err(e) => warn!("Kon wachtkamer rechten van {} niet opruimen: {:?}", owner_id, e),
+        }
+    }
+
+    /// Archives or deletes the paired text channel of a session that just ended.
+    async fn end_text_channel(
+        ctx: &Context,
+        guild_configs: &RwLock<HashMap<GuildId, GuildConfig>>,
+        guild_id: GuildId,
+        info: &ChannelInfo,
+    ) {
+        let text_channel_id = match info.text_channel_id {
+            Some(id) => id,
+            None => return,
+        };
+
+        let config = guild_configs.read().await.get(&guild_id).cloned().unwrap_or_default();
+        let result = match archive_category(&config, info.archive) {
+            Some(category_id) => {
+                // Kept with the archive for moderators, the voice channel's history goes with it
+                if info.name_history.len() > 1 {
+                    let now = Instant::now();
+                    let lines: Vec<String> = info.name_history.iter().map(|change| change.line(now)).collect();
+                    let sent = text_channel_id
+                        .send_message(&ctx.http, |m| {
+                            m.content(format!("Names of the voice channel this session:\n{}", lines.join("\n")))
+                                .allowed_mentions(|a| a.empty_parse())
+                        })
+                        .await;
+                    if let Err(e) = sent {
+                        warn!("Kon naamgeschiedenis niet posten in {}: {:?}", text_channel_id, e);
+                    }
+                }
+                archive_text_channel(ctx, guild_id, text_channel_id, category_id).await
+            }
+            None => delete_channel(&ctx.http, text_channel_id, &AuditReason::SessionEnded).await,
+        };
+
+        match result {
+            Ok(_) => info!("✓ Tekstkanaal {} opgeruimd", text_channel_id),
+            Err(e) if is_not_found(&e) => {}
+            Err(e) => error!("Fout bij opruimen tekstkanaal {}: {:?}", text_channel_id, e),
+        }
+    }
+
+    /// Marks a channel so its text channel is archived instead of deleted.
+    pub(crate) fn mark_for_archive(&self, channel_id: ChannelId) {
+        if let Some(mut info) = self.temp_channels.get_mut(&channel_id) {
+            info.archive = true;
+        }
+    }
+
+    pub(crate) fn text_channel(&self, channel_id: ChannelId) -> Option<ChannelId> {
+        self.temp_channels
+            .get(&channel_id)
+            .and_then(|info| info.text_channel_id)
+    }
+
+    /// Channels `/link_channels` merged into `channel_id`, as far as its change log goes back.
+    pub(crate) fn merged_channels(&self, channel_id: ChannelId) -> Vec<ChannelId> {
+        self.temp_channels
+            .get(&channel_id)
+            .map(|info| {
+                info.change_log
+                    .iter()
+                    .filter_map(|entry| match entry.action {
+                        ChangeAction::Merged(merged) => Some(merged),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn deletion_pending(&self, channel_id: ChannelId) -> bool {
+        self.temp_channels
+            .get(&channel_id)
+            .is_some_and(|info| info.delete_task.is_some())
+    }
+
+    pub(crate) fn video_quality(&self, channel_id: ChannelId) -> Option<VideoQualityMode> {
+        self.temp_channels.get(&channel_id).map(|info| info.video_quality)
+    }
+
+    pub(crate) fn set_video_quality(&self, channel_id: ChannelId, mode: VideoQualityMode) {
+        if let Some(mut info) = self.temp_channels.get_mut(&channel_id) {
+            info.video_quality = mode;
+        }
+    }
+
+    pub(crate) fn channel_mode(&self, channel_id: ChannelId) -> Option<ChannelMode> {
+        self.temp_channels.get(&channel_id).map(|info| info.mode)
+    }
+
+    pub(crate) fn set_channel_mode(&self, channel_id: ChannelId, mode: ChannelMode) {
+        if let Some(mut info) = self.temp_channels.get_mut(&channel_id) {
+            info.mode = mode;
+        }
+    }
+
+    pub(crate) fn is_stage_channel(&self, channel_id: ChannelId) -> bool {
+        self.temp_channels
+            .get(&channel_id)
+            .is_some_and(|info| info.is_stage)
+    }
+
+    pub(crate) fn is_tracked(&self, channel_id: ChannelId) -> bool {
+        self.temp_channels.contains_key(&channel_id)
+    }
+
+    /// The late-night maximum when it's reached and `member` would get an extra channel.
+    /// Members at their quota get one of theirs replaced, so the count doesn't grow.
+    async fn late_night_limit_reached(&self, ctx: &Context, guild_id: GuildId, member: &Member) -> Option<u32> {
+        let config = self.guild_config(guild_id).await;
+        let late_night = config.late_night_config.filter(|_| is_late_night(&config))?;
+        if self.owned_channel_count(member.user.id) >= config.channel_quota(&member.roles) as usize {
+            return None;
+        }
+        let active = guild_id
+            .to_guild_cached(&ctx.cache)
+            .map_or(0, |g| g.channels.keys().filter(|id| self.is_tracked(**id)).count());
+        (active >= late_night.max_channels as usize).then_some(late_night.max_channels)
+    }
+
+    /// How long an empty temp channel is kept, longer in late-night mode.
+    async fn empty_deletion_delay(&self, guild_id: GuildId) -> Duration {
+        let config = self.guild_config(guild_id).await;
+        match config.late_night_config {
+            Some(late_night) if is_late_night(&config) => Duration::from_secs(late_night.deletion_delay_secs),
+            _ => EMPTY_DELETION_DELAY,
+        }
+    }
+
+    /// Whether a voice event touches a temp channel or joins the creator channel.
+    fn concerns_us(&self, left: Option<ChannelId>, new: &VoiceState) -> bool {
+        voice_filter::concerns_us(
+            &self.temp_channels,
+            &self.creator_channels,
+            self.creator_channel_id,
+            new.guild_id,
+            left,
+            new.channel_id,
+        )
+    }
+
+    pub(crate) fn channel_owner(&self, channel_id: ChannelId) -> Option<UserId> {
+        self.temp_channels.get(&channel_id).map(|info| info.owner_id)
+    }
+
+    pub(crate) fn co_owners(&self, channel_id: ChannelId) -> Vec<UserId> {
+        self.temp_channels
+            .get(&channel_id)
+            .map(|info| info.co_owners.clone())
+            .unwrap_or_default()
+    }
+
+    /// Turns the queue of `channel_id` on or off, turning it off empties it. Returns
+    /// `false` if the channel isn't tracked.
+    pub(crate) fn set_queue_enabled(&self, channel_id: ChannelId, enabled: bool) -> bool {
+        let Some(mut info) = self.temp_channels.get_mut(&channel_id) else { return false };
+        info.queue_enabled = enabled;
+        if !enabled {
+            info.queue.clear();
+        }
+        true
+    }
+
+    /// A full channel with a queue that `user_id` was in earlier this session, where they
+    /// want to go back to when they join the creator channel.
+    fn desired_full_channel(&self, ctx: &Context, guild_id: GuildId, user_id: UserId) -> Option<ChannelId> {
+        let candidates: Vec<ChannelId> = self
+            .temp_channels
+            .iter()
+            .filter(|entry| entry.queue_enabled && entry.thread_id.is_none())
+            .filter(|entry| entry.session.all_members.contains(&user_id) && !entry.session.is_present(user_id))
+            .map(|entry| *entry.key())
+            .collect();
+        let guild = guild_id.to_guild_cached(&ctx.cache)?;
+        candidates.into_iter().find(|channel_id| Self::channel_full(&guild, *channel_id))
+    }
+
+    fn channel_full(guild: &Guild, channel_id: ChannelId) -> bool {
+        let Some(Channel::Guild(channel)) = guild.channels.get(&channel_id) else { return false };
+        let members = guild.voice_states.values().filter(|v| v.channel_id == Some(channel_id)).count();
+        is_full(channel.user_limit, members)
+    }
+
+    /// Puts `user_id` in the queue of the full `channel_id` and parks them in the waiting
+    /// room, if there is one, until a spot opens.
+    async fn join_queue(&self, ctx: &Context, guild_id: GuildId, channel_id: ChannelId, user_id: UserId) {
+        let position = self.temp_channels.get_mut(&channel_id).map(|mut info| enqueue(&mut info.queue, user_id));
+        let Some(position) = position else { return };
+        info!("{} staat als {} in de wachtrij van {}", user_id, position, channel_id);
+        if let Some(waiting_room_id) = self.waiting_room(guild_id).await {
+            if let Err(e) = guild_id.move_member(&ctx.http, user_id, waiting_room_id).await {
+                warn!("Kon {} niet naar de wachtkamer verplaatsen: {:?}", user_id, e);
+            }
+        }
+        self.send_dm(
+            ctx,
+            user_id,
+            format!(
+                "<#{}> is full, you're number {} in its queue. You'll be moved in when a spot opens.",
+                channel_id, position
+            ),
+        )
+        .await;
+    }
+
+    /// Moves the first queued user who's still in voice into `channel_id` if it has room.
+    async fn advance_queue(&self, ctx: &Context, guild_id: GuildId, channel_id: ChannelId) {
+        let Some(guild) = guild_id.to_guild_cached(&ctx.cache) else { return };
+        if Self::channel_full(&guild, channel_id) {
+            return;
+        }
+        loop {
+            let next = self
+                .temp_channels
+                .get_mut(&channel_id)
+                .filter(|info| info.queue_enabled)
+                .and_then(|mut info| info.queue.pop_front());
+            let Some(user_id) = next else { return };
+
+            // People who left voice meanwhile lost their place, Discord can only move connected members
+            let current = guild.voice_states.get(&user_id).and_then(|v| v.channel_id);
+            if current.is_none() || current == Some(channel_id) {
+                continue;
+            }
+            match guild_id.move_member(&ctx.http, user_id, channel_id).await {
+                Ok(_) => {
+                    info!("{} uit de wachtrij naar {} verplaatst", user_id, channel_id);
+                    return;
+                }
+                Err(e) => warn!("Kon {} niet uit de wachtrij verplaatsen: {:?}", user_id, e),
+            }
+        }
+    }
 
-        info!("✓ Kanaal aangemaakt: {} met beperkte move permissions", channel_name);
-        Ok(Channel::Guild(guild_channel))
+    /// Users with a running `/visit` grant for `channel_id`.
+    pub(crate) fn visitors(&self, channel_id: ChannelId) -> Vec<UserId> {
+        self.temp_channels
+            .get(&channel_id)
+            .map(|info| info.active_visits.keys().copied().collect())
+            .unwrap_or_default()
     }
 
-    async fn schedule_channel_deletion(
+    /// Dings in the temp channel that was joined and in the one that was left, unless
+    /// nobody is left to hear it.
+    async fn play_join_sounds(
         &self,
-        ctx: Context,
-        channel_id: ChannelId,
-        channel_name: String,
-    ) -> tokio::task::JoinHandle<()> {
-        let temp_channels = Arc::clone(&self.temp_channels);
+        ctx: &Context,
+        guild_id: GuildId,
+        left: Option<ChannelId>,
+        joined: Option<ChannelId>,
+    ) {
+        if !self.guild_config(guild_id).await.join_sound_enabled {
+            return;
+        }
 
-        tokio::spawn(async move {
-            sleep(Duration::from_secs(5)).await;
+        let bot_id = ctx.cache.current_user_id();
+        let occupied = |channel_id: ChannelId| {
+            guild_id.to_guild_cached(&ctx.cache).is_some_and(|g| {
+                g.voice_states
+                    .values()
+                    .any(|v| v.channel_id == Some(channel_id) && v.user_id != bot_id)
+            })
+        };
 
-            match channel_id.delete(&ctx.http).await {
-                Ok(_) => {
-                    info!("✓ Kanaal {} verwijderd", channel_name);
-                    let mut channels = temp_channels.write().await;
-                    channels.remove(&channel_id);
-                }
-                Err(e) => error!("Fout bij verwijderen kanaal {}: {:?}", channel_name, e),
+        for channel_id in [left, joined].into_iter().flatten() {
+            if self.temp_channels.contains_key(&channel_id) && occupied(channel_id) {
+                let ctx = ctx.clone();
+                tokio::spawn(async move {
+                    play_notification_sound(&ctx, channel_id, &notification_sound()).await;
+                });
             }
-        })
+        }
     }
-}
 
-#[async_trait]
-impl EventHandler for Handler {
-    async fn ready(&self, _: Context, ready: Ready) {
-        info!("Bot is online als {}!", ready.user.name);
-        info!("Watching creator channel ID: {}", self.creator_channel_id);
+    /// Forgets a temp channel that was deleted outside the bot, e.g. by an admin.
+    async fn handle_external_deletion(
+        temp_channels: &DashMap<ChannelId, ChannelInfo>,
+        db: &SqlitePool,
+        channel_id: ChannelId,
+    ) {
+        if let Some((_, info)) = temp_channels.remove(&channel_id) {
+            info.cancel_tasks();
+            Self::forget_channel(db, channel_id).await;
+            info!("Kanaal {} is extern verwijderd, niet meer getrackt", channel_id);
+        }
     }
 
-    async fn voice_state_update(&self, ctx: Context, old: Option<VoiceState>, new: VoiceState) {
-        if let Some(channel_id) = new.channel_id {
-            if channel_id == self.creator_channel_id {
-                let guild_id = match new.guild_id {
-                    Some(id) => id,
-                    None => return,
-                };
+    async fn handle_voice_state_update(&self, ctx: Context, old: Option<VoiceState>, new: VoiceState) {
+        self.count(Stat::VoiceEventsProcessed);
+        let previous_channel_id = old.as_ref().and_then(|o| o.channel_id);
+        let concerns_us = self.concerns_us(previous_channel_id, &new);
 
-                let guild = match guild_id.to_guild_cached(&ctx.cache) {
-                    Some(g) => g,
-                    None => {
-                        warn!("Guild niet gevonden in cache");
-                        return;
-                    }
-                };
+        // Most voice events are in channels the bot doesn't manage, they stop here unless
+        // a running event, a mute to lift or a `/watch` needs them
+        if !concerns_us
+            && self.event_channels.is_empty()
+            && self.server_mutes.is_empty()
+            && self.expired_timeouts.is_empty()
+            && self.watched_users.is_empty()
+        {
+            return;
+        }
 
-                let bot_id = ctx.cache.current_user_id();
-                let bot_member = match guild.member(&ctx.http, bot_id).await {
-                    Ok(m) => m,
-                    Err(e) => {
-                        error!("Kon bot member niet ophalen: {:?}", e);
-                        return;
-                    }
-                };
+        if let Some(guild_id) = new.guild_id {
+            if self.skipped_by_test_mode(guild_id, new.user_id).await {
+                return;
+            }
+        }
+        if !self.event_channels.is_empty() {
+            self.follow_event(&ctx, previous_channel_id, &new).await;
+        }
+        if !self.server_mutes.is_empty() {
+            self.lift_stale_mute(&ctx, &new).await;
+        }
+        if !self.expired_timeouts.is_empty() {
+            self.lift_expired_timeout(&ctx, &new).await;
+        }
+        if !concerns_us {
+            if let (Some(guild_id), true) = (new.guild_id, previous_channel_id != new.channel_id) {
+                self.report_watched_user(&ctx, guild_id, new.user_id, previous_channel_id, new.channel_id)
+                    .await;
+            }
+            return;
+        }
+
+        // The channel that was left first, its deletion gets scheduled from here
+        let correlation_id = previous_channel_id
+            .and_then(|id| self.correlation_id(id))
+            .or_else(|| new.channel_id.and_then(|id| self.correlation_id(id)));
+        if let Some(correlation_id) = correlation_id {
+            Span::current().record("correlation_id", tracing::field::display(correlation_id));
+        }
+
+        let is_bot = new.member.as_ref().is_some_and(|m| m.user.bot);
+        self.record_afk_state(previous_channel_id, &new, is_bot);
+        if previous_channel_id != new.channel_id && !is_bot {
+            let stay = self.record_session_move(new.user_id, previous_channel_id, new.channel_id);
+            if let Some(guild_id) = new.guild_id {
+                self.log_access(&ctx, guild_id, new.user_id, previous_channel_id, new.channel_id, stay)
+                    .await;
+                if let Some(channel_id) = new.channel_id {
+                    self.check_auto_lock(&ctx, guild_id, channel_id).await;
+                }
+                self.report_watched_user(&ctx, guild_id, new.user_id, previous_channel_id, new.channel_id)
+                    .await;
+            }
+        }
+
+        if let (Some(channel_id), Some(guild_id)) = (new.channel_id, new.guild_id) {
+            if Some(channel_id) == self.creator_channel(guild_id) {
 
-                if !bot_member
-                    .permissions(&ctx.cache)
-                    .map_or(false, |p| p.manage_channels())
-                {
-                    error!("Bot mist de benodigde permissies!");
+                if !self.check_bot_permissions(&ctx, guild_id).await {
                     return;
                 }
 
@@ -229,26 +3492,69 @@ impl EventHandler for Handler {
                     None => return,
                 };
 
-                let channel = new.channel_id
-                    .expect("Channel ID should exist")
-                    .to_channel_cached(&ctx.cache);
+                // Flaky connections rejoin a few times in a row, only act on the last join
+                let still_there = || {
+                    guild_id
+                        .to_guild_cached(&ctx.cache)
+                        .and_then(|g| g.voice_states.get(&member.user.id).and_then(|v| v.channel_id))
+                        == Some(channel_id)
+                };
+                if self.debounce_creation(member.user.id).await && still_there() {
+                    if let Some(max) = self.late_night_limit_reached(&ctx, guild_id, member).await {
+                        info!("Late-night limiet van {} kanalen bereikt in guild {}", max, guild_id);
+                        if let Err(e) = guild_id.disconnect_member(&ctx.http, member.user.id).await {
+                            warn!("Kon {} niet uit het creator kanaal halen: {:?}", member.user.id, e);
+                        }
+                        self.send_dm(
+                            &ctx,
+                            member.user.id,
+                            format!("It's late-night mode, the server allows {} temporary channels right now. Try joining one of them instead.", max),
+                        )
+                        .await;
+                        return;
+                    }
+
+                    // Back for a full channel they dropped out of, not for a new one
+                    if let Some(desired) = self.desired_full_channel(&ctx, guild_id, member.user.id) {
+                        self.join_queue(&ctx, guild_id, desired, member.user.id).await;
+                        return;
+                    }
 
-                let parent_id = channel.and_then(|c| match c {
-                    Channel::Guild(gc) => gc.parent_id,
-                    _ => None,
-                });
+                    let channel = new.channel_id
+                        .expect("Channel ID should exist")
+                        .to_channel_cached(&ctx.cache);
 
-                if let Err(e) = self.handle_creator_channel_join(&ctx, guild_id, member, parent_id).await {
-                    error!("Error handling creator channel join: {:?}", e);
+                    let parent_id = channel.and_then(|c| match c {
+                        Channel::Guild(gc) => gc.parent_id,
+                        _ => None,
+                    });
+
+                    if let Err(e) = self.handle_creator_channel_join(&ctx, guild_id, member, parent_id).await {
+                        error!("Error handling creator channel join: {:?}", e);
+                        self.count(Stat::Errors);
+                    }
                 }
             }
         }
 
+        if previous_channel_id != new.channel_id
+            && self.update_visit(new.user_id, previous_channel_id, new.channel_id)
+        {
+            if let Some(channel_id) = previous_channel_id {
+                Self::revoke_visit(&ctx.http, channel_id, new.user_id).await;
+            }
+        }
+
+        // A spot may have opened for the queue
+        if let (Some(left), Some(guild_id)) = (previous_channel_id, new.guild_id) {
+            if previous_channel_id != new.channel_id {
+                self.advance_queue(&ctx, guild_id, left).await;
+            }
+        }
+
         if let Some(old_state) = old {
             if let Some(old_channel_id) = old_state.channel_id {
-                let mut temp_channels = self.temp_channels.write().await;
-
-                if let Some(channel_info) = temp_channels.get_mut(&old_channel_id) {
+                if self.temp_channels.contains_key(&old_channel_id) {
                     let guild = match old_state
                         .guild_id
                         .and_then(|id| id.to_guild_cached(&ctx.cache))
@@ -264,24 +3570,33 @@ impl EventHandler for Handler {
                                     match gc.members(&ctx).await {
                                         Ok(members) => {
                                             if members.is_empty() {
+                                                let delay = self.empty_deletion_delay(guild.id).await;
                                                 info!(
-                                                    "Kanaal {} is leeg, wordt over 5 seconden verwijderd",
-                                                    gc.name
+                                                    "Kanaal {} is leeg, wordt over {} seconden verwijderd",
+                                                    gc.name,
+                                                    delay.as_secs()
                                                 );
 
-                                                if let Some(task) = channel_info.delete_task.take() {
-                                                    task.abort();
-                                                }
-
                                                 let delete_task = self
                                                     .schedule_channel_deletion(
                                                         ctx.clone(),
+                                                        guild.id,
                                                         old_channel_id,
                                                         gc.name.clone(),
+                                                        Deletion::Empty,
+                                                        delay,
                                                     )
                                                     .await;
 
-                                                channel_info.delete_task = Some(delete_task);
+                                                // Looked up again because the entry may be gone after the awaits above
+                                                match self.temp_channels.get_mut(&old_channel_id) {
+                                                    Some(mut channel_info) => {
+                                                        if let Some(task) = channel_info.delete_task.replace(delete_task) {
+                                                            task.cancel();
+                                                        }
+                                                    }
+                                                    None => delete_task.cancel(),
+                                                }
                                             }
                                         },
                                         Err(e) => error!("Fout bij ophalen kanaal members: {:?}", e),
@@ -297,14 +3612,679 @@ impl EventHandler for Handler {
         }
 
         if let Some(new_channel_id) = new.channel_id {
-            let mut temp_channels = self.temp_channels.write().await;
-
-            if let Some(channel_info) = temp_channels.get_mut(&new_channel_id) {
+            if let Some(mut channel_info) = self.temp_channels.get_mut(&new_channel_id) {
                 if let Some(task) = channel_info.delete_task.take() {
-                    task.abort();
+                    task.cancel();
                     info!("Verwijdering van kanaal geannuleerd omdat er iemand gejoind is");
                 }
             }
         }
+
+        if let Some(guild_id) = new.guild_id {
+            if previous_channel_id != new.channel_id && new.user_id != ctx.cache.current_user_id() {
+                self.play_join_sounds(&ctx, guild_id, previous_channel_id, new.channel_id)
+                    .await;
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl EventHandler for Handler {
+    async fn ready(&self, ctx: Context, ready: Ready) {
+        info!("Bot is online als {}!", ready.user.name);
+        self.uptime.connected();
+        match self.creator_channel_id {
+            Some(id) => info!("Watching creator channel ID: {}", id),
+            None => info!("Geen standaard creator channel, servers kunnen er een kiezen met /setup"),
+        }
+
+        if let Err(e) = self.commands.register(&ctx).await {
+            error!("Error registering slash commands: {:?}", e);
+        }
+        if let Some(guild_id) = self.dev.guild_id {
+            if let Err(e) = self.commands.register_dev(&ctx, guild_id).await {
+                error!("Error registering developer commands in {}: {:?}", guild_id, e);
+            }
+        }
+
+        let guild_ids: Vec<GuildId> = ready.guilds.iter().map(|g| g.id).collect();
+        // With start_autosharded every shard only gets the guilds the formula assigns it
+        if let Some([shard_id, total_shards]) = ready.shard {
+            info!("Shard {}/{} bedient {} guilds", shard_id + 1, total_shards, guild_ids.len());
+            let misplaced = guild_ids
+                .iter()
+                .filter(|id| shard_for_guild(**id, total_shards) != shard_id)
+                .count();
+            if misplaced > 0 {
+                warn!("{} guilds horen niet bij shard {}", misplaced, shard_id);
+            }
+        } else {
+            info!("Bot bedient {} guilds", guild_ids.len());
+        }
+        for (guild_id, config) in self.guild_configs.read().await.iter() {
+            if let Some(user_id) = config.test_mode_user_id {
+                warn!("Test mode actief in {}, alleen events van {} worden verwerkt", guild_id, user_id);
+            }
+        }
+        match self.validate_channels(&ctx.http, &guild_ids).await {
+            Ok(true) => info!("✓ Geconfigureerde kanalen gecontroleerd"),
+            Ok(false) => {}
+            Err(e) => warn!("Kon geconfigureerde kanalen niet controleren: {:?}", e),
+        }
+
+        self.restore_channel_statuses(&ctx).await;
+        self.start_background_tasks(ctx);
+    }
+
+    async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+        match interaction {
+            Interaction::ApplicationCommand(command) => {
+                self.commands.dispatch(self, &ctx, &command).await;
+            }
+            Interaction::MessageComponent(component) => {
+                commands::handle_component(self, &ctx, &component).await;
+            }
+            Interaction::Autocomplete(autocomplete) => {
+                commands::handle_autocomplete(&ctx, &autocomplete).await;
+            }
+            _ => {}
+        }
+    }
+
+    async fn message(&self, ctx: Context, msg: Message) {
+        // The StandardFramework dispatches them instead, see commands::framework
+        if cfg!(feature = "framework") {
+            return;
+        }
+        if let Some(prefix) = self.command_prefix() {
+            commands::prefix::handle_message(self, &ctx, &msg, prefix).await;
+        }
+    }
+
+    // Sent for every guild on startup as well as when the bot joins a new one
+    async fn guild_create(&self, ctx: Context, guild: Guild, _is_new: bool) {
+        self.check_bot_permissions(&ctx, guild.id).await;
+        self.readopt_channels(&ctx, &guild).await;
+        self.readopt_event_channels(&ctx, guild.id).await;
+    }
+
+    // Discord sends updates of the bot's own member without the GUILD_MEMBERS intent
+    async fn guild_member_update(&self, ctx: Context, _old: Option<Member>, new: Member) {
+        if new.user.id == ctx.cache.current_user_id() {
+            self.check_bot_permissions(&ctx, new.guild_id).await;
+        }
+    }
+
+    // Needs the privileged GUILD_MEMBERS intent
+    async fn guild_member_removal(&self, ctx: Context, guild_id: GuildId, user: User, _member: Option<Member>) {
+        self.handle_member_removal(&ctx, guild_id, user.id).await;
+    }
+
+    async fn voice_server_update(&self, ctx: Context, update: VoiceServerUpdateEvent) {
+        self.record_voice_server(&ctx, &update);
+    }
+
+    // Needs the GUILD_SCHEDULED_EVENTS intent
+    async fn guild_scheduled_event_update(&self, ctx: Context, event: ScheduledEvent) {
+        self.handle_scheduled_event(&ctx, &event).await;
+    }
+
+    async fn guild_scheduled_event_delete(&self, ctx: Context, event: ScheduledEvent) {
+        self.end_event_channel(&ctx, &event).await;
+    }
+
+    async fn thread_create(&self, ctx: Context, thread: GuildChannel) {
+        self.start_forum_post(&ctx, &thread).await;
+    }
+
+    // Archived by Discord after `FORUM_AUTO_ARCHIVE_MINUTES` or by a moderator
+    async fn thread_update(&self, ctx: Context, thread: GuildChannel) {
+        if thread.thread_metadata.is_some_and(|m| m.archived) {
+            self.end_forum_post(&ctx, thread.guild_id, thread.id).await;
+        }
+    }
+
+    async fn thread_delete(&self, _: Context, thread: PartialGuildChannel) {
+        Self::handle_external_deletion(&self.temp_channels, &self.db, thread.id).await;
+    }
+
+    // Owners can rename their channel in Discord too, those renames only show up here
+    async fn channel_update(&self, _: Context, _old: Option<Channel>, new: Channel) {
+        if let Channel::Guild(gc) = new {
+            self.record_name(gc.id, None, &gc.name);
+        }
     }
-}
\ No newline at end of file
+
+    async fn channel_delete(&self, _: Context, channel: &GuildChannel) {
+        Self::handle_external_deletion(&self.temp_channels, &self.db, channel.id).await;
+    }
+
+    async fn voice_state_update(&self, ctx: Context, old: Option<VoiceState>, new: VoiceState) {
+        if self.dev.allow_event_replay {
+            self.record_voice_event(&old, &new);
+        }
+        // A hub per event so the Sentry tags don't leak into other events
+        let hub = Arc::new(Hub::new_from_top(Hub::current()));
+        let span = info_span!(
+            "voice_state_update",
+            guild = ?new.guild_id,
+            user = %new.user_id,
+            correlation_id = tracing::field::Empty
+        );
+        async {
+            tag_sentry_scope(new.guild_id, Some(new.user_id), new.channel_id);
+            self.handle_voice_state_update(ctx, old, new).await;
+        }
+        .bind_hub(hub)
+        .instrument(span)
+        .await
+    }
+}
+
+/// End of a boost of `duration` started at `now`, added to the running one if there
+/// is one, capped at `MAX_BOOST` from now.
+pub fn boost_end(now: Instant, running: Option<Instant>, duration: Duration) -> Instant {
+    let start = running.map_or(now, |ends_at| ends_at.max(now));
+    (start + duration).min(now + MAX_BOOST)
+}
+
+/// The host part of a voice server endpoint, `c-fra09-1234.discord.media:443` gives
+/// `c-fra09-1234`.
+pub fn voice_server_host(endpoint: &str) -> &str {
+    let host = endpoint.split(':').next().unwrap_or(endpoint);
+    host.split('.').next().unwrap_or(host)
+}
+
+/// Shown as channel status and in `!info` while a boost runs.
+pub fn boost_label(remaining: Duration) -> String {
+    format!("⚡ Boosted ({}s remaining)", remaining.as_secs())
+}
+
+/// `name` with the owner emoji `old` taken off the front and `new` put there instead,
+/// cut to the 100 characters Discord allows.
+pub fn owner_emoji_name(name: &str, old: Option<&str>, new: Option<&str>) -> String {
+    let base = old.and_then(|emoji| name.strip_prefix(emoji)).map_or(name, str::trim_start);
+    let name = match new {
+        Some(emoji) => format!("{} {}", emoji, base),
+        None => base.to_string(),
+    };
+    name.chars().take(100).collect()
+}
+
+/// IDs of the forum tags named in `names`, case-insensitively, at most the 5 Discord
+/// allows on a post.
+pub fn forum_tag_ids(available: &[(ForumTagId, String)], names: &[String]) -> Vec<ForumTagId> {
+    available
+        .iter()
+        .filter(|(_, name)| names.iter().any(|n| n.eq_ignore_ascii_case(name)))
+        .map(|(id, _)| *id)
+        .take(MAX_FORUM_TAGS)
+        .collect()
+}
+
+/// Name for the channel of a scheduled event, Discord allows 100 characters.
+pub fn event_channel_name(event_name: &str, name_filter: &[String]) -> String {
+    let name: String = event_name.trim().chars().take(100).collect();
+    if name.is_empty() || filter_channel_name(&name, name_filter) {
+        "Event".to_string()
+    } else {
+        name
+    }
+}
+
+/// Where the text channel of a session is archived when it ends, `None` if it's deleted.
+/// `archive` is whether the channel was marked with `/archive`.
+fn archive_category(config: &GuildConfig, archive: bool) -> Option<ChannelId> {
+    config
+        .archive_category_id
+        .filter(|_| archive || config.archive_text_channel)
+}
+
+/// "`base` (N)" with the first N from 2 up that none of `categories` has yet.
+pub fn overflow_category_name(base: &str, categories: &[String]) -> String {
+    (2..)
+        .map(|n| format!("{} ({})", base, n))
+        .find(|name| !categories.iter().any(|c| c.eq_ignore_ascii_case(name)))
+        .expect("there are fewer categories than numbers")
+}
+
+/// New positions for the channels of a category so they count up without gaps, in
+/// their current order. Only the channels that move are returned.
+fn collapsed_positions(mut channels: Vec<(ChannelId, i64)>) -> Vec<(ChannelId, u64)> {
+    channels.sort_by_key(|(id, position)| (*position, *id));
+    channels
+        .into_iter()
+        .enumerate()
+        .filter(|(i, (_, position))| *i as i64 != *position)
+        .map(|(i, (id, _))| (id, i as u64))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::HTTP_TIMEOUT;
+
+    fn test_handler() -> Handler {
+        Handler::new(
+            None,
+            None,
+            None,
+            HTTP_TIMEOUT,
+            MuteBatch::default(),
+            DevConfig::default(),
+            SqlitePool::connect_lazy("sqlite::memory:").unwrap(),
+        )
+    }
+
+    #[cfg(feature = "mock")]
+    fn member(user_id: u64) -> Member {
+        serde_json::from_value(serde_json::json!({
+            "guild_id": "1",
+            "user": { "id": user_id.to_string(), "username": format!("user{}", user_id), "discriminator": "0000", "avatar": null },
+            "roles": [],
+            "joined_at": "2026-01-01T00:00:00Z",
+            "deaf": false,
+            "mute": false,
+        }))
+        .unwrap()
+    }
+
+    /// Runs the real creator channel join for `user_id` against the mock API.
+    #[cfg(feature = "mock")]
+    fn spawn_join(handler: &Handler, ctx: &Context, user_id: u64) -> tokio::task::JoinHandle<()> {
+        let (handler, ctx) = (handler.clone(), ctx.clone());
+        tokio::spawn(async move {
+            handler
+                .handle_creator_channel_join(&ctx, GuildId(1), &member(user_id), None)
+                .await
+                .unwrap();
+        })
+    }
+
+    #[cfg(feature = "mock")]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn simultaneous_joins_get_separate_channels() {
+        let mock = crate::mock_http::MockHttp::start().await;
+        let ctx = mock.context();
+        let handler = test_handler();
+        let (alice, bob) = (UserId(1), UserId(2));
+
+        let (first, second) = (spawn_join(&handler, &ctx, 1), spawn_join(&handler, &ctx, 2));
+        first.await.unwrap();
+        second.await.unwrap();
+
+        let alice_channel = handler.get_user_channel(alice).await.unwrap();
+        let bob_channel = handler.get_user_channel(bob).await.unwrap();
+        assert_ne!(alice_channel, bob_channel);
+        assert_eq!(handler.temp_channels.len(), 2);
+        let requests = mock.requests();
+        assert_eq!(requests.iter().filter(|r| *r == "POST /guilds/1/channels").count(), 2);
+        assert!(requests.contains(&"PATCH /guilds/1/members/1".to_string()));
+        assert!(requests.contains(&"PATCH /guilds/1/members/2".to_string()));
+    }
+
+    #[tokio::test]
+    async fn newer_join_supersedes_pending_creation() {
+        let handler = Arc::new(test_handler());
+        let user = UserId(1);
+
+        let first = tokio::spawn({
+            let handler = Arc::clone(&handler);
+            async move { handler.debounce_creation(user).await }
+        });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        assert!(handler.debounce_creation(user).await);
+        assert!(!first.await.unwrap());
+        assert!(handler.pending_creations.read().await.is_empty());
+    }
+
+    #[cfg(feature = "mock")]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn existing_channel_is_only_replaced_once() {
+        let mock = crate::mock_http::MockHttp::start().await;
+        let ctx = mock.context();
+        let handler = test_handler();
+        handler.track_channel(ChannelId(10), UserId(1));
+
+        let (first, second) = (spawn_join(&handler, &ctx, 1), spawn_join(&handler, &ctx, 1));
+        first.await.unwrap();
+        second.await.unwrap();
+
+        let deletes = mock.requests().into_iter().filter(|r| r == "DELETE /channels/10").count();
+        assert_eq!(deletes, 1);
+        assert!(!handler.is_tracked(ChannelId(10)));
+        assert!(handler.get_user_channel(UserId(1)).await.is_some());
+    }
+
+    #[cfg(feature = "mock")]
+    #[tokio::test]
+    async fn waiting_room_is_released_with_the_last_channel() {
+        let mock = crate::mock_http::MockHttp::start().await;
+        let ctx = mock.context();
+        let handler = test_handler();
+        let (owner, waiting_room) = (UserId(7), Some(ChannelId(5)));
+        handler.track_channel(ChannelId(10), owner);
+
+        let release = || {
+            Handler::release_waiting_room(&ctx, &handler.temp_channels, &handler.guild_configs, waiting_room, GuildId(1), owner)
+        };
+        release().await;
+        assert!(mock.requests().is_empty());
+
+        handler.temp_channels.remove(&ChannelId(10));
+        release().await;
+        assert_eq!(mock.requests(), ["DELETE /channels/5/permissions/7"]);
+    }
+
+    #[cfg(feature = "mock")]
+    #[tokio::test]
+    async fn summary_is_posted_where_it_outlives_the_session() {
+        let mock = crate::mock_http::MockHttp::start().await;
+        let ctx = mock.context();
+        let handler = test_handler();
+        let config = GuildConfig { session_summary: true, log_channel_id: Some(ChannelId(50)), ..GuildConfig::default() };
+        handler.guild_configs.write().await.insert(GuildId(1), config);
+        handler.track_channel(ChannelId(10), UserId(7));
+        if let Some(mut info) = handler.temp_channels.get_mut(&ChannelId(10)) {
+            info.text_channel_id = Some(ChannelId(20));
+        }
+
+        let deleted = handler
+            .schedule_channel_deletion(ctx, GuildId(1), ChannelId(10), "Games".to_string(), Deletion::Empty, Duration::ZERO)
+            .await;
+        deleted.cancelled().await;
+
+        // The text channel is deleted with the session, the log channel stays
+        let requests = mock.requests();
+        assert!(requests.contains(&"DELETE /channels/20".to_string()), "{:?}", requests);
+        assert!(requests.contains(&"POST /channels/50/messages".to_string()), "{:?}", requests);
+        assert!(!requests.contains(&"POST /channels/20/messages".to_string()), "{:?}", requests);
+    }
+
+    fn voice_state(user_id: u64, channel_id: Option<u64>, mute: bool) -> VoiceState {
+        serde_json::from_value(serde_json::json!({
+            "guild_id": "1",
+            "channel_id": channel_id.map(|id| id.to_string()),
+            "user_id": user_id.to_string(),
+            "session_id": "mock",
+            "deaf": false,
+            "mute": mute,
+            "self_deaf": false,
+            "self_mute": false,
+            "self_video": false,
+            "suppress": false,
+            "request_to_speak_timestamp": null,
+        }))
+        .unwrap()
+    }
+
+    #[cfg(feature = "mock")]
+    #[tokio::test]
+    async fn global_mute_is_lifted_outside_the_channel() {
+        let mock = crate::mock_http::MockHttp::start().await;
+        let ctx = mock.context();
+        let handler = test_handler();
+        handler.track_channel(ChannelId(10), UserId(1));
+        handler.add_global_muted(GuildId(1), ChannelId(10), &[UserId(7)]);
+
+        // Still in the channel, or disconnected and can't be unmuted
+        handler.lift_stale_mute(&ctx, &voice_state(7, Some(10), true)).await;
+        handler.lift_stale_mute(&ctx, &voice_state(7, None, true)).await;
+        assert!(mock.requests().is_empty());
+
+        // Back in voice somewhere else, e.g. after the channel was deleted
+        handler.temp_channels.remove(&ChannelId(10));
+        handler.lift_stale_mute(&ctx, &voice_state(7, Some(20), true)).await;
+        assert_eq!(mock.requests(), ["PATCH /guilds/1/members/7"]);
+        assert!(handler.server_mutes.is_empty());
+    }
+
+    #[cfg(feature = "mock")]
+    #[tokio::test]
+    async fn expired_timeout_is_lifted_when_the_member_is_back() {
+        let mock = crate::mock_http::MockHttp::start().await;
+        let ctx = mock.context();
+        let handler = test_handler();
+        handler.expired_timeouts.insert((GuildId(1), UserId(7)));
+
+        handler.lift_expired_timeout(&ctx, &voice_state(7, None, true)).await;
+        assert!(mock.requests().is_empty());
+
+        handler.lift_expired_timeout(&ctx, &voice_state(7, Some(20), true)).await;
+        assert_eq!(mock.requests(), ["PATCH /guilds/1/members/7"]);
+        assert!(handler.expired_timeouts.is_empty());
+    }
+
+    #[test]
+    fn owner_emoji_replaces_the_previous_one() {
+        assert_eq!(owner_emoji_name("Games", None, Some("🎮")), "🎮 Games");
+        assert_eq!(owner_emoji_name("🎮 Games", Some("🎮"), Some("🔥")), "🔥 Games");
+        assert_eq!(owner_emoji_name("🎮 Games", Some("🎮"), None), "Games");
+        // Renamed in the meantime, the old emoji is gone already
+        assert_eq!(owner_emoji_name("Study", Some("🎮"), Some("📚")), "📚 Study");
+    }
+
+    #[test]
+    fn event_channels_are_named_after_the_event() {
+        assert_eq!(event_channel_name("  Movie night ", &[]), "Movie night");
+        assert_eq!(event_channel_name(&"x".repeat(120), &[]).len(), 100);
+        assert_eq!(event_channel_name("Bad word night", &["bad".to_string()]), "Event");
+        assert_eq!(event_channel_name(" ", &[]), "Event");
+    }
+
+    #[test]
+    fn positions_close_up_in_order() {
+        let channels = vec![(ChannelId(3), 3), (ChannelId(1), 0), (ChannelId(5), 7), (ChannelId(4), 3)];
+        assert_eq!(
+            collapsed_positions(channels),
+            [(ChannelId(3), 1), (ChannelId(4), 2), (ChannelId(5), 3)]
+        );
+        assert!(collapsed_positions(vec![(ChannelId(1), 0), (ChannelId(2), 1)]).is_empty());
+    }
+
+    #[test]
+    fn afk_time_restarts_when_the_state_changes() {
+        let mut info = ChannelInfo::new(UserId(1));
+        let start = Instant::now();
+        let (afk, active) = (UserId(2), UserId(3));
+        info.note_afk(afk, true, start);
+        info.note_afk(active, false, start);
+        let threshold = Duration::from_secs(600);
+
+        info.note_afk(afk, true, start + Duration::from_secs(300));
+        assert!(info.afk_for(threshold, start + Duration::from_secs(599)).is_empty());
+        assert_eq!(info.afk_for(threshold, start + threshold), [afk]);
+
+        info.note_afk(afk, false, start + threshold);
+        info.note_afk(afk, true, start + threshold);
+        assert!(info.afk_for(threshold, start + threshold * 2 - Duration::from_secs(1)).is_empty());
+    }
+
+    #[tokio::test]
+    async fn visit_ends_when_visitor_leaves() {
+        let handler = test_handler();
+        let (channel, visitor) = (ChannelId(10), UserId(2));
+        handler.track_channel(channel, UserId(1));
+        handler.track_visit(channel, visitor, tokio::spawn(sleep(VISIT_EXPIRY)));
+
+        assert!(!handler.update_visit(visitor, None, Some(channel)));
+        tokio::task::yield_now().await;
+        assert!(handler.temp_channels.get(&channel).unwrap().active_visits[&visitor].is_finished());
+
+        assert!(handler.update_visit(visitor, Some(channel), None));
+        assert!(!handler.update_visit(visitor, Some(channel), None));
+    }
+
+    #[tokio::test]
+    async fn first_guest_cancels_ownership_expiry() {
+        let handler = test_handler();
+        let (channel, owner, guest) = (ChannelId(10), UserId(1), UserId(2));
+        handler.track_channel(channel, owner);
+        let expiry = CancellationToken::new();
+        handler.temp_channels.get_mut(&channel).unwrap().ownership_expiry = Some(expiry.clone());
+
+        handler.record_session_move(owner, None, Some(channel));
+        assert!(!handler.temp_channels.get(&channel).unwrap().had_guest);
+
+        handler.record_session_move(guest, None, Some(channel));
+        let info = handler.temp_channels.get(&channel).unwrap();
+        assert!(info.had_guest);
+        assert!(info.ownership_expiry.is_none());
+        assert!(expiry.is_cancelled());
+    }
+
+    #[test]
+    fn boost_extends_up_to_the_cap() {
+        let now = Instant::now();
+        let secs = Duration::from_secs;
+
+        assert_eq!(boost_end(now, None, secs(120)), now + secs(120));
+        assert_eq!(boost_end(now, Some(now + secs(120)), secs(60)), now + secs(180));
+        assert_eq!(boost_end(now, Some(now + secs(500)), secs(300)), now + MAX_BOOST);
+    }
+
+    #[test]
+    fn voice_server_host_drops_domain_and_port() {
+        assert_eq!(voice_server_host("c-fra09-1234.discord.media:443"), "c-fra09-1234");
+        assert_eq!(voice_server_host("rotterdam42.discord.gg"), "rotterdam42");
+    }
+
+    #[tokio::test]
+    async fn clones_share_temp_channels() {
+        let handler = test_handler();
+        let clone = handler.clone();
+        handler.track_channel(ChannelId(10), UserId(1));
+
+        assert_eq!(clone.get_user_channel(UserId(1)).await, Some(ChannelId(10)));
+    }
+
+    #[test]
+    fn forum_tags_match_by_name() {
+        let available: Vec<(ForumTagId, String)> =
+            ["LFG", "Ranked", "Casual"].iter().enumerate().map(|(i, n)| (ForumTagId(i as u64), n.to_string())).collect();
+
+        assert_eq!(forum_tag_ids(&available, &["casual".to_string(), "lfg".to_string()]), [ForumTagId(0), ForumTagId(2)]);
+        assert!(forum_tag_ids(&available, &["Missing".to_string()]).is_empty());
+
+        let many: Vec<(ForumTagId, String)> = (0..8).map(|i| (ForumTagId(i), format!("t{}", i))).collect();
+        let names: Vec<String> = many.iter().map(|(_, n)| n.clone()).collect();
+        assert_eq!(forum_tag_ids(&many, &names).len(), MAX_FORUM_TAGS);
+    }
+
+    #[test]
+    fn overflow_categories_are_numbered() {
+        assert_eq!(overflow_category_name("Games", &[]), "Games (2)");
+        let taken = ["Games".to_string(), "games (2)".to_string(), "Games (4)".to_string()];
+        assert_eq!(overflow_category_name("Games", &taken), "Games (3)");
+    }
+
+    #[tokio::test]
+    async fn owner_commands_use_the_channel_the_owner_is_in() {
+        let handler = test_handler();
+        let owner = UserId(1);
+        handler.track_channel(ChannelId(10), owner);
+        handler.track_channel(ChannelId(20), owner);
+        handler.track_channel(ChannelId(30), UserId(2));
+        if let Some(mut info) = handler.temp_channels.get_mut(&ChannelId(30)) {
+            info.co_owners.push(owner);
+        }
+        assert_eq!(handler.get_user_channel(owner).await, Some(ChannelId(20)));
+
+        handler.record_session_move(owner, None, Some(ChannelId(10)));
+        assert_eq!(handler.get_user_channel(owner).await, Some(ChannelId(10)));
+        handler.record_session_move(owner, Some(ChannelId(10)), Some(ChannelId(30)));
+        assert_eq!(handler.get_user_channel(owner).await, Some(ChannelId(30)));
+    }
+
+    #[tokio::test]
+    async fn co_owners_get_the_channel_they_co_own() {
+        let handler = test_handler();
+        let co_owner = UserId(2);
+        handler.track_channel(ChannelId(10), UserId(1));
+        assert_eq!(handler.get_user_channel(co_owner).await, None);
+
+        // What /changelog and /pin look the channel up with
+        if let Some(mut info) = handler.temp_channels.get_mut(&ChannelId(10)) {
+            info.co_owners.push(co_owner);
+        }
+        assert_eq!(handler.get_user_channel(co_owner).await, Some(ChannelId(10)));
+    }
+
+    #[tokio::test]
+    async fn auto_lock_fires_once_at_or_above_the_threshold() {
+        let handler = test_handler();
+        handler.track_channel(ChannelId(10), UserId(1));
+        assert_eq!(handler.claim_auto_lock(ChannelId(10), 5), None);
+
+        handler.set_auto_lock(ChannelId(10), Some(3)).await;
+        assert_eq!(handler.claim_auto_lock(ChannelId(10), 2), None);
+        // Already over the threshold counts too, two joins at once only lock once
+        assert_eq!(handler.claim_auto_lock(ChannelId(10), 4), Some((3, UserId(1))));
+        assert_eq!(handler.claim_auto_lock(ChannelId(10), 4), None);
+
+        handler.set_auto_lock(ChannelId(10), Some(4)).await;
+        assert_eq!(handler.claim_auto_lock(ChannelId(10), 4), Some((4, UserId(1))));
+    }
+
+    #[tokio::test]
+    async fn forum_posts_are_not_voice_channels() {
+        let handler = test_handler();
+        handler.track_channel(ChannelId(10), UserId(1));
+        if let Some(mut info) = handler.temp_channels.get_mut(&ChannelId(10)) {
+            info.thread_id = Some(ChannelId(10));
+        }
+
+        assert_eq!(handler.get_user_channel(UserId(1)).await, None);
+        assert!(handler.take_user_channel(UserId(1)).is_none());
+        assert!(handler.is_tracked(ChannelId(10)));
+    }
+
+    #[tokio::test]
+    async fn test_mode_only_lets_the_test_user_through() {
+        let handler = test_handler();
+        assert!(!handler.skipped_by_test_mode(GuildId(1), UserId(2)).await);
+
+        let config = GuildConfig { test_mode_user_id: Some(UserId(5)), ..GuildConfig::default() };
+        handler.guild_configs.write().await.insert(GuildId(1), config);
+        assert!(!handler.skipped_by_test_mode(GuildId(1), UserId(5)).await);
+        assert!(handler.skipped_by_test_mode(GuildId(1), UserId(2)).await);
+        assert!(!handler.skipped_by_test_mode(GuildId(3), UserId(2)).await);
+    }
+
+    #[tokio::test]
+    async fn only_temp_and_creator_channels_concern_us() {
+        let handler = test_handler();
+        handler.track_channel(ChannelId(10), UserId(1));
+        assert!(handler.concerns_us(Some(ChannelId(10)), &voice_state(2, None, false)));
+        assert!(handler.concerns_us(None, &voice_state(2, Some(10), false)));
+        assert!(!handler.concerns_us(Some(ChannelId(5)), &voice_state(2, Some(20), false)));
+
+        // The creator channel is picked up from the config without a lock on every event
+        handler.update_guild_config(GuildId(1), |c| c.creator_channel_id = Some(ChannelId(20))).await;
+        assert!(handler.concerns_us(Some(ChannelId(5)), &voice_state(2, Some(20), false)));
+        handler.update_guild_config(GuildId(1), |c| c.creator_channel_id = None).await;
+        assert!(!handler.concerns_us(None, &voice_state(2, Some(20), false)));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn concurrent_config_updates_are_saved_in_order() {
+        let db = db::init("sqlite::memory:", 1).await.unwrap();
+        let handler = Handler::new(None, None, None, HTTP_TIMEOUT, MuteBatch::default(), DevConfig::default(), db);
+
+        let updates: Vec<_> = (1..=20)
+            .map(|secs| {
+                let handler = handler.clone();
+                tokio::spawn(async move { handler.update_guild_config(GuildId(1), |c| c.ghost_period_secs = secs).await })
+            })
+            .collect();
+        for update in updates {
+            update.await.unwrap();
+        }
+
+        let saved = db::load_guild_configs(&handler.db).await.unwrap();
+        assert_eq!(saved[&GuildId(1)].ghost_period_secs, handler.guild_config(GuildId(1)).await.ghost_period_secs);
+    }
+}
@@ -0,0 +1,83 @@
+use serenity::model::id::UserId;
+use std::{collections::VecDeque, time::Instant};
+
+use super::changelog::relative_time;
+
+/// Names kept per channel for `/name_history`, the oldest ones are dropped first.
+pub const MAX_NAME_HISTORY: usize = 10;
+
+/// A name a temp channel got, for moderators checking on renames.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NameChange {
+    pub name: String,
+    /// `None` when the channel was renamed in Discord itself, which doesn't say by whom.
+    pub actor_id: Option<UserId>,
+    pub timestamp: Instant,
+}
+
+impl NameChange {
+    /// `/name_history` line, e.g. "**Games** by <@1>, 5 minutes ago".
+    pub fn line(&self, now: Instant) -> String {
+        let by = match self.actor_id {
+            Some(actor_id) => format!(" by <@{}>", actor_id),
+            None => " in Discord".to_string(),
+        };
+        format!("**{}**{}, {}", self.name, by, relative_time(now.duration_since(self.timestamp)))
+    }
+}
+
+/// Adds a rename to `history`, dropping the oldest entry past `MAX_NAME_HISTORY`.
+///
+/// Renames the bot makes come back as a channel update, so a name equal to the newest
+/// entry isn't added again. That update can also arrive first, then the actor is filled in.
+pub fn push_name(history: &mut VecDeque<NameChange>, change: NameChange) {
+    if let Some(newest) = history.back_mut().filter(|newest| newest.name == change.name) {
+        newest.actor_id = newest.actor_id.or(change.actor_id);
+        return;
+    }
+    history.push_back(change);
+    while history.len() > MAX_NAME_HISTORY {
+        history.pop_front();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(name: &str, actor_id: Option<u64>, timestamp: Instant) -> NameChange {
+        NameChange {
+            name: name.to_string(),
+            actor_id: actor_id.map(UserId),
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn keeps_the_newest_names_once() {
+        let now = Instant::now();
+        let mut history = VecDeque::new();
+        for i in 0..MAX_NAME_HISTORY + 2 {
+            push_name(&mut history, change(&format!("Name {}", i), Some(1), now));
+        }
+        assert_eq!(history.len(), MAX_NAME_HISTORY);
+        assert_eq!(history.front().unwrap().name, "Name 2");
+
+        // The channel update of the command's own rename
+        push_name(&mut history, change("Name 11", None, now));
+        assert_eq!(history.len(), MAX_NAME_HISTORY);
+        assert_eq!(history.back().unwrap().actor_id, Some(UserId(1)));
+
+        // The channel update came in before the command
+        push_name(&mut history, change("Late", None, now));
+        push_name(&mut history, change("Late", Some(2), now));
+        assert_eq!(history.back().unwrap().actor_id, Some(UserId(2)));
+    }
+
+    #[test]
+    fn lines_say_who_renamed() {
+        let now = Instant::now();
+        assert_eq!(change("Games", Some(1), now).line(now), "**Games** by <@1>, just now");
+        assert_eq!(change("Games", None, now).line(now), "**Games** in Discord, just now");
+    }
+}
@@ -0,0 +1,41 @@
+use serenity::model::id::UserId;
+use std::collections::VecDeque;
+
+/// Adds `user_id` to the back of the queue unless they're in it already. Returns their
+/// place in it, starting at 1.
+pub fn enqueue(queue: &mut VecDeque<UserId>, user_id: UserId) -> usize {
+    match queue.iter().position(|id| *id == user_id) {
+        Some(index) => index + 1,
+        None => {
+            queue.push_back(user_id);
+            queue.len()
+        }
+    }
+}
+
+/// Whether a channel with `members` in it has no room left, `None` or 0 is no limit.
+pub fn is_full(user_limit: Option<u64>, members: usize) -> bool {
+    user_limit.is_some_and(|limit| limit > 0 && members as u64 >= limit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queueing_twice_keeps_the_place() {
+        let mut queue = VecDeque::new();
+        assert_eq!(enqueue(&mut queue, UserId(1)), 1);
+        assert_eq!(enqueue(&mut queue, UserId(2)), 2);
+        assert_eq!(enqueue(&mut queue, UserId(1)), 1);
+        assert_eq!(queue, [UserId(1), UserId(2)]);
+    }
+
+    #[test]
+    fn only_limited_channels_fill_up() {
+        assert!(is_full(Some(2), 2));
+        assert!(!is_full(Some(2), 1));
+        assert!(!is_full(Some(0), 10));
+        assert!(!is_full(None, 10));
+    }
+}
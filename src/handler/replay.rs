@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+use serenity::model::voice::VoiceState;
+
+/// A voice event as stored for `/event_replay`, the arguments `voice_state_update` got.
+#[derive(Serialize, Deserialize)]
+pub struct StoredVoiceEvent {
+    pub old: Option<VoiceState>,
+    pub new: VoiceState,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serenity::model::id::{ChannelId, UserId};
+
+    #[test]
+    fn stored_events_round_trip() {
+        let new: VoiceState = serde_json::from_str(
+            r#"{
+                "channel_id": "10",
+                "user_id": "1",
+                "session_id": "abc",
+                "deaf": false,
+                "mute": false,
+                "self_deaf": false,
+                "self_mute": true,
+                "self_video": false,
+                "suppress": false,
+                "request_to_speak_timestamp": null
+            }"#,
+        )
+        .unwrap();
+        let json = serde_json::to_string(&StoredVoiceEvent { old: None, new }).unwrap();
+
+        let event: StoredVoiceEvent = serde_json::from_str(&json).unwrap();
+        assert!(event.old.is_none());
+        assert_eq!(event.new.channel_id, Some(ChannelId(10)));
+        assert_eq!(event.new.user_id, UserId(1));
+        assert!(event.new.self_mute);
+    }
+}
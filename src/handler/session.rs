@@ -0,0 +1,220 @@
+use serenity::{builder::CreateEmbed, model::id::UserId};
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant},
+};
+
+use crate::utils::{fit_list, MAX_FIELD_LEN};
+
+/// Who was in a temp channel and for how long, kept up to date from voice state events.
+#[derive(Debug)]
+pub struct SessionStats {
+    started_at: Instant,
+    peak_members: usize,
+    /// Members currently in the channel and when they joined.
+    active: HashMap<UserId, Instant>,
+    /// Voice time of members that already left, added to on every leave.
+    voice_time: HashMap<UserId, Duration>,
+    /// Everyone that was in the channel at some point.
+    pub all_members: HashSet<UserId>,
+}
+
+/// End-of-session report, posted when a temp channel is deleted and kept for `/summary`.
+#[derive(Debug, Clone)]
+pub struct SessionSummary {
+    pub channel_name: String,
+    pub duration: Duration,
+    pub peak_members: usize,
+    /// Participants sorted by voice time, longest first.
+    pub participants: Vec<(UserId, Duration)>,
+    /// Every name the channel had, oldest first, when it was renamed.
+    pub names: Vec<String>,
+}
+
+impl SessionStats {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            started_at: now,
+            peak_members: 0,
+            active: HashMap::new(),
+            voice_time: HashMap::new(),
+            all_members: HashSet::new(),
+        }
+    }
+
+    pub fn join(&mut self, user_id: UserId, now: Instant) {
+        self.active.entry(user_id).or_insert(now);
+        self.all_members.insert(user_id);
+        self.peak_members = self.peak_members.max(self.active.len());
+    }
+
+    /// Returns how long `user_id` was in the channel this time.
+    pub fn leave(&mut self, user_id: UserId, now: Instant) -> Option<Duration> {
+        let stay = now.duration_since(self.active.remove(&user_id)?);
+        *self.voice_time.entry(user_id).or_default() += stay;
+        Some(stay)
+    }
+
+    /// Members in the channel right now.
+    pub fn present_count(&self) -> usize {
+        self.active.len()
+    }
+
+    pub fn present(&self) -> impl Iterator<Item = UserId> + '_ {
+        self.active.keys().copied()
+    }
+
+    pub fn is_present(&self, user_id: UserId) -> bool {
+        self.active.contains_key(&user_id)
+    }
+
+    /// Whoever is in the channel now and joined first, lowest ID on a tie.
+    pub fn longest_present(&self) -> Option<UserId> {
+        self.active
+            .iter()
+            .min_by(|a, b| a.1.cmp(b.1).then(a.0.cmp(b.0)))
+            .map(|(user_id, _)| *user_id)
+    }
+
+    pub fn summary(&self, channel_name: &str, now: Instant) -> SessionSummary {
+        let mut voice_time = self.voice_time.clone();
+        for (user_id, joined) in &self.active {
+            *voice_time.entry(*user_id).or_default() += now.duration_since(*joined);
+        }
+
+        let mut participants: Vec<(UserId, Duration)> = voice_time.into_iter().collect();
+        participants.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        SessionSummary {
+            channel_name: channel_name.to_string(),
+            duration: now.duration_since(self.started_at),
+            peak_members: self.peak_members,
+            participants,
+            names: Vec::new(),
+        }
+    }
+}
+
+impl SessionSummary {
+    pub fn embed<'a>(&self, embed: &'a mut CreateEmbed) -> &'a mut CreateEmbed {
+        embed
+            .title(format!("Session summary: {}", self.channel_name))
+            .field("Duration", format_duration(self.duration), true)
+            .field("Peak members", self.peak_members, true)
+            .field(
+                format!("Participants ({})", self.participants.len()),
+                self.participant_list(),
+                false,
+            );
+        if self.names.len() > 1 {
+            embed.field("Names", self.names.join(" → "), false);
+        }
+        embed
+    }
+
+    /// One line per participant, the ones with the least voice time are cut off when
+    /// they don't fit in the embed field.
+    fn participant_list(&self) -> String {
+        if self.participants.is_empty() {
+            return "Nobody".to_string();
+        }
+        let lines: Vec<String> = self
+            .participants
+            .iter()
+            .map(|(user_id, time)| format!("<@{}> — {}", user_id, format_duration(*time)))
+            .collect();
+        fit_list(&lines, "\n", MAX_FIELD_LEN)
+    }
+}
+
+/// Access log line for a join, `unix_time` is shown in each reader's own timezone.
+pub fn access_joined(user_id: UserId, unix_time: i64) -> String {
+    format!("→ <@{}> joined at <t:{}:T>", user_id, unix_time)
+}
+
+pub fn access_left(user_id: UserId, unix_time: i64, stay: Duration) -> String {
+    let secs = stay.as_secs();
+    format!(
+        "← <@{}> left at <t:{}:T> (was in for {}m {}s)",
+        user_id,
+        unix_time,
+        secs / 60,
+        secs % 60
+    )
+}
+
+fn format_duration(duration: Duration) -> String {
+    let minutes = duration.as_secs() / 60;
+    match minutes {
+        0 => format!("{}s", duration.as_secs()),
+        1..=59 => format!("{}m", minutes),
+        _ => format!("{}h {:02}m", minutes / 60, minutes % 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracks_peak_and_voice_time() {
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let (alice, bob) = (UserId(1), UserId(2));
+
+        let mut stats = SessionStats::new(start);
+        stats.join(alice, at(0));
+        stats.join(bob, at(60));
+        assert_eq!(stats.leave(bob, at(120)), Some(Duration::from_secs(60)));
+        assert_eq!(stats.leave(bob, at(130)), None);
+        stats.join(bob, at(300));
+
+        let summary = stats.summary("Gaming", at(600));
+        assert_eq!(summary.peak_members, 2);
+        assert_eq!(summary.duration, Duration::from_secs(600));
+        assert_eq!(
+            summary.participants,
+            [(alice, Duration::from_secs(600)), (bob, Duration::from_secs(360))]
+        );
+    }
+
+    #[test]
+    fn longest_present_skips_members_that_left() {
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let (alice, bob, carol) = (UserId(1), UserId(2), UserId(3));
+
+        let mut stats = SessionStats::new(start);
+        assert_eq!(stats.longest_present(), None);
+        stats.join(alice, at(0));
+        stats.join(carol, at(30));
+        stats.join(bob, at(30));
+        stats.leave(alice, at(60));
+        assert!(!stats.is_present(alice));
+        assert_eq!(stats.longest_present(), Some(bob));
+    }
+
+    #[test]
+    fn participant_list_fits_in_a_field() {
+        let start = Instant::now();
+        let mut stats = SessionStats::new(start);
+        for id in 0..200 {
+            stats.join(UserId(100_000_000_000_000_000 + id), start);
+        }
+        let summary = stats.summary("Crowded", start + Duration::from_secs(60));
+
+        let list = summary.participant_list();
+        assert!(list.len() <= MAX_FIELD_LEN, "{}", list.len());
+        assert!(list.ends_with(" more"), "{}", list);
+        assert!(list.starts_with("<@100000000000000000> — 1m\n"), "{}", list);
+    }
+
+    #[test]
+    fn access_lines() {
+        assert_eq!(access_joined(UserId(1), 1_700_000_000), "→ <@1> joined at <t:1700000000:T>");
+        assert_eq!(
+            access_left(UserId(1), 1_700_000_000, Duration::from_secs(125)),
+            "← <@1> left at <t:1700000000:T> (was in for 2m 5s)"
+        );
+    }
+}
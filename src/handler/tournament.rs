@@ -0,0 +1,106 @@
+use serenity::model::id::{ChannelId, RoleId};
+
+/// Most teams a `/tournament` can have, the first round then fills 16 channels.
+pub const MAX_TEAMS: usize = 32;
+
+/// A single-elimination bracket with a voice channel per match, see `/tournament`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tournament {
+    pub name: String,
+    pub category_id: ChannelId,
+    pub round: u32,
+    pub matches: Vec<Match>,
+}
+
+/// Two teams, identified by their role, playing in one channel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Match {
+    pub home: RoleId,
+    /// `None` is a bye, `home` goes through without playing and gets no channel.
+    pub away: Option<RoleId>,
+    pub channel_id: Option<ChannelId>,
+}
+
+impl Match {
+    pub fn has_team(&self, team: RoleId) -> bool {
+        self.home == team || self.away == Some(team)
+    }
+}
+
+/// Pairs the teams in order, the last one gets a bye when the count is odd.
+pub fn pair_teams(teams: &[RoleId]) -> Vec<Match> {
+    teams
+        .chunks(2)
+        .map(|pair| Match {
+            home: pair[0],
+            away: pair.get(1).copied(),
+            channel_id: None,
+        })
+        .collect()
+}
+
+/// "Match 1 - Team A vs Team B"
+pub fn match_name(number: usize, home: &str, away: &str) -> String {
+    format!("Match {} - {} vs {}", number, home, away)
+}
+
+impl Tournament {
+    /// The teams that go through when `winners` won their matches, in bracket order.
+    /// Every match needs exactly one winner, teams with a bye go through by themselves.
+    pub fn next_round(&self, winners: &[RoleId]) -> Result<Vec<RoleId>, String> {
+        if let Some(team) = winners.iter().find(|w| !self.matches.iter().any(|m| m.has_team(**w))) {
+            return Err(format!("<@&{}> is not playing in this round.", team));
+        }
+
+        let mut through = Vec::with_capacity(self.matches.len());
+        for (i, m) in self.matches.iter().enumerate() {
+            if m.away.is_none() {
+                through.push(m.home);
+                continue;
+            }
+            let won: Vec<RoleId> = winners.iter().copied().filter(|w| m.has_team(*w)).collect();
+            match won[..] {
+                [winner] => through.push(winner),
+                [] => return Err(format!("Match {} has no winner yet.", i + 1)),
+                _ => return Err(format!("Match {} can only have one winner.", i + 1)),
+            }
+        }
+        Ok(through)
+    }
+
+    pub fn channels(&self) -> impl Iterator<Item = ChannelId> + '_ {
+        self.matches.iter().filter_map(|m| m.channel_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tournament(teams: &[u64]) -> Tournament {
+        let teams: Vec<RoleId> = teams.iter().copied().map(RoleId).collect();
+        Tournament {
+            name: "Cup".to_string(),
+            category_id: ChannelId(1),
+            round: 1,
+            matches: pair_teams(&teams),
+        }
+    }
+
+    #[test]
+    fn odd_team_gets_a_bye() {
+        let t = tournament(&[1, 2, 3]);
+        assert_eq!(t.matches.len(), 2);
+        assert_eq!(t.matches[1].away, None);
+        assert_eq!(t.next_round(&[RoleId(2)]), Ok(vec![RoleId(2), RoleId(3)]));
+    }
+
+    #[test]
+    fn needs_one_winner_per_match() {
+        let t = tournament(&[1, 2, 3, 4]);
+        assert_eq!(t.next_round(&[RoleId(4), RoleId(1)]), Ok(vec![RoleId(1), RoleId(4)]));
+        assert!(t.next_round(&[RoleId(1)]).unwrap_err().contains("Match 2"));
+        assert!(t.next_round(&[RoleId(1), RoleId(2), RoleId(3)]).unwrap_err().contains("one winner"));
+        assert!(t.next_round(&[RoleId(1), RoleId(3), RoleId(9)]).unwrap_err().contains("not playing"));
+    }
+}
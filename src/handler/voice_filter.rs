@@ -0,0 +1,28 @@
+//! The first check of every voice event. Most events are in channels the bot doesn't
+//! manage, this tells them apart with `DashMap` lookups only, so they never wait on a
+//! lock or the database. `benches/voice_fast_path.rs` measures this same code.
+
+use dashmap::DashMap;
+use serenity::model::id::{ChannelId, GuildId};
+
+/// Whether a move from `left` to `joined` touches a temp channel or joins the creator
+/// channel. `creator_channels` has the creator channel of each guild that set one,
+/// `fallback` is the one from the config file.
+pub fn concerns_us<V>(
+    temp_channels: &DashMap<ChannelId, V>,
+    creator_channels: &DashMap<GuildId, ChannelId>,
+    fallback: Option<ChannelId>,
+    guild_id: Option<GuildId>,
+    left: Option<ChannelId>,
+    joined: Option<ChannelId>,
+) -> bool {
+    if left.is_some_and(|id| temp_channels.contains_key(&id)) {
+        return true;
+    }
+    let Some(joined) = joined else { return false };
+    if temp_channels.contains_key(&joined) {
+        return true;
+    }
+    let creator = guild_id.and_then(|id| creator_channels.get(&id).map(|c| *c)).or(fallback);
+    guild_id.is_some() && creator == Some(joined)
+}
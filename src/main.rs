@@ -1,52 +1,205 @@
 mod handler;
 mod commands;
+mod config;
+mod db;
+mod error;
 mod utils;
 
-use std::env;
-use serenity::prelude::*;
-use serenity::model::prelude::ChannelId;
+#[cfg(all(test, feature = "mock"))]
+#[path = "../tests/mock_http.rs"]
+mod mock_http;
+
+use serenity::{client::ClientBuilder, http::HttpBuilder, prelude::*};
 use dotenv::dotenv;
+use config::Config;
 use handler::Handler;
+use std::env;
+use tracing::{error, info, warn};
+use tracing_subscriber::prelude::*;
 
 #[tokio::main]
 async fn main() {
-    // Initialize logger
-    tracing_subscriber::fmt::init();
-
     // Load .env file
     dotenv().ok();
+
+    // Error tracking, stays off without a DSN
+    let sentry_dsn = env::var("SENTRY_DSN").ok();
+    let _sentry = sentry::init((
+        sentry_dsn.clone(),
+        sentry::ClientOptions {
+            release: sentry::release_name!(),
+            traces_sample_rate: 0.1,
+            ..Default::default()
+        },
+    ));
+
+    // Initialize logger, error! events also go to Sentry
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer())
+        .with(sentry::integrations::tracing::layer())
+        .init();
+
+    if sentry_dsn.is_none() {
+        warn!("SENTRY_DSN is niet ingesteld, error tracking staat uit");
+    }
     
-    // Get bot token
-    let token = env::var("DISCORD_TOKEN")
-        .expect("Token niet gevonden");
-    
-    // Get channel IDs
-    let creator_channel_id = ChannelId(
-        env::var("CREATOR_CHANNEL_ID")
-            .expect("Creator channel ID niet gevonden")
-            .parse()
-            .expect("Invalid channel ID")
-    );
+    // Load config.toml and env vars (env vars take precedence)
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            error!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if config.creator_channel_id.is_none() {
+        info!("Geen CREATOR_CHANNEL_ID ingesteld, een admin kan /setup draaien in de server");
+    }
+
+    // Set intents (GUILD_PRESENCES and GUILD_MEMBERS are privileged and must be enabled
+    // in the Developer Portal, they're needed for activity-based channel names and for
+    // handing over channels when their owner leaves the server)
+    let mut intents = GatewayIntents::GUILDS 
+        | GatewayIntents::GUILD_VOICE_STATES
+        | GatewayIntents::GUILD_PRESENCES
+        | GatewayIntents::GUILD_MEMBERS
+        | GatewayIntents::GUILD_SCHEDULED_EVENTS;
+
+    // MESSAGE_CONTENT is privileged too, prefix commands only work once it's enabled
+    // in the Developer Portal (see commands::prefix)
+    if let Some(prefix) = &config.command_prefix {
+        intents |= GatewayIntents::GUILD_MESSAGES | GatewayIntents::MESSAGE_CONTENT;
+        info!("Prefix commands staan aan met prefix '{}'", prefix);
+    }
 
-    let waiting_room_id = ChannelId(
-        env::var("WAITING_ROOM_ID")
-            .expect("Waiting room ID niet gevonden")
-            .parse()
-            .expect("Invalid channel ID")
+    // Serenity only reports a bad token once the gateway refuses it
+    if let Err(e) = utils::validate_token(&config.token) {
+        error!("Ongeldige DISCORD_TOKEN: {}", e);
+        std::process::exit(1);
+    }
+
+    // Containers should run as a normal user, see the Dockerfile
+    match utils::effective_uid() {
+        Some(0) => warn!("Bot draait als root, gebruik liever een gewone gebruiker"),
+        Some(uid) => info!("Bot draait als uid {}", uid),
+        None => {}
+    }
+
+    // Open the database and run pending migrations
+    let db = match db::init(&config.database_url, config.db_pool_size).await {
+        Ok(db) => db,
+        Err(e) => {
+            error!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let handler = Handler::new(
+        config.creator_channel_id,
+        config.waiting_room_id,
+        config.command_prefix.clone(),
+        config.http_timeout,
+        config.mute_batch,
+        config.dev,
+        db,
     );
+    match handler.load_guild_configs().await {
+        Ok(count) => info!("{} guild configs geladen", count),
+        Err(e) => {
+            error!("{}", e);
+            std::process::exit(1);
+        }
+    }
+    match handler.load_watched_users().await {
+        Ok(count) => info!("{} gevolgde gebruikers geladen", count),
+        Err(e) => {
+            error!("{}", e);
+            std::process::exit(1);
+        }
+    }
+    match handler.load_announce_opt_outs().await {
+        Ok(count) => info!("{} gebruikers staan niet in aankondigingen", count),
+        Err(e) => {
+            error!("{}", e);
+            std::process::exit(1);
+        }
+    }
 
-    // Set intents
-    let intents = GatewayIntents::GUILDS 
-        | GatewayIntents::GUILD_VOICE_STATES;
+    // No REST call may hang forever, the circuit breaker also times out the calls it wraps
+    let http_client = reqwest::Client::builder()
+        .use_rustls_tls()
+        .timeout(config.http_timeout)
+        .build()
+        .expect("Error bij maken HTTP client");
+    let http = HttpBuilder::new(&config.token).client(http_client).build();
+
+    // Check the configured channels over REST and exit, without opening the gateway
+    if env::args().any(|arg| arg == "--validate-only") {
+        let guild_ids = match http.get_guilds(None, None).await {
+            Ok(guilds) => guilds.into_iter().map(|g| g.id).collect::<Vec<_>>(),
+            Err(e) => {
+                error!("Kon guilds niet ophalen: {:?}", e);
+                std::process::exit(1);
+            }
+        };
+
+        match handler.validate_channels(&http, &guild_ids).await {
+            Ok(true) => {
+                info!("✓ Configuratie is geldig");
+                std::process::exit(0);
+            }
+            Ok(false) => std::process::exit(1),
+            Err(e) => {
+                error!("Kon kanalen niet controleren: {:?}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    // Bot owners may use the owner-only prefix commands
+    #[cfg(feature = "framework")]
+    let framework = match &config.command_prefix {
+        Some(prefix) => {
+            let owners = match http.get_current_application_info().await {
+                Ok(info) => {
+                    let mut owners: std::collections::HashSet<_> = info
+                        .team
+                        .map(|team| team.members.into_iter().map(|m| m.user.id).collect())
+                        .unwrap_or_default();
+                    owners.insert(info.owner.id);
+                    owners
+                }
+                Err(e) => {
+                    warn!("Kon bot eigenaren niet ophalen: {:?}", e);
+                    Default::default()
+                }
+            };
+            Some(commands::framework::standard_framework(prefix, owners))
+        }
+        None => None,
+    };
 
     // Create client
-    let mut client = Client::builder(&token, intents)
-        .event_handler(Handler::new(creator_channel_id, waiting_room_id))
-        .await
-        .expect("Error bij maken client");
+    #[cfg(feature = "framework")]
+    let builder = ClientBuilder::new_with_http(http, intents)
+        .type_map_insert::<commands::framework::HandlerKey>(handler.clone())
+        .event_handler(handler);
+    #[cfg(not(feature = "framework"))]
+    let builder = ClientBuilder::new_with_http(http, intents).event_handler(handler);
+
+    #[cfg(feature = "framework")]
+    let builder = match framework {
+        Some(framework) => builder.framework(framework),
+        None => builder,
+    };
+
+    #[cfg(feature = "sound")]
+    let builder = songbird::SerenityInit::register_songbird(builder);
+
+    let mut client = builder.await.expect("Error bij maken client");
 
-    // Start bot
-    if let Err(why) = client.start().await {
+    // Start bot, with as many shards as Discord recommends for the number of guilds
+    if let Err(why) = client.start_autosharded().await {
         println!("Client error: {:?}", why);
     }
 }
\ No newline at end of file
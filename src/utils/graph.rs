@@ -0,0 +1,139 @@
+use serenity::model::id::{ChannelId, GuildId, UserId};
+use std::{collections::BTreeMap, fmt::Write};
+
+/// A temp channel as `/channel_graph` draws it, with the names looked up in the cache.
+#[derive(Debug, Clone)]
+pub struct GraphChannel {
+    pub id: ChannelId,
+    pub name: String,
+    pub category: Option<(ChannelId, String)>,
+    pub owner: (UserId, String),
+    pub text_channel: Option<(ChannelId, String)>,
+    /// Channels `/link_channels` merged into this one, they're deleted by now.
+    pub merged: Vec<ChannelId>,
+    /// Listed in the announce channel of the guild `/mirror` points at.
+    pub mirrored: bool,
+    pub pending_deletion: bool,
+}
+
+/// Mermaid quotes labels with `"`, which can't be escaped with a backslash.
+fn label(text: &str) -> String {
+    text.replace('"', "#quot;")
+}
+
+/// Mermaid flowchart of `channels`: owners point at their channels, channels are
+/// grouped by category, links are dashed and channels about to be deleted are red.
+pub fn mermaid(channels: &[GraphChannel], mirror: Option<(GuildId, &str)>) -> String {
+    let mut graph = String::from("flowchart LR\n");
+
+    let mut categories: BTreeMap<ChannelId, (&str, Vec<&GraphChannel>)> = BTreeMap::new();
+    for channel in channels {
+        match &channel.category {
+            Some((id, name)) => categories.entry(*id).or_insert((name, Vec::new())).1.push(channel),
+            None => {
+                let _ = writeln!(graph, "    ch_{}[\"🔊 {}\"]", channel.id, label(&channel.name));
+            }
+        }
+    }
+    for (id, (name, members)) in &categories {
+        let _ = writeln!(graph, "    subgraph cat_{}[\"{}\"]", id, label(name));
+        for channel in members {
+            let _ = writeln!(graph, "        ch_{}[\"🔊 {}\"]", channel.id, label(&channel.name));
+        }
+        graph.push_str("    end\n");
+    }
+
+    let owners: BTreeMap<UserId, &str> = channels.iter().map(|c| (c.owner.0, c.owner.1.as_str())).collect();
+    for (id, name) in &owners {
+        let _ = writeln!(graph, "    user_{}([\"{}\"])", id, label(name));
+    }
+    if let Some((guild_id, name)) = mirror {
+        let _ = writeln!(graph, "    guild_{}[[\"{}\"]]", guild_id, label(name));
+    }
+
+    for channel in channels {
+        let _ = writeln!(graph, "    user_{} -->|owns| ch_{}", channel.owner.0, channel.id);
+        if let Some((id, name)) = &channel.text_channel {
+            let _ = writeln!(graph, "    ch_{} -.- txt_{}[\"# {}\"]", channel.id, id, label(name));
+        }
+        for merged in &channel.merged {
+            let _ = writeln!(
+                graph,
+                "    merged_{}[\"deleted {}\"] -.->|merged| ch_{}",
+                merged, merged, channel.id
+            );
+        }
+        if let (true, Some((guild_id, _))) = (channel.mirrored, mirror) {
+            let _ = writeln!(graph, "    ch_{} -.->|mirror| guild_{}", channel.id, guild_id);
+        }
+    }
+
+    let pending: Vec<String> = channels
+        .iter()
+        .filter(|c| c.pending_deletion)
+        .map(|c| format!("ch_{}", c.id))
+        .collect();
+    if !pending.is_empty() {
+        graph.push_str("    classDef pending fill:#f88,stroke:#c00\n");
+        let _ = writeln!(graph, "    class {} pending", pending.join(","));
+    }
+    graph
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn channel(id: u64, owner: u64) -> GraphChannel {
+        GraphChannel {
+            id: ChannelId(id),
+            name: format!("Lobby {}", id),
+            category: None,
+            owner: (UserId(owner), format!("user{}", owner)),
+            text_channel: None,
+            merged: Vec::new(),
+            mirrored: false,
+            pending_deletion: false,
+        }
+    }
+
+    #[test]
+    fn channels_are_grouped_by_category() {
+        let mut games = channel(10, 1);
+        games.category = Some((ChannelId(100), "Games".to_string()));
+        let graph = mermaid(&[games, channel(11, 1)], None);
+
+        assert!(graph.starts_with("flowchart LR\n"));
+        assert!(graph.contains("    subgraph cat_100[\"Games\"]\n        ch_10[\"🔊 Lobby 10\"]\n    end\n"));
+        assert!(graph.contains("    ch_11[\"🔊 Lobby 11\"]\n"));
+        assert_eq!(graph.matches("user_1([").count(), 1);
+        assert!(graph.contains("user_1 -->|owns| ch_10"));
+        assert!(graph.contains("user_1 -->|owns| ch_11"));
+        assert!(!graph.contains("classDef"));
+    }
+
+    #[test]
+    fn links_are_dashed_and_pending_deletions_red() {
+        let mut linked = channel(10, 1);
+        linked.text_channel = Some((ChannelId(20), "lobby-chat".to_string()));
+        linked.merged = vec![ChannelId(30)];
+        linked.mirrored = true;
+        let mut pending = channel(11, 2);
+        pending.pending_deletion = true;
+        let graph = mermaid(&[linked, pending, channel(12, 3)], Some((GuildId(99), "Partner")));
+
+        assert!(graph.contains("ch_10 -.- txt_20[\"# lobby-chat\"]"));
+        assert!(graph.contains("merged_30[\"deleted 30\"] -.->|merged| ch_10"));
+        assert!(graph.contains("guild_99[[\"Partner\"]]"));
+        assert!(graph.contains("ch_10 -.->|mirror| guild_99"));
+        assert!(!graph.contains("ch_11 -.->|mirror|"));
+        assert!(graph.ends_with("    class ch_11 pending\n"));
+    }
+
+    #[test]
+    fn quotes_in_names_are_escaped() {
+        let mut quoted = channel(10, 1);
+        quoted.name = "The \"best\" lobby".to_string();
+        assert!(mermaid(&[quoted], None).contains("ch_10[\"🔊 The #quot;best#quot; lobby\"]"));
+    }
+}
@@ -1,16 +1,1573 @@
-use serenity::{model::prelude::*, prelude::*};
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use reqwest::header::{HeaderMap, HeaderValue};
+use regex::Regex;
+use serenity::{
+    builder::{CreateChannel, CreateEmbed, CreateMessage},
+    http::{error::Error as HttpError, request::RequestBuilder, routing::RouteInfo, Http},
+    json::hashmap_to_json_map,
+    model::prelude::*,
+    prelude::*,
+};
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    future::Future,
+    sync::{Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+use tracing::{error, info, warn, Instrument, Span};
 
-pub async fn check_permissions(ctx: &Context, guild_id: GuildId) -> bool {
-    let guild = match guild_id.to_guild_cached(&ctx.cache) {
-        Some(guild) => guild,
-        None => return false,
-    };
+use crate::{config::GuildConfig, error::BotError};
+
+pub mod graph;
+
+/// Everything the bot needs in a guild: creating and deleting channels, moving members
+/// into them and managing their permission overwrites.
+pub fn required_permissions() -> Permissions {
+    Permissions::CONNECT
+        | Permissions::MOVE_MEMBERS
+        | Permissions::MANAGE_CHANNELS
+        | Permissions::VIEW_CHANNEL
+        | Permissions::MANAGE_ROLES
+}
 
+/// The part of `required_permissions()` that `granted` lacks.
+pub fn missing_permissions(granted: Permissions) -> Permissions {
+    if granted.administrator() {
+        return Permissions::empty();
+    }
+    required_permissions() - granted
+}
+
+/// What the bot lacks of `required_permissions()` in `guild_id`, `None` if the guild
+/// isn't cached or the bot's member can't be fetched.
+pub async fn check_permissions(ctx: &Context, guild_id: GuildId) -> Option<Permissions> {
+    let guild = guild_id.to_guild_cached(&ctx.cache)?;
     let bot_user_id = ctx.cache.current_user_id();
-    let bot_member = match guild.member(&ctx.http, bot_user_id).await {
-        Ok(member) => member,
-        Err(_) => return false,
+    let bot_member = guild.member(&ctx.http, bot_user_id).await.ok()?;
+    bot_member.permissions(&ctx.cache).ok().map(missing_permissions)
+}
+
+#[derive(serde::Serialize)]
+struct WebhookMessage<'a> {
+    username: &'a str,
+    avatar_url: String,
+    embeds: [WebhookEmbed<'a>; 1],
+}
+
+#[derive(serde::Serialize)]
+struct WebhookEmbed<'a> {
+    description: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    color: Option<u32>,
+}
+
+/// Embed with the guild's `/color` applied, every embed the bot sends starts from this.
+pub fn create_embed(config: &GuildConfig) -> CreateEmbed {
+    let mut embed = CreateEmbed::default();
+    if config.embed_color != 0 {
+        embed.colour(config.embed_color);
+    }
+    embed
+}
+
+/// Posts `message` to the guild's log webhook if one is set, otherwise as an embed in
+/// the log channel. Does nothing when neither is configured.
+pub async fn send_audit_log(
+    ctx: &Context,
+    client: &reqwest::Client,
+    config: &GuildConfig,
+    message: &str,
+) -> Result<(), SerenityError> {
+    if let Some(url) = &config.log_webhook_url {
+        let bot = ctx.cache.current_user();
+        let response = client
+            .post(url)
+            .json(&WebhookMessage {
+                username: &bot.name,
+                avatar_url: bot.face(),
+                embeds: [WebhookEmbed {
+                    description: message,
+                    color: (config.embed_color != 0).then_some(config.embed_color),
+                }],
+            })
+            .send()
+            .await?;
+
+        return if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(HttpError::from_response(response).await.into())
+        };
+    }
+
+    if let Some(channel_id) = config.log_channel_id {
+        channel_id
+            .send_message(&ctx.http, |m| {
+                let mut embed = create_embed(config);
+                embed.description(message).timestamp(Timestamp::now());
+                m.set_embed(embed)
+            })
+            .await?;
+    }
+    Ok(())
+}
+
+/// Moves a text channel into the archive category as `archive-<date>-<name>`, makes it
+/// read-only for everyone and posts a closing message.
+pub async fn archive_text_channel(
+    ctx: &Context,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+    archive_category_id: ChannelId,
+) -> Result<(), SerenityError> {
+    let name = channel_id
+        .name(&ctx.cache)
+        .await
+        .unwrap_or_else(|| channel_id.to_string());
+    let date = Timestamp::now().to_string();
+    let date = date.get(..10).unwrap_or_default();
+
+    channel_id
+        .edit(&ctx.http, |c| {
+            c.name(format!("archive-{}-{}", date, name))
+                .category(archive_category_id)
+        })
+        .await?;
+
+    create_permission(
+        &ctx.http,
+        channel_id,
+        &PermissionOverwrite {
+            kind: PermissionOverwriteType::Role(guild_id.0.into()),
+            allow: Permissions::empty(),
+            deny: Permissions::SEND_MESSAGES,
+        },
+        &AuditReason::SessionEnded,
+    )
+    .await?;
+
+    channel_id
+        .say(&ctx.http, "Session ended. This channel is now archived.")
+        .await?;
+    Ok(())
+}
+
+/// Sample rate songbird expects for raw PCM input.
+const SAMPLE_RATE: usize = 48_000;
+
+/// Builds a short "ding" as interleaved stereo f32 PCM at 48kHz.
+pub fn notification_sound() -> Vec<u8> {
+    let samples = SAMPLE_RATE / 4;
+    (0..samples)
+        .flat_map(|i| {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            let fade = 1.0 - i as f32 / samples as f32;
+            let sample = (t * 880.0 * std::f32::consts::TAU).sin() * 0.3 * fade;
+            [sample, sample]
+        })
+        .flat_map(f32::to_le_bytes)
+        .collect()
+}
+
+/// Joins `channel_id`, plays `sound_bytes` (stereo f32 PCM, see [`notification_sound`])
+/// and leaves again.
+#[cfg(feature = "sound")]
+pub async fn play_notification_sound(ctx: &Context, channel_id: ChannelId, sound_bytes: &[u8]) {
+    use songbird::input::{reader::Reader, Codec, Container, Input};
+
+    let guild_id = match channel_id.to_channel_cached(&ctx.cache).and_then(|c| c.guild()) {
+        Some(channel) => channel.guild_id,
+        None => return,
+    };
+
+    let manager = match songbird::get(ctx).await {
+        Some(manager) => manager,
+        None => {
+            warn!("Songbird is niet geregistreerd, join sound overgeslagen");
+            return;
+        }
+    };
+
+    let (call, joined) = manager.join(guild_id, channel_id).await;
+    if let Err(e) = joined {
+        warn!("Kon niet joinen voor join sound in {}: {:?}", channel_id, e);
+        let _ = manager.remove(guild_id).await;
+        return;
+    }
+
+    let input = Input::new(
+        true,
+        Reader::from_memory(sound_bytes.to_vec()),
+        Codec::FloatPcm,
+        Container::Raw,
+        None,
+    );
+    call.lock().await.play_source(input);
+
+    // 2 channels of 4-byte samples
+    let length = Duration::from_secs_f64(sound_bytes.len() as f64 / (SAMPLE_RATE * 8) as f64);
+    tokio::time::sleep(length + Duration::from_millis(250)).await;
+
+    if let Err(e) = manager.remove(guild_id).await {
+        warn!("Kon voice channel niet verlaten na join sound: {:?}", e);
+    }
+}
+
+#[cfg(not(feature = "sound"))]
+pub async fn play_notification_sound(_: &Context, channel_id: ChannelId, _: &[u8]) {
+    warn!(
+        "Join sound voor {} overgeslagen, de bot is gebouwd zonder de `sound` feature",
+        channel_id
+    );
+}
+
+/// How long `probe_voice_connection` waits for the voice connection to come up.
+pub const VOICE_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A voice connection `probe_voice_connection` made and closed again.
+#[derive(Debug)]
+pub struct VoiceProbe {
+    pub latency: Duration,
+    /// Voice server Discord assigned, like `c-fra09-1234.discord.media:443`.
+    pub endpoint: Option<String>,
+}
+
+// Only the `sound` build can fail after connecting
+#[cfg_attr(not(feature = "sound"), allow(dead_code))]
+#[derive(Debug, PartialEq)]
+pub enum VoiceProbeError {
+    /// Built without the `sound` feature, so there is no voice client.
+    #[cfg_attr(feature = "sound", allow(dead_code))]
+    NoVoiceSupport,
+    /// The `sound` feature is on but songbird isn't registered on the client.
+    NoSongbird,
+    /// The bot already has a voice connection in the guild, probing would move it.
+    Busy,
+    /// No connection within `VOICE_PROBE_TIMEOUT`.
+    TimedOut,
+    Failed(String),
+}
+
+/// Joins `channel_id`, measures how long the connection takes and leaves right away.
+#[cfg(feature = "sound")]
+pub async fn probe_voice_connection(
+    ctx: &Context,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+) -> Result<VoiceProbe, VoiceProbeError> {
+    use songbird::error::JoinError;
+
+    let manager = songbird::get(ctx).await.ok_or(VoiceProbeError::NoSongbird)?;
+    if manager.get(guild_id).is_some() {
+        return Err(VoiceProbeError::Busy);
+    }
+
+    let started = Instant::now();
+    let joined = tokio::time::timeout(VOICE_PROBE_TIMEOUT, manager.join(guild_id, channel_id)).await;
+    let latency = started.elapsed();
+    let result = match joined {
+        Err(_) | Ok((_, Err(JoinError::TimedOut))) => Err(VoiceProbeError::TimedOut),
+        Ok((_, Err(e))) => Err(VoiceProbeError::Failed(e.to_string())),
+        Ok((call, Ok(()))) => {
+            let endpoint = call.lock().await.current_connection().map(|c| c.endpoint.clone());
+            Ok(VoiceProbe { latency, endpoint })
+        }
+    };
+
+    // Also cleans up a join the timeout cut off
+    match manager.remove(guild_id).await {
+        Ok(()) | Err(JoinError::NoCall) => {}
+        Err(e) => warn!("Kon voice channel {} niet verlaten na voice test: {:?}", channel_id, e),
+    }
+    result
+}
+
+#[cfg(not(feature = "sound"))]
+pub async fn probe_voice_connection(
+    _: &Context,
+    _: GuildId,
+    _: ChannelId,
+) -> Result<VoiceProbe, VoiceProbeError> {
+    Err(VoiceProbeError::NoVoiceSupport)
+}
+
+/// Discord's limit on the number of channels in a single category.
+pub const MAX_CATEGORY_CHANNELS: u32 = 50;
+
+/// Returns `(current, max)` channel counts for a category, based on the cache.
+pub fn check_category_capacity(ctx: &Context, guild_id: GuildId, category_id: ChannelId) -> (u32, u32) {
+    let current = guild_id
+        .to_guild_cached(&ctx.cache)
+        .map(|g| {
+            g.channels
+                .values()
+                .filter(|c| matches!(c, Channel::Guild(gc) if gc.parent_id == Some(category_id)))
+                .count() as u32
+        })
+        .unwrap_or(0);
+
+    (current, MAX_CATEGORY_CHANNELS)
+}
+
+/// Whether `text` is a single Unicode emoji, including skin tones, flags, keycaps and
+/// sequences joined with ZWJ.
+pub fn is_unicode_emoji(text: &str) -> bool {
+    static EMOJI: OnceLock<Regex> = OnceLock::new();
+    let emoji = EMOJI.get_or_init(|| {
+        let single = r"\p{Extended_Pictographic}\x{FE0F}?\p{Emoji_Modifier}?";
+        Regex::new(&format!(
+            r"^(?:\p{{Regional_Indicator}}{{2}}|[#*0-9]\x{{FE0F}}?\x{{20E3}}|{single}(?:\x{{200D}}{single})*)$"
+        ))
+        .expect("emoji pattern is valid")
+    });
+    emoji.is_match(text)
+}
+
+/// Whether `text` looks like a custom emoji, `<:name:id>` or `<a:name:id>`.
+pub fn is_custom_emoji(text: &str) -> bool {
+    static CUSTOM: OnceLock<Regex> = OnceLock::new();
+    CUSTOM
+        .get_or_init(|| Regex::new(r"^<a?:\w{2,32}:\d+>$").expect("custom emoji pattern is valid"))
+        .is_match(text)
+}
+
+/// Returns `true` if `name` contains any of the banned terms (case-insensitive).
+pub fn filter_channel_name(name: &str, filter: &[String]) -> bool {
+    let name = name.to_lowercase();
+    filter
+        .iter()
+        .filter(|term| !term.is_empty())
+        .any(|term| name.contains(&term.to_lowercase()))
+}
+
+/// The game most of `presences` are playing, for `GuildConfig::use_popular_game_name`.
+/// A tie goes to `owner_id`'s game if it's one of the tied ones, else to the first name
+/// alphabetically.
+pub fn majority_game(presences: &HashMap<UserId, Presence>, owner_id: UserId) -> Option<String> {
+    let playing = |presence: &Presence| {
+        presence
+            .activities
+            .iter()
+            .find(|a| a.kind == ActivityType::Playing)
+            .map(|a| a.name.clone())
+    };
+
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for presence in presences.values() {
+        if let Some(game) = playing(presence) {
+            *counts.entry(game).or_default() += 1;
+        }
+    }
+
+    let top = *counts.values().max()?;
+    let owner_game = presences.get(&owner_id).and_then(playing);
+    match owner_game {
+        Some(game) if counts.get(&game) == Some(&top) => Some(game),
+        _ => counts.into_iter().filter(|(_, n)| *n == top).map(|(game, _)| game).min(),
+    }
+}
+
+/// Shard that Discord sends the events of `guild_id` to, `(guild_id >> 22) % total_shards`.
+pub fn shard_for_guild(guild_id: GuildId, total_shards: u64) -> u64 {
+    (guild_id.0 >> 22) % total_shards.max(1)
+}
+
+/// Effective user ID of the process, read from `/proc`, so `None` outside Linux.
+pub fn effective_uid() -> Option<u32> {
+    parse_effective_uid(&std::fs::read_to_string("/proc/self/status").ok()?)
+}
+
+/// The `Uid:` line lists the real, effective, saved and filesystem UID.
+fn parse_effective_uid(status: &str) -> Option<u32> {
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("Uid:"))?
+        .split_whitespace()
+        .nth(1)?
+        .parse()
+        .ok()
+}
+
+/// Bot tokens are around 72 characters, older ones a bit shorter.
+const TOKEN_LENGTH: std::ops::RangeInclusive<usize> = 59..=90;
+
+/// Catches a malformed `DISCORD_TOKEN` before serenity turns it into a vague gateway
+/// error. A token is `<base64 bot ID>.<timestamp>.<HMAC>`, optionally after `Bot `.
+pub fn validate_token(token: &str) -> Result<(), String> {
+    if token.trim() != token {
+        return Err("Token has leading or trailing whitespace, check DISCORD_TOKEN for stray spaces.".to_string());
+    }
+    if token.starts_with(['"', '\'']) || token.ends_with(['"', '\'']) {
+        return Err("Token is wrapped in quotes, remove them from DISCORD_TOKEN.".to_string());
+    }
+    if token.starts_with("Bearer ") {
+        return Err("This is an OAuth2 bearer token, use the token from the Bot page of the Developer Portal instead.".to_string());
+    }
+    let token = token.strip_prefix("Bot ").unwrap_or(token);
+
+    let segments: Vec<&str> = token.split('.').collect();
+    if segments.len() != 3 || segments.iter().any(|s| s.is_empty()) {
+        return Err(format!(
+            "Token should be three parts separated by dots, this one has {}. Copy it again from the Bot page \
+             of the Developer Portal, the client secret is not a bot token.",
+            segments.len()
+        ));
+    }
+    if !TOKEN_LENGTH.contains(&token.len()) {
+        return Err(format!(
+            "Token is {} characters long, expected {} to {}. It may have been cut off while copying.",
+            token.len(),
+            TOKEN_LENGTH.start(),
+            TOKEN_LENGTH.end()
+        ));
+    }
+    if let Some(c) = token.chars().find(|c| !c.is_ascii_alphanumeric() && !"-_.".contains(*c)) {
+        return Err(format!("Token contains '{}', which never appears in a bot token.", c));
+    }
+
+    let bot_id = decode_base64(segments[0])
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .and_then(|id| id.parse::<u64>().ok());
+    match bot_id {
+        // Snowflakes carry a timestamp in the upper bits, anything smaller is no real ID
+        Some(id) if id >= 1 << 22 => Ok(()),
+        _ => Err("The first part of the token is not a bot ID, check that DISCORD_TOKEN is the whole bot token.".to_string()),
+    }
+}
+
+/// Decodes unpadded base64, the URL-safe alphabet included.
+fn decode_base64(input: &str) -> Option<Vec<u8>> {
+    let value = |c: u8| match c {
+        b'A'..=b'Z' => Some(c - b'A'),
+        b'a'..=b'z' => Some(c - b'a' + 26),
+        b'0'..=b'9' => Some(c - b'0' + 52),
+        b'+' | b'-' => Some(62),
+        b'/' | b'_' => Some(63),
+        _ => None,
+    };
+
+    let mut bytes = Vec::with_capacity(input.len() * 3 / 4);
+    let (mut buffer, mut bits) = (0u32, 0);
+    for c in input.trim_end_matches('=').bytes() {
+        buffer = buffer << 6 | u32::from(value(c)?);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Some(bytes)
+}
+
+/// Tags the current Sentry scope so captured errors can be traced back to where they
+/// happened.
+pub fn tag_sentry_scope(
+    guild_id: Option<GuildId>,
+    user_id: Option<UserId>,
+    channel_id: Option<ChannelId>,
+) {
+    sentry::configure_scope(|scope| {
+        if let Some(id) = guild_id {
+            scope.set_tag("guild_id", id);
+        }
+        if let Some(id) = user_id {
+            scope.set_tag("user_id", id);
+        }
+        if let Some(id) = channel_id {
+            scope.set_tag("channel_id", id);
+        }
+    });
+}
+
+/// Furthest timezones from UTC, -12:00 and +14:00, in minutes.
+pub const UTC_OFFSET_RANGE: std::ops::RangeInclusive<i16> = -12 * 60..=14 * 60;
+
+/// Whether the guild's late-night mode is on right now, in its own timezone.
+pub fn is_late_night(guild: &GuildConfig) -> bool {
+    is_late_night_at(guild, Timestamp::now().unix_timestamp())
+}
+
+pub fn is_late_night_at(guild: &GuildConfig, unix_secs: i64) -> bool {
+    let Some(late_night) = guild.late_night_config else { return false };
+    let local = unix_secs + i64::from(guild.utc_offset_minutes) * 60;
+    let hour = local.rem_euclid(24 * 60 * 60) / (60 * 60);
+    let (start, end) = (i64::from(late_night.start_hour), i64::from(late_night.end_hour));
+    if start <= end {
+        (start..end).contains(&hour)
+    } else {
+        hour >= start || hour < end
+    }
+}
+
+/// `+02:00`, `-5`, `+5:30` or `0` as minutes ahead of UTC.
+pub fn parse_utc_offset(input: &str) -> Option<i16> {
+    let input = input.trim().trim_start_matches("UTC");
+    let (sign, rest) = match input.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, input.strip_prefix('+').unwrap_or(input)),
+    };
+    let (hours, minutes) = rest.split_once(':').unwrap_or((rest, "0"));
+    let (hours, minutes): (i16, i16) = (hours.parse().ok()?, minutes.parse().ok()?);
+    if !(0..60).contains(&minutes) {
+        return None;
+    }
+    let offset = sign * (hours.checked_mul(60)? + minutes);
+    UTC_OFFSET_RANGE.contains(&offset).then_some(offset)
+}
+
+/// "23:00-06:00 UTC+02:00, 3 channels" or "off", for settings overviews.
+pub fn late_night_label(guild: &GuildConfig) -> String {
+    match guild.late_night_config {
+        Some(l) => format!(
+            "{:02}:00-{:02}:00 UTC{}, {} channel(s)",
+            l.start_hour,
+            l.end_hour,
+            format_utc_offset(guild.utc_offset_minutes),
+            l.max_channels
+        ),
+        None => "off".to_string(),
+    }
+}
+
+/// "+02:00", "-05:30"
+pub fn format_utc_offset(minutes: i16) -> String {
+    let sign = if minutes < 0 { '-' } else { '+' };
+    format!("{}{:02}:{:02}", sign, minutes.abs() / 60, minutes.abs() % 60)
+}
+
+/// `tokio::spawn` inside the current tracing span, so the task's logs show up under the
+/// event that started it. `Span::enter` would be wrong here, its guard is held across
+/// the task's awaits.
+pub fn spawn_in_span<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    tokio::spawn(future.instrument(Span::current()))
+}
+
+/// Returns the HTTP status code of a failed Discord API call, if there is one.
+pub fn http_status(error: &SerenityError) -> Option<u16> {
+    match error {
+        SerenityError::Http(e) => e.status_code().map(|s| s.as_u16()),
+        _ => None,
+    }
+}
+
+pub fn is_not_found(error: &SerenityError) -> bool {
+    http_status(error) == Some(404)
+}
+
+/// Discord's error code for moving a member that isn't connected to voice.
+const TARGET_NOT_CONNECTED: isize = 40032;
+
+/// Returns `true` if a move failed because the member left the guild or disconnected.
+pub fn is_member_gone(error: &SerenityError) -> bool {
+    match error {
+        SerenityError::Http(e) => match e.as_ref() {
+            HttpError::UnsuccessfulRequest(response) => {
+                response.status_code.as_u16() == 404 || response.error.code == TARGET_NOT_CONNECTED
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Why a configured channel can't be used as creator channel or waiting room, given
+/// the kind of the channel with that ID in the guild (`None` if there is none).
+pub fn voice_channel_problem(kind: Option<ChannelType>) -> Option<&'static str> {
+    match kind {
+        Some(ChannelType::Voice | ChannelType::Stage) => None,
+        Some(_) => Some("is not a voice or stage channel"),
+        None => Some("does not exist"),
+    }
+}
+
+/// Endpoint for the voice channel status, serenity 0.11 has no route for it.
+const VOICE_STATUS_URL: &str = "https://discord.com/api/v10/channels";
+
+#[derive(serde::Serialize)]
+struct VoiceStatus<'a> {
+    status: Option<&'a str>,
+}
+
+/// Sets the status shown under a voice channel in the sidebar, `None` clears it.
+pub async fn set_voice_status(
+    client: &reqwest::Client,
+    http: &Http,
+    channel_id: ChannelId,
+    status: Option<&str>,
+) -> Result<(), SerenityError> {
+    let response = client
+        .put(format!("{}/{}/voice-status", VOICE_STATUS_URL, channel_id))
+        .header("Authorization", &http.token)
+        .json(&VoiceStatus { status })
+        .send()
+        .await?;
+
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(HttpError::from_response(response).await.into())
+    }
+}
+
+/// Reason shown in the server's audit log for the channel changes the bot makes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuditReason {
+    /// Temp channel, its text channel or its waiting room access, for this member.
+    TempChannel(String),
+    /// Everyone left and the channel stayed empty for this long.
+    Empty(Duration),
+    /// The owner left before anyone else joined, or before being moved in.
+    OwnerLeft,
+    /// The owner created a new channel, which replaces the old one.
+    Replaced,
+    /// The creator channel's category is full.
+    CategoryFull,
+    /// Text channel deleted or archived because its voice channel is gone.
+    SessionEnded,
+    /// Permission change the channel owner asked for with this command.
+    Command(&'static str),
+    /// A `/visit` grant was used or expired.
+    VisitEnded,
+    /// Everyone was moved to another temp channel with `/link_channels`.
+    Merged,
+    /// The channel's ghost period is over, see `GuildConfig::ghost_period_secs`.
+    GhostPeriodEnded,
+    /// `/auto_lock` locked the channel at this member count.
+    AutoLock(u32),
+    /// Match channels and category of the `/tournament` with this name.
+    Tournament(String),
+    /// A periodic check found the owner's permissions missing and restored them.
+    Reconciled,
+    /// Test channel of `/diagnostics`.
+    Diagnostics,
+    /// Channel of the scheduled event with this name.
+    Event(String),
+}
+
+impl fmt::Display for AuditReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AuditReason::TempChannel(name) => write!(f, "Temporary channel for @{}", name),
+            AuditReason::Empty(delay) => write!(f, "Temporary channel empty for {}s", delay.as_secs()),
+            AuditReason::OwnerLeft => write!(f, "Owner left"),
+            AuditReason::Replaced => write!(f, "Owner created a new temporary channel"),
+            AuditReason::CategoryFull => write!(f, "Temporary channel category is full"),
+            AuditReason::SessionEnded => write!(f, "Temporary channel session ended"),
+            AuditReason::Command(command) => write!(f, "Requested by the channel owner with {}", command),
+            AuditReason::VisitEnded => write!(f, "Visit ended"),
+            AuditReason::Merged => write!(f, "Temporary channel merged into another"),
+            AuditReason::GhostPeriodEnded => write!(f, "Temporary channel ghost period ended"),
+            AuditReason::AutoLock(threshold) => write!(f, "Auto lock at {} members", threshold),
+            AuditReason::Tournament(name) => write!(f, "Tournament {}", name),
+            AuditReason::Reconciled => write!(f, "Restoring the temporary channel owner's permissions"),
+            AuditReason::Diagnostics => write!(f, "Bot self-test"),
+            AuditReason::Event(name) => write!(f, "Scheduled event {}", name),
+        }
+    }
+}
+
+/// Discord wants the reason URL-encoded in the `X-Audit-Log-Reason` header.
+fn reason_header(reason: &AuditReason) -> HeaderMap {
+    let encoded = utf8_percent_encode(&reason.to_string(), NON_ALPHANUMERIC).to_string();
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        "X-Audit-Log-Reason",
+        HeaderValue::from_str(&encoded).expect("percent-encoded reason is a valid header"),
+    );
+    headers
+}
+
+/// `GuildId::create_channel`, with an audit log reason.
+pub async fn create_channel(
+    http: &Http,
+    guild_id: GuildId,
+    reason: &AuditReason,
+    f: impl FnOnce(&mut CreateChannel) -> &mut CreateChannel,
+) -> Result<GuildChannel, SerenityError> {
+    let mut builder = CreateChannel::default();
+    f(&mut builder);
+    let map = hashmap_to_json_map(builder.0);
+    http.create_channel(guild_id.0, &map, Some(&reason.to_string())).await
+}
+
+// Serenity 0.11 has no reason parameter for the calls below, so they're sent by hand
+
+/// `ChannelId::delete`, with an audit log reason.
+pub async fn delete_channel(
+    http: &Http,
+    channel_id: ChannelId,
+    reason: &AuditReason,
+) -> Result<(), SerenityError> {
+    let mut request = RequestBuilder::new(RouteInfo::DeleteChannel { channel_id: channel_id.0 });
+    request.headers(Some(reason_header(reason)));
+    http.request(request.build()).await.map(|_| ())
+}
+
+/// `ChannelId::create_permission`, with an audit log reason.
+pub async fn create_permission(
+    http: &Http,
+    channel_id: ChannelId,
+    overwrite: &PermissionOverwrite,
+    reason: &AuditReason,
+) -> Result<(), SerenityError> {
+    let (target_id, kind) = overwrite_target(overwrite.kind);
+    let body = serde_json::to_vec(&serde_json::json!({
+        "allow": overwrite.allow.bits(),
+        "deny": overwrite.deny.bits(),
+        "id": target_id,
+        "type": kind,
+    }))?;
+
+    let mut request = RequestBuilder::new(RouteInfo::CreatePermission {
+        channel_id: channel_id.0,
+        target_id,
+    });
+    request.body(Some(&body)).headers(Some(reason_header(reason)));
+    http.request(request.build()).await.map(|_| ())
+}
+
+/// `ChannelId::delete_permission`, with an audit log reason.
+pub async fn delete_permission(
+    http: &Http,
+    channel_id: ChannelId,
+    kind: PermissionOverwriteType,
+    reason: &AuditReason,
+) -> Result<(), SerenityError> {
+    let (target_id, _) = overwrite_target(kind);
+    let mut request = RequestBuilder::new(RouteInfo::DeletePermission {
+        channel_id: channel_id.0,
+        target_id,
+    });
+    request.headers(Some(reason_header(reason)));
+    http.request(request.build()).await.map(|_| ())
+}
+
+// Serenity 0.11 drops channel flags it doesn't know when deserializing, so these are
+// read and written as raw bits
+
+/// Channel flags by the name Discord gives them, see `/flags`.
+pub const CHANNEL_FLAGS: [(&str, u64); 3] = [
+    ("PINNED", 1 << 1),
+    ("REQUIRE_TAG", 1 << 4),
+    ("HIDE_MEDIA_DOWNLOAD_OPTIONS", 1 << 15),
+];
+
+/// The flags of `CHANNEL_FLAGS` that Discord accepts on voice channels.
+pub const VOICE_CHANNEL_FLAGS: [&str; 1] = ["HIDE_MEDIA_DOWNLOAD_OPTIONS"];
+
+/// Names of the flags set in `bits`, unknown bits as their value.
+pub fn flag_names(bits: u64) -> Vec<String> {
+    let known: u64 = CHANNEL_FLAGS.iter().map(|(_, bit)| bit).sum();
+    let mut names: Vec<String> = CHANNEL_FLAGS
+        .iter()
+        .filter(|(_, bit)| bits & bit != 0)
+        .map(|(name, _)| name.to_string())
+        .collect();
+    names.extend((0..64).map(|i| 1u64 << i).filter(|bit| bits & bit & !known != 0).map(|bit| bit.to_string()));
+    names
+}
+
+/// Name and bit of a flag owners may toggle on their voice channel, case-insensitive.
+pub fn voice_channel_flag(name: &str) -> Result<(&'static str, u64), String> {
+    let name = name.trim().to_uppercase();
+    match CHANNEL_FLAGS.iter().find(|(flag, _)| *flag == name) {
+        Some((flag, bit)) if VOICE_CHANNEL_FLAGS.contains(flag) => Ok((flag, *bit)),
+        Some((flag, _)) => Err(format!(
+            "`{}` doesn't apply to voice channels, try {}.",
+            flag,
+            VOICE_CHANNEL_FLAGS.join(", ")
+        )),
+        None => Err(format!("`{}` is not a channel flag.", name)),
+    }
+}
+
+/// The `flags` of a channel as Discord sends them.
+pub async fn channel_flags(http: &Http, channel_id: ChannelId) -> Result<u64, SerenityError> {
+    let request = RequestBuilder::new(RouteInfo::GetChannel { channel_id: channel_id.0 });
+    let channel: serde_json::Value = http.request(request.build()).await?.json().await?;
+    Ok(channel.get("flags").and_then(serde_json::Value::as_u64).unwrap_or(0))
+}
+
+/// Replaces the `flags` of a channel, with an audit log reason.
+pub async fn set_channel_flags(
+    http: &Http,
+    channel_id: ChannelId,
+    flags: u64,
+    reason: &AuditReason,
+) -> Result<(), SerenityError> {
+    let body = serde_json::to_vec(&serde_json::json!({ "flags": flags }))?;
+    let mut request = RequestBuilder::new(RouteInfo::EditChannel { channel_id: channel_id.0 });
+    request.body(Some(&body)).headers(Some(reason_header(reason)));
+    http.request(request.build()).await.map(|_| ())
+}
+
+/// Target ID and the overwrite type Discord expects, 0 for roles and 1 for members.
+fn overwrite_target(kind: PermissionOverwriteType) -> (u64, u8) {
+    match kind {
+        PermissionOverwriteType::Role(id) => (id.0, 0),
+        PermissionOverwriteType::Member(id) => (id.0, 1),
+        _ => (0, 0),
+    }
+}
+
+/// How long a Discord API call may take before it's given up on, unless
+/// `HTTP_TIMEOUT_SECS` says otherwise.
+pub const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Calls go through normally.
+    Closed,
+    /// Too many failures, calls are rejected until the cooldown has passed.
+    Open,
+    /// Cooldown has passed, a single test call is let through.
+    HalfOpen,
+}
+
+#[derive(Debug)]
+struct CircuitInner {
+    state: CircuitState,
+    failures: u32,
+    opened_at: Option<Instant>,
+    trial_in_flight: bool,
+}
+
+/// Stops calling the Discord API after repeated server errors, so a Discord outage
+/// doesn't pile up retries. See [`CircuitState`] for the states.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    inner: Mutex<CircuitInner>,
+    failure_threshold: u32,
+    cooldown: Duration,
+    /// Calls that take longer than this fail with `BotError::Timeout`.
+    timeout: Duration,
+}
+
+impl CircuitBreaker {
+    /// Opens after 5 failures in a row and tries again after 30 seconds.
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self::new(5, Duration::from_secs(30), timeout)
+    }
+
+    pub fn new(failure_threshold: u32, cooldown: Duration, timeout: Duration) -> Self {
+        Self {
+            inner: Mutex::new(CircuitInner {
+                state: CircuitState::Closed,
+                failures: 0,
+                opened_at: None,
+                trial_in_flight: false,
+            }),
+            failure_threshold,
+            cooldown,
+            timeout,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn state(&self) -> CircuitState {
+        self.lock().state
+    }
+
+    /// Runs `call` unless the circuit is open. Only server errors, failed requests and
+    /// timeouts count as failures, a 4xx means Discord is up and just didn't like the
+    /// request.
+    pub async fn call<T, F>(&self, call: F) -> Result<T, BotError>
+    where
+        F: Future<Output = Result<T, SerenityError>>,
+    {
+        if !self.try_acquire() {
+            return Err(BotError::CircuitOpen);
+        }
+
+        match tokio::time::timeout(self.timeout, call).await {
+            Ok(Ok(value)) => {
+                self.record_success();
+                Ok(value)
+            }
+            Ok(Err(e)) => {
+                if is_server_error(&e) {
+                    self.record_failure();
+                } else {
+                    self.record_success();
+                }
+                Err(BotError::Discord(e))
+            }
+            Err(_) => {
+                warn!("Discord API call duurde langer dan {:?}", self.timeout);
+                self.record_failure();
+                Err(BotError::Timeout)
+            }
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CircuitInner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn try_acquire(&self) -> bool {
+        let mut inner = self.lock();
+        match inner.state {
+            CircuitState::Closed => true,
+            CircuitState::Open => {
+                let cooled_down = inner
+                    .opened_at
+                    .is_none_or(|at| at.elapsed() >= self.cooldown);
+                if cooled_down {
+                    info!("Circuit breaker: Open -> HalfOpen, test call toegestaan");
+                    inner.state = CircuitState::HalfOpen;
+                    inner.trial_in_flight = true;
+                }
+                cooled_down
+            }
+            CircuitState::HalfOpen if inner.trial_in_flight => false,
+            CircuitState::HalfOpen => {
+                inner.trial_in_flight = true;
+                true
+            }
+        }
+    }
+
+    fn record_success(&self) {
+        let mut inner = self.lock();
+        if inner.state != CircuitState::Closed {
+            info!("Circuit breaker: {:?} -> Closed", inner.state);
+        }
+        inner.state = CircuitState::Closed;
+        inner.failures = 0;
+        inner.opened_at = None;
+        inner.trial_in_flight = false;
+    }
+
+    fn record_failure(&self) {
+        let mut inner = self.lock();
+        inner.failures += 1;
+        inner.trial_in_flight = false;
+        warn!(
+            failures = inner.failures,
+            threshold = self.failure_threshold,
+            "Circuit breaker: Discord API call mislukt"
+        );
+
+        let trips = inner.state == CircuitState::HalfOpen || inner.failures >= self.failure_threshold;
+        if trips && inner.state != CircuitState::Open {
+            error!("Circuit breaker: {:?} -> Open voor {:?}", inner.state, self.cooldown);
+            inner.state = CircuitState::Open;
+            inner.opened_at = Some(Instant::now());
+        }
+    }
+}
+
+/// Keeps track of the guilds where the bot lacks `required_permissions()`. Temp channels
+/// are turned off there until the permissions are back.
+#[derive(Debug, Default)]
+pub struct BotPermissionChecker {
+    disabled: Mutex<HashMap<GuildId, Permissions>>,
+}
+
+impl BotPermissionChecker {
+    /// Records what the bot is missing in `guild_id`. Returns it only when the guild was
+    /// enabled until now, so the owner is told once and not on every check.
+    pub fn update(&self, guild_id: GuildId, missing: Permissions) -> Option<Permissions> {
+        let mut disabled = self.disabled.lock().unwrap();
+
+        if missing.is_empty() {
+            if disabled.remove(&guild_id).is_some() {
+                info!("✓ Permissies van guild {} weer in orde, tijdelijke kanalen aan", guild_id);
+            }
+            return None;
+        }
+        match disabled.insert(guild_id, missing) {
+            None => Some(missing),
+            Some(_) => None,
+        }
+    }
+
+    pub fn is_disabled(&self, guild_id: GuildId) -> bool {
+        self.disabled.lock().unwrap().contains_key(&guild_id)
+    }
+}
+
+/// How often `Cooldown` drops entries that have run out.
+const COOLDOWN_CLEANUP_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Per-user cooldowns for commands that edit the channel, so users hit our limit with a
+/// clear message before the bot hits Discord's rate limits.
+#[derive(Debug)]
+pub struct Cooldown {
+    durations: HashMap<&'static str, Duration>,
+    state: Mutex<CooldownState>,
+}
+
+#[derive(Debug)]
+struct CooldownState {
+    /// When each user last used each command.
+    last_used: HashMap<(UserId, &'static str), Instant>,
+    last_cleanup: Instant,
+}
+
+impl Cooldown {
+    pub fn new(durations: &[(&'static str, Duration)]) -> Self {
+        Self {
+            durations: durations.iter().copied().collect(),
+            state: Mutex::new(CooldownState {
+                last_used: HashMap::new(),
+                last_cleanup: Instant::now(),
+            }),
+        }
+    }
+
+    /// Returns the time left if `user_id` is still on cooldown for `command`, otherwise
+    /// starts a new cooldown. Commands without a cooldown always return `None`.
+    pub fn check_and_set(&self, user_id: UserId, command: &str) -> Option<Duration> {
+        self.check_and_set_at(user_id, command, Instant::now())
+    }
+
+    fn check_and_set_at(&self, user_id: UserId, command: &str, now: Instant) -> Option<Duration> {
+        let (&command, &duration) = self.durations.get_key_value(command)?;
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        if now.duration_since(state.last_cleanup) >= COOLDOWN_CLEANUP_INTERVAL {
+            let durations = &self.durations;
+            state
+                .last_used
+                .retain(|(_, command), used| now.duration_since(*used) < durations[command]);
+            state.last_cleanup = now;
+        }
+
+        if let Some(used) = state.last_used.get(&(user_id, command)) {
+            let elapsed = now.duration_since(*used);
+            if elapsed < duration {
+                return Some(duration - elapsed);
+            }
+        }
+        state.last_used.insert((user_id, command), now);
+        None
+    }
+
+    /// Clears every cooldown of `user_id`. Returns the ones that were still running
+    /// and the time they had left, by command name.
+    pub fn reset(&self, user_id: UserId) -> Vec<(&'static str, Duration)> {
+        self.reset_at(user_id, Instant::now())
+    }
+
+    fn reset_at(&self, user_id: UserId, now: Instant) -> Vec<(&'static str, Duration)> {
+        let cleared = self.remaining_at(user_id, now);
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.last_used.retain(|(user, _), _| *user != user_id);
+        cleared
+    }
+
+    /// The cooldowns `user_id` is on and the time they have left, by command name.
+    pub fn remaining(&self, user_id: UserId) -> Vec<(&'static str, Duration)> {
+        self.remaining_at(user_id, Instant::now())
+    }
+
+    fn remaining_at(&self, user_id: UserId, now: Instant) -> Vec<(&'static str, Duration)> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let mut remaining: Vec<(&'static str, Duration)> = state
+            .last_used
+            .iter()
+            .filter(|((user, _), _)| *user == user_id)
+            .map(|((_, command), used)| {
+                (*command, self.durations[command].saturating_sub(now.duration_since(*used)))
+            })
+            .filter(|(_, left)| !left.is_zero())
+            .collect();
+        remaining.sort();
+        remaining
+    }
+}
+
+/// Random (v4) ID that ties together the log lines of one temp channel, from the
+/// creator channel join to its deletion.
+pub fn new_correlation_id() -> Uuid {
+    uuid::Builder::from_random_bytes(rand::random()).into_uuid()
+}
+
+/// When the bot first connected to Discord. Set on the first `ready`, reconnects
+/// don't reset it.
+#[derive(Debug, Default)]
+pub struct BotUptime {
+    connected_at: OnceLock<Instant>,
+}
+
+impl BotUptime {
+    pub fn connected(&self) {
+        self.connected_at.get_or_init(Instant::now);
+    }
+
+    /// Zero until the bot has connected.
+    pub fn elapsed(&self) -> Duration {
+        self.connected_at.get().map_or(Duration::ZERO, Instant::elapsed)
+    }
+}
+
+/// "3d 14h 22m 5s", leading zero units are left out.
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let units = [
+        (secs / (24 * 60 * 60), "d"),
+        (secs / (60 * 60) % 24, "h"),
+        (secs / 60 % 60, "m"),
+        (secs % 60, "s"),
+    ];
+    let first = units.iter().position(|(value, _)| *value > 0).unwrap_or(units.len() - 1);
+    units[first..]
+        .iter()
+        .map(|(value, unit)| format!("{}{}", value, unit))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Discord cuts embed field values off at 1024 characters.
+pub const MAX_FIELD_LEN: usize = 1024;
+
+/// `items` joined by `separator`, cut off with "and N more" so they fit in `max_len`.
+pub fn fit_list(items: &[String], separator: &str, max_len: usize) -> String {
+    let more = |n: usize| match separator {
+        "\n" => format!("\nand {} more", n),
+        _ => format!(" and {} more", n),
     };
+    let mut list = String::new();
+    for (i, item) in items.iter().enumerate() {
+        let separator = if list.is_empty() { "" } else { separator };
+        // Room for the longest possible "and N more" after this item
+        if list.len() + separator.len() + item.len() + more(items.len()).len() > max_len {
+            list.push_str(&more(items.len() - i));
+            break;
+        }
+        list.push_str(separator);
+        list.push_str(item);
+    }
+    list
+}
+
+/// Sliding window limit on DMs per user, so features that DM people can't get the
+/// bot rate limited or flagged as spam.
+#[derive(Debug)]
+pub struct DmThrottle {
+    sent: Mutex<HashMap<UserId, VecDeque<Instant>>>,
+    max_per_window: usize,
+    window: Duration,
+}
+
+impl Default for DmThrottle {
+    fn default() -> Self {
+        Self::new(5, Duration::from_secs(10 * 60))
+    }
+}
+
+impl DmThrottle {
+    pub fn new(max_per_window: usize, window: Duration) -> Self {
+        Self {
+            sent: Mutex::new(HashMap::new()),
+            max_per_window,
+            window,
+        }
+    }
+
+    /// Records a DM to `user_id` at `now` if it fits in the window.
+    fn try_record(&self, user_id: UserId, now: Instant) -> bool {
+        let mut sent = self.sent.lock().unwrap_or_else(|e| e.into_inner());
+        let window = self.window;
+
+        sent.retain(|_, times| {
+            while times.front().is_some_and(|t| now.duration_since(*t) >= window) {
+                times.pop_front();
+            }
+            !times.is_empty()
+        });
+
+        let times = sent.entry(user_id).or_default();
+        if times.len() >= self.max_per_window {
+            return false;
+        }
+        times.push_back(now);
+        true
+    }
+
+    /// DMs `content` to `user_id`, or skips it with a warning if they've had too many.
+    /// Returns whether the DM was sent.
+    pub async fn send(
+        &self,
+        http: &Http,
+        user_id: UserId,
+        content: impl std::fmt::Display,
+    ) -> Result<bool, SerenityError> {
+        self.send_message(http, user_id, |m| m.content(content)).await
+    }
+
+    /// Like [`DmThrottle::send`], for DMs that need more than text, e.g. buttons.
+    pub async fn send_message<'a, F>(
+        &self,
+        http: &Http,
+        user_id: UserId,
+        f: F,
+    ) -> Result<bool, SerenityError>
+    where
+        for<'b> F: FnOnce(&'b mut CreateMessage<'a>) -> &'b mut CreateMessage<'a>,
+    {
+        if !self.try_record(user_id, Instant::now()) {
+            warn!(
+                "DM naar {} overgeslagen, limiet van {} per {:?} bereikt",
+                user_id, self.max_per_window, self.window
+            );
+            return Ok(false);
+        }
+
+        let channel = user_id.create_dm_channel(http).await?;
+        channel.send_message(http, f).await?;
+        Ok(true)
+    }
+}
+
+/// Returns `true` for errors that point at Discord being unavailable: 5xx responses
+/// and requests that never got a response.
+pub fn is_server_error(error: &SerenityError) -> bool {
+    match error {
+        SerenityError::Http(e) => match e.as_ref() {
+            HttpError::Request(_) => true,
+            _ => http_status(error).is_some_and(|s| s >= 500),
+        },
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing::info_span;
+    use tracing_test::traced_test;
+
+    #[test]
+    fn shard_for_guild_matches_discord() {
+        assert_eq!(shard_for_guild(GuildId(81384788765712384), 1), 0);
+        assert_eq!(shard_for_guild(GuildId(81384788765712384), 16), 2);
+        assert_eq!(shard_for_guild(GuildId(613425648685547541), 5), 4);
+        assert_eq!(shard_for_guild(GuildId(175928847299117063), 16), 4);
+        assert_eq!(shard_for_guild(GuildId(175928847299117063), 0), 0);
+    }
+
+    #[test]
+    fn validates_token_format() {
+        let token = "MTIzNDU2Nzg5MDEyMzQ1Njc4.GxYzAb.abcdefghijklmnopqrstuvwxyz0123456789_-AB";
+        assert_eq!(validate_token(token), Ok(()));
+        assert_eq!(validate_token(&format!("Bot {}", token)), Ok(()));
+
+        let error = |token: &str| validate_token(token).unwrap_err();
+        assert!(error(&format!("{} ", token)).contains("whitespace"));
+        assert!(error(&format!("\"{}\"", token)).contains("quotes"));
+        assert!(error("MTIzNDU2Nzg5MDEyMzQ1Njc4GxYzAb").contains("three parts"));
+        assert!(error("MTIzNDU2Nzg5MDEyMzQ1Njc4.GxYzAb.abc").contains("characters long"));
+        assert!(error(&token.replace("MTIz", "aGVs")).contains("bot ID"));
+        assert_eq!(decode_base64("aGVsbG8"), Some(b"hello".to_vec()));
+    }
+
+    fn server_error() -> SerenityError {
+        SerenityError::Http(Box::new(HttpError::UnsuccessfulRequest(
+            serenity::http::error::ErrorResponse {
+                status_code: serenity::http::StatusCode::BAD_GATEWAY,
+                url: "https://discord.com/api/v10".parse().unwrap(),
+                error: toml::from_str("code = 0\nmessage = 'Bad Gateway'").unwrap(),
+            },
+        )))
+    }
+
+    fn presence(user_id: u64, game: Option<&str>) -> (UserId, Presence) {
+        let activities = match game {
+            Some(name) => serde_json::json!([{ "name": name, "type": 0 }]),
+            None => serde_json::json!([]),
+        };
+        let presence = serde_json::from_value(serde_json::json!({
+            "user": { "id": user_id.to_string() },
+            "status": "online",
+            "activities": activities,
+        }))
+        .unwrap();
+        (UserId(user_id), presence)
+    }
+
+    #[test]
+    fn majority_game_breaks_ties_with_owner() {
+        let owner = UserId(1);
+        let presences: HashMap<_, _> = [
+            presence(1, Some("Minecraft")),
+            presence(2, Some("Valorant")),
+            presence(3, Some("Valorant")),
+            presence(4, None),
+        ]
+        .into();
+        assert_eq!(majority_game(&presences, owner).as_deref(), Some("Valorant"));
+
+        let tied: HashMap<_, _> = [
+            presence(1, Some("Minecraft")),
+            presence(2, Some("Valorant")),
+            presence(3, Some("Minecraft")),
+            presence(4, Some("Valorant")),
+        ]
+        .into();
+        assert_eq!(majority_game(&tied, owner).as_deref(), Some("Minecraft"));
+        assert_eq!(majority_game(&tied, UserId(5)).as_deref(), Some("Minecraft"));
+
+        let idle: HashMap<_, _> = [presence(1, None)].into();
+        assert_eq!(majority_game(&idle, owner), None);
+    }
+
+    #[test]
+    fn reads_effective_uid() {
+        let status = "Name:\tbot\nUid:\t1000\t1001\t1000\t1000\nGid:\t1000\t1000\t1000\t1000\n";
+        assert_eq!(parse_effective_uid(status), Some(1001));
+        assert_eq!(parse_effective_uid("Name:\tbot\n"), None);
+    }
+
+    #[test]
+    fn only_voice_channels_are_valid() {
+        assert_eq!(voice_channel_problem(Some(ChannelType::Voice)), None);
+        assert!(voice_channel_problem(Some(ChannelType::Text)).is_some());
+        assert!(voice_channel_problem(None).is_some());
+    }
+
+    #[test]
+    fn stage_channels_are_valid() {
+        assert_eq!(voice_channel_problem(Some(ChannelType::Stage)), None);
+    }
+
+    #[test]
+    fn dm_throttle_slides_window() {
+        let throttle = DmThrottle::new(2, Duration::from_secs(60));
+        let (user, start) = (UserId(1), Instant::now());
+
+        assert!(throttle.try_record(user, start));
+        assert!(throttle.try_record(user, start + Duration::from_secs(10)));
+        assert!(!throttle.try_record(user, start + Duration::from_secs(20)));
+        assert!(throttle.try_record(UserId(2), start + Duration::from_secs(20)));
+
+        // The first DM has dropped out of the window
+        assert!(throttle.try_record(user, start + Duration::from_secs(61)));
+    }
+
+    #[tokio::test]
+    async fn opens_after_threshold_and_recovers() {
+        let breaker = CircuitBreaker::new(2, Duration::ZERO, HTTP_TIMEOUT);
+
+        for _ in 0..2 {
+            let _ = breaker.call(async { Err::<(), _>(server_error()) }).await;
+        }
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        breaker.call(async { Ok(()) }).await.unwrap();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[tokio::test]
+    async fn rejects_calls_while_open() {
+        let breaker = CircuitBreaker::new(1, Duration::from_secs(30), HTTP_TIMEOUT);
+        let _ = breaker.call(async { Err::<(), _>(server_error()) }).await;
+
+        let err = breaker.call(async { Ok(()) }).await.unwrap_err();
+        assert!(matches!(err, BotError::CircuitOpen));
+    }
+
+    #[tokio::test]
+    async fn hanging_call_times_out_and_counts_as_failure() {
+        let breaker = CircuitBreaker::new(1, Duration::from_secs(30), Duration::from_millis(10));
+
+        let hanging = std::future::pending::<Result<(), SerenityError>>();
+        let err = breaker.call(hanging).await.unwrap_err();
+
+        assert!(matches!(err, BotError::Timeout));
+        assert_eq!(breaker.state(), CircuitState::Open);
+    }
+
+    #[cfg(feature = "mock")]
+    #[tokio::test]
+    async fn creates_channels_and_moves_members_through_the_mock() {
+        let mock = crate::mock_http::MockHttp::start().await;
+        let ctx = mock.context();
+
+        let channel = create_channel(&ctx.http, GuildId(1), &AuditReason::Diagnostics, |c| {
+            c.name("Test").kind(ChannelType::Voice)
+        })
+        .await
+        .unwrap();
+        assert_eq!((channel.name.as_str(), channel.kind), ("Test", ChannelType::Voice));
+
+        GuildId(1).move_member(&ctx.http, UserId(2), channel.id).await.unwrap();
+        assert_eq!(mock.requests(), ["POST /guilds/1/channels", "PATCH /guilds/1/members/2"]);
+    }
+
+    #[test]
+    fn correlation_ids_are_random_v4() {
+        let (a, b) = (new_correlation_id(), new_correlation_id());
+        assert_eq!(a.get_version_num(), 4);
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn recognises_single_emojis() {
+        for emoji in ["🎮", "❤️", "👍🏽", "🇳🇱", "1️⃣", "👨‍👩‍👧"] {
+            assert!(is_unicode_emoji(emoji), "{}", emoji);
+        }
+        for text in ["", "a", "🎮🎮", "🎮 x", ":smile:", "<:pog:123>"] {
+            assert!(!is_unicode_emoji(text), "{}", text);
+        }
+        assert!(is_custom_emoji("<:pog:123>"));
+        assert!(is_custom_emoji("<a:party_parrot:456>"));
+        assert!(!is_custom_emoji("pog"));
+    }
+
+    #[test]
+    fn formats_durations_from_the_largest_unit() {
+        let format = |secs| format_duration(Duration::from_secs(secs));
+        assert_eq!(format(0), "0s");
+        assert_eq!(format(42), "42s");
+        assert_eq!(format(5 * 60 + 3), "5m 3s");
+        assert_eq!(format(2 * 60 * 60), "2h 0m 0s");
+        assert_eq!(format(((3 * 24 + 14) * 60 + 22) * 60 + 5), "3d 14h 22m 5s");
+    }
+
+    #[test]
+    fn audit_reasons_read_well() {
+        assert_eq!(
+            AuditReason::TempChannel("meeso".to_string()).to_string(),
+            "Temporary channel for @meeso"
+        );
+        assert_eq!(
+            AuditReason::Empty(Duration::from_secs(30)).to_string(),
+            "Temporary channel empty for 30s"
+        );
+        assert_eq!(AuditReason::OwnerLeft.to_string(), "Owner left");
+
+        let header = reason_header(&AuditReason::OwnerLeft);
+        assert_eq!(header["X-Audit-Log-Reason"], "Owner%20left");
+    }
+
+    #[test]
+    fn permission_checker_reports_once_and_recovers() {
+        let checker = BotPermissionChecker::default();
+        let guild = GuildId(1);
+        let partial = Permissions::CONNECT | Permissions::MANAGE_CHANNELS | Permissions::VIEW_CHANNEL;
+        let missing = missing_permissions(partial);
+
+        assert_eq!(missing, Permissions::MOVE_MEMBERS | Permissions::MANAGE_ROLES);
+        assert_eq!(checker.update(guild, missing), Some(missing));
+        assert!(checker.is_disabled(guild));
+        assert_eq!(checker.update(guild, missing), None);
+
+        assert_eq!(checker.update(guild, Permissions::empty()), None);
+        assert!(!checker.is_disabled(guild));
+        assert!(missing_permissions(Permissions::ADMINISTRATOR).is_empty());
+    }
+
+    #[test]
+    fn cooldown_blocks_until_it_runs_out() {
+        let cooldown = Cooldown::new(&[("region", Duration::from_secs(30))]);
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let (alice, bob) = (UserId(1), UserId(2));
+
+        assert_eq!(cooldown.check_and_set_at(alice, "region", at(0)), None);
+        assert_eq!(cooldown.check_and_set_at(alice, "region", at(10)), Some(Duration::from_secs(20)));
+        assert_eq!(cooldown.check_and_set_at(bob, "region", at(10)), None);
+        assert_eq!(cooldown.check_and_set_at(alice, "summary", at(10)), None);
+        assert_eq!(cooldown.check_and_set_at(alice, "region", at(30)), None);
+
+        cooldown.check_and_set_at(alice, "region", at(20 * 60));
+        assert_eq!(cooldown.state.lock().unwrap().last_used.len(), 1);
+    }
+
+    #[test]
+    fn reset_clears_only_that_user() {
+        let cooldown = Cooldown::new(&[
+            ("region", Duration::from_secs(30)),
+            ("status", Duration::from_secs(15)),
+        ]);
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let (alice, bob) = (UserId(1), UserId(2));
+
+        cooldown.check_and_set_at(alice, "region", at(0));
+        cooldown.check_and_set_at(alice, "status", at(0));
+        cooldown.check_and_set_at(bob, "region", at(0));
+
+        assert_eq!(cooldown.reset_at(alice, at(20)), [("region", Duration::from_secs(10))]);
+        assert_eq!(cooldown.check_and_set_at(alice, "region", at(21)), None);
+        assert!(cooldown.check_and_set_at(bob, "region", at(21)).is_some());
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn spawned_task_logs_under_parent_span() {
+        let task = info_span!("voice_state_update", user = 7)
+            .in_scope(|| spawn_in_span(async { info!("Kanaal verwijderd") }));
+        task.await.unwrap();
+
+        logs_assert(|lines: &[&str]| {
+            let nested = lines
+                .iter()
+                .any(|l| l.contains("voice_state_update{user=7}") && l.contains("Kanaal verwijderd"));
+            if nested {
+                Ok(())
+            } else {
+                Err(format!("no log line in the span: {:?}", lines))
+            }
+        });
+    }
+
+    #[test]
+    fn names_and_checks_channel_flags() {
+        assert_eq!(flag_names(0), Vec::<String>::new());
+        assert_eq!(flag_names((1 << 15) | (1 << 1) | (1 << 20)), ["PINNED", "HIDE_MEDIA_DOWNLOAD_OPTIONS", "1048576"]);
+
+        assert_eq!(voice_channel_flag("hide_media_download_options"), Ok(("HIDE_MEDIA_DOWNLOAD_OPTIONS", 1 << 15)));
+        assert!(voice_channel_flag("PINNED").unwrap_err().contains("voice channels"));
+        assert!(voice_channel_flag("LOUD").unwrap_err().contains("not a channel flag"));
+    }
+
+    #[test]
+    fn late_night_runs_over_midnight_in_local_time() {
+        let mut config = GuildConfig {
+            late_night_config: Some(crate::config::LateNightConfig {
+                start_hour: 23,
+                end_hour: 6,
+                max_channels: 2,
+                deletion_delay_secs: 60,
+            }),
+            ..GuildConfig::default()
+        };
+        let at = |hour: i64| hour * 60 * 60;
+        assert!(is_late_night_at(&config, at(23)));
+        assert!(is_late_night_at(&config, at(2)));
+        assert!(!is_late_night_at(&config, at(6)));
+        assert!(!is_late_night_at(&config, at(12)));
+
+        // 21:00 UTC is 23:00 at UTC+2
+        config.utc_offset_minutes = 120;
+        assert!(is_late_night_at(&config, at(21)));
+        assert!(!is_late_night_at(&config, at(4)));
+
+        config.late_night_config = None;
+        assert!(!is_late_night_at(&config, at(2)));
+    }
 
-    bot_member.permissions(&ctx.cache).map_or(false, |p| p.manage_channels())
-}
\ No newline at end of file
+    #[test]
+    fn parses_utc_offsets() {
+        assert_eq!(parse_utc_offset("+02:00"), Some(120));
+        assert_eq!(parse_utc_offset("UTC-5"), Some(-300));
+        assert_eq!(parse_utc_offset("5:30"), Some(330));
+        assert_eq!(parse_utc_offset("0"), Some(0));
+        assert_eq!(parse_utc_offset("+15"), None);
+        assert_eq!(parse_utc_offset("+1:75"), None);
+        assert_eq!(parse_utc_offset("CET"), None);
+        assert_eq!(format_utc_offset(-330), "-05:30");
+        assert_eq!(format_utc_offset(0), "+00:00");
+    }
+}
@@ -0,0 +1,187 @@
+//! A stand-in for the Discord API, so code that takes a `&Context` can be tested
+//! without a token or a network connection. Built with `--features mock`.
+//!
+//! `MockHttp::start` listens on a local port and `MockHttp::context` hands out a
+//! `Context` whose `Http` sends every request there through serenity's proxy setting.
+//! Requests get canned answers: created channels come back as a `GuildChannel` with
+//! the requested name and type, member edits (moves) as the member, and everything
+//! else as an empty 204. `MockHttp::requests` lists what was called, in order.
+//!
+//! The bot is a binary crate, so its unit tests pull this file in with `#[path]`.
+#![cfg(feature = "mock")]
+
+use serde_json::{json, Value};
+use serenity::{
+    cache::Cache,
+    client::{bridge::gateway::ShardMessenger, Context},
+    gateway::InterMessage,
+    http::{Http, HttpBuilder},
+    prelude::{RwLock, TypeMap},
+};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex,
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+    task::JoinHandle,
+};
+
+/// IDs handed out to created channels start here, far from the ones tests pick.
+const FIRST_CREATED_ID: u64 = 900_000;
+
+pub struct MockHttp {
+    base_url: String,
+    requests: Arc<Mutex<Vec<String>>>,
+    server: JoinHandle<()>,
+    // Keeps the shard channel open, the runner on the other end doesn't exist
+    _shard: futures::channel::mpsc::UnboundedReceiver<InterMessage>,
+    shard_tx: futures::channel::mpsc::UnboundedSender<InterMessage>,
+}
+
+impl MockHttp {
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind mock Discord API");
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let next_id = Arc::new(AtomicU64::new(FIRST_CREATED_ID));
+
+        let server = tokio::spawn({
+            let requests = Arc::clone(&requests);
+            async move {
+                while let Ok((stream, _)) = listener.accept().await {
+                    let requests = Arc::clone(&requests);
+                    let next_id = Arc::clone(&next_id);
+                    tokio::spawn(async move {
+                        let _ = serve(stream, &requests, &next_id).await;
+                    });
+                }
+            }
+        });
+
+        let (shard_tx, shard_rx) = futures::channel::mpsc::unbounded();
+        Self {
+            base_url,
+            requests,
+            server,
+            _shard: shard_rx,
+            shard_tx,
+        }
+    }
+
+    /// An `Http` that talks to this mock instead of Discord.
+    pub fn http(&self) -> Http {
+        HttpBuilder::new("mock-token")
+            .proxy(self.base_url.as_str())
+            .expect("mock URL is valid")
+            .ratelimiter_disabled(true)
+            .build()
+    }
+
+    /// A `Context` with an empty cache and this mock as its `Http`.
+    pub fn context(&self) -> Context {
+        Context {
+            data: Arc::new(RwLock::new(TypeMap::new())),
+            shard: ShardMessenger::new(self.shard_tx.clone()),
+            shard_id: 0,
+            http: Arc::new(self.http()),
+            cache: Arc::new(Cache::new()),
+        }
+    }
+
+    /// Every request so far as `"METHOD /path"`, without the `/api/v10` prefix.
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+impl Drop for MockHttp {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+async fn serve(stream: TcpStream, requests: &Mutex<Vec<String>>, next_id: &AtomicU64) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default();
+    let path = path.strip_prefix("/api/v10").unwrap_or(path).to_string();
+
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header).await?;
+        if header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).await?;
+    let body: Value = serde_json::from_slice(&body).unwrap_or(Value::Null);
+
+    requests.lock().unwrap().push(format!("{} {}", method, path));
+    let response = match canned_response(&method, &path, &body, next_id) {
+        Some(json) => {
+            let json = json.to_string();
+            format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                json.len(),
+                json
+            )
+        }
+        None => "HTTP/1.1 204 No Content\r\nConnection: close\r\n\r\n".to_string(),
+    };
+    reader.get_mut().write_all(response.as_bytes()).await?;
+    reader.get_mut().shutdown().await
+}
+
+fn canned_response(method: &str, path: &str, body: &Value, next_id: &AtomicU64) -> Option<Value> {
+    let segments: Vec<&str> = path.trim_start_matches('/').split(['/', '?']).collect();
+    match (method, segments.as_slice()) {
+        ("POST", ["guilds", guild_id, "channels"]) => Some(json!({
+            "id": next_id.fetch_add(1, Ordering::Relaxed).to_string(),
+            "guild_id": guild_id,
+            "name": body["name"].as_str().unwrap_or("mock"),
+            "type": body["type"].as_u64().unwrap_or(2),
+            "position": 0,
+            "permission_overwrites": overwrites(body),
+            "parent_id": body.get("parent_id").cloned().unwrap_or(Value::Null),
+        })),
+        ("PATCH", ["guilds", _, "members", user_id]) => Some(json!({
+            "user": { "id": user_id, "username": "mock", "discriminator": "0000", "avatar": null },
+            "roles": [],
+            "joined_at": "2026-01-01T00:00:00Z",
+            "deaf": false,
+            "mute": false,
+        })),
+        _ => None,
+    }
+}
+
+/// The overwrites of a channel create as Discord sends them back, serenity writes the
+/// permissions as numbers but only reads them as strings.
+fn overwrites(body: &Value) -> Value {
+    let Some(overwrites) = body["permission_overwrites"].as_array() else { return json!([]) };
+    overwrites
+        .iter()
+        .map(|overwrite| {
+            let mut overwrite = overwrite.clone();
+            for key in ["allow", "deny"] {
+                if let Some(bits) = overwrite[key].as_u64() {
+                    overwrite[key] = json!(bits.to_string());
+                }
+            }
+            overwrite
+        })
+        .collect()
+}